@@ -0,0 +1,397 @@
+/*
+ * BSD 3-Clause License
+ *
+ * Copyright (c) 2020, InterlockLedger Network
+ * All rights reserved.
+ *
+ * Redistribution and use in source and binary forms, with or without
+ * modification, are permitted provided that the following conditions are met:
+ *
+ * * Redistributions of source code must retain the above copyright notice, this
+ *   list of conditions and the following disclaimer.
+ *
+ * * Redistributions in binary form must reproduce the above copyright notice,
+ *   this list of conditions and the following disclaimer in the documentation
+ *   and/or other materials provided with the distribution.
+ *
+ * * Neither the name of the copyright holder nor the names of its
+ *   contributors may be used to endorse or promote products derived from
+ *   this software without specific prior written permission.
+ *
+ * THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS"
+ * AND ANY EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE
+ * IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+ * DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE
+ * FOR ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL, EXEMPLARY, OR CONSEQUENTIAL
+ * DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+ * SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER
+ * CAUSED AND ON ANY THEORY OF LIABILITY, WHETHER IN CONTRACT, STRICT LIABILITY,
+ * OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE
+ * OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+ */
+//! This crate implements the derive macros `ILTag` and `ILTagPayload` for the
+//! crate [il2-iltags](https://crates.io/crates/il2-iltags). It should not be
+//! used directly. Instead, enable the feature `derive` of `il2-iltags` and
+//! use the macros re-exported by it.
+//!
+//! Each field of the annotated type is serialized as a tag by the trait
+//! `il2_iltags::tags::field::ILTagField`, in the order they are declared.
+//! Enums are serialized as tagged unions, the index of the variant encoded
+//! as an ILInt followed by the fields of the variant.
+//!
+//! Example:
+//!
+//! ```
+//! use il2_iltags::io::array::ByteArrayReader;
+//! use il2_iltags::tags::{ILTag, ILTagFactory};
+//! use il2_iltags::tags::standard::ILStandardTagFactory;
+//!
+//! #[derive(ILTag, Debug, PartialEq)]
+//! #[iltag(id = 1234)]
+//! struct Point {
+//!     x: i32,
+//!     y: i32,
+//!     label: Option<String>,
+//! }
+//!
+//! let p = Point { x: 1, y: 2, label: None };
+//! let bytes = p.to_bytes().unwrap();
+//!
+//! let factory = ILStandardTagFactory::new(false);
+//! let mut q = Point { x: 0, y: 0, label: None };
+//! factory
+//!     .deserialize_into(&mut ByteArrayReader::new(&bytes), &mut q)
+//!     .unwrap();
+//! assert_eq!(p, q);
+//! ```
+//!
+//! New since 2.0.0.
+use proc_macro::TokenStream;
+use proc_macro2::TokenStream as TokenStream2;
+use quote::{format_ident, quote};
+use syn::spanned::Spanned;
+use syn::{parse_macro_input, Data, DeriveInput, Error, Fields, Ident, LitInt, Member, Result};
+
+/// The largest id reserved by the standard. It includes the implicit tags,
+/// that have no size field, and the standard explicit tags, so those ids
+/// cannot be used by derived tags.
+const RESERVED_ID_MAX: u64 = 0x1F;
+
+/// Derives `il2_iltags::tags::ILTag` and `il2_iltags::tags::field::ILTagField`
+/// for a struct or an enum. The id of the tag must be set by the attribute
+/// `#[iltag(id = N)]` and must not be one of the ids reserved by the
+/// standard (0 to 31).
+#[proc_macro_derive(ILTag, attributes(iltag))]
+pub fn derive_iltag(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    expand_iltag(&input)
+        .unwrap_or_else(Error::into_compile_error)
+        .into()
+}
+
+/// Derives `il2_iltags::tags::payload::ILTagPayload` for a struct or an enum.
+#[proc_macro_derive(ILTagPayload)]
+pub fn derive_iltag_payload(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    expand_iltag_payload(&input)
+        .unwrap_or_else(Error::into_compile_error)
+        .into()
+}
+
+//=============================================================================
+// Code generation
+//-----------------------------------------------------------------------------
+/// The code that handles the fields of a type.
+struct Body {
+    /// Expression that computes the size of the value as an u64.
+    value_size: TokenStream2,
+    /// Statements that serialize the value into `writer`.
+    serialize: TokenStream2,
+    /// Expression that deserializes the value from `_reader`. The fields are
+    /// deserialized at the depth `_depth`.
+    build: TokenStream2,
+}
+
+fn expand_iltag(input: &DeriveInput) -> Result<TokenStream2> {
+    let id = parse_tag_id(input)?;
+    let body = expand_body(input)?;
+    let Body {
+        value_size,
+        serialize,
+        build,
+    } = body;
+    let name = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    Ok(quote! {
+        impl #impl_generics ::il2_iltags::tags::ILTag for #name #ty_generics #where_clause {
+            fn id(&self) -> u64 {
+                #id
+            }
+
+            fn value_size(&self) -> u64 {
+                #value_size
+            }
+
+            fn serialize_value(
+                &self,
+                writer: &mut dyn ::il2_iltags::io::Writer,
+            ) -> ::il2_iltags::tags::Result<()> {
+                #serialize
+                Ok(())
+            }
+
+            fn deserialize_value(
+                &mut self,
+                factory: &dyn ::il2_iltags::tags::ILTagFactory,
+                value_size: usize,
+                reader: &mut dyn ::il2_iltags::io::Reader,
+            ) -> ::il2_iltags::tags::Result<()> {
+                let depth = ::il2_iltags::tags::ILTagFactory::depth(factory).saturating_sub(1);
+                *self = ::il2_iltags::tags::field::deserialize_fields(
+                    factory,
+                    depth,
+                    value_size,
+                    reader,
+                    |_reader| {
+                        let _depth: usize = depth + 1;
+                        #build
+                    },
+                )?;
+                Ok(())
+            }
+
+            fn as_any(&self) -> &dyn ::std::any::Any {
+                self
+            }
+
+            fn as_mut_any(&mut self) -> &mut dyn ::std::any::Any {
+                self
+            }
+        }
+
+        impl #impl_generics ::il2_iltags::tags::field::ILTagField for #name #ty_generics #where_clause {
+            fn field_size(&self) -> u64 {
+                ::il2_iltags::tags::ILTag::size(self)
+            }
+
+            fn serialize_field(
+                &self,
+                writer: &mut dyn ::il2_iltags::io::Writer,
+            ) -> ::il2_iltags::tags::Result<()> {
+                ::il2_iltags::tags::ILTag::serialize(self, writer)
+            }
+
+            fn deserialize_field_with_id(
+                factory: &dyn ::il2_iltags::tags::ILTagFactory,
+                depth: usize,
+                id: u64,
+                reader: &mut dyn ::il2_iltags::io::Reader,
+            ) -> ::il2_iltags::tags::Result<Self> {
+                ::il2_iltags::tags::field::check_field_id(id, #id)?;
+                ::il2_iltags::tags::field::deserialize_explicit_field(
+                    factory,
+                    depth,
+                    reader,
+                    |_reader| {
+                        let _depth: usize = depth + 1;
+                        #build
+                    },
+                )
+            }
+        }
+    })
+}
+
+fn expand_iltag_payload(input: &DeriveInput) -> Result<TokenStream2> {
+    let Body {
+        value_size,
+        serialize,
+        build,
+    } = expand_body(input)?;
+    let name = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    Ok(quote! {
+        impl #impl_generics ::il2_iltags::tags::payload::ILTagPayload for #name #ty_generics #where_clause {
+            fn serialized_size(&self) -> usize {
+                (#value_size) as usize
+            }
+
+            fn serialize(
+                &self,
+                writer: &mut dyn ::il2_iltags::io::Writer,
+            ) -> ::il2_iltags::tags::Result<()> {
+                #serialize
+                Ok(())
+            }
+
+            fn deserialize(
+                &mut self,
+                factory: &dyn ::il2_iltags::tags::ILTagFactory,
+                value_size: usize,
+                reader: &mut dyn ::il2_iltags::io::Reader,
+            ) -> ::il2_iltags::tags::Result<()> {
+                let depth = ::il2_iltags::tags::ILTagFactory::depth(factory).saturating_sub(1);
+                *self = ::il2_iltags::tags::field::deserialize_fields(
+                    factory,
+                    depth,
+                    value_size,
+                    reader,
+                    |_reader| {
+                        let _depth: usize = depth + 1;
+                        #build
+                    },
+                )?;
+                Ok(())
+            }
+        }
+    })
+}
+
+/// Extracts the tag id from the attribute `#[iltag(id = N)]`.
+fn parse_tag_id(input: &DeriveInput) -> Result<u64> {
+    let mut id: Option<u64> = None;
+    for attr in input.attrs.iter().filter(|a| a.path().is_ident("iltag")) {
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("id") {
+                let lit: LitInt = meta.value()?.parse()?;
+                let value: u64 = lit.base10_parse()?;
+                if value <= RESERVED_ID_MAX {
+                    return Err(Error::new(
+                        lit.span(),
+                        "reserved tag ids cannot be used by derived tags",
+                    ));
+                }
+                id = Some(value);
+                Ok(())
+            } else {
+                Err(meta.error("unsupported iltag attribute"))
+            }
+        })?;
+    }
+    id.ok_or_else(|| {
+        Error::new(
+            input.ident.span(),
+            "the tag id must be set with #[iltag(id = N)]",
+        )
+    })
+}
+
+/// Returns the members of the given fields.
+fn members(fields: &Fields) -> Vec<Member> {
+    fields
+        .iter()
+        .enumerate()
+        .map(|(i, f)| match &f.ident {
+            Some(ident) => Member::Named(ident.clone()),
+            None => Member::Unnamed(i.into()),
+        })
+        .collect()
+}
+
+/// Generates the expression that adds the sizes of the given fields to the
+/// given initial size.
+fn sum_field_sizes(initial: TokenStream2, fields: &[TokenStream2]) -> TokenStream2 {
+    quote! {
+        #initial #(+ ::il2_iltags::tags::field::ILTagField::field_size(#fields))*
+    }
+}
+
+fn expand_body(input: &DeriveInput) -> Result<Body> {
+    match &input.data {
+        Data::Struct(data) => Ok(expand_struct_body(&data.fields)),
+        Data::Enum(data) => {
+            if data.variants.is_empty() {
+                return Err(Error::new(
+                    input.ident.span(),
+                    "enums without variants are not supported",
+                ));
+            }
+            let variants: Vec<(&Ident, &Fields)> = data
+                .variants
+                .iter()
+                .map(|v| (&v.ident, &v.fields))
+                .collect();
+            Ok(expand_enum_body(&variants))
+        }
+        Data::Union(data) => Err(Error::new(
+            data.union_token.span(),
+            "unions are not supported",
+        )),
+    }
+}
+
+fn expand_struct_body(fields: &Fields) -> Body {
+    let members = members(fields);
+    let refs: Vec<TokenStream2> = members.iter().map(|m| quote!(&self.#m)).collect();
+    let value_size = sum_field_sizes(quote!(0u64), &refs);
+    let serialize = quote! {
+        #(::il2_iltags::tags::field::ILTagField::serialize_field(#refs, writer)?;)*
+    };
+    let build = quote! {
+        Ok(Self {
+            #(#members: ::il2_iltags::tags::field::ILTagField::deserialize_field(
+                factory, _depth, _reader,
+            )?,)*
+        })
+    };
+    Body {
+        value_size,
+        serialize,
+        build,
+    }
+}
+
+fn expand_enum_body(variants: &[(&Ident, &Fields)]) -> Body {
+    let mut size_arms = Vec::new();
+    let mut serialize_arms = Vec::new();
+    let mut build_arms = Vec::new();
+    for (index, (ident, fields)) in variants.iter().enumerate() {
+        let index = index as u64;
+        let members = members(fields);
+        let bindings: Vec<Ident> = (0..members.len())
+            .map(|i| format_ident!("__field{}", i))
+            .collect();
+        let refs: Vec<TokenStream2> = bindings.iter().map(|b| quote!(#b)).collect();
+        let pattern = quote! {
+            Self::#ident { #(#members: #bindings,)* }
+        };
+        let value_size = sum_field_sizes(
+            quote!(::il2_iltags::ilint::encoded_size(#index) as u64),
+            &refs,
+        );
+        size_arms.push(quote! {
+            #pattern => #value_size,
+        });
+        serialize_arms.push(quote! {
+            #pattern => {
+                ::il2_iltags::tags::field::serialize_variant_index(#index, writer)?;
+                #(::il2_iltags::tags::field::ILTagField::serialize_field(#bindings, writer)?;)*
+            }
+        });
+        build_arms.push(quote! {
+            #index => Ok(Self::#ident {
+                #(#members: ::il2_iltags::tags::field::ILTagField::deserialize_field(
+                    factory, _depth, _reader,
+                )?,)*
+            }),
+        });
+    }
+    Body {
+        value_size: quote! {
+            match self {
+                #(#size_arms)*
+            }
+        },
+        serialize: quote! {
+            match self {
+                #(#serialize_arms)*
+            }
+        },
+        build: quote! {
+            match ::il2_iltags::tags::field::deserialize_variant_index(factory, _reader)? {
+                #(#build_arms)*
+                _ => Err(::il2_iltags::tags::ErrorKind::CorruptedData),
+            }
+        },
+    }
+}
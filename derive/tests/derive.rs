@@ -0,0 +1,409 @@
+/*
+ * BSD 3-Clause License
+ *
+ * Copyright (c) 2020, InterlockLedger Network
+ * All rights reserved.
+ *
+ * Redistribution and use in source and binary forms, with or without
+ * modification, are permitted provided that the following conditions are met:
+ *
+ * * Redistributions of source code must retain the above copyright notice, this
+ *   list of conditions and the following disclaimer.
+ *
+ * * Redistributions in binary form must reproduce the above copyright notice,
+ *   this list of conditions and the following disclaimer in the documentation
+ *   and/or other materials provided with the distribution.
+ *
+ * * Neither the name of the copyright holder nor the names of its
+ *   contributors may be used to endorse or promote products derived from
+ *   this software without specific prior written permission.
+ *
+ * THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS"
+ * AND ANY EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE
+ * IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+ * DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE
+ * FOR ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL, EXEMPLARY, OR CONSEQUENTIAL
+ * DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+ * SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER
+ * CAUSED AND ON ANY THEORY OF LIABILITY, WHETHER IN CONTRACT, STRICT LIABILITY,
+ * OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE
+ * OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+ */
+use il2_iltags::io::array::ByteArrayReader;
+use il2_iltags::tags::field::ILTagField;
+use il2_iltags::tags::payload::{ILGenericPayloadTag, ILTagPayload};
+use il2_iltags::tags::standard::constants::*;
+use il2_iltags::tags::standard::{ILStandardTagFactory, ILTagSeqTag};
+use il2_iltags::tags::util::iltag_are_equal;
+use il2_iltags::tags::{
+    DeserializationLimits, ErrorKind, ILDefaultTagCreator, ILTag, ILTagFactory,
+};
+
+#[derive(ILTag, Debug, PartialEq)]
+#[iltag(id = 1234)]
+struct Point {
+    x: i32,
+    y: i32,
+    label: Option<String>,
+}
+
+#[derive(ILTag, Debug, PartialEq)]
+#[iltag(id = 1235)]
+struct Pair(u8, bool);
+
+#[derive(ILTag, Debug, PartialEq)]
+#[iltag(id = 1236)]
+struct Empty;
+
+#[derive(ILTag, Debug, PartialEq)]
+#[iltag(id = 1237)]
+struct Shape {
+    name: String,
+    origin: Point,
+    points: Vec<Point>,
+    tag: Option<Pair>,
+}
+
+#[derive(ILTag, Debug, PartialEq)]
+#[iltag(id = 1238)]
+enum Value {
+    Nothing,
+    Number(u64),
+    Named { name: String, value: f64 },
+    Point(Point),
+}
+
+#[derive(ILTag, Debug, Default, PartialEq)]
+#[iltag(id = 1239)]
+struct Node {
+    value: u8,
+    children: Vec<Node>,
+}
+
+#[derive(ILTagPayload, Debug, Default, PartialEq)]
+struct Payload {
+    a: u16,
+    b: Vec<String>,
+}
+
+#[derive(ILTagPayload, Debug, Default, PartialEq)]
+enum PayloadEnum {
+    #[default]
+    A,
+    B(i8),
+}
+
+fn sample_point() -> Point {
+    Point {
+        x: 1,
+        y: -1,
+        label: Some(String::from("p")),
+    }
+}
+
+fn sample_shape() -> Shape {
+    Shape {
+        name: String::from("shape"),
+        origin: sample_point(),
+        points: vec![
+            sample_point(),
+            Point {
+                x: 2,
+                y: 3,
+                label: None,
+            },
+        ],
+        tag: Some(Pair(1, true)),
+    }
+}
+
+fn deserialize_into<T: ILTag>(
+    factory: &dyn ILTagFactory,
+    bytes: &[u8],
+    tag: &mut T,
+) -> Result<(), ErrorKind> {
+    factory.deserialize_into(&mut ByteArrayReader::new(bytes), tag)
+}
+
+#[test]
+fn test_derive_iltag_struct() {
+    let factory = ILStandardTagFactory::new(false);
+    let p = sample_point();
+    assert_eq!(p.id(), 1234);
+    let bytes = p.to_bytes().unwrap();
+    assert_eq!(bytes.len() as u64, p.size());
+    assert_eq!(bytes.len() as u64, p.field_size());
+    assert_eq!(
+        bytes,
+        [
+            0xF9,
+            0x03,
+            0xDA, // id
+            13,   // size
+            IL_INT32_TAG_ID as u8,
+            0,
+            0,
+            0,
+            1, // x
+            IL_INT32_TAG_ID as u8,
+            0xFF,
+            0xFF,
+            0xFF,
+            0xFF, // y
+            IL_STRING_TAG_ID as u8,
+            1,
+            b'p', // label
+        ]
+    );
+
+    let mut q = Point {
+        x: 0,
+        y: 0,
+        label: None,
+    };
+    deserialize_into(&factory, &bytes, &mut q).unwrap();
+    assert_eq!(p, q);
+
+    let p = Point {
+        x: 0,
+        y: 0,
+        label: None,
+    };
+    let bytes = p.to_bytes().unwrap();
+    assert_eq!(bytes.len(), 3 + 1 + 5 + 5 + 1);
+    deserialize_into(&factory, &bytes, &mut q).unwrap();
+    assert_eq!(p, q);
+}
+
+#[test]
+fn test_derive_iltag_tuple_unit() {
+    let factory = ILStandardTagFactory::new(false);
+
+    let p = Pair(2, true);
+    let bytes = p.to_bytes().unwrap();
+    assert_eq!(
+        bytes,
+        [
+            0xF9,
+            0x03,
+            0xDB,
+            4,
+            IL_UINT8_TAG_ID as u8,
+            2,
+            IL_BOOL_TAG_ID as u8,
+            1
+        ]
+    );
+    let mut q = Pair(0, false);
+    deserialize_into(&factory, &bytes, &mut q).unwrap();
+    assert_eq!(p, q);
+
+    let bytes = Empty.to_bytes().unwrap();
+    assert_eq!(bytes, [0xF9, 0x03, 0xDC, 0]);
+    let mut q = Empty;
+    deserialize_into(&factory, &bytes, &mut q).unwrap();
+    assert!(matches!(
+        deserialize_into(&factory, &[0xF9, 0x03, 0xDC, 1, 0], &mut q),
+        Err(ErrorKind::CorruptedData)
+    ));
+}
+
+#[test]
+fn test_derive_iltag_nested() {
+    let factory = ILStandardTagFactory::new(false);
+    let s = sample_shape();
+    let bytes = s.to_bytes().unwrap();
+    assert_eq!(bytes.len() as u64, s.size());
+
+    let mut q = Shape {
+        name: String::new(),
+        origin: Point {
+            x: 0,
+            y: 0,
+            label: None,
+        },
+        points: Vec::new(),
+        tag: None,
+    };
+    deserialize_into(&factory, &bytes, &mut q).unwrap();
+    assert_eq!(s, q);
+
+    // The nested tags can also be read as fields
+    let mut reader = ByteArrayReader::new(&bytes);
+    let q = Shape::deserialize_field(&factory, 0, &mut reader).unwrap();
+    assert_eq!(s, q);
+
+    // Wrong id of a nested tag
+    let mut p = Pair(0, false);
+    let bytes = sample_point().to_bytes().unwrap();
+    assert!(matches!(
+        deserialize_into(&factory, &bytes, &mut p),
+        Err(ErrorKind::UnexpectedTagType)
+    ));
+    let mut reader = ByteArrayReader::new(&bytes);
+    assert!(matches!(
+        Pair::deserialize_field(&factory, 0, &mut reader),
+        Err(ErrorKind::UnexpectedTagType)
+    ));
+
+    // Wrong type of a field
+    let bytes = [
+        0xF9,
+        0x03,
+        0xDB,
+        4,
+        IL_INT8_TAG_ID as u8,
+        2,
+        IL_BOOL_TAG_ID as u8,
+        1,
+    ];
+    assert!(matches!(
+        deserialize_into(&factory, &bytes, &mut p),
+        Err(ErrorKind::UnexpectedTagType)
+    ));
+
+    // Truncated value
+    let bytes = [0xF9, 0x03, 0xDB, 2, IL_UINT8_TAG_ID as u8, 2];
+    assert!(matches!(
+        deserialize_into(&factory, &bytes, &mut p),
+        Err(ErrorKind::IOError(_))
+    ));
+}
+
+#[test]
+fn test_derive_iltag_enum() {
+    let factory = ILStandardTagFactory::new(false);
+
+    let samples = [
+        Value::Nothing,
+        Value::Number(1),
+        Value::Named {
+            name: String::from("half"),
+            value: 0.5,
+        },
+        Value::Point(sample_point()),
+    ];
+    for (index, v) in samples.iter().enumerate() {
+        let bytes = v.to_bytes().unwrap();
+        assert_eq!(bytes.len() as u64, v.size());
+        assert_eq!(
+            &bytes[..5],
+            [0xF9, 0x03, 0xDE, v.value_size() as u8, index as u8]
+        );
+        let mut q = Value::Nothing;
+        deserialize_into(&factory, &bytes, &mut q).unwrap();
+        assert_eq!(v, &q);
+    }
+    assert_eq!(Value::Nothing.to_bytes().unwrap(), [0xF9, 0x03, 0xDE, 1, 0]);
+
+    // Unknown variant
+    let mut q = Value::Nothing;
+    assert!(matches!(
+        deserialize_into(&factory, &[0xF9, 0x03, 0xDE, 1, 4], &mut q),
+        Err(ErrorKind::CorruptedData)
+    ));
+    // Missing fields
+    assert!(matches!(
+        deserialize_into(&factory, &[0xF9, 0x03, 0xDE, 1, 1], &mut q),
+        Err(ErrorKind::IOError(_))
+    ));
+}
+
+#[test]
+fn test_derive_iltag_limits() {
+    let mut node = Node {
+        value: 0,
+        children: Vec::new(),
+    };
+    for i in 1..5 {
+        node = Node {
+            value: i,
+            children: vec![node],
+        };
+    }
+    let bytes = node.to_bytes().unwrap();
+
+    let factory = ILStandardTagFactory::new(false);
+    let mut q = Node {
+        value: 0,
+        children: Vec::new(),
+    };
+    deserialize_into(&factory, &bytes, &mut q).unwrap();
+    assert_eq!(node, q);
+
+    // Each level of the tree uses a node and an array
+    let mut limits = DeserializationLimits::new();
+    limits.set_max_depth(9);
+    let factory = ILStandardTagFactory::with_limits(false, limits);
+    deserialize_into(&factory, &bytes, &mut q).unwrap();
+    let mut limits = DeserializationLimits::new();
+    limits.set_max_depth(8);
+    let factory = ILStandardTagFactory::with_limits(false, limits);
+    assert!(matches!(
+        deserialize_into(&factory, &bytes, &mut q),
+        Err(ErrorKind::DepthLimitExceeded)
+    ));
+}
+
+#[test]
+fn test_derive_iltag_limits_nested() {
+    let mut node = Node {
+        value: 0,
+        children: Vec::new(),
+    };
+    for i in 1..5 {
+        node = Node {
+            value: i,
+            children: vec![node],
+        };
+    }
+    let mut seq = ILTagSeqTag::new();
+    seq.mut_value().push(Box::new(node));
+    let bytes = seq.to_bytes().unwrap();
+
+    // The sequence adds one level to the limits of the derived tag
+    let mut limits = DeserializationLimits::new();
+    limits.set_max_depth(10);
+    let mut factory = ILStandardTagFactory::with_limits(false, limits);
+    factory
+        .engine()
+        .register(1239, Box::new(ILDefaultTagCreator::<Node>::new()));
+    let mut q = ILTagSeqTag::new();
+    deserialize_into(&factory, &bytes, &mut q).unwrap();
+    assert!(iltag_are_equal(&q, &seq));
+
+    let mut limits = DeserializationLimits::new();
+    limits.set_max_depth(9);
+    factory.set_limits(limits);
+    assert!(matches!(
+        deserialize_into(&factory, &bytes, &mut q),
+        Err(ErrorKind::DepthLimitExceeded)
+    ));
+}
+
+#[test]
+fn test_derive_iltagpayload() {
+    let factory = ILStandardTagFactory::new(false);
+
+    let mut tag: ILGenericPayloadTag<Payload> = ILGenericPayloadTag::new(1240);
+    tag.mut_payload().a = 0x1234;
+    tag.mut_payload().b = vec![String::from("a"), String::from("bc")];
+    assert_eq!(tag.payload().serialized_size(), 3 + 2 + 1 + 3 + 4);
+    let bytes = tag.to_bytes().unwrap();
+    assert_eq!(
+        &bytes[..6],
+        [0xF9, 0x03, 0xE0, 13, IL_UINT16_TAG_ID as u8, 0x12]
+    );
+
+    let mut q: ILGenericPayloadTag<Payload> = ILGenericPayloadTag::new(1240);
+    deserialize_into(&factory, &bytes, &mut q).unwrap();
+    assert_eq!(tag.payload(), q.payload());
+
+    let mut tag: ILGenericPayloadTag<PayloadEnum> = ILGenericPayloadTag::new(1241);
+    *tag.mut_payload() = PayloadEnum::B(-1);
+    let bytes = tag.to_bytes().unwrap();
+    assert_eq!(bytes, [0xF9, 0x03, 0xE1, 3, 1, IL_INT8_TAG_ID as u8, 0xFF]);
+    let mut q: ILGenericPayloadTag<PayloadEnum> = ILGenericPayloadTag::new(1241);
+    deserialize_into(&factory, &bytes, &mut q).unwrap();
+    assert_eq!(tag.payload(), q.payload());
+}
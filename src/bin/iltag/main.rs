@@ -0,0 +1,650 @@
+/*
+ * BSD 3-Clause License
+ *
+ * Copyright (c) 2020, InterlockLedger Network
+ * All rights reserved.
+ *
+ * Redistribution and use in source and binary forms, with or without
+ * modification, are permitted provided that the following conditions are met:
+ *
+ * * Redistributions of source code must retain the above copyright notice, this
+ *   list of conditions and the following disclaimer.
+ *
+ * * Redistributions in binary form must reproduce the above copyright notice,
+ *   this list of conditions and the following disclaimer in the documentation
+ *   and/or other materials provided with the distribution.
+ *
+ * * Neither the name of the copyright holder nor the names of its
+ *   contributors may be used to endorse or promote products derived from
+ *   this software without specific prior written permission.
+ *
+ * THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS"
+ * AND ANY EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE
+ * IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+ * DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE
+ * FOR ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL, EXEMPLARY, OR CONSEQUENTIAL
+ * DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+ * SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER
+ * CAUSED AND ON ANY THEORY OF LIABILITY, WHETHER IN CONTRACT, STRICT LIABILITY,
+ * OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE
+ * OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+ */
+//! `iltag` is a command line tool that inspects and converts files that
+//! contain serialized tags. It requires the feature `cli`.
+//!
+//! New since 2.0.0.
+#[cfg(test)]
+mod tests;
+
+use il2_iltags::io::array::{ByteArrayReader, MemoryReader};
+use il2_iltags::io::std_io::{BufferedReader, BufferedWriter};
+use il2_iltags::io::{Reader, Writer};
+use il2_iltags::tags::dump::RawTagDumper;
+use il2_iltags::tags::json::{from_json, write_json};
+use il2_iltags::tags::path::standard_tag_name;
+use il2_iltags::tags::raw::{
+    standard_container_layout, RawTagNode, RawTagOffset, RawTagScanner, RawTagWalker,
+};
+use il2_iltags::tags::standard::constants::*;
+use il2_iltags::tags::standard::ILStandardTagFactory;
+use il2_iltags::tags::text::{from_text, to_text, to_text_pretty};
+use il2_iltags::tags::validation::{validate, ValidationOptions};
+use il2_iltags::tags::ILTag;
+use std::collections::BTreeMap;
+use std::io::{Read, Write};
+
+const USAGE: &str = "\
+Usage: iltag <command> [options] [FILE]
+
+Reads the tags from FILE or from the standard input if FILE is omitted or is
+'-'. Binary inputs may contain a sequence of tags.
+
+Commands:
+  dump [FILE]                     Annotated hex dump
+  tree [FILE]                     Tree of tags with their offsets and sizes
+  validate [FILE]                 Verifies if the file contains valid tags
+  convert [options] [FILE]        Converts between formats
+      --from <bin|json|text>      Format of the input (default: bin)
+      --to <bin|json|text>        Format of the output (default: text)
+      --pretty                    Indents the JSON and text outputs
+  extract [options] PATH [FILE]   Extracts a tag. PATH is composed by the
+                                  index of the top level tag followed by the
+                                  indexes or keys of the children separated
+                                  by '/' (e.g.: 0/payload/2)
+      --to <bin|json|text>        Format of the output (default: text)
+      --pretty                    Indents the JSON and text outputs
+  stats [FILE]                    Counts the tags of each type
+  help                            Shows this message
+";
+
+//=============================================================================
+// Errors
+//-----------------------------------------------------------------------------
+/// Errors reported by the commands.
+#[derive(Debug, PartialEq)]
+enum CliError {
+    /// The command line is invalid.
+    Usage(String),
+    /// The command failed.
+    Failed(String),
+}
+
+type CliResult<T> = Result<T, CliError>;
+
+fn failed<E: std::fmt::Display>(e: E) -> CliError {
+    CliError::Failed(e.to_string())
+}
+
+//=============================================================================
+// Arguments
+//-----------------------------------------------------------------------------
+/// Formats supported by `convert` and `extract`.
+#[derive(Debug, Copy, Clone, PartialEq)]
+enum Format {
+    Binary,
+    Json,
+    Text,
+}
+
+impl Format {
+    fn parse(s: &str) -> CliResult<Self> {
+        match s {
+            "bin" => Ok(Format::Binary),
+            "json" => Ok(Format::Json),
+            "text" => Ok(Format::Text),
+            _ => Err(CliError::Usage(format!("unknown format '{}'", s))),
+        }
+    }
+}
+
+/// Parsed options of a command.
+struct Options {
+    from: Format,
+    to: Format,
+    pretty: bool,
+    positional: Vec<String>,
+}
+
+impl Options {
+    /// Parses the arguments of a command.
+    ///
+    /// Arguments:
+    /// - `args`: The arguments after the name of the command;
+    /// - `formats`: If true, `--from`, `--to` and `--pretty` are accepted;
+    /// - `max_positional`: Maximum number of positional arguments;
+    fn parse(args: &[String], formats: bool, max_positional: usize) -> CliResult<Self> {
+        let mut options = Options {
+            from: Format::Binary,
+            to: Format::Text,
+            pretty: false,
+            positional: Vec::new(),
+        };
+        let mut args = args.iter();
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--from" | "--to" if formats => {
+                    let value = match args.next() {
+                        Some(v) => Format::parse(v)?,
+                        None => return Err(CliError::Usage(format!("{} requires a value", arg))),
+                    };
+                    if arg == "--from" {
+                        options.from = value;
+                    } else {
+                        options.to = value;
+                    }
+                }
+                "--pretty" if formats => options.pretty = true,
+                s if s.starts_with("--") => {
+                    return Err(CliError::Usage(format!("unknown option '{}'", s)))
+                }
+                _ => options.positional.push(arg.clone()),
+            }
+        }
+        if options.positional.len() > max_positional {
+            return Err(CliError::Usage(String::from("too many arguments")));
+        }
+        Ok(options)
+    }
+
+    /// Returns the positional argument at `index` or "-".
+    fn file(&self, index: usize) -> &str {
+        match self.positional.get(index) {
+            Some(f) => f,
+            None => "-",
+        }
+    }
+}
+
+/// Opens the input.
+///
+/// Arguments:
+/// - `file`: The name of the file or "-" for the standard input;
+fn open_input(file: &str) -> CliResult<Box<dyn Read>> {
+    let ret: std::io::Result<Box<dyn Read>> = if file == "-" {
+        Ok(Box::new(std::io::stdin()))
+    } else {
+        std::fs::File::open(file).map(|f| Box::new(f) as Box<dyn Read>)
+    };
+    ret.map_err(|e| CliError::Failed(format!("unable to read '{}': {}", file, e)))
+}
+
+/// Reads the whole input. It is used only by the commands that cannot work
+/// on one tag at a time.
+///
+/// Arguments:
+/// - `file`: The name of the file or "-" for the standard input;
+fn read_input(file: &str) -> CliResult<Vec<u8>> {
+    let mut buff = Vec::new();
+    open_input(file)?
+        .read_to_end(&mut buff)
+        .map_err(|e| CliError::Failed(format!("unable to read '{}': {}", file, e)))?;
+    Ok(buff)
+}
+
+/// Reads the top level tags of a binary input and calls `f` with each one
+/// of them. Only the current top level tag is kept in memory.
+///
+/// Arguments:
+/// - `file`: The name of the file or "-" for the standard input;
+/// - `f`: The function that receives the tags. It returns `Ok(false)` to
+///   stop the reading;
+fn for_each_tag<F>(file: &str, mut f: F) -> CliResult<()>
+where
+    F: FnMut(InputTag) -> CliResult<bool>,
+{
+    let mut source = open_input(file)?;
+    let mut reader = BufferedReader::new(&mut source);
+    let mut recorder = TagRecorder {
+        reader: &mut reader,
+        tag: Vec::new(),
+    };
+    let mut index = 0;
+    let mut offset = 0;
+    loop {
+        recorder.tag.clear();
+        match RawTagScanner::new(&mut recorder).next_tag() {
+            Ok(Some(_)) => (),
+            Ok(None) => return Ok(()),
+            Err(e) => {
+                return Err(CliError::Failed(format!(
+                    "corrupted data at offset {}: {}",
+                    offset, e
+                )))
+            }
+        }
+        let tag = InputTag {
+            index,
+            offset,
+            raw: &recorder.tag,
+        };
+        if !f(tag)? {
+            return Ok(());
+        }
+        index += 1;
+        offset += recorder.tag.len() as u64;
+    }
+}
+
+/// A top level tag of the input.
+struct InputTag<'a> {
+    /// The index of the tag.
+    index: u64,
+    /// The offset of the tag in the input.
+    offset: u64,
+    /// The serialized tag.
+    raw: &'a [u8],
+}
+
+/// This [`Reader`] keeps all bytes read or skipped. It allows
+/// [`RawTagScanner`] to extract the top level tags from the input.
+struct TagRecorder<'a> {
+    reader: &'a mut dyn Reader,
+    tag: Vec<u8>,
+}
+
+impl<'a> Reader for TagRecorder<'a> {
+    fn read(&mut self) -> il2_iltags::io::Result<u8> {
+        let v = self.reader.read()?;
+        self.tag.push(v);
+        Ok(v)
+    }
+
+    fn read_all(&mut self, buff: &mut [u8]) -> il2_iltags::io::Result<()> {
+        self.reader.read_all(buff)?;
+        self.tag.extend_from_slice(buff);
+        Ok(())
+    }
+}
+
+//=============================================================================
+// Commands
+//-----------------------------------------------------------------------------
+/// Executes the command line.
+///
+/// Arguments:
+/// - `args`: The arguments without the name of the program;
+/// - `out`: The standard output;
+fn run(args: &[String], out: &mut dyn Write) -> CliResult<()> {
+    let (command, args) = match args.split_first() {
+        Some((command, args)) => (command.as_str(), args),
+        None => return Err(CliError::Usage(String::from("command expected"))),
+    };
+    match command {
+        "dump" => {
+            let options = Options::parse(args, false, 1)?;
+            cmd_dump(&read_input(options.file(0))?, out)
+        }
+        "tree" => {
+            let options = Options::parse(args, false, 1)?;
+            cmd_tree(options.file(0), out)
+        }
+        "validate" => {
+            let options = Options::parse(args, false, 1)?;
+            cmd_validate(options.file(0), out)
+        }
+        "convert" => {
+            let options = Options::parse(args, true, 1)?;
+            cmd_convert(options.file(0), &options, out)
+        }
+        "extract" => {
+            let options = Options::parse(args, true, 2)?;
+            let path = match options.positional.first() {
+                Some(path) => path,
+                None => return Err(CliError::Usage(String::from("PATH expected"))),
+            };
+            cmd_extract(options.file(1), path, options.to, options.pretty, out)
+        }
+        "stats" => {
+            let options = Options::parse(args, false, 1)?;
+            cmd_stats(options.file(0), out)
+        }
+        "help" | "--help" | "-h" => out.write_all(USAGE.as_bytes()).map_err(failed),
+        _ => Err(CliError::Usage(format!("unknown command '{}'", command))),
+    }
+}
+
+/// Dumps the whole input at once as the dump also lists the bytes that do
+/// not belong to any tag.
+fn cmd_dump(raw: &[u8], out: &mut dyn Write) -> CliResult<()> {
+    let dump = RawTagDumper::new().dump(raw);
+    out.write_all(dump.as_bytes()).map_err(failed)
+}
+
+fn tag_name(id: u64) -> String {
+    match standard_tag_name(id) {
+        Some(name) => String::from(name),
+        None => format!("tag({})", id),
+    }
+}
+
+/// Walks through a top level tag of the input.
+fn walk_tag<F>(tag: &InputTag, mut f: F) -> CliResult<()>
+where
+    F: FnMut(&RawTagNode) -> CliResult<()>,
+{
+    for node in RawTagWalker::new(tag.raw) {
+        let node = node.map_err(|e| {
+            CliError::Failed(format!(
+                "corrupted data in the tag at offset {}: {}",
+                tag.offset, e
+            ))
+        })?;
+        f(&node)?;
+    }
+    Ok(())
+}
+
+fn cmd_tree(file: &str, out: &mut dyn Write) -> CliResult<()> {
+    for_each_tag(file, |tag| {
+        walk_tag(&tag, |node| {
+            let offset = node.offset();
+            let index = if node.depth() == 0 {
+                tag.index
+            } else {
+                node.index()
+            };
+            writeln!(
+                out,
+                "{:indent$}[{}] {} (id {}) at {}, {} bytes",
+                "",
+                index,
+                tag_name(offset.id()),
+                offset.id(),
+                tag.offset + offset.offset(),
+                offset.size(),
+                indent = node.depth() * 2
+            )
+            .map_err(failed)
+        })?;
+        Ok(true)
+    })
+}
+
+fn cmd_validate(file: &str, out: &mut dyn Write) -> CliResult<()> {
+    let options = ValidationOptions::new();
+    let mut count = 0;
+    for_each_tag(file, |tag| {
+        if let Err(e) = validate(tag.raw, &options) {
+            return Err(CliError::Failed(format!(
+                "invalid data in the tag at offset {}: {}",
+                tag.offset, e
+            )));
+        }
+        // The factory verifies the values of the standard tags. Unknown
+        // tags are accepted as they may be defined by the application.
+        parse_binary(tag.raw, tag.offset)?;
+        count += 1;
+        Ok(true)
+    })?;
+    writeln!(out, "OK: {} tag(s)", count).map_err(failed)
+}
+
+/// Parses the input according to its format. Binary inputs may contain
+/// multiple tags.
+fn parse_tags(input: &[u8], format: Format) -> CliResult<Vec<Box<dyn ILTag>>> {
+    match format {
+        Format::Binary => parse_binary(input, 0),
+        Format::Json | Format::Text => {
+            let input = std::str::from_utf8(input).map_err(failed)?;
+            let tag = if format == Format::Json {
+                from_json(input)
+            } else {
+                from_text(&ILStandardTagFactory::new(false), input)
+            };
+            match tag {
+                Ok(tag) => Ok(vec![tag]),
+                Err(e) => Err(failed(e)),
+            }
+        }
+    }
+}
+
+/// Deserializes a sequence of binary tags.
+///
+/// Arguments:
+/// - `input`: The serialized tags;
+/// - `base_offset`: The offset of `input` in the file, used by the error
+///   messages;
+fn parse_binary(input: &[u8], base_offset: u64) -> CliResult<Vec<Box<dyn ILTag>>> {
+    let factory = ILStandardTagFactory::new(false);
+    let mut reader = ByteArrayReader::new(input);
+    let mut tags = Vec::new();
+    while reader.available() > 0 {
+        let offset = base_offset + reader.offset() as u64;
+        match factory.deserialize_with_context(&mut reader) {
+            Ok(tag) => tags.push(tag),
+            Err(e) => {
+                return Err(CliError::Failed(format!(
+                    "invalid tag at offset {}: {}",
+                    offset, e
+                )))
+            }
+        }
+    }
+    Ok(tags)
+}
+
+/// Converts the input. Binary inputs are converted one top level tag at a
+/// time.
+fn cmd_convert(file: &str, options: &Options, out: &mut dyn Write) -> CliResult<()> {
+    if options.from != Format::Binary {
+        let tags = parse_tags(&read_input(file)?, options.from)?;
+        return write_tags(&tags, options.to, options.pretty, out);
+    }
+    let mut writer = BufferedWriter::new(out);
+    for_each_tag(file, |tag| {
+        for t in parse_binary(tag.raw, tag.offset)? {
+            write_tag(t.as_ref(), options.to, options.pretty, &mut writer)?;
+        }
+        Ok(true)
+    })?;
+    writer.flush().map_err(failed)
+}
+
+/// Writes the tags in the given format. JSON and text outputs contain one
+/// tag per line unless `pretty` is set.
+fn write_tags(
+    tags: &[Box<dyn ILTag>],
+    format: Format,
+    pretty: bool,
+    out: &mut dyn Write,
+) -> CliResult<()> {
+    let mut writer = BufferedWriter::new(out);
+    for tag in tags {
+        write_tag(tag.as_ref(), format, pretty, &mut writer)?;
+    }
+    writer.flush().map_err(failed)
+}
+
+/// Writes a single tag. See [`write_tags()`] for details.
+fn write_tag(
+    tag: &dyn ILTag,
+    format: Format,
+    pretty: bool,
+    writer: &mut BufferedWriter,
+) -> CliResult<()> {
+    match format {
+        Format::Binary => tag.serialize(writer).map_err(failed),
+        Format::Json => {
+            write_json(tag, pretty, writer).map_err(failed)?;
+            writer.write_all(b"\n").map_err(failed)
+        }
+        Format::Text => {
+            let text = if pretty {
+                to_text_pretty(tag)
+            } else {
+                to_text(tag)
+            };
+            let text = text.map_err(failed)?;
+            writer.write_all(text.as_bytes()).map_err(failed)?;
+            writer.write_all(b"\n").map_err(failed)
+        }
+    }
+}
+
+/// Reads the headers of all tags inside `raw`.
+fn scan_tags(raw: &[u8]) -> CliResult<Vec<RawTagOffset>> {
+    let mut reader = ByteArrayReader::new(raw);
+    RawTagScanner::new(&mut reader)
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| CliError::Failed(format!("corrupted data: {}", e)))
+}
+
+/// Finds the child of a standard container.
+///
+/// Arguments:
+/// - `tag`: The serialized container;
+/// - `segment`: The key of the child in dictionaries or its index in the
+///   other containers;
+///
+/// Returns the serialized child.
+fn find_child<'a>(tag: &'a [u8], segment: &str) -> CliResult<&'a [u8]> {
+    let offset = match scan_tags(tag)?.first() {
+        Some(offset) => *offset,
+        None => return Err(CliError::Failed(String::from("tag expected"))),
+    };
+    let layout = match standard_container_layout(offset.id()) {
+        Some(layout) => layout,
+        None => {
+            return Err(CliError::Failed(format!(
+                "{} is not a container",
+                tag_name(offset.id())
+            )))
+        }
+    };
+    let value = offset.value_slice(tag);
+    let (prefix_size, _) = layout.parse_prefix(value).map_err(failed)?;
+    let children = &value[prefix_size as usize..];
+    let offsets = scan_tags(children)?;
+    let found = match offset.id() {
+        IL_DICTIONARY_TAG_ID | IL_STRING_DICTIONARY_TAG_ID => offsets
+            .chunks(2)
+            .find(|e| e[0].value_slice(children) == segment.as_bytes())
+            .and_then(|e| e.get(1)),
+        _ => match segment.parse::<usize>() {
+            Ok(i) => offsets.get(i),
+            Err(_) => return Err(CliError::Failed(format!("invalid index '{}'", segment))),
+        },
+    };
+    match found {
+        Some(child) => Ok(child.tag_slice(children)),
+        None => Err(CliError::Failed(format!("'{}' not found", segment))),
+    }
+}
+
+fn cmd_extract(
+    file: &str,
+    path: &str,
+    format: Format,
+    pretty: bool,
+    out: &mut dyn Write,
+) -> CliResult<()> {
+    let mut segments = path.split('/').filter(|s| !s.is_empty());
+    let index = match segments.next().map(|s| s.parse::<u64>()) {
+        Some(Ok(index)) => index,
+        _ => return Err(CliError::Usage(format!("invalid path '{}'", path))),
+    };
+    // The input is read only up to the selected top level tag.
+    let mut found = false;
+    for_each_tag(file, |top| {
+        if top.index < index {
+            return Ok(true);
+        }
+        found = true;
+        let mut tag = top.raw;
+        for segment in segments.by_ref() {
+            tag = find_child(tag, segment)?;
+        }
+        match format {
+            Format::Binary => out.write_all(tag).map_err(failed)?,
+            _ => write_tags(&parse_tags(tag, Format::Binary)?, format, pretty, out)?,
+        }
+        Ok(false)
+    })?;
+    if found {
+        Ok(())
+    } else {
+        Err(CliError::Failed(format!("'{}' not found", index)))
+    }
+}
+
+fn cmd_stats(file: &str, out: &mut dyn Write) -> CliResult<()> {
+    let mut stats: BTreeMap<u64, (u64, u64)> = BTreeMap::new();
+    let mut count = 0;
+    let mut top_level = 0;
+    let mut max_depth = 0;
+    let mut bytes = 0;
+    for_each_tag(file, |tag| {
+        top_level += 1;
+        bytes += tag.raw.len();
+        walk_tag(&tag, |node| {
+            let entry = stats.entry(node.offset().id()).or_insert((0, 0));
+            entry.0 += 1;
+            entry.1 += node.offset().size();
+            count += 1;
+            max_depth = std::cmp::max(max_depth, node.depth());
+            Ok(())
+        })?;
+        Ok(true)
+    })?;
+    let mut s = format!(
+        "tags: {}\ntop level tags: {}\nmax depth: {}\nbytes: {}\n\n",
+        count, top_level, max_depth, bytes
+    );
+    s.push_str(&format!(
+        "{:>8}  {:<16}  {:>10}  {:>12}\n",
+        "id", "type", "count", "bytes"
+    ));
+    for (id, (count, size)) in stats {
+        s.push_str(&format!(
+            "{:>8}  {:<16}  {:>10}  {:>12}\n",
+            id,
+            tag_name(id),
+            count,
+            size
+        ));
+    }
+    out.write_all(s.as_bytes()).map_err(failed)
+}
+
+fn main() {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let stdout = std::io::stdout();
+    let mut out = stdout.lock();
+    let ret = run(&args, &mut out);
+    if let Err(e) = out.flush() {
+        eprintln!("iltag: {}", e);
+        std::process::exit(1);
+    }
+    match ret {
+        Ok(()) => (),
+        Err(CliError::Usage(msg)) => {
+            eprintln!("iltag: {}\n\n{}", msg, USAGE);
+            std::process::exit(2);
+        }
+        Err(CliError::Failed(msg)) => {
+            eprintln!("iltag: {}", msg);
+            std::process::exit(1);
+        }
+    }
+}
@@ -0,0 +1,266 @@
+/*
+ * BSD 3-Clause License
+ *
+ * Copyright (c) 2020, InterlockLedger Network
+ * All rights reserved.
+ *
+ * Redistribution and use in source and binary forms, with or without
+ * modification, are permitted provided that the following conditions are met:
+ *
+ * * Redistributions of source code must retain the above copyright notice, this
+ *   list of conditions and the following disclaimer.
+ *
+ * * Redistributions in binary form must reproduce the above copyright notice,
+ *   this list of conditions and the following disclaimer in the documentation
+ *   and/or other materials provided with the distribution.
+ *
+ * * Neither the name of the copyright holder nor the names of its
+ *   contributors may be used to endorse or promote products derived from
+ *   this software without specific prior written permission.
+ *
+ * THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS"
+ * AND ANY EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE
+ * IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+ * DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE
+ * FOR ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL, EXEMPLARY, OR CONSEQUENTIAL
+ * DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+ * SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER
+ * CAUSED AND ON ANY THEORY OF LIABILITY, WHETHER IN CONTRACT, STRICT LIABILITY,
+ * OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE
+ * OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+ */
+use super::*;
+use il2_iltags::tags::text::text_to_bytes;
+
+const SAMPLE: &str = r#"dict{ "a": array[ u8 1, str"x" ], "b": #1234 h'ff' }"#;
+
+/// Writes the data into a temporary file and returns its name.
+fn temp_file(name: &str, data: &[u8]) -> String {
+    let mut path = std::env::temp_dir();
+    path.push(format!("iltag-{}-{}", std::process::id(), name));
+    std::fs::write(&path, data).unwrap();
+    path.to_str().unwrap().to_string()
+}
+
+fn run_args(args: &[&str]) -> (CliResult<()>, String) {
+    let args: Vec<String> = args.iter().map(|s| s.to_string()).collect();
+    let mut out = Vec::new();
+    let ret = run(&args, &mut out);
+    (ret, String::from_utf8_lossy(&out).into_owned())
+}
+
+fn sample_file(name: &str) -> String {
+    let mut raw = text_to_bytes(SAMPLE).unwrap();
+    raw.extend_from_slice(&text_to_bytes("u16 7").unwrap());
+    temp_file(name, &raw)
+}
+
+#[test]
+fn test_run_usage() {
+    assert!(matches!(run_args(&[]).0, Err(CliError::Usage(_))));
+    assert!(matches!(run_args(&["x"]).0, Err(CliError::Usage(_))));
+    assert!(matches!(
+        run_args(&["dump", "--x"]).0,
+        Err(CliError::Usage(_))
+    ));
+    assert!(matches!(
+        run_args(&["dump", "--pretty"]).0,
+        Err(CliError::Usage(_))
+    ));
+    assert!(matches!(
+        run_args(&["dump", "a", "b"]).0,
+        Err(CliError::Usage(_))
+    ));
+    assert!(matches!(
+        run_args(&["convert", "--to"]).0,
+        Err(CliError::Usage(_))
+    ));
+    assert!(matches!(
+        run_args(&["convert", "--to", "xml"]).0,
+        Err(CliError::Usage(_))
+    ));
+    assert!(matches!(run_args(&["extract"]).0, Err(CliError::Usage(_))));
+    let (ret, out) = run_args(&["help"]);
+    assert_eq!(ret, Ok(()));
+    assert_eq!(out, USAGE);
+    assert!(matches!(
+        run_args(&["dump", "/nonexistent/file"]).0,
+        Err(CliError::Failed(_))
+    ));
+}
+
+#[test]
+fn test_run_dump() {
+    let file = sample_file("dump");
+    let (ret, out) = run_args(&["dump", &file]);
+    assert_eq!(ret, Ok(()));
+    assert_eq!(
+        out,
+        RawTagDumper::new().dump(&std::fs::read(&file).unwrap())
+    );
+}
+
+#[test]
+fn test_run_tree() {
+    let file = sample_file("tree");
+    let (ret, out) = run_args(&["tree", &file]);
+    assert_eq!(ret, Ok(()));
+    assert_eq!(
+        out,
+        "\
+[0] dict (id 30) at 0, 22 bytes
+  [0] string (id 17) at 3, 3 bytes
+  [1] array (id 21) at 6, 8 bytes
+    [0] uint8 (id 3) at 9, 2 bytes
+    [1] string (id 17) at 11, 3 bytes
+  [2] string (id 17) at 14, 3 bytes
+  [3] tag(1234) (id 1234) at 17, 5 bytes
+[1] uint16 (id 5) at 22, 3 bytes
+"
+    );
+}
+
+#[test]
+fn test_run_tree_truncated() {
+    let mut raw = text_to_bytes(SAMPLE).unwrap();
+    raw.extend_from_slice(&[0x11, 0x02, 0x61]);
+    let file = temp_file("tree-truncated", &raw);
+    let (ret, out) = run_args(&["tree", &file]);
+    assert!(matches!(ret, Err(CliError::Failed(_))));
+    // The complete tags are listed before the error
+    assert_eq!(out.lines().count(), 7);
+}
+
+#[test]
+fn test_run_validate() {
+    let file = sample_file("validate");
+    let (ret, out) = run_args(&["validate", &file]);
+    assert_eq!(ret, Ok(()));
+    assert_eq!(out, "OK: 2 tag(s)\n");
+
+    // Structural error
+    let file = temp_file("validate-bad", &[0x11, 0x02, 0x61]);
+    assert!(matches!(
+        run_args(&["validate", &file]).0,
+        Err(CliError::Failed(_))
+    ));
+
+    // Invalid boolean
+    let file = temp_file("validate-bool", &[0x01, 0x02]);
+    assert!(matches!(
+        run_args(&["validate", &file]).0,
+        Err(CliError::Failed(_))
+    ));
+}
+
+#[test]
+fn test_run_convert() {
+    let file = sample_file("convert");
+    let (ret, text) = run_args(&["convert", &file]);
+    assert_eq!(ret, Ok(()));
+    assert_eq!(text, format!("{}\nu16 7\n", SAMPLE));
+
+    let (ret, json) = run_args(&["convert", "--to", "json", &file]);
+    assert_eq!(ret, Ok(()));
+    assert_eq!(json.lines().count(), 2);
+
+    // JSON to text
+    let json_file = temp_file("convert.json", json.lines().next().unwrap().as_bytes());
+    let (ret, out) = run_args(&["convert", "--from", "json", "--to", "text", &json_file]);
+    assert_eq!(ret, Ok(()));
+    assert_eq!(out, format!("{}\n", SAMPLE));
+
+    // Text to binary
+    let text_file = temp_file("convert.txt", SAMPLE.as_bytes());
+    let args: Vec<String> = ["convert", "--from", "text", "--to", "bin", &text_file]
+        .iter()
+        .map(|s| s.to_string())
+        .collect();
+    let mut out = Vec::new();
+    assert_eq!(run(&args, &mut out), Ok(()));
+    assert_eq!(out, text_to_bytes(SAMPLE).unwrap());
+
+    // Pretty
+    let (ret, out) = run_args(&["convert", "--pretty", "--from", "text", &text_file]);
+    assert_eq!(ret, Ok(()));
+    assert_eq!(out.lines().count(), 7);
+
+    // Errors
+    let bad_file = temp_file("convert-bad.txt", b"u8 256");
+    match run_args(&["convert", "--from", "text", &bad_file]).0 {
+        Err(CliError::Failed(msg)) => assert_eq!(msg, "invalid integer at offset 3"),
+        _ => panic!("Error expected."),
+    }
+    let bad_file = temp_file("convert-bad.bin", &[0x11, 0x02, 0x61]);
+    assert!(matches!(
+        run_args(&["convert", &bad_file]).0,
+        Err(CliError::Failed(_))
+    ));
+}
+
+#[test]
+fn test_run_extract() {
+    let file = sample_file("extract");
+    let (ret, out) = run_args(&["extract", "1", &file]);
+    assert_eq!(ret, Ok(()));
+    assert_eq!(out, "u16 7\n");
+
+    let (ret, out) = run_args(&["extract", "0/a/1", &file]);
+    assert_eq!(ret, Ok(()));
+    assert_eq!(out, "str\"x\"\n");
+
+    let (ret, out) = run_args(&["extract", "--to", "json", "/0/b", &file]);
+    assert_eq!(ret, Ok(()));
+    assert_eq!(out, "{\"type\":\"raw\",\"id\":1234,\"value\":\"ff\"}\n");
+
+    assert!(matches!(
+        run_args(&["extract", "x", &file]).0,
+        Err(CliError::Usage(_))
+    ));
+    // The input is read only up to the selected tag
+    let mut raw = text_to_bytes(SAMPLE).unwrap();
+    raw.extend_from_slice(&[0x11, 0x02, 0x61]);
+    let truncated = temp_file("extract-truncated", &raw);
+    let (ret, out) = run_args(&["extract", "0/a/0", &truncated]);
+    assert_eq!(ret, Ok(()));
+    assert_eq!(out, "u8 1\n");
+    match run_args(&["extract", "1", &truncated]).0 {
+        Err(CliError::Failed(msg)) => assert!(msg.starts_with("corrupted data at offset 22")),
+        _ => panic!("Error expected."),
+    }
+
+    for path in ["2", "0/c", "0/a/2", "0/a/x", "0/a/0/0"] {
+        assert!(
+            matches!(
+                run_args(&["extract", path, &file]).0,
+                Err(CliError::Failed(_))
+            ),
+            "{}",
+            path
+        );
+    }
+}
+
+#[test]
+fn test_run_stats() {
+    let file = sample_file("stats");
+    let (ret, out) = run_args(&["stats", &file]);
+    assert_eq!(ret, Ok(()));
+    assert_eq!(
+        out,
+        "\
+tags: 8
+top level tags: 2
+max depth: 2
+bytes: 25
+
+      id  type                   count         bytes
+       3  uint8                      1             2
+       5  uint16                     1             3
+      17  string                     3             9
+      21  array                      1             8
+      30  dict                       1            22
+    1234  tag(1234)                  1             5
+"
+    );
+}
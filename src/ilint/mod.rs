@@ -42,16 +42,51 @@ mod tests;
 use super::io::{Reader, Writer};
 
 /// Error codes generated by this module.
+///
+/// Since 2.0.0 this enum is marked as `#[non_exhaustive]`, so new errors
+/// can be added without breaking existing code.
 #[derive(Debug)]
+#[non_exhaustive]
 pub enum ErrorKind {
     /// The encoded value is larger than 2^64 - 1.
     ValueOverflow,
     /// In case of an invalid format.
     InvalidFormat,
+    /// The value was not encoded using the shortest possible form.
+    /// New since 2.0.0.
+    NonCanonical,
     /// I/O error.
     IOError(crate::io::ErrorKind),
 }
 
+impl std::fmt::Display for ErrorKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ErrorKind::ValueOverflow => write!(f, "ILInt value overflow"),
+            ErrorKind::InvalidFormat => write!(f, "invalid ILInt format"),
+            ErrorKind::NonCanonical => write!(f, "ILInt value is not in its canonical form"),
+            ErrorKind::IOError(_) => write!(f, "I/O error while handling an ILInt value"),
+        }
+    }
+}
+
+/// New since 2.0.0.
+impl std::error::Error for ErrorKind {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            ErrorKind::IOError(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+/// New since 2.0.0.
+impl From<crate::io::ErrorKind> for ErrorKind {
+    fn from(error: crate::io::ErrorKind) -> Self {
+        ErrorKind::IOError(error)
+    }
+}
+
 /// A specialized [`std::result::Result`] generated by functions and methods from this package.
 pub type Result<T> = std::result::Result<T, ErrorKind>;
 
@@ -101,11 +136,11 @@ pub fn encode(value: u64, writer: &mut dyn Writer) -> Result<()> {
         }
     } else {
         let mut tmp: [u8; 9] = [0; 9];
-        tmp[0] = (ILINT_BASE + ((size - 2) as u8)) as u8;
+        tmp[0] = ILINT_BASE + ((size - 2) as u8);
         let mut v = value - ILINT_BASE_U64;
         for i in (1..size).rev() {
             tmp[i] = (v & 0xFF) as u8;
-            v = v >> 8;
+            v >>= 8;
         }
         match writer.write_all(&tmp[..size]) {
             Ok(()) => (),
@@ -189,7 +224,7 @@ pub fn decode_from_bytes(value: &[u8]) -> Result<(u64, usize)> {
 ///
 /// Arguments:
 /// * `reader`: The reader that contains the encoded
-/// value;
+///   value;
 ///
 /// Returns:
 /// * `Ok(u64)`: On success, returns the value read.
@@ -212,6 +247,80 @@ pub fn decode(reader: &mut dyn Reader) -> Result<u64> {
     }
 }
 
+/// Decodes the body of a multi-byte **ILInt** and ensures that it is
+/// encoded in its canonical form, in other words, using the smallest
+/// possible number of bytes.
+///
+/// Arguments:
+/// * `body`: The multibyte **ILInt** value.
+///
+/// Returns:
+/// * `Ok(u64)`: The value of the ILInt.
+/// * `Err(ErrorKind::NonCanonical)`: If the value is not in its canonical form.
+/// * `Err(ErrorKind)`: In case of error.
+///
+/// New since 2.0.0.
+pub fn canonical_decode_body(body: &[u8]) -> Result<u64> {
+    let v = decode_body(body)?;
+    if encoded_size(v) != body.len() + 1 {
+        Err(ErrorKind::NonCanonical)
+    } else {
+        Ok(v)
+    }
+}
+
+/// Decodes an **ILInt** from a byte slice and ensures that it is encoded
+/// in its canonical form.
+///
+/// Arguments:
+/// * `value`: The **ILInt** value.
+///
+/// Returns:
+/// * `Ok((u64,usize))`: The value of the **ILInt** and the number of bytes used.
+/// * `Err(ErrorKind::NonCanonical)`: If the value is not in its canonical form.
+/// * `Err(ErrorKind)`: In case of error.
+///
+/// New since 2.0.0.
+pub fn canonical_decode_from_bytes(value: &[u8]) -> Result<(u64, usize)> {
+    let (v, size) = decode_from_bytes(value)?;
+    if encoded_size(v) != size {
+        Err(ErrorKind::NonCanonical)
+    } else {
+        Ok((v, size))
+    }
+}
+
+/// Decodes an **ILInt** value and ensures that it is encoded in its
+/// canonical form.
+///
+/// Arguments:
+/// * `reader`: The reader that contains the encoded
+///   value;
+///
+/// Returns:
+/// * `Ok(u64)`: On success, returns the value read.
+/// * `Err(ErrorKind::NonCanonical)`: If the value is not in its canonical form.
+/// * `Err(ErrorKind)`: In case of error.
+///
+/// New since 2.0.0.
+pub fn canonical_decode(reader: &mut dyn Reader) -> Result<u64> {
+    let header = match reader.read() {
+        Ok(v) => v,
+        Err(e) => return Err(ErrorKind::IOError(e)),
+    };
+    let size = decoded_size(header);
+    if size == 1 {
+        Ok(header as u64)
+    } else {
+        let mut tmp: [u8; 8] = [0; 8];
+        match reader.read_all(&mut tmp[0..size - 1]) {
+            Ok(()) => (),
+            Err(e) => return Err(ErrorKind::IOError(e)),
+        }
+        canonical_decode_body(&tmp[0..size - 1])
+    }
+}
+
 /// Encodes a signed value into an unsigned value suitable
 /// to be encoded as **ILInt**.
 ///
@@ -289,7 +398,7 @@ pub fn signed_encode(value: i64, writer: &mut dyn Writer) -> Result<()> {
 ///
 /// Arguments:
 /// * `reader`: The reader that contains the encoded
-/// value;
+///   value;
 ///
 /// Returns:
 /// * `Ok(i64)`: On success, returns the value read.
@@ -300,3 +409,21 @@ pub fn signed_decode(reader: &mut dyn Reader) -> Result<i64> {
     let tmp = decode(reader)?;
     Ok(decode_sign(tmp))
 }
+
+/// Decodes a signed **ILInt** value and ensures that it is encoded in its
+/// canonical form.
+///
+/// Arguments:
+/// * `reader`: The reader that contains the encoded
+///   value;
+///
+/// Returns:
+/// * `Ok(i64)`: On success, returns the value read.
+/// * `Err(ErrorKind::NonCanonical)`: If the value is not in its canonical form.
+/// * `Err(ErrorKind)`: In case of error.
+///
+/// New since 2.0.0.
+pub fn signed_canonical_decode(reader: &mut dyn Reader) -> Result<i64> {
+    let tmp = canonical_decode(reader)?;
+    Ok(decode_sign(tmp))
+}
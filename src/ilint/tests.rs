@@ -36,6 +36,7 @@ use crate::io::array::{ByteArrayReader, MemoryReader, VecWriter};
 fn test_errorkind_debug() {
     assert_eq!(format!("{:?}", ErrorKind::InvalidFormat), "InvalidFormat");
     assert_eq!(format!("{:?}", ErrorKind::ValueOverflow), "ValueOverflow");
+    assert_eq!(format!("{:?}", ErrorKind::NonCanonical), "NonCanonical");
     assert_eq!(
         format!(
             "{:?}",
@@ -45,6 +46,36 @@ fn test_errorkind_debug() {
     );
 }
 
+#[test]
+fn test_errorkind_error() {
+    use std::error::Error;
+
+    assert_eq!(
+        format!("{}", ErrorKind::InvalidFormat),
+        "invalid ILInt format"
+    );
+    assert_eq!(
+        format!("{}", ErrorKind::ValueOverflow),
+        "ILInt value overflow"
+    );
+    assert_eq!(
+        format!("{}", ErrorKind::NonCanonical),
+        "ILInt value is not in its canonical form"
+    );
+    assert!(ErrorKind::InvalidFormat.source().is_none());
+
+    let e: ErrorKind = crate::io::ErrorKind::EndOfData.into();
+    assert!(matches!(
+        e,
+        ErrorKind::IOError(crate::io::ErrorKind::EndOfData)
+    ));
+    assert_eq!(format!("{}", e), "I/O error while handling an ILInt value");
+    assert_eq!(format!("{}", e.source().unwrap()), "end of data");
+
+    fn assert_send_sync<T: Send + Sync + 'static>() {}
+    assert_send_sync::<ErrorKind>();
+}
+
 pub struct SampleILInt {
     pub value: u64,
     pub encoded_size: usize,
@@ -177,7 +208,7 @@ fn test_encode() {
 
         let mut writer = VecWriter::new();
         writer.set_read_only(true);
-        assert!(encode(sample.value as u64, &mut writer).is_err());
+        assert!(encode(sample.value, &mut writer).is_err());
     }
 }
 
@@ -308,6 +339,126 @@ fn test_decode() {
     }
 }
 
+/// Non canonical encodings. They are the smallest and the largest values that
+/// can be encoded in a shorter form using more bytes than necessary.
+const NON_CANONICAL_SAMPLES: [&[u8]; 8] = [
+    &[0xF9, 0x00, 0x00],
+    &[0xF9, 0x00, 0xFF],
+    &[0xFA, 0x00, 0xFF, 0xFF],
+    &[0xFB, 0x00, 0xFF, 0xFF, 0xFF],
+    &[0xFC, 0x00, 0xFF, 0xFF, 0xFF, 0xFF],
+    &[0xFD, 0x00, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF],
+    &[0xFE, 0x00, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF],
+    &[0xFF, 0x00, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF],
+];
+
+#[test]
+fn test_canonical_decode_body() {
+    for sample in &SAMPLE_VALUES {
+        let enc_size = sample.encoded_size;
+        if enc_size > 1 {
+            match canonical_decode_body(&sample.encoded[1..enc_size]) {
+                Ok(v) => assert_eq!(v, sample.value),
+                _ => panic!(),
+            }
+        }
+    }
+    for sample in &NON_CANONICAL_SAMPLES {
+        assert!(decode_body(&sample[1..]).is_ok());
+        match canonical_decode_body(&sample[1..]) {
+            Err(ErrorKind::NonCanonical) => (),
+            _ => panic!(),
+        }
+    }
+
+    let sample: [u8; 9] = [0; 9];
+    match canonical_decode_body(&sample[0..0]) {
+        Err(ErrorKind::InvalidFormat) => (),
+        _ => panic!(),
+    }
+    match canonical_decode_body(&sample) {
+        Err(ErrorKind::InvalidFormat) => (),
+        _ => panic!(),
+    }
+}
+
+#[test]
+fn test_canonical_decode_from_bytes() {
+    for sample in &SAMPLE_VALUES {
+        match canonical_decode_from_bytes(&sample.encoded) {
+            Ok((v, size)) => {
+                assert_eq!(v, sample.value);
+                assert_eq!(size, sample.encoded_size);
+            }
+            _ => panic!(),
+        }
+        if sample.encoded_size > 1 {
+            match canonical_decode_from_bytes(&sample.encoded[0..sample.encoded_size - 1]) {
+                Err(ErrorKind::InvalidFormat) => (),
+                _ => panic!(),
+            }
+        }
+    }
+    for sample in &NON_CANONICAL_SAMPLES {
+        assert!(decode_from_bytes(sample).is_ok());
+        match canonical_decode_from_bytes(sample) {
+            Err(ErrorKind::NonCanonical) => (),
+            _ => panic!(),
+        }
+    }
+}
+
+#[test]
+fn test_canonical_decode() {
+    // All with 1 byte
+    for i in 0..0xF8 {
+        let buff: [u8; 1] = [i as u8];
+        let mut reader = ByteArrayReader::new(&buff);
+        match canonical_decode(&mut reader) {
+            Ok(v) => {
+                assert_eq!(v, i as u64);
+                assert_eq!(reader.offset(), 1);
+            }
+            _ => panic!(),
+        }
+    }
+
+    // From samples
+    for sample in &SAMPLE_VALUES {
+        let enc_size = sample.encoded_size;
+        let mut reader = ByteArrayReader::new(&sample.encoded[0..enc_size]);
+        match canonical_decode(&mut reader) {
+            Ok(v) => {
+                assert_eq!(v, sample.value);
+                assert_eq!(reader.offset(), enc_size);
+            }
+            _ => panic!(),
+        }
+        let mut reader = ByteArrayReader::new(&sample.encoded[0..enc_size - 1]);
+        match canonical_decode(&mut reader) {
+            Err(ErrorKind::IOError(_)) => (),
+            _ => panic!(),
+        }
+    }
+
+    // Non canonical
+    for sample in &NON_CANONICAL_SAMPLES {
+        let mut reader = ByteArrayReader::new(sample);
+        match canonical_decode(&mut reader) {
+            Err(ErrorKind::NonCanonical) => (),
+            _ => panic!(),
+        }
+    }
+
+    // Overflow!
+    let encoded: [u8; 9] = [0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0x08];
+    let mut reader = ByteArrayReader::new(&encoded);
+    match canonical_decode(&mut reader) {
+        Err(ErrorKind::ValueOverflow) => (),
+        _ => panic!(),
+    }
+}
+
 #[test]
 fn test_encode_sign() {
     // Unsigned
@@ -406,3 +557,24 @@ fn test_signed_decode() {
         };
     }
 }
+
+#[test]
+fn test_signed_canonical_decode() {
+    for s in SIGNED_SAMPLES {
+        let mut exp = VecWriter::new();
+        assert!(encode(encode_sign(s), &mut exp).is_ok());
+
+        let mut reader = ByteArrayReader::new(exp.as_slice());
+        match signed_canonical_decode(&mut reader) {
+            Ok(v) => assert_eq!(s, v),
+            _ => panic!("Reading error"),
+        };
+    }
+    for sample in &NON_CANONICAL_SAMPLES {
+        let mut reader = ByteArrayReader::new(sample);
+        match signed_canonical_decode(&mut reader) {
+            Err(ErrorKind::NonCanonical) => (),
+            _ => panic!(),
+        }
+    }
+}
@@ -208,7 +208,7 @@ impl VecReader {
     }
 }
 
-impl<'a> MemoryReader for VecReader {
+impl MemoryReader for VecReader {
     fn len(&self) -> usize {
         self.vector.len()
     }
@@ -226,7 +226,7 @@ impl<'a> MemoryReader for VecReader {
     }
 }
 
-impl<'a> Reader for VecReader {
+impl Reader for VecReader {
     fn read(&mut self) -> Result<u8> {
         self.can_read(1)?;
         let r = self.vector[self.offset];
@@ -258,7 +258,7 @@ macro_rules! basevecwriter_basic_impl {
         ///
         /// Returns:
         /// - The current offset. It is guaranteed to be at most
-        /// the total size of the data.
+        ///   the total size of the data.
         pub fn get_offset(&self) -> usize {
             self.offset
         }
@@ -292,7 +292,7 @@ macro_rules! basevecwriter_basic_impl {
         /// Returns:
         /// - `Ok(())`: If it is possible to write;
         /// - `Err(ErrorKind::UnableToWriteData)`: If it is not possible
-        /// to write;
+        ///   to write;
         pub fn can_write(&self) -> Result<()> {
             if self.read_only {
                 Err(ErrorKind::UnableToWriteData)
@@ -479,7 +479,7 @@ impl<'a> ByteArrayWriter<'a> {
     ///
     /// Returns:
     /// - The current offset. It is guaranteed to be at most
-    /// the total size of the data.
+    ///   the total size of the data.
     pub fn get_offset(&self) -> usize {
         self.offset
     }
@@ -171,9 +171,9 @@ fn test_bytearrayreader_reader_read() {
     let mut src = [0u8; 8];
     fill_sample(&mut src);
     let mut ba = ByteArrayReader::new(&src);
-    for i in 0..src.len() {
+    for exp in src.iter() {
         match ba.read() {
-            Ok(v) => assert_eq!(v, src[i]),
+            Ok(v) => assert_eq!(v, *exp),
             _ => panic!("Unexpected read error!"),
         }
     }
@@ -306,9 +306,9 @@ fn test_vecreader_reader_read() {
     let mut src = [0u8; 8];
     fill_sample(&mut src);
     let mut ba = VecReader::new(&src);
-    for i in 0..src.len() {
+    for exp in src.iter() {
         match ba.read() {
-            Ok(v) => assert_eq!(v, src[i]),
+            Ok(v) => assert_eq!(v, *exp),
             _ => panic!("Unexpected read error!"),
         }
     }
@@ -622,7 +622,7 @@ fn test_borrowedvecwriter_writer_write_all() {
     let mut src2 = [0u8; 8];
     fill_sample(&mut src2);
 
-    let mut count = 0 as usize;
+    let mut count = 0_usize;
     match w.write_all(&src2) {
         Ok(_) => (),
         _ => panic!("Unexpected read_all error!"),
@@ -643,11 +643,10 @@ fn test_borrowedvecwriter_writer_write_all() {
     assert_eq!(exp, w.as_slice());
 
     // With a vector with a given size
-    let mut vec: Vec<u8> = Vec::new();
-    vec.resize(10, 0);
+    let mut vec: Vec<u8> = vec![0; 10];
     let mut w = BorrowedVecWriter::new(&mut vec);
 
-    let mut count = 0 as usize;
+    let mut count = 0_usize;
     match w.write_all(&src2) {
         Ok(_) => (),
         _ => panic!("Unexpected read_all error!"),
@@ -679,7 +678,6 @@ fn test_bytearraywriter_impl_new() {
     let w = ByteArrayWriter::new(&mut backend);
     assert_eq!(w.get_offset(), 0);
     assert_eq!(w.available(), 16);
-    drop(w);
 }
 
 #[test]
@@ -738,7 +736,7 @@ fn test_bytearraywriter_writer_write() {
     let mut exp: [u8; 16] = [0; 16];
 
     let mut w = ByteArrayWriter::new(&mut backend);
-    for i in 0..16 as u8 {
+    for i in 0..16_u8 {
         assert_eq!(w.get_offset(), i as usize);
         w.write(i).unwrap();
         exp[i as usize] = i;
@@ -748,7 +746,6 @@ fn test_bytearraywriter_writer_write() {
         Err(ErrorKind::UnableToWriteData) => (),
         _ => panic!(),
     }
-    drop(w);
     assert_eq!(&backend, &exp);
 }
 
@@ -771,6 +768,5 @@ fn test_bytearraywriter_writer_all() {
         Err(ErrorKind::UnableToWriteData) => (),
         _ => panic!(),
     }
-    drop(w);
     assert_eq!(&backend, &exp);
 }
@@ -414,7 +414,7 @@ pub fn write_f64(v: f64, writer: &mut dyn Writer) -> Result<()> {
 /// - Ok(()): For success;
 /// - Err(_): For failure;
 pub fn write_string(v: &str, writer: &mut dyn Writer) -> Result<()> {
-    writer.write_all(&v.as_bytes())
+    writer.write_all(v.as_bytes())
 }
 
 //=============================================================================
@@ -149,7 +149,7 @@ fn test_float_data_reader_f32() {
     let r: &mut dyn Reader = &mut reader;
 
     match read_f32(r) {
-        Ok(v) => assert_eq!(3.14159274101257324, v),
+        Ok(v) => assert_eq!(std::f32::consts::PI, v),
         _ => panic!(),
     };
     assert!(read_f32(r).is_err());
@@ -162,7 +162,7 @@ fn test_float_data_reader_f64() {
     let r: &mut dyn Reader = &mut reader;
 
     match read_f64(r) {
-        Ok(v) => assert_eq!(3.1415926535897932384626433, v),
+        Ok(v) => assert_eq!(std::f64::consts::PI, v),
         _ => panic!(),
     };
     assert!(read_f64(r).is_err());
@@ -243,10 +243,7 @@ fn test_data_reader() {
         Err(_) => panic!(),
     }
 
-    match read_u8(dr) as Result<u8> {
-        Ok(_) => panic!(),
-        _ => (),
-    }
+    assert!(read_u8(dr).is_err());
 }
 
 macro_rules! test_int_data_writer_t {
@@ -334,11 +331,11 @@ fn test_float_data_writer_f32() {
 
     let exp: [u8; 8] = [0x40, 0x49, 0x0f, 0xdb, 0x40, 0x49, 0x0f, 0xdb];
 
-    match write_f32(3.14159274101257324 as f32, w) {
+    match write_f32(std::f32::consts::PI, w) {
         Ok(_) => (),
         Err(_) => panic!(),
     }
-    match write_f32(3.14159274101257324 as f32, w) {
+    match write_f32(std::f32::consts::PI, w) {
         Ok(_) => (),
         Err(_) => panic!(),
     }
@@ -354,11 +351,11 @@ fn test_float_data_writer_f64() {
         0x18,
     ];
 
-    match write_f64(3.1415926535897932384626433 as f64, w) {
+    match write_f64(std::f64::consts::PI, w) {
         Ok(_) => (),
         Err(_) => panic!(),
     }
-    match write_f64(3.1415926535897932384626433 as f64, w) {
+    match write_f64(std::f64::consts::PI, w) {
         Ok(_) => (),
         Err(_) => panic!(),
     }
@@ -456,7 +453,7 @@ fn test_data_writer() {
         Ok(_) => (),
         Err(_) => panic!(),
     }
-    match write_f32(SAMPLE_VALUES_08_F32 as f32, &mut writer) {
+    match write_f32(SAMPLE_VALUES_08_F32, &mut writer) {
         Ok(_) => (),
         Err(_) => panic!(),
     }
@@ -0,0 +1,161 @@
+/*
+ * BSD 3-Clause License
+ *
+ * Copyright (c) 2020, InterlockLedger Network
+ * All rights reserved.
+ *
+ * Redistribution and use in source and binary forms, with or without
+ * modification, are permitted provided that the following conditions are met:
+ *
+ * * Redistributions of source code must retain the above copyright notice, this
+ *   list of conditions and the following disclaimer.
+ *
+ * * Redistributions in binary form must reproduce the above copyright notice,
+ *   this list of conditions and the following disclaimer in the documentation
+ *   and/or other materials provided with the distribution.
+ *
+ * * Neither the name of the copyright holder nor the names of its
+ *   contributors may be used to endorse or promote products derived from
+ *   this software without specific prior written permission.
+ *
+ * THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS"
+ * AND ANY EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE
+ * IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+ * DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE
+ * FOR ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL, EXEMPLARY, OR CONSEQUENTIAL
+ * DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+ * SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER
+ * CAUSED AND ON ANY THEORY OF LIABILITY, WHETHER IN CONTRACT, STRICT LIABILITY,
+ * OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE
+ * OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+ */
+//! This module defines the [`Digest`] trait and the [`Reader`] and [`Writer`]
+//! wrappers that compute the digest of the bytes that pass through them. They
+//! allow the computation of the hash of serialized tags without holding the
+//! whole serialization in memory.
+//!
+//! The built-in SHA-256 and SHA-512 implementations are available in
+//! [`sha2`] when the feature `sha2` is enabled.
+//!
+//! New since 2.0.0.
+#[cfg(feature = "sha2")]
+pub mod sha2;
+#[cfg(test)]
+mod tests;
+
+use super::{Reader, Result, Writer};
+
+//=============================================================================
+// Digest
+//-----------------------------------------------------------------------------
+/// This trait must be implemented by all hash functions used by
+/// [`HashingWriter`], [`HashingReader`] and [`crate::tags::ILTag::digest()`].
+///
+/// New since 2.0.0.
+pub trait Digest {
+    /// Returns the size of the digest in bytes.
+    fn output_size(&self) -> usize;
+
+    /// Adds the given bytes to the digest.
+    ///
+    /// Arguments:
+    /// * `data`: The bytes to be hashed;
+    fn update(&mut self, data: &[u8]);
+
+    /// Returns the digest of all bytes added since the last reset and
+    /// resets this instance to its initial state.
+    ///
+    /// Returns:
+    /// * The digest with [`Self::output_size()`] bytes.
+    fn finalize(&mut self) -> Vec<u8>;
+
+    /// Resets this instance to its initial state.
+    fn reset(&mut self);
+}
+
+//=============================================================================
+// HashingWriter
+//-----------------------------------------------------------------------------
+/// The `HashingWriter` implements a [`Writer`] that wraps another [`Writer`]
+/// and adds all bytes successfully written into it to a [`Digest`].
+///
+/// It can be combined with [`crate::io::NullWriter`] in order to compute the
+/// digest without storing the data.
+///
+/// New since 2.0.0.
+pub struct HashingWriter<'a> {
+    dest: &'a mut dyn Writer,
+    digest: &'a mut dyn Digest,
+}
+
+impl<'a> HashingWriter<'a> {
+    /// Creates a new instance of this struct.
+    ///
+    /// Parameters:
+    /// * `dst`: A mutable reference to the destination Writer.
+    /// * `digest`: The digest that will receive the bytes.
+    pub fn new(dst: &'a mut dyn Writer, digest: &'a mut dyn Digest) -> HashingWriter<'a> {
+        HashingWriter { dest: dst, digest }
+    }
+}
+
+impl<'a> Writer for HashingWriter<'a> {
+    fn write(&mut self, value: u8) -> Result<()> {
+        self.dest.write(value)?;
+        self.digest.update(&[value]);
+        Ok(())
+    }
+
+    fn write_all(&mut self, buff: &[u8]) -> Result<()> {
+        self.dest.write_all(buff)?;
+        self.digest.update(buff);
+        Ok(())
+    }
+
+    fn as_writer(&mut self) -> &mut dyn Writer {
+        self
+    }
+}
+
+//=============================================================================
+// HashingReader
+//-----------------------------------------------------------------------------
+/// The `HashingReader` implements a [`Reader`] that wraps another [`Reader`]
+/// and adds all bytes successfully read from it to a [`Digest`].
+///
+/// The skipped bytes are also added to the digest, thus they are always read
+/// from the source.
+///
+/// New since 2.0.0.
+pub struct HashingReader<'a> {
+    source: &'a mut dyn Reader,
+    digest: &'a mut dyn Digest,
+}
+
+impl<'a> HashingReader<'a> {
+    /// Creates a new instance of this struct.
+    ///
+    /// Parameters:
+    /// * `src`: A mutable reference to the source Reader.
+    /// * `digest`: The digest that will receive the bytes.
+    pub fn new(src: &'a mut dyn Reader, digest: &'a mut dyn Digest) -> HashingReader<'a> {
+        HashingReader {
+            source: src,
+            digest,
+        }
+    }
+}
+
+impl<'a> Reader for HashingReader<'a> {
+    fn read(&mut self) -> Result<u8> {
+        let b = self.source.read()?;
+        self.digest.update(&[b]);
+        Ok(b)
+    }
+
+    fn read_all(&mut self, buff: &mut [u8]) -> Result<()> {
+        self.source.read_all(buff)?;
+        self.digest.update(buff);
+        Ok(())
+    }
+}
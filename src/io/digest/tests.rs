@@ -0,0 +1,170 @@
+/*
+ * BSD 3-Clause License
+ *
+ * Copyright (c) 2020, InterlockLedger Network
+ * All rights reserved.
+ *
+ * Redistribution and use in source and binary forms, with or without
+ * modification, are permitted provided that the following conditions are met:
+ *
+ * * Redistributions of source code must retain the above copyright notice, this
+ *   list of conditions and the following disclaimer.
+ *
+ * * Redistributions in binary form must reproduce the above copyright notice,
+ *   this list of conditions and the following disclaimer in the documentation
+ *   and/or other materials provided with the distribution.
+ *
+ * * Neither the name of the copyright holder nor the names of its
+ *   contributors may be used to endorse or promote products derived from
+ *   this software without specific prior written permission.
+ *
+ * THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS"
+ * AND ANY EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE
+ * IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+ * DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE
+ * FOR ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL, EXEMPLARY, OR CONSEQUENTIAL
+ * DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+ * SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER
+ * CAUSED AND ON ANY THEORY OF LIABILITY, WHETHER IN CONTRACT, STRICT LIABILITY,
+ * OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE
+ * OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+ */
+use super::*;
+use crate::io::array::{ByteArrayReader, ByteArrayWriter, VecWriter};
+use crate::tests::{fill_sample, RecordingDigest};
+
+//=============================================================================
+// HashingWriter
+//-----------------------------------------------------------------------------
+#[test]
+fn test_hashingwriter() {
+    let mut sample: [u8; 10] = [0; 10];
+    fill_sample(&mut sample);
+    let mut digest = RecordingDigest::default();
+    let mut inner = VecWriter::new();
+    let mut writer = HashingWriter::new(&mut inner, &mut digest);
+    writer.write(sample[0]).unwrap();
+    writer.write_all(&sample[1..5]).unwrap();
+    writer.as_writer().write_all(&sample[5..10]).unwrap();
+    assert_eq!(inner.as_slice(), &sample);
+    assert_eq!(digest.finalize(), &sample);
+
+    // Failed writes are not hashed
+    let mut buff: [u8; 4] = [0; 4];
+    let mut inner = ByteArrayWriter::new(&mut buff);
+    let mut writer = HashingWriter::new(&mut inner, &mut digest);
+    writer.write_all(&sample[0..3]).unwrap();
+    assert!(writer.write_all(&sample[3..5]).is_err());
+    writer.write(sample[3]).unwrap();
+    assert!(writer.write(sample[4]).is_err());
+    assert_eq!(digest.finalize(), &sample[0..4]);
+}
+
+//=============================================================================
+// HashingReader
+//-----------------------------------------------------------------------------
+#[test]
+fn test_hashingreader() {
+    let mut sample: [u8; 10] = [0; 10];
+    fill_sample(&mut sample);
+    let mut buff: [u8; 10] = [0; 10];
+    let mut digest = RecordingDigest::default();
+    let mut inner = ByteArrayReader::new(&sample);
+    let mut reader = HashingReader::new(&mut inner, &mut digest);
+    assert_eq!(reader.read().unwrap(), 0);
+    reader.read_all(&mut buff[1..4]).unwrap();
+    reader.skip(2).unwrap();
+    reader.read_all(&mut buff[6..9]).unwrap();
+
+    // Failed reads are not hashed
+    assert!(reader.read_all(&mut buff[0..2]).is_err());
+    assert_eq!(reader.read().unwrap(), 9);
+    assert!(reader.read().is_err());
+    assert_eq!(digest.finalize(), &sample);
+}
+
+//=============================================================================
+// Sha256 and Sha512
+//-----------------------------------------------------------------------------
+#[cfg(feature = "sha2")]
+mod sha2_tests {
+    use super::super::sha2::{Sha256, Sha512};
+    use super::super::Digest;
+    use crate::tags::util::hex_encode;
+
+    fn samples() -> Vec<Vec<u8>> {
+        let mut long: Vec<u8> = Vec::new();
+        for _ in 0..4 {
+            long.extend((0..=255).map(|x: u32| x as u8));
+        }
+        long.extend_from_slice(&[b'x'; 55]);
+        vec![
+            Vec::new(),
+            b"abc".to_vec(),
+            b"abcdbcdecdefdefgefghfghighijhijkijkljklmklmnlmnomnopnopq".to_vec(),
+            vec![b'a'; 1_000_000],
+            long,
+        ]
+    }
+
+    /// Hashes the sample in one step and in small chunks.
+    fn assert_digest(digest: &mut dyn Digest, sample: &[u8], expected: &str) {
+        digest.update(sample);
+        assert_eq!(hex_encode(&digest.finalize()), expected);
+        for chunk in sample.chunks(7) {
+            digest.update(chunk);
+        }
+        let ret = digest.finalize();
+        assert_eq!(ret.len(), digest.output_size());
+        assert_eq!(hex_encode(&ret), expected);
+    }
+
+    #[test]
+    fn test_sha256() {
+        let expected = [
+            "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855",
+            "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad",
+            "248d6a61d20638b8e5c026930c3e6039a33ce45964ff2167f6ecedd419db06c1",
+            "cdc76e5c9914fb9281a1c7e284d73e67f1809a48a497200e046d39ccc7112cd0",
+            "415c3b5c395cb8896358cda2f03a8c43395029c22ed119c0e76617d75c9b65e9",
+        ];
+        let mut digest = Sha256::new();
+        assert_eq!(digest.output_size(), Sha256::OUTPUT_SIZE);
+        for (sample, exp) in samples().iter().zip(expected.iter()) {
+            assert_digest(&mut digest, sample, exp);
+        }
+
+        // Reset
+        digest.update(b"xyz");
+        digest.reset();
+        digest.update(b"abc");
+        assert_eq!(hex_encode(&digest.finalize()), expected[1]);
+    }
+
+    #[test]
+    fn test_sha512() {
+        let expected = [
+            "cf83e1357eefb8bdf1542850d66d8007d620e4050b5715dc83f4a921d36ce9ce\
+             47d0d13c5d85f2b0ff8318d2877eec2f63b931bd47417a81a538327af927da3e",
+            "ddaf35a193617abacc417349ae20413112e6fa4e89a97ea20a9eeee64b55d39a\
+             2192992a274fc1a836ba3c23a3feebbd454d4423643ce80e2a9ac94fa54ca49f",
+            "204a8fc6dda82f0a0ced7beb8e08a41657c16ef468b228a8279be331a703c335\
+             96fd15c13b1b07f9aa1d3bea57789ca031ad85c7a71dd70354ec631238ca3445",
+            "e718483d0ce769644e2e42c7bc15b4638e1f98b13b2044285632a803afa973eb\
+             de0ff244877ea60a4cb0432ce577c31beb009c5c2c49aa2e4eadb217ad8cc09b",
+            "b0bc7d85438f2ea05e9dac41a04dc20c99e03f04fa5cec1a1a2c5e9959134db6\
+             596d73ede8236bb21a19f33466a636034ad9b8e35cd7ae3f0fa3e5c2663f3266",
+        ];
+        let mut digest = Sha512::new();
+        assert_eq!(digest.output_size(), Sha512::OUTPUT_SIZE);
+        for (sample, exp) in samples().iter().zip(expected.iter()) {
+            assert_digest(&mut digest, sample, exp);
+        }
+
+        // Reset
+        digest.update(b"xyz");
+        digest.reset();
+        digest.update(b"abc");
+        assert_eq!(hex_encode(&digest.finalize()), expected[1]);
+    }
+}
@@ -34,10 +34,14 @@
 //! operations.
 pub mod array;
 pub mod data;
+pub mod digest;
 pub mod std_io;
 #[cfg(test)]
 mod tests;
 
+pub use digest::{Digest, HashingReader, HashingWriter};
+pub use std_io::BufferedReader;
+pub use std_io::BufferedWriter;
 pub use std_io::ReadReader;
 pub use std_io::WriteWriter;
 
@@ -55,8 +59,34 @@ pub enum ErrorKind {
     EndOfData,
     /// Wrapper to standard `std::io::Error`.
     IOError(std::io::Error),
-    /// Wrapper to a boxed error `std::error::Error`.
-    BoxedError(Box<dyn std::error::Error>),
+    /// Wrapper to a boxed error `std::error::Error`. Since 2.0.0 the boxed
+    /// error must be [`Send`] and [`Sync`]. It is displayed as the boxed
+    /// error itself.
+    BoxedError(Box<dyn std::error::Error + Send + Sync>),
+}
+
+impl std::fmt::Display for ErrorKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ErrorKind::UnableToReadData => write!(f, "unable to read data"),
+            ErrorKind::UnableToWriteData => write!(f, "unable to write data"),
+            ErrorKind::CorruptedData => write!(f, "corrupted data"),
+            ErrorKind::EndOfData => write!(f, "end of data"),
+            ErrorKind::IOError(_) => write!(f, "I/O error"),
+            ErrorKind::BoxedError(e) => e.fmt(f),
+        }
+    }
+}
+
+/// New since 2.0.0.
+impl std::error::Error for ErrorKind {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            ErrorKind::IOError(e) => Some(e),
+            ErrorKind::BoxedError(e) => e.source(),
+            _ => None,
+        }
+    }
 }
 
 /// A specialized [`std::result::Result`] generated by functions and methods from this package.
@@ -215,7 +245,7 @@ impl<'a> LimitedReader<'a> {
     /// Parameters:
     /// * `src`: A mutable reference to the source Reader.
     /// * `available`: Number of bytes available for reading.
-    pub fn new(src: &mut dyn Reader, available: usize) -> LimitedReader {
+    pub fn new(src: &mut dyn Reader, available: usize) -> LimitedReader<'_> {
         LimitedReader {
             source: src,
             available,
@@ -294,3 +324,519 @@ impl<'a> Reader for LimitedReader<'a> {
         ret
     }
 }
+
+//=============================================================================
+// LimitedWriter
+//-----------------------------------------------------------------------------
+/// The `LimitedWriter` implements a [`Writer`] that wraps another
+/// [`Writer`] but defines a limit to the amount of bytes that can be
+/// written into it. It is the counterpart of [`LimitedReader`].
+///
+/// Just like [`LimitedReader`], it tests the limits prior to the attempt to
+/// write the data, thus failed attempts will not write anything into the
+/// inner writer.
+///
+/// New since 2.0.0.
+pub struct LimitedWriter<'a> {
+    dest: &'a mut dyn Writer,
+    available: u64,
+    exceeded: bool,
+}
+
+impl<'a> LimitedWriter<'a> {
+    /// Creates a new instance of this struct.
+    ///
+    /// Parameters:
+    /// * `dst`: A mutable reference to the destination Writer.
+    /// * `available`: Number of bytes that can be written.
+    pub fn new(dst: &mut dyn Writer, available: u64) -> LimitedWriter<'_> {
+        LimitedWriter {
+            dest: dst,
+            available,
+            exceeded: false,
+        }
+    }
+
+    /// Verifies if it is possible to write a given number of bytes.
+    ///
+    /// Parameters:
+    /// * `size`: The number of bytes to write.
+    ///
+    /// Returns:
+    /// * `Ok(())`: On success.
+    /// * `Err(ErrorKind)`: If the specified number of bytes exceeds the limit.
+    pub fn can_write(&self, size: usize) -> Result<()> {
+        if size as u64 > self.available {
+            Err(ErrorKind::UnableToWriteData)
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Returns the number of bytes that can still be written.
+    ///
+    /// Returns:
+    /// * The number of available bytes.
+    pub fn available(&self) -> u64 {
+        self.available
+    }
+
+    /// Verifies if the limit was reached.
+    ///
+    /// Returns:
+    /// - true: if no more bytes can be written;
+    /// - false: otherwise;
+    pub fn full(&self) -> bool {
+        self.available == 0
+    }
+
+    /// Verifies if there was an attempt to write beyond the limit. It
+    /// allows the caller to distinguish this condition from the failures
+    /// of the inner writer.
+    ///
+    /// Returns:
+    /// - true: if at least one write was rejected due to the limit;
+    /// - false: otherwise;
+    pub fn exceeded(&self) -> bool {
+        self.exceeded
+    }
+
+    /// Checks the limit and records the violations.
+    fn check_limit(&mut self, size: usize) -> Result<()> {
+        let ret = self.can_write(size);
+        if ret.is_err() {
+            self.exceeded = true;
+        }
+        ret
+    }
+}
+
+impl<'a> Writer for LimitedWriter<'a> {
+    fn write(&mut self, value: u8) -> Result<()> {
+        self.check_limit(1)?;
+        self.dest.write(value)?;
+        self.available -= 1;
+        Ok(())
+    }
+
+    fn write_all(&mut self, buff: &[u8]) -> Result<()> {
+        self.check_limit(buff.len())?;
+        self.dest.write_all(buff)?;
+        self.available -= buff.len() as u64;
+        Ok(())
+    }
+
+    fn as_writer(&mut self) -> &mut dyn Writer {
+        self
+    }
+}
+
+//=============================================================================
+// NullWriter
+//-----------------------------------------------------------------------------
+/// The `NullWriter` implements a [`Writer`] that discards all bytes written
+/// into it but counts them. It can be used to compute the size of the
+/// serialized data without actually storing it.
+///
+/// New since 2.0.0.
+#[derive(Default)]
+pub struct NullWriter {
+    count: u64,
+}
+
+impl NullWriter {
+    /// Creates a new instance of this struct.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the number of bytes written so far.
+    pub fn count(&self) -> u64 {
+        self.count
+    }
+}
+
+impl Writer for NullWriter {
+    fn write(&mut self, _value: u8) -> Result<()> {
+        self.count += 1;
+        Ok(())
+    }
+
+    fn write_all(&mut self, buff: &[u8]) -> Result<()> {
+        self.count += buff.len() as u64;
+        Ok(())
+    }
+
+    fn as_writer(&mut self) -> &mut dyn Writer {
+        self
+    }
+}
+
+//=============================================================================
+// CountingWriter
+//-----------------------------------------------------------------------------
+/// The `CountingWriter` implements a [`Writer`] that wraps another [`Writer`]
+/// and counts the number of bytes successfully written into it.
+///
+/// New since 2.0.0.
+pub struct CountingWriter<'a> {
+    dest: &'a mut dyn Writer,
+    count: u64,
+}
+
+impl<'a> CountingWriter<'a> {
+    /// Creates a new instance of this struct.
+    ///
+    /// Parameters:
+    /// * `dst`: A mutable reference to the destination Writer.
+    pub fn new(dst: &mut dyn Writer) -> CountingWriter<'_> {
+        CountingWriter {
+            dest: dst,
+            count: 0,
+        }
+    }
+
+    /// Returns the number of bytes written so far.
+    pub fn count(&self) -> u64 {
+        self.count
+    }
+}
+
+impl<'a> Writer for CountingWriter<'a> {
+    fn write(&mut self, value: u8) -> Result<()> {
+        self.dest.write(value)?;
+        self.count += 1;
+        Ok(())
+    }
+
+    fn write_all(&mut self, buff: &[u8]) -> Result<()> {
+        self.dest.write_all(buff)?;
+        self.count += buff.len() as u64;
+        Ok(())
+    }
+
+    fn as_writer(&mut self) -> &mut dyn Writer {
+        self
+    }
+}
+
+//=============================================================================
+// TeeWriter
+//-----------------------------------------------------------------------------
+/// The `TeeWriter` implements a [`Writer`] that forwards all bytes to two
+/// other writers.
+///
+/// The bytes are written into the first writer and then into the second.
+/// If the second writer fails, the bytes already written into the first
+/// writer are not rolled back.
+///
+/// New since 2.0.0.
+pub struct TeeWriter<'a> {
+    first: &'a mut dyn Writer,
+    second: &'a mut dyn Writer,
+}
+
+impl<'a> TeeWriter<'a> {
+    /// Creates a new instance of this struct.
+    ///
+    /// Parameters:
+    /// * `first`: The first destination.
+    /// * `second`: The second destination.
+    pub fn new(first: &'a mut dyn Writer, second: &'a mut dyn Writer) -> TeeWriter<'a> {
+        TeeWriter { first, second }
+    }
+}
+
+impl<'a> Writer for TeeWriter<'a> {
+    fn write(&mut self, value: u8) -> Result<()> {
+        self.first.write(value)?;
+        self.second.write(value)
+    }
+
+    fn write_all(&mut self, buff: &[u8]) -> Result<()> {
+        self.first.write_all(buff)?;
+        self.second.write_all(buff)
+    }
+
+    fn as_writer(&mut self) -> &mut dyn Writer {
+        self
+    }
+}
+
+//=============================================================================
+// OffsetReader
+//-----------------------------------------------------------------------------
+/// The `OffsetReader` implements a [`Reader`] that wraps another [`Reader`]
+/// and keeps track of the offset of the next byte to be read. It is used to
+/// locate the errors found during the deserialization of the tags.
+///
+/// New since 2.0.0.
+pub struct OffsetReader<'a> {
+    source: &'a mut dyn Reader,
+    offset: u64,
+}
+
+impl<'a> OffsetReader<'a> {
+    /// Creates a new instance of this struct. The initial offset is 0.
+    ///
+    /// Parameters:
+    /// * `src`: A mutable reference to the source Reader.
+    pub fn new(src: &mut dyn Reader) -> OffsetReader<'_> {
+        Self::with_offset(src, 0)
+    }
+
+    /// Creates a new instance of this struct with the given initial offset.
+    /// It allows the report of absolute offsets when the source reader is not
+    /// positioned at the beginning of the data.
+    ///
+    /// Parameters:
+    /// * `src`: A mutable reference to the source Reader.
+    /// * `offset`: The initial offset.
+    pub fn with_offset(src: &mut dyn Reader, offset: u64) -> OffsetReader<'_> {
+        OffsetReader {
+            source: src,
+            offset,
+        }
+    }
+
+    /// Returns the offset of the next byte to be read.
+    ///
+    /// Returns:
+    /// * The current offset.
+    pub fn offset(&self) -> u64 {
+        self.offset
+    }
+}
+
+impl<'a> Reader for OffsetReader<'a> {
+    fn read(&mut self) -> Result<u8> {
+        let b = self.source.read()?;
+        self.offset += 1;
+        Ok(b)
+    }
+
+    fn read_all(&mut self, buff: &mut [u8]) -> Result<()> {
+        self.source.read_all(buff)?;
+        self.offset += buff.len() as u64;
+        Ok(())
+    }
+
+    fn skip(&mut self, count: usize) -> Result<()> {
+        self.source.skip(count)?;
+        self.offset += count as u64;
+        Ok(())
+    }
+}
+
+//=============================================================================
+// PeekableReader
+//-----------------------------------------------------------------------------
+/// The `PeekableReader` implements a [`Reader`] that wraps another [`Reader`]
+/// and allows the inspection of the next bytes without consuming them. It
+/// also allows bytes to be pushed back into it.
+///
+/// It keeps an internal pushback buffer with a limited capacity. Peeked bytes
+/// and unread bytes are stored in this buffer and are returned by subsequent
+/// reads before any byte from the source.
+///
+/// New since 2.0.0.
+pub struct PeekableReader<'a> {
+    source: &'a mut dyn Reader,
+    buffer: std::collections::VecDeque<u8>,
+    capacity: usize,
+}
+
+impl<'a> PeekableReader<'a> {
+    /// Minimum capacity of the pushback buffer. It is large enough to hold
+    /// the header of any tag, in other words, its id and its size.
+    pub const MIN_CAPACITY: usize = 18;
+
+    /// Creates a new instance of this struct with a pushback buffer of
+    /// [`Self::MIN_CAPACITY`] bytes.
+    ///
+    /// Parameters:
+    /// * `src`: A mutable reference to the source Reader.
+    pub fn new(src: &mut dyn Reader) -> PeekableReader<'_> {
+        Self::with_capacity(src, Self::MIN_CAPACITY)
+    }
+
+    /// Creates a new instance of this struct with a pushback buffer of the
+    /// given capacity. Values smaller than [`Self::MIN_CAPACITY`] are
+    /// replaced by [`Self::MIN_CAPACITY`].
+    ///
+    /// Parameters:
+    /// * `src`: A mutable reference to the source Reader.
+    /// * `capacity`: The capacity of the pushback buffer.
+    pub fn with_capacity(src: &mut dyn Reader, capacity: usize) -> PeekableReader<'_> {
+        let capacity = std::cmp::max(capacity, Self::MIN_CAPACITY);
+        PeekableReader {
+            source: src,
+            buffer: std::collections::VecDeque::with_capacity(capacity),
+            capacity,
+        }
+    }
+
+    /// Returns the capacity of the pushback buffer.
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// Returns the number of bytes in the pushback buffer.
+    pub fn buffered(&self) -> usize {
+        self.buffer.len()
+    }
+
+    /// Ensures that the pushback buffer has at least `size` bytes. It
+    /// fails if `size` is larger than the capacity.
+    fn fill(&mut self, size: usize) -> Result<()> {
+        if self.buffer.len() >= size {
+            return Ok(());
+        }
+        if size > self.capacity {
+            return Err(ErrorKind::UnableToReadData);
+        }
+        let mut tmp = vec![0_u8; size - self.buffer.len()];
+        self.source.read_all(&mut tmp)?;
+        self.buffer.extend(tmp.iter());
+        Ok(())
+    }
+
+    /// Returns the next byte without consuming it.
+    ///
+    /// Returns:
+    /// * `Ok(v)`: The value of the next byte;
+    /// * `Err(ErrorKind)`: In case of error;
+    pub fn peek(&mut self) -> Result<u8> {
+        self.fill(1)?;
+        Ok(self.buffer[0])
+    }
+
+    /// Fills `buff` with the next bytes without consuming them. The size
+    /// of `buff` must not be larger than [`Self::capacity()`].
+    ///
+    /// Arguments:
+    /// * `buff`: The output buffer;
+    ///
+    /// Returns:
+    /// * `Ok(())`: On success;
+    /// * `Err(ErrorKind)`: In case of error;
+    pub fn peek_all(&mut self, buff: &mut [u8]) -> Result<()> {
+        self.fill(buff.len())?;
+        for (d, s) in buff.iter_mut().zip(self.buffer.iter()) {
+            *d = *s;
+        }
+        Ok(())
+    }
+
+    /// Decodes the ILInt that starts at the given offset from the next byte
+    /// without consuming it.
+    ///
+    /// Returns:
+    /// * `Ok((value, size))`: The value and its encoded size;
+    /// * `Err(ErrorKind)`: In case of error;
+    fn peek_ilint_at(&mut self, offset: usize) -> Result<(u64, usize)> {
+        self.fill(offset + 1)?;
+        let size = crate::ilint::decoded_size(self.buffer[offset]);
+        self.fill(offset + size)?;
+        let mut tmp = [0_u8; 9];
+        for (d, s) in tmp.iter_mut().zip(self.buffer.iter().skip(offset)) {
+            *d = *s;
+        }
+        match crate::ilint::decode_from_bytes(&tmp[..size]) {
+            Ok(v) => Ok(v),
+            Err(_) => Err(ErrorKind::CorruptedData),
+        }
+    }
+
+    /// Decodes the next ILInt value without consuming it.
+    ///
+    /// Returns:
+    /// * `Ok(v)`: The value of the ILInt;
+    /// * `Err(ErrorKind::CorruptedData)`: If the ILInt is invalid;
+    /// * `Err(ErrorKind)`: In case of error;
+    pub fn peek_ilint(&mut self) -> Result<u64> {
+        Ok(self.peek_ilint_at(0)?.0)
+    }
+
+    /// Decodes the header of the next tag without consuming it. For implicit
+    /// tags, the size is determined by the id, except for the ILInt tags whose
+    /// size is determined by the first byte of their values.
+    ///
+    /// Returns:
+    /// * `Ok((id, size))`: The id of the tag and the size of its value;
+    /// * `Err(ErrorKind::CorruptedData)`: If the header is invalid;
+    /// * `Err(ErrorKind)`: In case of error;
+    pub fn peek_tag_header(&mut self) -> Result<(u64, u64)> {
+        use crate::tags::standard::constants::*;
+
+        let (id, id_size) = self.peek_ilint_at(0)?;
+        if !crate::tags::is_implicit_tag(id) {
+            let (size, _) = self.peek_ilint_at(id_size)?;
+            return Ok((id, size));
+        }
+        match id {
+            IL_ILINT_TAG_ID | IL_SIGNED_ILINT_TAG_ID => {
+                self.fill(id_size + 1)?;
+                let header = self.buffer[id_size];
+                Ok((id, crate::ilint::decoded_size(header) as u64))
+            }
+            IL_NULL_TAG_ID..=IL_BIN128_TAG_ID => {
+                Ok((id, crate::tags::standard::implicit::implicit_tag_size(id)))
+            }
+            _ => Err(ErrorKind::CorruptedData),
+        }
+    }
+
+    /// Pushes the given bytes back into this reader. The next read will
+    /// return `buff[0]`, followed by the remaining bytes of `buff`.
+    ///
+    /// Arguments:
+    /// * `buff`: The bytes to be pushed back;
+    ///
+    /// Returns:
+    /// * `Ok(())`: On success;
+    /// * `Err(ErrorKind::UnableToWriteData)`: If the bytes do not fit into
+    ///   the pushback buffer. Nothing is pushed back in this case;
+    pub fn unread(&mut self, buff: &[u8]) -> Result<()> {
+        if self.buffer.len() + buff.len() > self.capacity {
+            return Err(ErrorKind::UnableToWriteData);
+        }
+        for b in buff.iter().rev() {
+            self.buffer.push_front(*b);
+        }
+        Ok(())
+    }
+}
+
+impl<'a> Reader for PeekableReader<'a> {
+    fn read(&mut self) -> Result<u8> {
+        match self.buffer.pop_front() {
+            Some(b) => Ok(b),
+            None => self.source.read(),
+        }
+    }
+
+    fn read_all(&mut self, buff: &mut [u8]) -> Result<()> {
+        let buffered = std::cmp::min(self.buffer.len(), buff.len());
+        if buffered < buff.len() {
+            self.source.read_all(&mut buff[buffered..])?;
+        }
+        for (d, s) in buff.iter_mut().zip(self.buffer.drain(..buffered)) {
+            *d = s;
+        }
+        Ok(())
+    }
+
+    fn skip(&mut self, count: usize) -> Result<()> {
+        self.skip_u64(count as u64)
+    }
+
+    fn skip_u64(&mut self, count: u64) -> Result<()> {
+        let buffered = std::cmp::min(self.buffer.len() as u64, count);
+        if buffered < count {
+            self.source.skip_u64(count - buffered)?;
+        }
+        self.buffer.drain(..buffered as usize);
+        Ok(())
+    }
+}
@@ -74,7 +74,7 @@ impl<T: std::io::Seek> SizedSeek for T {
     fn len(&mut self) -> Result<u64> {
         // Replace this implementation with Seek::stream_len() when
         // it became available.
-        let curr = self.seek(std::io::SeekFrom::Current(0))?;
+        let curr = self.stream_position()?;
         let end = self.seek(std::io::SeekFrom::End(0))?;
         self.seek(std::io::SeekFrom::Start(curr))?;
         Ok(end)
@@ -90,8 +90,13 @@ impl<T: std::io::Seek> SizedSeek for T {
 //-----------------------------------------------------------------------------
 /// This struct implements a [`Reader`] that uses a [`std::io::Read`] as the
 /// source of bytes.
+///
+/// Since [`std::io::Read::read_exact()`] may consume bytes even when it
+/// fails, this implementation keeps the bytes of an incomplete read
+/// in order to preserve the all-or-nothing semantics of [`Reader`].
 pub struct ReadReader<'a, T: std::io::Read> {
     source: &'a mut T,
+    pending: Vec<u8>,
 }
 
 impl<'a, T: std::io::Read> ReadReader<'a, T> {
@@ -100,24 +105,46 @@ impl<'a, T: std::io::Read> ReadReader<'a, T> {
     /// Parameters:
     /// * `src`: The source of bytes.
     pub fn new(src: &'a mut T) -> ReadReader<'a, T> {
-        ReadReader { source: src }
+        ReadReader {
+            source: src,
+            pending: Vec::new(),
+        }
     }
 }
 
 impl<'a, T: std::io::Read> Reader for ReadReader<'a, T> {
     fn read(&mut self) -> Result<u8> {
         let mut buff: [u8; 1] = [0; 1];
-        match self.source.read_exact(&mut buff) {
-            Ok(()) => Ok(buff[0]),
-            Err(e) => Err(ErrorKind::IOError(e)),
-        }
+        self.read_all(&mut buff)?;
+        Ok(buff[0])
     }
 
     fn read_all(&mut self, buff: &mut [u8]) -> Result<()> {
-        match self.source.read_exact(buff) {
-            Ok(()) => Ok(()),
-            Err(e) => Err(ErrorKind::IOError(e)),
+        if self.pending.len() >= buff.len() {
+            buff.copy_from_slice(&self.pending[..buff.len()]);
+            self.pending.drain(..buff.len());
+            return Ok(());
+        }
+        let mut offs = self.pending.len();
+        buff[..offs].copy_from_slice(&self.pending);
+        while offs < buff.len() {
+            match self.source.read(&mut buff[offs..]) {
+                Ok(0) => {
+                    self.pending = buff[..offs].to_vec();
+                    return Err(ErrorKind::IOError(std::io::Error::from(
+                        std::io::ErrorKind::UnexpectedEof,
+                    )));
+                }
+                Ok(n) => offs += n,
+                Err(e) if e.kind() == std::io::ErrorKind::Interrupted => (),
+                Err(e) => {
+                    self.pending = buff[..offs].to_vec();
+                    return Err(ErrorKind::IOError(e));
+                }
+            }
         }
+        self.pending.clear();
+        Ok(())
     }
 }
 
@@ -151,7 +178,7 @@ impl<'a> Writer for WriteWriter<'a> {
     }
 
     fn write_all(&mut self, buff: &[u8]) -> Result<()> {
-        match self.dest.write(&buff) {
+        match self.dest.write(buff) {
             Ok(n) if n == buff.len() => Ok(()),
             Ok(_) => Err(ErrorKind::UnableToWriteData),
             Err(e) => Err(ErrorKind::IOError(e)),
@@ -163,6 +190,305 @@ impl<'a> Writer for WriteWriter<'a> {
     }
 }
 
+//=============================================================================
+// BufferedReader
+//-----------------------------------------------------------------------------
+/// Default size of the internal buffers used by [`BufferedReader`] and
+/// [`BufferedWriter`].
+///
+/// New since 2.0.0.
+pub const DEFAULT_BUFFER_SIZE: usize = 8192;
+
+/// This struct implements a [`Reader`] that uses a [`std::io::Read`] as the
+/// source of bytes and keeps an internal read-ahead buffer.
+///
+/// Unlike [`ReadReader`], single byte reads are served from the internal
+/// buffer, thus the source is only accessed when the buffer runs out of
+/// data. Reads larger than the buffer are performed directly on the
+/// caller's buffer.
+///
+/// As [`ReadReader`], the bytes of an incomplete read are kept in order to
+/// preserve the all-or-nothing semantics of [`Reader`]. Skips are also
+/// all-or-nothing as long as they are not larger than the internal buffer.
+/// Larger skips consume the source, thus the skipped bytes are lost if the
+/// source ends before the end of the skip. Since this reader may consume
+/// more bytes from the source than were actually requested, the position
+/// of the source must not be used after its creation.
+///
+/// Like [`crate::io::array::ByteArrayReader`], it reports
+/// [`ErrorKind::EndOfData`] when there is no more data to read and
+/// [`ErrorKind::UnableToReadData`] when there is some data but not enough.
+///
+/// New since 2.0.0.
+pub struct BufferedReader<'a, T: std::io::Read> {
+    source: &'a mut T,
+    buffer: Vec<u8>,
+    pos: usize,
+    end: usize,
+}
+
+impl<'a, T: std::io::Read> BufferedReader<'a, T> {
+    /// Creates a new instance of `BufferedReader` with a buffer of
+    /// [`DEFAULT_BUFFER_SIZE`] bytes.
+    ///
+    /// Parameters:
+    /// * `src`: The source of bytes.
+    pub fn new(src: &'a mut T) -> BufferedReader<'a, T> {
+        Self::with_capacity(src, DEFAULT_BUFFER_SIZE)
+    }
+
+    /// Creates a new instance of `BufferedReader` with a buffer of the
+    /// specified size.
+    ///
+    /// Parameters:
+    /// * `src`: The source of bytes.
+    /// * `capacity`: The size of the buffer. It must be at least 1.
+    pub fn with_capacity(src: &'a mut T, capacity: usize) -> BufferedReader<'a, T> {
+        assert!(capacity > 0);
+        BufferedReader {
+            source: src,
+            buffer: vec![0; capacity],
+            pos: 0,
+            end: 0,
+        }
+    }
+
+    /// Returns the number of bytes already extracted from the source
+    /// that are waiting in the internal buffer.
+    pub fn buffered(&self) -> usize {
+        self.end - self.pos
+    }
+
+    /// Reads from the source until there is at least `count` bytes in the
+    /// buffer. `count` must not be larger than the size of the buffer.
+    ///
+    /// Returns:
+    /// - `Ok(())`: On success;
+    /// - `Err(ErrorKind::EndOfData)`: If there is no more data to read;
+    /// - `Err(ErrorKind::UnableToReadData)`: If there is not enough data;
+    /// - `Err(ErrorKind::IOError(_))`: If the source fails;
+    fn fill(&mut self, count: usize) -> Result<()> {
+        if self.buffered() >= count {
+            return Ok(());
+        }
+        if self.pos + count > self.buffer.len() {
+            self.buffer.copy_within(self.pos..self.end, 0);
+            self.end -= self.pos;
+            self.pos = 0;
+        }
+        while self.buffered() < count {
+            match self.source.read(&mut self.buffer[self.end..]) {
+                Ok(0) if self.buffered() == 0 => return Err(ErrorKind::EndOfData),
+                Ok(0) => return Err(ErrorKind::UnableToReadData),
+                Ok(n) => self.end += n,
+                Err(e) if e.kind() == std::io::ErrorKind::Interrupted => (),
+                Err(e) => return Err(ErrorKind::IOError(e)),
+            }
+        }
+        Ok(())
+    }
+
+    /// Reads a buffer larger than the internal buffer directly from the
+    /// source. On failure, the bytes already read are moved into the
+    /// internal buffer.
+    fn read_direct(&mut self, buff: &mut [u8]) -> Result<()> {
+        let mut offs = self.buffered();
+        buff[..offs].copy_from_slice(&self.buffer[self.pos..self.end]);
+        self.pos = 0;
+        self.end = 0;
+        while offs < buff.len() {
+            let err = match self.source.read(&mut buff[offs..]) {
+                Ok(0) if offs == 0 => ErrorKind::EndOfData,
+                Ok(0) => ErrorKind::UnableToReadData,
+                Ok(n) => {
+                    offs += n;
+                    continue;
+                }
+                Err(e) if e.kind() == std::io::ErrorKind::Interrupted => continue,
+                Err(e) => ErrorKind::IOError(e),
+            };
+            if offs > self.buffer.len() {
+                self.buffer.resize(offs, 0);
+            }
+            self.buffer[..offs].copy_from_slice(&buff[..offs]);
+            self.end = offs;
+            return Err(err);
+        }
+        Ok(())
+    }
+}
+
+impl<'a, T: std::io::Read> Reader for BufferedReader<'a, T> {
+    fn read(&mut self) -> Result<u8> {
+        self.fill(1)?;
+        let v = self.buffer[self.pos];
+        self.pos += 1;
+        Ok(v)
+    }
+
+    fn read_all(&mut self, buff: &mut [u8]) -> Result<()> {
+        if buff.len() > self.buffer.len() {
+            return self.read_direct(buff);
+        }
+        self.fill(buff.len())?;
+        buff.copy_from_slice(&self.buffer[self.pos..self.pos + buff.len()]);
+        self.pos += buff.len();
+        Ok(())
+    }
+
+    fn skip(&mut self, count: usize) -> Result<()> {
+        self.skip_u64(count as u64)
+    }
+
+    fn skip_u64(&mut self, count: u64) -> Result<()> {
+        if count <= self.buffer.len() as u64 {
+            self.fill(count as usize)?;
+            self.pos += count as usize;
+            return Ok(());
+        }
+        // Larger skips must consume the source in order to find its end.
+        self.fill(1)?;
+        let mut remaining = count;
+        loop {
+            let chunk = std::cmp::min(remaining, self.buffered() as u64);
+            self.pos += chunk as usize;
+            remaining -= chunk;
+            if remaining == 0 {
+                return Ok(());
+            }
+            match self.fill(1) {
+                Ok(()) => (),
+                Err(ErrorKind::EndOfData) => return Err(ErrorKind::UnableToReadData),
+                Err(e) => return Err(e),
+            }
+        }
+    }
+}
+
+//=============================================================================
+// BufferedWriter
+//-----------------------------------------------------------------------------
+/// This struct implements a [`Writer`] that uses a [`std::io::Write`] as the
+/// destination of bytes and keeps an internal write buffer.
+///
+/// The buffered bytes are only sent to the destination when the buffer is
+/// full or when [`BufferedWriter::flush()`] is called. Writes larger than the
+/// buffer are sent directly to the destination.
+///
+/// The remaining bytes are flushed when the instance is dropped, but any
+/// error is ignored at that point. Because of that, it is strongly
+/// recommended to call [`BufferedWriter::flush()`] explicitly.
+///
+/// New since 2.0.0.
+pub struct BufferedWriter<'a> {
+    dest: &'a mut dyn std::io::Write,
+    buffer: Vec<u8>,
+    capacity: usize,
+}
+
+impl<'a> BufferedWriter<'a> {
+    /// Creates a new instance of `BufferedWriter` with a buffer of
+    /// [`DEFAULT_BUFFER_SIZE`] bytes.
+    ///
+    /// Parameters:
+    /// * `dst`: The destination for the bytes.
+    pub fn new(dst: &'a mut dyn std::io::Write) -> BufferedWriter<'a> {
+        Self::with_capacity(dst, DEFAULT_BUFFER_SIZE)
+    }
+
+    /// Creates a new instance of `BufferedWriter` with a buffer of the
+    /// specified size.
+    ///
+    /// Parameters:
+    /// * `dst`: The destination for the bytes.
+    /// * `capacity`: The size of the buffer. It must be at least 1.
+    pub fn with_capacity(dst: &'a mut dyn std::io::Write, capacity: usize) -> BufferedWriter<'a> {
+        assert!(capacity > 0);
+        BufferedWriter {
+            dest: dst,
+            buffer: Vec::with_capacity(capacity),
+            capacity,
+        }
+    }
+
+    /// Returns the number of bytes waiting in the internal buffer.
+    pub fn buffered(&self) -> usize {
+        self.buffer.len()
+    }
+
+    /// Sends all buffered bytes to the destination and flushes it.
+    ///
+    /// Returns:
+    /// * `Ok(())`: On success;
+    /// * `Err(ErrorKind)`: In case of error;
+    pub fn flush(&mut self) -> Result<()> {
+        self.flush_buffer()?;
+        match self.dest.flush() {
+            Ok(()) => Ok(()),
+            Err(e) => Err(ErrorKind::IOError(e)),
+        }
+    }
+
+    /// Sends the buffered bytes to the destination. The bytes that were
+    /// accepted by the destination are removed from the buffer even if
+    /// this method fails.
+    fn flush_buffer(&mut self) -> Result<()> {
+        let mut written = 0;
+        let mut ret = Ok(());
+        while written < self.buffer.len() {
+            match self.dest.write(&self.buffer[written..]) {
+                Ok(0) => {
+                    ret = Err(ErrorKind::UnableToWriteData);
+                    break;
+                }
+                Ok(n) => written += n,
+                Err(e) if e.kind() == std::io::ErrorKind::Interrupted => (),
+                Err(e) => {
+                    ret = Err(ErrorKind::IOError(e));
+                    break;
+                }
+            }
+        }
+        self.buffer.drain(..written);
+        ret
+    }
+}
+
+impl<'a> Writer for BufferedWriter<'a> {
+    fn write(&mut self, value: u8) -> Result<()> {
+        if self.buffer.len() >= self.capacity {
+            self.flush_buffer()?;
+        }
+        self.buffer.push(value);
+        Ok(())
+    }
+
+    fn write_all(&mut self, buff: &[u8]) -> Result<()> {
+        if self.buffer.len() + buff.len() > self.capacity {
+            self.flush_buffer()?;
+        }
+        if buff.len() >= self.capacity {
+            match self.dest.write_all(buff) {
+                Ok(()) => Ok(()),
+                Err(e) => Err(ErrorKind::IOError(e)),
+            }
+        } else {
+            self.buffer.extend_from_slice(buff);
+            Ok(())
+        }
+    }
+
+    fn as_writer(&mut self) -> &mut dyn Writer {
+        self
+    }
+}
+
+impl<'a> Drop for BufferedWriter<'a> {
+    fn drop(&mut self) {
+        let _ = self.flush();
+    }
+}
+
 //=============================================================================
 // Reader for std::io::Read + std::io::Seek
 //-----------------------------------------------------------------------------
@@ -177,10 +503,33 @@ where
         Ok(buff[0])
     }
 
+    /// Since 2.0.0, it returns [`ErrorKind::EndOfData`] if there is no data left
+    /// and restores the position of the stream if only part of the data is
+    /// available.
     fn read_all(&mut self, buff: &mut [u8]) -> Result<()> {
-        match std::io::Read::read_exact(self, buff) {
-            Ok(()) => Ok(()),
-            Err(_) => Err(ErrorKind::UnableToReadData),
+        let mut offs: usize = 0;
+        let mut eof = false;
+        while offs < buff.len() {
+            match std::io::Read::read(self, &mut buff[offs..]) {
+                Ok(0) => {
+                    eof = true;
+                    break;
+                }
+                Ok(n) => offs += n,
+                Err(e) if e.kind() == std::io::ErrorKind::Interrupted => (),
+                Err(_) => break,
+            }
+        }
+        if offs == buff.len() {
+            return Ok(());
+        }
+        if offs > 0 {
+            self.seek(std::io::SeekFrom::Current(-(offs as i64)))?;
+        }
+        if eof && offs == 0 {
+            Err(ErrorKind::EndOfData)
+        } else {
+            Err(ErrorKind::UnableToReadData)
         }
     }
 
@@ -192,7 +541,7 @@ where
         if count == 0 {
             Ok(())
         } else {
-            let curr_pos = self.seek(std::io::SeekFrom::Current(0))?;
+            let curr_pos = self.stream_position()?;
             let end_pos = self.seek(std::io::SeekFrom::End(0))?;
             if curr_pos == end_pos {
                 Err(ErrorKind::EndOfData)
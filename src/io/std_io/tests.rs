@@ -49,7 +49,7 @@ fn test_errorkind_debug() {
 impl DummyReader {
     pub fn new(available: usize) -> DummyReader {
         DummyReader {
-            available: available,
+            available,
             read_count: 0,
         }
     }
@@ -59,13 +59,13 @@ impl DummyReader {
     }
 }
 
-impl<'a> Reader for DummyReader {
+impl Reader for DummyReader {
     fn read(&mut self) -> Result<u8> {
         if self.available > 0 {
             self.available -= 1;
             let r = self.read_count as u8;
             self.read_count += 1;
-            Ok(r as u8)
+            Ok(r)
         } else {
             Err(ErrorKind::UnableToReadData)
         }
@@ -227,11 +227,9 @@ fn test_limitedreader_read() {
 
 fn assert_sequence(value: &[u8], expected_size: usize) {
     assert_eq!(value.len(), expected_size);
-    if value.len() > 0 {
-        let mut i = 0;
-        for x in value {
+    if !value.is_empty() {
+        for (i, x) in value.iter().enumerate() {
             assert_eq!(*x, i as u8);
-            i += 1
         }
     }
 }
@@ -355,7 +353,7 @@ fn test_writewriter_new() {
         Ok(()) => (),
         _ => panic!("Unexpected error."),
     }
-    assert_eq!(&buff, &[0 as u8])
+    assert_eq!(&buff, &[0_u8])
 }
 
 #[test]
@@ -398,6 +396,252 @@ fn test_writewriter_write_all() {
     assert_eq!(&buff, &sample)
 }
 
+//=============================================================================
+// BufferedReader
+//-----------------------------------------------------------------------------
+/// A [`std::io::Read`] that returns at most `chunk` bytes per call and
+/// counts the number of calls.
+struct ChunkedRead<'a> {
+    data: &'a [u8],
+    chunk: usize,
+    calls: usize,
+}
+
+impl<'a> ChunkedRead<'a> {
+    fn new(data: &'a [u8], chunk: usize) -> Self {
+        Self {
+            data,
+            chunk,
+            calls: 0,
+        }
+    }
+}
+
+impl<'a> std::io::Read for ChunkedRead<'a> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        self.calls += 1;
+        let n = std::cmp::min(std::cmp::min(buf.len(), self.chunk), self.data.len());
+        buf[..n].copy_from_slice(&self.data[..n]);
+        self.data = &self.data[n..];
+        Ok(n)
+    }
+}
+
+#[test]
+fn test_bufferedreader_read() {
+    let mut sample: [u8; 100] = [0; 100];
+    fill_sample(&mut sample);
+    let mut src = ChunkedRead::new(&sample, 100);
+    let mut reader = BufferedReader::with_capacity(&mut src, 16);
+    for i in 0..100 {
+        assert_eq!(reader.read().unwrap(), i as u8);
+    }
+    assert_eq!(reader.buffered(), 0);
+    assert!(matches!(reader.read(), Err(ErrorKind::EndOfData)));
+    // 7 calls to fill the buffer plus the one that detected the end
+    assert_eq!(src.calls, 8);
+}
+
+#[test]
+fn test_bufferedreader_read_all() {
+    let mut sample: [u8; 100] = [0; 100];
+    fill_sample(&mut sample);
+    let mut src = ChunkedRead::new(&sample, 3);
+    let mut reader = BufferedReader::with_capacity(&mut src, 16);
+    let mut read_buff: [u8; 110] = [0; 110];
+
+    reader.read_all(&mut read_buff[0..0]).unwrap();
+    reader.read_all(&mut read_buff[0..10]).unwrap();
+    assert_sequence(&read_buff[0..10], 10);
+    reader.read_all(&mut read_buff[10..25]).unwrap();
+    assert_sequence(&read_buff[0..25], 25);
+    // Larger than the buffer
+    reader.read_all(&mut read_buff[25..90]).unwrap();
+    assert_sequence(&read_buff[0..90], 90);
+
+    // Incomplete reads must not consume data
+    assert!(matches!(
+        reader.read_all(&mut read_buff[90..104]),
+        Err(ErrorKind::UnableToReadData)
+    ));
+    assert_eq!(reader.buffered(), 10);
+    reader.read_all(&mut read_buff[90..92]).unwrap();
+    assert_sequence(&read_buff[0..92], 92);
+    reader.read_all(&mut read_buff[92..99]).unwrap();
+    assert_sequence(&read_buff[0..99], 99);
+    assert_eq!(reader.read().unwrap(), 99);
+    assert!(matches!(reader.read(), Err(ErrorKind::EndOfData)));
+    assert!(matches!(
+        reader.read_all(&mut read_buff[0..2]),
+        Err(ErrorKind::EndOfData)
+    ));
+    assert!(matches!(
+        reader.read_all(&mut read_buff[0..20]),
+        Err(ErrorKind::EndOfData)
+    ));
+}
+
+#[test]
+fn test_bufferedreader_read_all_direct_failure() {
+    let mut sample: [u8; 40] = [0; 40];
+    fill_sample(&mut sample);
+    let mut src = ChunkedRead::new(&sample, 7);
+    let mut reader = BufferedReader::with_capacity(&mut src, 4);
+    let mut read_buff: [u8; 50] = [0; 50];
+
+    reader.read_all(&mut read_buff[0..2]).unwrap();
+    assert!(matches!(
+        reader.read_all(&mut read_buff[2..50]),
+        Err(ErrorKind::UnableToReadData)
+    ));
+    assert_eq!(reader.buffered(), 38);
+    reader.read_all(&mut read_buff[2..40]).unwrap();
+    assert_sequence(&read_buff[0..40], 40);
+    assert_eq!(reader.buffered(), 0);
+}
+
+#[test]
+fn test_bufferedreader_skip() {
+    let mut sample: [u8; 100] = [0; 100];
+    fill_sample(&mut sample);
+    let mut src = ChunkedRead::new(&sample, 5);
+    let mut reader = BufferedReader::with_capacity(&mut src, 16);
+
+    reader.skip(0).unwrap();
+    assert_eq!(reader.read().unwrap(), 0);
+    reader.skip(3).unwrap();
+    assert_eq!(reader.read().unwrap(), 4);
+    reader.skip(50).unwrap();
+    assert_eq!(reader.read().unwrap(), 55);
+    reader.skip_u64(40).unwrap();
+    assert_eq!(reader.read().unwrap(), 96);
+
+    // Failed skips must not consume data
+    assert!(matches!(reader.skip(4), Err(ErrorKind::UnableToReadData)));
+    assert_eq!(reader.buffered(), 3);
+    reader.skip_u64(2).unwrap();
+    assert_eq!(reader.read().unwrap(), 99);
+    assert!(matches!(reader.skip(1), Err(ErrorKind::EndOfData)));
+    assert!(matches!(reader.skip_u64(50), Err(ErrorKind::EndOfData)));
+    reader.skip(0).unwrap();
+}
+
+#[test]
+fn test_bufferedreader_skip_larger_than_buffer() {
+    let mut sample: [u8; 40] = [0; 40];
+    fill_sample(&mut sample);
+    let mut src = ChunkedRead::new(&sample, 5);
+    let mut reader = BufferedReader::with_capacity(&mut src, 4);
+
+    reader.skip_u64(30).unwrap();
+    assert_eq!(reader.read().unwrap(), 30);
+    // The source ends before the end of the skip
+    assert!(matches!(
+        reader.skip_u64(10),
+        Err(ErrorKind::UnableToReadData)
+    ));
+    assert!(matches!(reader.read(), Err(ErrorKind::EndOfData)));
+}
+
+#[test]
+fn test_bufferedreader_new() {
+    let mut sample: [u8; 10] = [0; 10];
+    fill_sample(&mut sample);
+    let mut cursor = std::io::Cursor::new(&sample);
+    let mut reader = BufferedReader::new(&mut cursor);
+    let mut read_buff: [u8; 10] = [0; 10];
+    reader.read_all(&mut read_buff).unwrap();
+    assert_eq!(&read_buff, &sample);
+}
+
+//=============================================================================
+// BufferedWriter
+//-----------------------------------------------------------------------------
+/// A [`std::io::Write`] that accepts at most `chunk` bytes per call and
+/// counts the number of calls.
+struct ChunkedWrite {
+    data: Vec<u8>,
+    chunk: usize,
+    calls: usize,
+    flushes: usize,
+}
+
+impl ChunkedWrite {
+    fn new(chunk: usize) -> Self {
+        Self {
+            data: Vec::new(),
+            chunk,
+            calls: 0,
+            flushes: 0,
+        }
+    }
+}
+
+impl std::io::Write for ChunkedWrite {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.calls += 1;
+        let n = std::cmp::min(buf.len(), self.chunk);
+        self.data.extend_from_slice(&buf[..n]);
+        Ok(n)
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        self.flushes += 1;
+        Ok(())
+    }
+}
+
+#[test]
+fn test_bufferedwriter_write() {
+    let mut sample: [u8; 100] = [0; 100];
+    fill_sample(&mut sample);
+    let mut dst = ChunkedWrite::new(100);
+    let mut writer = BufferedWriter::with_capacity(&mut dst, 16);
+    for x in &sample {
+        writer.write(*x).unwrap();
+    }
+    assert_eq!(writer.buffered(), 4);
+    writer.flush().unwrap();
+    assert_eq!(writer.buffered(), 0);
+    drop(writer);
+    assert_eq!(&dst.data, &sample);
+    assert_eq!(dst.calls, 7);
+    assert_eq!(dst.flushes, 2);
+}
+
+#[test]
+fn test_bufferedwriter_write_all() {
+    let mut sample: [u8; 100] = [0; 100];
+    fill_sample(&mut sample);
+    let mut dst = ChunkedWrite::new(7);
+    let mut writer = BufferedWriter::with_capacity(&mut dst, 16);
+    writer.write_all(&sample[0..0]).unwrap();
+    writer.write_all(&sample[0..10]).unwrap();
+    writer.write_all(&sample[10..15]).unwrap();
+    assert_eq!(writer.buffered(), 15);
+    // Larger than the buffer
+    writer.write_all(&sample[15..50]).unwrap();
+    assert_eq!(writer.buffered(), 0);
+    writer.write_all(&sample[50..60]).unwrap();
+    writer.write_all(&sample[60..100]).unwrap();
+    writer.write(0xFF).unwrap();
+    assert_eq!(writer.buffered(), 1);
+    drop(writer);
+    assert_eq!(&dst.data[..100], &sample);
+    assert_eq!(&dst.data[100..], &[0xFF]);
+    assert_eq!(dst.flushes, 1);
+}
+
+#[test]
+fn test_bufferedwriter_as_writer() {
+    let mut buff: Vec<u8> = Vec::new();
+    let mut writer = BufferedWriter::new(&mut buff);
+    writer.as_writer().write_all(&[1, 2, 3]).unwrap();
+    writer.flush().unwrap();
+    drop(writer);
+    assert_eq!(&buff, &[1, 2, 3]);
+}
+
 //=============================================================================
 // Reader for std::io::Read + std::io::Seek
 //-----------------------------------------------------------------------------
@@ -410,7 +654,7 @@ fn test_reader_for_read_seek_read() {
     assert_eq!(read.read().unwrap(), 2);
     assert_eq!(read.read().unwrap(), 3);
     assert_eq!(read.read().unwrap(), 4);
-    assert!(matches!(read.read(), Err(ErrorKind::UnableToReadData)));
+    assert!(matches!(read.read(), Err(ErrorKind::EndOfData)));
 }
 
 #[test]
@@ -426,7 +670,7 @@ fn test_reader_for_read_seek_read_all() {
     read.read_all(&mut r[0..0]).unwrap();
     assert!(matches!(
         read.read_all(&mut r[0..1]),
-        Err(ErrorKind::UnableToReadData)
+        Err(ErrorKind::EndOfData)
     ));
 
     let mut r: [u8; 4] = [0; 4];
@@ -437,16 +681,21 @@ fn test_reader_for_read_seek_read_all() {
     assert_eq!(buff, r);
     assert!(matches!(
         read.read_all(&mut r[0..1]),
-        Err(ErrorKind::UnableToReadData)
+        Err(ErrorKind::EndOfData)
     ));
 
     let mut r: [u8; 5] = [0; 5];
     let mut inner_read = std::io::Cursor::new(&buff);
     let read = &mut inner_read;
+    read.skip(1).unwrap();
     assert!(matches!(
         read.read_all(&mut r),
         Err(ErrorKind::UnableToReadData)
     ));
+    // The failed read must not consume any data
+    assert_eq!(read.position(), 1);
+    read.read_all(&mut r[1..4]).unwrap();
+    assert_eq!(&buff[1..], &r[1..4]);
 }
 
 #[test]
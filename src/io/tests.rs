@@ -45,10 +45,41 @@ fn test_errorkind_debug() {
     );
 }
 
+#[test]
+fn test_errorkind_error() {
+    use std::error::Error;
+
+    assert_eq!(
+        format!("{}", ErrorKind::UnableToReadData),
+        "unable to read data"
+    );
+    assert_eq!(
+        format!("{}", ErrorKind::UnableToWriteData),
+        "unable to write data"
+    );
+    assert_eq!(format!("{}", ErrorKind::CorruptedData), "corrupted data");
+    assert_eq!(format!("{}", ErrorKind::EndOfData), "end of data");
+    assert!(ErrorKind::CorruptedData.source().is_none());
+
+    let e = ErrorKind::IOError(std::io::Error::other("oops"));
+    assert_eq!(format!("{}", e), "I/O error");
+    assert_eq!(format!("{}", e.source().unwrap()), "oops");
+
+    // Boxed errors are transparent
+    let e = ErrorKind::BoxedError(Box::new(ErrorKind::CorruptedData));
+    assert_eq!(format!("{}", e), "corrupted data");
+    assert!(e.source().is_none());
+    let e = ErrorKind::BoxedError(Box::new(std::io::Error::other("oops")));
+    assert_eq!(format!("{}", e), "oops");
+
+    fn assert_send_sync<T: Send + Sync + 'static>() {}
+    assert_send_sync::<ErrorKind>();
+}
+
 impl DummyReader {
     pub fn new(available: usize) -> DummyReader {
         DummyReader {
-            available: available,
+            available,
             read_count: 0,
         }
     }
@@ -58,13 +89,13 @@ impl DummyReader {
     }
 }
 
-impl<'a> Reader for DummyReader {
+impl Reader for DummyReader {
     fn read(&mut self) -> Result<u8> {
         if self.available > 0 {
             self.available -= 1;
             let r = self.read_count as u8;
             self.read_count += 1;
-            Ok(r as u8)
+            Ok(r)
         } else {
             Err(ErrorKind::UnableToReadData)
         }
@@ -226,11 +257,9 @@ fn test_limitedreader_read() {
 
 fn assert_sequence(value: &[u8], expected_size: usize) {
     assert_eq!(value.len(), expected_size);
-    if value.len() > 0 {
-        let mut i = 0;
-        for x in value {
+    if !value.is_empty() {
+        for (i, x) in value.iter().enumerate() {
             assert_eq!(*x, i as u8);
-            i += 1
         }
     }
 }
@@ -270,3 +299,323 @@ fn test_limitedreader_read_all() {
         _ => panic!("Unexpected error"),
     }
 }
+
+//=============================================================================
+// LimitedWriter
+//-----------------------------------------------------------------------------
+#[test]
+fn test_limitedwriter() {
+    let mut sample: [u8; 10] = [0; 10];
+    crate::tests::fill_sample(&mut sample);
+    let mut writer = crate::io::array::VecWriter::new();
+    let mut limited = LimitedWriter::new(&mut writer, 10);
+    assert_eq!(limited.available(), 10);
+    assert!(!limited.full());
+    assert!(limited.can_write(10).is_ok());
+    assert!(limited.can_write(11).is_err());
+
+    limited.write(sample[0]).unwrap();
+    limited.write_all(&sample[1..5]).unwrap();
+    limited.as_writer().write_all(&sample[5..8]).unwrap();
+    assert_eq!(limited.available(), 2);
+    assert!(!limited.exceeded());
+
+    // Failed attempts write nothing
+    assert!(matches!(
+        limited.write_all(&sample[5..8]),
+        Err(ErrorKind::UnableToWriteData)
+    ));
+    assert!(limited.exceeded());
+    assert_eq!(limited.available(), 2);
+    limited.write_all(&sample[8..10]).unwrap();
+    assert!(limited.full());
+    assert!(matches!(
+        limited.write(0),
+        Err(ErrorKind::UnableToWriteData)
+    ));
+    assert_eq!(writer.as_slice(), &sample);
+
+    // Errors from the inner writer
+    let mut buff: [u8; 2] = [0; 2];
+    let mut writer = crate::io::array::ByteArrayWriter::new(&mut buff);
+    let mut limited = LimitedWriter::new(&mut writer, 10);
+    assert!(limited.write_all(&sample[0..3]).is_err());
+    assert!(!limited.exceeded());
+    assert_eq!(limited.available(), 10);
+}
+
+//=============================================================================
+// NullWriter
+//-----------------------------------------------------------------------------
+#[test]
+fn test_nullwriter() {
+    let mut writer = NullWriter::new();
+    assert_eq!(writer.count(), 0);
+    writer.write(1).unwrap();
+    writer.write_all(&[1, 2, 3]).unwrap();
+    writer.write_all(&[]).unwrap();
+    writer.as_writer().write(4).unwrap();
+    assert_eq!(writer.count(), 5);
+}
+
+//=============================================================================
+// CountingWriter
+//-----------------------------------------------------------------------------
+#[test]
+fn test_countingwriter() {
+    let mut sample: [u8; 10] = [0; 10];
+    crate::tests::fill_sample(&mut sample);
+    let mut buff: [u8; 8] = [0; 8];
+    let mut inner = crate::io::array::ByteArrayWriter::new(&mut buff);
+    let mut writer = CountingWriter::new(&mut inner);
+    assert_eq!(writer.count(), 0);
+    writer.write(sample[0]).unwrap();
+    writer.write_all(&sample[1..5]).unwrap();
+    writer.as_writer().write_all(&sample[5..7]).unwrap();
+    assert_eq!(writer.count(), 7);
+
+    // Failed writes are not counted
+    assert!(writer.write_all(&sample[7..10]).is_err());
+    assert_eq!(writer.count(), 7);
+    writer.write(sample[7]).unwrap();
+    assert!(writer.write(sample[8]).is_err());
+    assert_eq!(writer.count(), 8);
+    assert_sequence(&buff, 8);
+}
+
+//=============================================================================
+// TeeWriter
+//-----------------------------------------------------------------------------
+#[test]
+fn test_teewriter() {
+    let mut sample: [u8; 10] = [0; 10];
+    crate::tests::fill_sample(&mut sample);
+    let mut first = crate::io::array::VecWriter::new();
+    let mut second = NullWriter::new();
+    let mut writer = TeeWriter::new(&mut first, &mut second);
+    writer.write(sample[0]).unwrap();
+    writer.write_all(&sample[1..5]).unwrap();
+    writer.as_writer().write_all(&sample[5..10]).unwrap();
+    assert_eq!(first.as_slice(), &sample);
+    assert_eq!(second.count(), 10);
+
+    // Errors
+    let mut buff: [u8; 4] = [0; 4];
+    let mut first = crate::io::array::VecWriter::new();
+    let mut second = crate::io::array::ByteArrayWriter::new(&mut buff);
+    let mut writer = TeeWriter::new(&mut first, &mut second);
+    writer.write_all(&sample[0..4]).unwrap();
+    assert!(writer.write(sample[4]).is_err());
+    assert!(writer.write_all(&sample[4..6]).is_err());
+    let mut writer = TeeWriter::new(&mut second, &mut first);
+    assert!(writer.write(sample[4]).is_err());
+    // The first writer is not rolled back
+    assert_eq!(first.as_slice(), &[0, 1, 2, 3, 4, 4, 5]);
+    assert_sequence(&buff, 4);
+}
+
+//=============================================================================
+// OffsetReader
+//-----------------------------------------------------------------------------
+#[test]
+fn test_offsetreader() {
+    let mut buff: [u8; 10] = [0; 10];
+
+    let mut reader = DummyReader::new(10);
+    let mut offset_reader = OffsetReader::new(&mut reader);
+    assert_eq!(offset_reader.offset(), 0);
+    assert_eq!(offset_reader.read().unwrap(), 0);
+    assert_eq!(offset_reader.offset(), 1);
+    offset_reader.read_all(&mut buff[1..4]).unwrap();
+    assert_sequence(&buff[0..4], 4);
+    assert_eq!(offset_reader.offset(), 4);
+    offset_reader.skip(2).unwrap();
+    assert_eq!(offset_reader.offset(), 6);
+
+    // Failures do not change the offset
+    assert!(matches!(
+        offset_reader.read_all(&mut buff[0..5]),
+        Err(ErrorKind::UnableToReadData)
+    ));
+    assert_eq!(offset_reader.offset(), 6);
+    assert!(offset_reader.skip(5).is_err());
+    assert_eq!(offset_reader.offset(), 6);
+
+    let mut reader = DummyReader::new(10);
+    let mut offset_reader = OffsetReader::with_offset(&mut reader, 100);
+    assert_eq!(offset_reader.offset(), 100);
+    offset_reader.read_all(&mut buff[0..3]).unwrap();
+    assert_eq!(offset_reader.offset(), 103);
+}
+
+//=============================================================================
+// PeekableReader
+//-----------------------------------------------------------------------------
+#[test]
+fn test_peekablereader_new() {
+    let mut reader = DummyReader::new(10);
+    let peekable = PeekableReader::new(&mut reader);
+    assert_eq!(peekable.capacity(), PeekableReader::MIN_CAPACITY);
+    assert_eq!(peekable.buffered(), 0);
+
+    let mut reader = DummyReader::new(10);
+    let peekable = PeekableReader::with_capacity(&mut reader, 1);
+    assert_eq!(peekable.capacity(), PeekableReader::MIN_CAPACITY);
+
+    let mut reader = DummyReader::new(10);
+    let peekable = PeekableReader::with_capacity(&mut reader, 100);
+    assert_eq!(peekable.capacity(), 100);
+}
+
+#[test]
+fn test_peekablereader_peek_read() {
+    let mut buff: [u8; 10] = [0; 10];
+    let mut sample: [u8; 10] = [0; 10];
+    crate::tests::fill_sample(&mut sample);
+    let mut reader = crate::io::array::ByteArrayReader::new(&sample);
+    let mut peekable = PeekableReader::new(&mut reader);
+
+    assert_eq!(peekable.peek().unwrap(), 0);
+    assert_eq!(peekable.peek().unwrap(), 0);
+    assert_eq!(peekable.buffered(), 1);
+    assert_eq!(peekable.read().unwrap(), 0);
+    assert_eq!(peekable.buffered(), 0);
+
+    peekable.peek_all(&mut buff[1..4]).unwrap();
+    assert_sequence(&buff[0..4], 4);
+    assert_eq!(peekable.buffered(), 3);
+    peekable.read_all(&mut buff[1..6]).unwrap();
+    assert_sequence(&buff[0..6], 6);
+    assert_eq!(peekable.buffered(), 0);
+
+    // Peeking beyond the end does not consume data
+    assert!(peekable.peek_all(&mut buff[0..5]).is_err());
+    assert!(peekable.peek_all(&mut [0; 19]).is_err());
+    peekable.peek_all(&mut buff[6..8]).unwrap();
+    assert!(peekable.read_all(&mut [0; 5]).is_err());
+    assert_eq!(peekable.buffered(), 2);
+    peekable.skip(3).unwrap();
+    assert_eq!(peekable.read().unwrap(), 9);
+    assert!(peekable.peek().is_err());
+    assert!(peekable.read().is_err());
+}
+
+#[test]
+fn test_peekablereader_unread() {
+    let mut buff: [u8; 10] = [0; 10];
+    let mut reader = DummyReader::new(10);
+    let mut peekable = PeekableReader::new(&mut reader);
+
+    peekable.read_all(&mut buff[0..5]).unwrap();
+    peekable.unread(&buff[3..5]).unwrap();
+    assert_eq!(peekable.buffered(), 2);
+    peekable.unread(&[0xFF]).unwrap();
+    assert_eq!(peekable.read().unwrap(), 0xFF);
+    peekable.read_all(&mut buff[3..7]).unwrap();
+    assert_sequence(&buff[0..7], 7);
+
+    // The capacity is respected
+    peekable.unread(&[0; 10]).unwrap();
+    assert!(matches!(
+        peekable.unread(&[0; 9]),
+        Err(ErrorKind::UnableToWriteData)
+    ));
+    assert_eq!(peekable.buffered(), 10);
+    peekable.unread(&[0; 8]).unwrap();
+    peekable.skip_u64(18).unwrap();
+    assert_eq!(peekable.read().unwrap(), 7);
+}
+
+#[test]
+fn test_peekablereader_peek_ilint() {
+    let mut encoded: Vec<u8> = Vec::new();
+    let mut writer = crate::io::array::BorrowedVecWriter::new(&mut encoded);
+    crate::ilint::encode(0xF7, &mut writer).unwrap();
+    crate::ilint::encode(0x1234_5678, &mut writer).unwrap();
+    crate::ilint::encode(u64::MAX, &mut writer).unwrap();
+    encoded.push(0xFF);
+
+    let mut reader = crate::io::array::ByteArrayReader::new(&encoded);
+    let mut peekable = PeekableReader::new(&mut reader);
+    assert_eq!(peekable.peek_ilint().unwrap(), 0xF7);
+    assert_eq!(crate::ilint::decode(&mut peekable).unwrap(), 0xF7);
+    assert_eq!(peekable.peek_ilint().unwrap(), 0x1234_5678);
+    assert_eq!(peekable.buffered(), 5);
+    assert_eq!(crate::ilint::decode(&mut peekable).unwrap(), 0x1234_5678);
+    assert_eq!(peekable.peek_ilint().unwrap(), u64::MAX);
+    assert_eq!(crate::ilint::decode(&mut peekable).unwrap(), u64::MAX);
+
+    // Incomplete ILInt
+    assert!(peekable.peek_ilint().is_err());
+    assert_eq!(peekable.read().unwrap(), 0xFF);
+
+    // Overflow
+    let encoded = [0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF];
+    let mut reader = crate::io::array::ByteArrayReader::new(&encoded);
+    let mut peekable = PeekableReader::new(&mut reader);
+    assert!(matches!(
+        peekable.peek_ilint(),
+        Err(ErrorKind::CorruptedData)
+    ));
+    assert_eq!(peekable.buffered(), 9);
+}
+
+#[test]
+fn test_peekablereader_peek_tag_header() {
+    use crate::tags::standard::*;
+    use crate::tags::{ILRawTag, ILTag, ILTagFactory};
+
+    let tags: Vec<Box<dyn ILTag>> = vec![
+        Box::new(ILNullTag::new()),
+        Box::new(ILUInt32Tag::with_value(1234)),
+        Box::new(ILILInt64Tag::with_value(u64::MAX)),
+        Box::new(ILSignedILInt64Tag::with_value(-1)),
+        Box::new(ILStringTag::with_value("hello")),
+        Box::new(ILRawTag::with_value(0x1234_5678, &[1, 2, 3])),
+    ];
+    let expected: [(u64, u64); 6] = [
+        (IL_NULL_TAG_ID, 0),
+        (IL_UINT32_TAG_ID, 4),
+        (IL_ILINT_TAG_ID, 9),
+        (IL_SIGNED_ILINT_TAG_ID, 1),
+        (IL_STRING_TAG_ID, 5),
+        (0x1234_5678, 3),
+    ];
+    let mut serialized: Vec<u8> = Vec::new();
+    let mut writer = crate::io::array::BorrowedVecWriter::new(&mut serialized);
+    for t in &tags {
+        t.serialize(&mut writer).unwrap();
+    }
+
+    let factory = ILStandardTagFactory::new(false);
+    let mut reader = crate::io::array::ByteArrayReader::new(&serialized);
+    let mut peekable = PeekableReader::new(&mut reader);
+    for (tag, exp) in tags.iter().zip(expected.iter()) {
+        assert_eq!(peekable.peek_tag_header().unwrap(), *exp);
+        assert_eq!(peekable.peek_tag_header().unwrap(), *exp);
+        let t = factory.deserialize(&mut peekable).unwrap();
+        assert_eq!(t.id(), tag.id());
+        assert_eq!(t.value_size(), tag.value_size());
+    }
+    assert!(peekable.peek_tag_header().is_err());
+
+    // Under a LimitedReader
+    let mut reader = crate::io::array::ByteArrayReader::new(&serialized);
+    let mut peekable = PeekableReader::new(&mut reader);
+    let mut limited = LimitedReader::new(&mut peekable, 6);
+    let t = factory.deserialize(&mut limited).unwrap();
+    assert_eq!(t.id(), IL_NULL_TAG_ID);
+    let t = factory.deserialize(&mut limited).unwrap();
+    assert_eq!(t.id(), IL_UINT32_TAG_ID);
+    assert!(limited.empty());
+    assert_eq!(peekable.peek_tag_header().unwrap(), expected[2]);
+
+    // Reserved implicit id
+    let mut reader = crate::io::array::ByteArrayReader::new(&[15, 0]);
+    let mut peekable = PeekableReader::new(&mut reader);
+    assert!(matches!(
+        peekable.peek_tag_header(),
+        Err(ErrorKind::CorruptedData)
+    ));
+    assert_eq!(peekable.read().unwrap(), 15);
+}
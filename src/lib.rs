@@ -55,6 +55,8 @@
 //!
 pub mod ilint;
 pub mod io;
+#[cfg(feature = "serde")]
+pub mod serde;
 pub mod tags;
 #[cfg(test)]
 mod tests;
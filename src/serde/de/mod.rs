@@ -0,0 +1,342 @@
+/*
+ * BSD 3-Clause License
+ *
+ * Copyright (c) 2020, InterlockLedger Network
+ * All rights reserved.
+ *
+ * Redistribution and use in source and binary forms, with or without
+ * modification, are permitted provided that the following conditions are met:
+ *
+ * * Redistributions of source code must retain the above copyright notice, this
+ *   list of conditions and the following disclaimer.
+ *
+ * * Redistributions in binary form must reproduce the above copyright notice,
+ *   this list of conditions and the following disclaimer in the documentation
+ *   and/or other materials provided with the distribution.
+ *
+ * * Neither the name of the copyright holder nor the names of its
+ *   contributors may be used to endorse or promote products derived from
+ *   this software without specific prior written permission.
+ *
+ * THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS"
+ * AND ANY EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE
+ * IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+ * DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE
+ * FOR ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL, EXEMPLARY, OR CONSEQUENTIAL
+ * DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+ * SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER
+ * CAUSED AND ON ANY THEORY OF LIABILITY, WHETHER IN CONTRACT, STRICT LIABILITY,
+ * OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE
+ * OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+ */
+//! This module implements the serde [`Deserializer`] that converts standard
+//! tags into values. See [`crate::serde`] for further details about the
+//! mapping.
+//!
+//! New since 2.0.0.
+use super::{ErrorKind, Result};
+use crate::tags::standard::*;
+use crate::tags::{tag_downcast_ref, ILTag};
+use ::serde::de::value::{BorrowedStrDeserializer, MapDeserializer, SeqDeserializer};
+use ::serde::de::{
+    DeserializeSeed, Deserializer, EnumAccess, Error, MapAccess, SeqAccess, Unexpected,
+    VariantAccess, Visitor,
+};
+use std::collections::hash_map;
+
+//=============================================================================
+// TagDeserializer
+//-----------------------------------------------------------------------------
+/// This struct implements the serde [`Deserializer`] that converts standard
+/// tags into values. The tags are self-describing, thus the types requested
+/// by the values are used only to handle options and enums.
+pub struct TagDeserializer<'de> {
+    tag: &'de dyn ILTag,
+}
+
+impl<'de> TagDeserializer<'de> {
+    /// Creates a new instance of this struct.
+    ///
+    /// Arguments:
+    /// - `tag`: The tag to be deserialized;
+    pub fn new(tag: &'de dyn ILTag) -> Self {
+        Self { tag }
+    }
+
+    /// Returns the tag as its concrete standard type.
+    fn downcast<T: ILTag>(&self) -> Result<&'de T> {
+        match tag_downcast_ref::<T>(self.tag) {
+            Some(t) => Ok(t),
+            None => Err(ErrorKind::TagError(
+                crate::tags::ErrorKind::UnexpectedTagType,
+            )),
+        }
+    }
+
+    /// Returns the elements of the tag if it is a tag array or a tag
+    /// sequence.
+    fn elements(&self) -> Option<&'de [Box<dyn ILTag>]> {
+        match self.tag.id() {
+            IL_ILTAGARRAY_TAG_ID => {
+                tag_downcast_ref::<ILTagArrayTag>(self.tag).map(|t| t.value().as_slice())
+            }
+            IL_ILTAGSEQ_TAG_ID => {
+                tag_downcast_ref::<ILTagSeqTag>(self.tag).map(|t| t.value().as_slice())
+            }
+            _ => None,
+        }
+    }
+}
+
+impl<'de> Deserializer<'de> for TagDeserializer<'de> {
+    type Error = ErrorKind;
+
+    fn is_human_readable(&self) -> bool {
+        false
+    }
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        match self.tag.id() {
+            IL_NULL_TAG_ID => visitor.visit_unit(),
+            IL_BOOL_TAG_ID => visitor.visit_bool(self.downcast::<ILBoolTag>()?.value()),
+            IL_INT8_TAG_ID => visitor.visit_i8(self.downcast::<ILInt8Tag>()?.value()),
+            IL_UINT8_TAG_ID => visitor.visit_u8(self.downcast::<ILUInt8Tag>()?.value()),
+            IL_INT16_TAG_ID => visitor.visit_i16(self.downcast::<ILInt16Tag>()?.value()),
+            IL_UINT16_TAG_ID => visitor.visit_u16(self.downcast::<ILUInt16Tag>()?.value()),
+            IL_INT32_TAG_ID => visitor.visit_i32(self.downcast::<ILInt32Tag>()?.value()),
+            IL_UINT32_TAG_ID => visitor.visit_u32(self.downcast::<ILUInt32Tag>()?.value()),
+            IL_INT64_TAG_ID => visitor.visit_i64(self.downcast::<ILInt64Tag>()?.value()),
+            IL_UINT64_TAG_ID => visitor.visit_u64(self.downcast::<ILUInt64Tag>()?.value()),
+            IL_ILINT_TAG_ID => visitor.visit_u64(self.downcast::<ILILInt64Tag>()?.value()),
+            IL_SIGNED_ILINT_TAG_ID => {
+                visitor.visit_i64(self.downcast::<ILSignedILInt64Tag>()?.value())
+            }
+            IL_BIN32_TAG_ID => visitor.visit_f32(self.downcast::<ILBin32Tag>()?.value()),
+            IL_BIN64_TAG_ID => visitor.visit_f64(self.downcast::<ILBin64Tag>()?.value()),
+            IL_BYTES_TAG_ID => {
+                visitor.visit_borrowed_bytes(self.downcast::<ILByteArrayTag>()?.value())
+            }
+            IL_STRING_TAG_ID => visitor.visit_borrowed_str(self.downcast::<ILStringTag>()?.value()),
+            IL_ILINTARRAY_TAG_ID => visitor.visit_seq(SeqDeserializer::new(
+                self.downcast::<ILILIntArrayTag>()?.value().iter().copied(),
+            )),
+            IL_ILTAGARRAY_TAG_ID | IL_ILTAGSEQ_TAG_ID => match self.elements() {
+                Some(elements) => visitor.visit_seq(TagSeqAccess {
+                    iter: elements.iter(),
+                }),
+                None => Err(ErrorKind::TagError(
+                    crate::tags::ErrorKind::UnexpectedTagType,
+                )),
+            },
+            IL_DICTIONARY_TAG_ID => visitor.visit_map(TagMapAccess {
+                iter: self.downcast::<ILDictTag>()?.value().iter(),
+                value: None,
+            }),
+            IL_STRING_DICTIONARY_TAG_ID => visitor.visit_map(MapDeserializer::new(
+                self.downcast::<ILStrDictTag>()?
+                    .value()
+                    .iter()
+                    .map(|(k, v)| (k.as_str(), v.as_str())),
+            )),
+            _ => Err(ErrorKind::TagError(crate::tags::ErrorKind::UnsupportedTag)),
+        }
+    }
+
+    fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        if self.tag.id() == IL_NULL_TAG_ID {
+            visitor.visit_none()
+        } else {
+            visitor.visit_some(self)
+        }
+    }
+
+    fn deserialize_newtype_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value> {
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_enum<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        _variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value> {
+        match self.tag.id() {
+            IL_STRING_TAG_ID | IL_UINT32_TAG_ID => visitor.visit_enum(TagEnumAccess {
+                variant: VariantId::Tag(self.tag),
+                value: None,
+            }),
+            IL_DICTIONARY_TAG_ID => {
+                let dict = self.downcast::<ILDictTag>()?;
+                match dict.value().iter().next() {
+                    Some((k, v)) if dict.len() == 1 => visitor.visit_enum(TagEnumAccess {
+                        variant: VariantId::Name(k.as_str()),
+                        value: Some(v.as_ref()),
+                    }),
+                    _ => Err(ErrorKind::invalid_length(dict.len(), &"one variant")),
+                }
+            }
+            IL_ILTAGARRAY_TAG_ID | IL_ILTAGSEQ_TAG_ID => match self.elements() {
+                Some([variant, value]) => visitor.visit_enum(TagEnumAccess {
+                    variant: VariantId::Tag(variant.as_ref()),
+                    value: Some(value.as_ref()),
+                }),
+                Some(elements) => Err(ErrorKind::invalid_length(
+                    elements.len(),
+                    &"variant and value",
+                )),
+                None => Err(ErrorKind::TagError(
+                    crate::tags::ErrorKind::UnexpectedTagType,
+                )),
+            },
+            _ => Err(ErrorKind::TagError(
+                crate::tags::ErrorKind::UnexpectedTagType,
+            )),
+        }
+    }
+
+    ::serde::forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string
+        bytes byte_buf unit unit_struct seq tuple tuple_struct map struct
+        identifier ignored_any
+    }
+}
+
+//=============================================================================
+// TagSeqAccess
+//-----------------------------------------------------------------------------
+/// This struct gives access to the elements of [`ILTagArrayTag`] and
+/// [`ILTagSeqTag`].
+struct TagSeqAccess<'de> {
+    iter: std::slice::Iter<'de, Box<dyn ILTag>>,
+}
+
+impl<'de> SeqAccess<'de> for TagSeqAccess<'de> {
+    type Error = ErrorKind;
+
+    fn next_element_seed<T: DeserializeSeed<'de>>(&mut self, seed: T) -> Result<Option<T::Value>> {
+        match self.iter.next() {
+            Some(tag) => Ok(Some(seed.deserialize(TagDeserializer::new(tag.as_ref()))?)),
+            None => Ok(None),
+        }
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        Some(self.iter.len())
+    }
+}
+
+//=============================================================================
+// TagMapAccess
+//-----------------------------------------------------------------------------
+/// This struct gives access to the entries of [`ILDictTag`].
+struct TagMapAccess<'de> {
+    iter: hash_map::Iter<'de, String, Box<dyn ILTag>>,
+    value: Option<&'de dyn ILTag>,
+}
+
+impl<'de> MapAccess<'de> for TagMapAccess<'de> {
+    type Error = ErrorKind;
+
+    fn next_key_seed<K: DeserializeSeed<'de>>(&mut self, seed: K) -> Result<Option<K::Value>> {
+        match self.iter.next() {
+            Some((k, v)) => {
+                self.value = Some(v.as_ref());
+                Ok(Some(seed.deserialize(BorrowedStrDeserializer::<
+                    ErrorKind,
+                >::new(k.as_str()))?))
+            }
+            None => Ok(None),
+        }
+    }
+
+    fn next_value_seed<V: DeserializeSeed<'de>>(&mut self, seed: V) -> Result<V::Value> {
+        match self.value.take() {
+            Some(v) => seed.deserialize(TagDeserializer::new(v)),
+            None => Err(ErrorKind::custom("map value without a key")),
+        }
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        Some(self.iter.len())
+    }
+}
+
+//=============================================================================
+// TagEnumAccess
+//-----------------------------------------------------------------------------
+/// Identifier of an enum variant.
+enum VariantId<'de> {
+    /// The name of the variant used as the key of a dictionary.
+    Name(&'de str),
+    /// A tag with the name or the index of the variant.
+    Tag(&'de dyn ILTag),
+}
+
+/// This struct gives access to the variants of the enums.
+struct TagEnumAccess<'de> {
+    variant: VariantId<'de>,
+    value: Option<&'de dyn ILTag>,
+}
+
+impl<'de> EnumAccess<'de> for TagEnumAccess<'de> {
+    type Error = ErrorKind;
+    type Variant = TagVariantAccess<'de>;
+
+    fn variant_seed<V: DeserializeSeed<'de>>(self, seed: V) -> Result<(V::Value, Self::Variant)> {
+        let variant = match self.variant {
+            VariantId::Name(name) => {
+                seed.deserialize(BorrowedStrDeserializer::<ErrorKind>::new(name))?
+            }
+            VariantId::Tag(tag) => seed.deserialize(TagDeserializer::new(tag))?,
+        };
+        Ok((variant, TagVariantAccess { value: self.value }))
+    }
+}
+
+/// This struct gives access to the value of an enum variant.
+struct TagVariantAccess<'de> {
+    value: Option<&'de dyn ILTag>,
+}
+
+impl<'de> TagVariantAccess<'de> {
+    fn value(self, expected: &'static str) -> Result<TagDeserializer<'de>> {
+        match self.value {
+            Some(v) => Ok(TagDeserializer::new(v)),
+            None => Err(ErrorKind::invalid_type(Unexpected::UnitVariant, &expected)),
+        }
+    }
+}
+
+impl<'de> VariantAccess<'de> for TagVariantAccess<'de> {
+    type Error = ErrorKind;
+
+    fn unit_variant(self) -> Result<()> {
+        match self.value {
+            Some(v) if v.id() != IL_NULL_TAG_ID => Err(ErrorKind::invalid_type(
+                Unexpected::NewtypeVariant,
+                &"unit variant",
+            )),
+            _ => Ok(()),
+        }
+    }
+
+    fn newtype_variant_seed<T: DeserializeSeed<'de>>(self, seed: T) -> Result<T::Value> {
+        seed.deserialize(self.value("newtype variant")?)
+    }
+
+    fn tuple_variant<V: Visitor<'de>>(self, _len: usize, visitor: V) -> Result<V::Value> {
+        self.value("tuple variant")?.deserialize_any(visitor)
+    }
+
+    fn struct_variant<V: Visitor<'de>>(
+        self,
+        _fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value> {
+        self.value("struct variant")?.deserialize_any(visitor)
+    }
+}
@@ -0,0 +1,297 @@
+/*
+ * BSD 3-Clause License
+ *
+ * Copyright (c) 2020, InterlockLedger Network
+ * All rights reserved.
+ *
+ * Redistribution and use in source and binary forms, with or without
+ * modification, are permitted provided that the following conditions are met:
+ *
+ * * Redistributions of source code must retain the above copyright notice, this
+ *   list of conditions and the following disclaimer.
+ *
+ * * Redistributions in binary form must reproduce the above copyright notice,
+ *   this list of conditions and the following disclaimer in the documentation
+ *   and/or other materials provided with the distribution.
+ *
+ * * Neither the name of the copyright holder nor the names of its
+ *   contributors may be used to endorse or promote products derived from
+ *   this software without specific prior written permission.
+ *
+ * THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS"
+ * AND ANY EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE
+ * IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+ * DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE
+ * FOR ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL, EXEMPLARY, OR CONSEQUENTIAL
+ * DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+ * SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER
+ * CAUSED AND ON ANY THEORY OF LIABILITY, WHETHER IN CONTRACT, STRICT LIABILITY,
+ * OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE
+ * OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+ */
+//! This module maps the [serde](https://serde.rs) data model onto the
+//! standard tags. It is available only if the feature `serde` is enabled.
+//!
+//! The values are mapped as follows:
+//! - `bool`, `i8`, `u8`, `i16`, `u16`, `i32`, `u32`, `i64`, `u64`, `f32` and
+//!   `f64`: The corresponding implicit standard tags;
+//! - `char` and strings: [`ILStringTag`];
+//! - Byte arrays: [`ILByteArrayTag`];
+//! - `None`, `()` and unit structs: [`ILNullTag`];
+//! - `Some(v)` and newtype structs: The tag of the inner value;
+//! - Sequences and tuples: [`ILTagArrayTag`];
+//! - Maps: [`ILDictTag`]. The keys must be strings;
+//! - Structs: [`ILDictTag`] or [`ILTagArrayTag`] according to the
+//!   [`StructLayout`];
+//! - Enums: The unit variants are mapped into the name of the variant
+//!   ([`StructLayout::Dictionary`]) or into its index as an [`ILUInt32Tag`]
+//!   ([`StructLayout::Sequence`]). The other variants are mapped into an
+//!   [`ILDictTag`] with a single entry or into an [`ILTagArrayTag`] with the
+//!   index and the value respectively;
+//!
+//! The deserialization accepts both layouts regardless of the one used to
+//! serialize the data.
+//!
+//! Since the size of each container must be written before its contents,
+//! the serialization does not stream. [`to_bytes()`], [`to_writer()`] and
+//! their variants first convert the whole value into a tag tree with
+//! [`to_tag()`] and only then serialize it. As such, the memory required
+//! grows with the size of the value and nothing is written before the tree
+//! is complete.
+//!
+//! Example:
+//!
+//! ```
+//! use serde::{Deserialize, Serialize};
+//!
+//! #[derive(Serialize, Deserialize, Debug, PartialEq)]
+//! struct Point {
+//!     x: i32,
+//!     y: i32,
+//! }
+//!
+//! let p = Point { x: 1, y: 2 };
+//! let bytes = il2_iltags::serde::to_bytes(&p).unwrap();
+//! let q: Point = il2_iltags::serde::from_bytes(&bytes).unwrap();
+//! assert_eq!(p, q);
+//! ```
+//!
+//! New since 2.0.0.
+#[cfg(test)]
+mod tests;
+
+pub mod de;
+pub mod ser;
+
+use crate::io::{Reader, Writer};
+use crate::tags::standard::ILStandardTagFactory;
+#[cfg(doc)]
+use crate::tags::standard::{
+    ILByteArrayTag, ILDictTag, ILNullTag, ILStringTag, ILTagArrayTag, ILUInt32Tag,
+};
+use crate::tags::{ILTag, ILTagFactory};
+use ::serde::de::DeserializeOwned;
+use ::serde::{Deserialize, Serialize};
+use std::fmt::Display;
+
+//=============================================================================
+// ErrorKind
+//-----------------------------------------------------------------------------
+/// Errors reported by this module.
+#[derive(Debug)]
+pub enum ErrorKind {
+    /// The value cannot be represented by the standard tags.
+    UnsupportedType(&'static str),
+    /// Error reported by the implementations of [`Serialize`] and
+    /// [`Deserialize`].
+    Message(String),
+    /// Wrapper to the errors reported by the tags.
+    TagError(crate::tags::ErrorKind),
+}
+
+/// Alias to the results of this module.
+pub type Result<T> = std::result::Result<T, ErrorKind>;
+
+impl Display for ErrorKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ErrorKind::UnsupportedType(t) => write!(f, "unsupported type: {}", t),
+            ErrorKind::Message(m) => f.write_str(m),
+            ErrorKind::TagError(e) => Display::fmt(e, f),
+        }
+    }
+}
+
+impl std::error::Error for ErrorKind {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            ErrorKind::TagError(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<crate::tags::ErrorKind> for ErrorKind {
+    fn from(error: crate::tags::ErrorKind) -> Self {
+        ErrorKind::TagError(error)
+    }
+}
+
+impl ::serde::ser::Error for ErrorKind {
+    fn custom<T: Display>(msg: T) -> Self {
+        ErrorKind::Message(msg.to_string())
+    }
+}
+
+impl ::serde::de::Error for ErrorKind {
+    fn custom<T: Display>(msg: T) -> Self {
+        ErrorKind::Message(msg.to_string())
+    }
+}
+
+//=============================================================================
+// StructLayout
+//-----------------------------------------------------------------------------
+/// Defines how structs and enum variants are mapped into tags.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Default)]
+pub enum StructLayout {
+    /// Structs are mapped into dictionaries indexed by the name of the
+    /// fields and the variants are identified by their names. This is the
+    /// default layout.
+    #[default]
+    Dictionary,
+    /// Structs are mapped into arrays with the fields in the order they are
+    /// declared and the variants are identified by their indexes. This
+    /// layout is more compact but it does not support skipped fields.
+    Sequence,
+}
+
+//=============================================================================
+// Functions
+//-----------------------------------------------------------------------------
+/// Converts a value into a tag.
+///
+/// Arguments:
+/// - `value`: The value;
+/// - `layout`: The layout of the structs;
+///
+/// Returns:
+/// - `Ok(tag)`: The tag that represents the value;
+/// - `Err(_)`: For failure;
+pub fn to_tag<T: Serialize + ?Sized>(value: &T, layout: StructLayout) -> Result<Box<dyn ILTag>> {
+    value.serialize(ser::TagSerializer::new(layout))
+}
+
+/// Serializes a value using [`StructLayout::Dictionary`].
+///
+/// The value is fully converted into a tag tree before being serialized.
+/// See the documentation of this module for details.
+///
+/// Arguments:
+/// - `value`: The value;
+///
+/// Returns:
+/// - `Ok(bytes)`: The serialized value;
+/// - `Err(_)`: For failure;
+pub fn to_bytes<T: Serialize + ?Sized>(value: &T) -> Result<Vec<u8>> {
+    to_bytes_with_layout(value, StructLayout::default())
+}
+
+/// Serializes a value using the given layout.
+///
+/// The value is fully converted into a tag tree before being serialized.
+/// See the documentation of this module for details.
+///
+/// Arguments:
+/// - `value`: The value;
+/// - `layout`: The layout of the structs;
+///
+/// Returns:
+/// - `Ok(bytes)`: The serialized value;
+/// - `Err(_)`: For failure;
+pub fn to_bytes_with_layout<T: Serialize + ?Sized>(
+    value: &T,
+    layout: StructLayout,
+) -> Result<Vec<u8>> {
+    Ok(to_tag(value, layout)?.to_bytes()?)
+}
+
+/// Serializes a value into a writer using [`StructLayout::Dictionary`].
+///
+/// The value is fully converted into a tag tree before being serialized.
+/// See the documentation of this module for details.
+///
+/// Arguments:
+/// - `value`: The value;
+/// - `writer`: The writer;
+///
+/// Returns:
+/// - `Ok(())`: For success;
+/// - `Err(_)`: For failure;
+pub fn to_writer<T: Serialize + ?Sized>(value: &T, writer: &mut dyn Writer) -> Result<()> {
+    to_writer_with_layout(value, StructLayout::default(), writer)
+}
+
+/// Serializes a value into a writer using the given layout.
+///
+/// The value is fully converted into a tag tree before being serialized.
+/// See the documentation of this module for details.
+///
+/// Arguments:
+/// - `value`: The value;
+/// - `layout`: The layout of the structs;
+/// - `writer`: The writer;
+///
+/// Returns:
+/// - `Ok(())`: For success;
+/// - `Err(_)`: For failure;
+pub fn to_writer_with_layout<T: Serialize + ?Sized>(
+    value: &T,
+    layout: StructLayout,
+    writer: &mut dyn Writer,
+) -> Result<()> {
+    Ok(to_tag(value, layout)?.serialize(writer)?)
+}
+
+/// Converts a tag into a value.
+///
+/// Arguments:
+/// - `tag`: The tag;
+///
+/// Returns:
+/// - `Ok(v)`: The value;
+/// - `Err(_)`: For failure;
+pub fn from_tag<'de, T: Deserialize<'de>>(tag: &'de dyn ILTag) -> Result<T> {
+    T::deserialize(de::TagDeserializer::new(tag))
+}
+
+/// Deserializes a value from a byte slice. The tag is deserialized by a
+/// strict [`ILStandardTagFactory`] and the slice must contain exactly one
+/// tag.
+///
+/// Arguments:
+/// - `bytes`: The serialized value;
+///
+/// Returns:
+/// - `Ok(v)`: The value;
+/// - `Err(_)`: For failure;
+pub fn from_bytes<T: DeserializeOwned>(bytes: &[u8]) -> Result<T> {
+    let factory = ILStandardTagFactory::new(true);
+    let tag = factory.from_bytes(bytes)?;
+    from_tag(tag.as_ref())
+}
+
+/// Deserializes a value from a reader. The tag is deserialized by a strict
+/// [`ILStandardTagFactory`].
+///
+/// Arguments:
+/// - `reader`: The reader;
+///
+/// Returns:
+/// - `Ok(v)`: The value;
+/// - `Err(_)`: For failure;
+pub fn from_reader<T: DeserializeOwned>(reader: &mut dyn Reader) -> Result<T> {
+    let factory = ILStandardTagFactory::new(true);
+    let tag = factory.deserialize(reader)?;
+    from_tag(tag.as_ref())
+}
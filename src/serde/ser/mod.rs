@@ -0,0 +1,612 @@
+/*
+ * BSD 3-Clause License
+ *
+ * Copyright (c) 2020, InterlockLedger Network
+ * All rights reserved.
+ *
+ * Redistribution and use in source and binary forms, with or without
+ * modification, are permitted provided that the following conditions are met:
+ *
+ * * Redistributions of source code must retain the above copyright notice, this
+ *   list of conditions and the following disclaimer.
+ *
+ * * Redistributions in binary form must reproduce the above copyright notice,
+ *   this list of conditions and the following disclaimer in the documentation
+ *   and/or other materials provided with the distribution.
+ *
+ * * Neither the name of the copyright holder nor the names of its
+ *   contributors may be used to endorse or promote products derived from
+ *   this software without specific prior written permission.
+ *
+ * THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS"
+ * AND ANY EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE
+ * IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+ * DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE
+ * FOR ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL, EXEMPLARY, OR CONSEQUENTIAL
+ * DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+ * SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER
+ * CAUSED AND ON ANY THEORY OF LIABILITY, WHETHER IN CONTRACT, STRICT LIABILITY,
+ * OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE
+ * OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+ */
+//! This module implements the serde [`Serializer`] that converts values into
+//! standard tags. See [`crate::serde`] for further details about the mapping.
+//!
+//! New since 2.0.0.
+use super::{ErrorKind, Result, StructLayout};
+use crate::tags::standard::*;
+use crate::tags::ILTag;
+use ::serde::ser::{
+    Impossible, Serialize, SerializeMap, SerializeSeq, SerializeStruct, SerializeStructVariant,
+    SerializeTuple, SerializeTupleStruct, SerializeTupleVariant, Serializer,
+};
+
+/// Wraps the value of an enum variant according to the given layout.
+///
+/// Arguments:
+/// - `layout`: The layout;
+/// - `index`: The index of the variant;
+/// - `name`: The name of the variant;
+/// - `value`: The value of the variant;
+///
+/// Returns:
+/// - The tag that represents the variant.
+fn wrap_variant(
+    layout: StructLayout,
+    index: u32,
+    name: &'static str,
+    value: Box<dyn ILTag>,
+) -> Box<dyn ILTag> {
+    match layout {
+        StructLayout::Dictionary => {
+            let mut tag = ILDictTag::new();
+            tag.insert(name, value);
+            Box::new(tag)
+        }
+        StructLayout::Sequence => {
+            let mut tag = ILTagArrayTag::new();
+            tag.mut_value()
+                .push(Box::new(ILUInt32Tag::with_value(index)));
+            tag.mut_value().push(value);
+            Box::new(tag)
+        }
+    }
+}
+
+//=============================================================================
+// TagSerializer
+//-----------------------------------------------------------------------------
+/// This struct implements the serde [`Serializer`] that converts values into
+/// standard tags.
+pub struct TagSerializer {
+    layout: StructLayout,
+}
+
+impl TagSerializer {
+    /// Creates a new instance of this struct.
+    ///
+    /// Arguments:
+    /// - `layout`: The layout of the structs;
+    pub fn new(layout: StructLayout) -> Self {
+        Self { layout }
+    }
+}
+
+impl Serializer for TagSerializer {
+    type Ok = Box<dyn ILTag>;
+    type Error = ErrorKind;
+    type SerializeSeq = SeqSerializer;
+    type SerializeTuple = SeqSerializer;
+    type SerializeTupleStruct = SeqSerializer;
+    type SerializeTupleVariant = SeqSerializer;
+    type SerializeMap = MapSerializer;
+    type SerializeStruct = StructSerializer;
+    type SerializeStructVariant = StructSerializer;
+
+    fn is_human_readable(&self) -> bool {
+        false
+    }
+
+    fn serialize_bool(self, v: bool) -> Result<Self::Ok> {
+        Ok(Box::new(ILBoolTag::with_value(v)))
+    }
+
+    fn serialize_i8(self, v: i8) -> Result<Self::Ok> {
+        Ok(Box::new(ILInt8Tag::with_value(v)))
+    }
+
+    fn serialize_i16(self, v: i16) -> Result<Self::Ok> {
+        Ok(Box::new(ILInt16Tag::with_value(v)))
+    }
+
+    fn serialize_i32(self, v: i32) -> Result<Self::Ok> {
+        Ok(Box::new(ILInt32Tag::with_value(v)))
+    }
+
+    fn serialize_i64(self, v: i64) -> Result<Self::Ok> {
+        Ok(Box::new(ILInt64Tag::with_value(v)))
+    }
+
+    fn serialize_u8(self, v: u8) -> Result<Self::Ok> {
+        Ok(Box::new(ILUInt8Tag::with_value(v)))
+    }
+
+    fn serialize_u16(self, v: u16) -> Result<Self::Ok> {
+        Ok(Box::new(ILUInt16Tag::with_value(v)))
+    }
+
+    fn serialize_u32(self, v: u32) -> Result<Self::Ok> {
+        Ok(Box::new(ILUInt32Tag::with_value(v)))
+    }
+
+    fn serialize_u64(self, v: u64) -> Result<Self::Ok> {
+        Ok(Box::new(ILUInt64Tag::with_value(v)))
+    }
+
+    fn serialize_f32(self, v: f32) -> Result<Self::Ok> {
+        Ok(Box::new(ILBin32Tag::with_value(v)))
+    }
+
+    fn serialize_f64(self, v: f64) -> Result<Self::Ok> {
+        Ok(Box::new(ILBin64Tag::with_value(v)))
+    }
+
+    fn serialize_char(self, v: char) -> Result<Self::Ok> {
+        let mut tmp = [0u8; 4];
+        self.serialize_str(v.encode_utf8(&mut tmp))
+    }
+
+    fn serialize_str(self, v: &str) -> Result<Self::Ok> {
+        Ok(Box::new(ILStringTag::with_value(v)))
+    }
+
+    fn serialize_bytes(self, v: &[u8]) -> Result<Self::Ok> {
+        Ok(Box::new(ILByteArrayTag::with_value(v)))
+    }
+
+    fn serialize_none(self) -> Result<Self::Ok> {
+        Ok(Box::new(ILNullTag::new()))
+    }
+
+    fn serialize_some<T: Serialize + ?Sized>(self, value: &T) -> Result<Self::Ok> {
+        value.serialize(self)
+    }
+
+    fn serialize_unit(self) -> Result<Self::Ok> {
+        Ok(Box::new(ILNullTag::new()))
+    }
+
+    fn serialize_unit_struct(self, _name: &'static str) -> Result<Self::Ok> {
+        self.serialize_unit()
+    }
+
+    fn serialize_unit_variant(
+        self,
+        _name: &'static str,
+        variant_index: u32,
+        variant: &'static str,
+    ) -> Result<Self::Ok> {
+        match self.layout {
+            StructLayout::Dictionary => self.serialize_str(variant),
+            StructLayout::Sequence => self.serialize_u32(variant_index),
+        }
+    }
+
+    fn serialize_newtype_struct<T: Serialize + ?Sized>(
+        self,
+        _name: &'static str,
+        value: &T,
+    ) -> Result<Self::Ok> {
+        value.serialize(self)
+    }
+
+    fn serialize_newtype_variant<T: Serialize + ?Sized>(
+        self,
+        _name: &'static str,
+        variant_index: u32,
+        variant: &'static str,
+        value: &T,
+    ) -> Result<Self::Ok> {
+        let layout = self.layout;
+        let value = value.serialize(self)?;
+        Ok(wrap_variant(layout, variant_index, variant, value))
+    }
+
+    fn serialize_seq(self, _len: Option<usize>) -> Result<Self::SerializeSeq> {
+        Ok(SeqSerializer::new(self.layout, None))
+    }
+
+    fn serialize_tuple(self, len: usize) -> Result<Self::SerializeTuple> {
+        self.serialize_seq(Some(len))
+    }
+
+    fn serialize_tuple_struct(
+        self,
+        _name: &'static str,
+        len: usize,
+    ) -> Result<Self::SerializeTupleStruct> {
+        self.serialize_seq(Some(len))
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        _name: &'static str,
+        variant_index: u32,
+        variant: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeTupleVariant> {
+        Ok(SeqSerializer::new(
+            self.layout,
+            Some((variant_index, variant)),
+        ))
+    }
+
+    fn serialize_map(self, _len: Option<usize>) -> Result<Self::SerializeMap> {
+        Ok(MapSerializer {
+            layout: self.layout,
+            tag: ILDictTag::new(),
+            key: None,
+        })
+    }
+
+    fn serialize_struct(self, _name: &'static str, _len: usize) -> Result<Self::SerializeStruct> {
+        Ok(StructSerializer::new(self.layout, None))
+    }
+
+    fn serialize_struct_variant(
+        self,
+        _name: &'static str,
+        variant_index: u32,
+        variant: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeStructVariant> {
+        Ok(StructSerializer::new(
+            self.layout,
+            Some((variant_index, variant)),
+        ))
+    }
+}
+
+//=============================================================================
+// SeqSerializer
+//-----------------------------------------------------------------------------
+/// This struct serializes sequences, tuples and tuple variants into
+/// [`ILTagArrayTag`].
+pub struct SeqSerializer {
+    layout: StructLayout,
+    variant: Option<(u32, &'static str)>,
+    tag: ILTagArrayTag,
+}
+
+impl SeqSerializer {
+    fn new(layout: StructLayout, variant: Option<(u32, &'static str)>) -> Self {
+        Self {
+            layout,
+            variant,
+            tag: ILTagArrayTag::new(),
+        }
+    }
+
+    fn push<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<()> {
+        let value = value.serialize(TagSerializer::new(self.layout))?;
+        self.tag.mut_value().push(value);
+        Ok(())
+    }
+
+    fn finish(self) -> Result<Box<dyn ILTag>> {
+        let value: Box<dyn ILTag> = Box::new(self.tag);
+        match self.variant {
+            Some((index, name)) => Ok(wrap_variant(self.layout, index, name, value)),
+            None => Ok(value),
+        }
+    }
+}
+
+impl SerializeSeq for SeqSerializer {
+    type Ok = Box<dyn ILTag>;
+    type Error = ErrorKind;
+
+    fn serialize_element<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<()> {
+        self.push(value)
+    }
+
+    fn end(self) -> Result<Self::Ok> {
+        self.finish()
+    }
+}
+
+impl SerializeTuple for SeqSerializer {
+    type Ok = Box<dyn ILTag>;
+    type Error = ErrorKind;
+
+    fn serialize_element<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<()> {
+        self.push(value)
+    }
+
+    fn end(self) -> Result<Self::Ok> {
+        self.finish()
+    }
+}
+
+impl SerializeTupleStruct for SeqSerializer {
+    type Ok = Box<dyn ILTag>;
+    type Error = ErrorKind;
+
+    fn serialize_field<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<()> {
+        self.push(value)
+    }
+
+    fn end(self) -> Result<Self::Ok> {
+        self.finish()
+    }
+}
+
+impl SerializeTupleVariant for SeqSerializer {
+    type Ok = Box<dyn ILTag>;
+    type Error = ErrorKind;
+
+    fn serialize_field<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<()> {
+        self.push(value)
+    }
+
+    fn end(self) -> Result<Self::Ok> {
+        self.finish()
+    }
+}
+
+//=============================================================================
+// MapSerializer
+//-----------------------------------------------------------------------------
+/// This struct serializes maps into [`ILDictTag`]. Only string keys are
+/// supported.
+pub struct MapSerializer {
+    layout: StructLayout,
+    tag: ILDictTag,
+    key: Option<String>,
+}
+
+impl SerializeMap for MapSerializer {
+    type Ok = Box<dyn ILTag>;
+    type Error = ErrorKind;
+
+    fn serialize_key<T: Serialize + ?Sized>(&mut self, key: &T) -> Result<()> {
+        self.key = Some(key.serialize(KeySerializer)?);
+        Ok(())
+    }
+
+    fn serialize_value<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<()> {
+        let key = match self.key.take() {
+            Some(key) => key,
+            None => return Err(ErrorKind::Message(String::from("map value without a key"))),
+        };
+        let value = value.serialize(TagSerializer::new(self.layout))?;
+        self.tag.insert(&key, value);
+        Ok(())
+    }
+
+    fn end(self) -> Result<Self::Ok> {
+        Ok(Box::new(self.tag))
+    }
+}
+
+//=============================================================================
+// StructSerializer
+//-----------------------------------------------------------------------------
+/// This struct serializes structs and struct variants into [`ILDictTag`] or
+/// [`ILTagArrayTag`] according to the [`StructLayout`].
+pub struct StructSerializer {
+    variant: Option<(u32, &'static str)>,
+    dict: ILDictTag,
+    seq: SeqSerializer,
+}
+
+impl StructSerializer {
+    fn new(layout: StructLayout, variant: Option<(u32, &'static str)>) -> Self {
+        Self {
+            variant,
+            dict: ILDictTag::new(),
+            seq: SeqSerializer::new(layout, None),
+        }
+    }
+
+    fn push<T: Serialize + ?Sized>(&mut self, key: &'static str, value: &T) -> Result<()> {
+        match self.seq.layout {
+            StructLayout::Dictionary => {
+                let value = value.serialize(TagSerializer::new(self.seq.layout))?;
+                self.dict.insert(key, value);
+                Ok(())
+            }
+            StructLayout::Sequence => self.seq.push(value),
+        }
+    }
+
+    fn finish(self) -> Result<Box<dyn ILTag>> {
+        let layout = self.seq.layout;
+        let value: Box<dyn ILTag> = match layout {
+            StructLayout::Dictionary => Box::new(self.dict),
+            StructLayout::Sequence => self.seq.finish()?,
+        };
+        match self.variant {
+            Some((index, name)) => Ok(wrap_variant(layout, index, name, value)),
+            None => Ok(value),
+        }
+    }
+}
+
+impl SerializeStruct for StructSerializer {
+    type Ok = Box<dyn ILTag>;
+    type Error = ErrorKind;
+
+    fn serialize_field<T: Serialize + ?Sized>(
+        &mut self,
+        key: &'static str,
+        value: &T,
+    ) -> Result<()> {
+        self.push(key, value)
+    }
+
+    fn end(self) -> Result<Self::Ok> {
+        self.finish()
+    }
+}
+
+impl SerializeStructVariant for StructSerializer {
+    type Ok = Box<dyn ILTag>;
+    type Error = ErrorKind;
+
+    fn serialize_field<T: Serialize + ?Sized>(
+        &mut self,
+        key: &'static str,
+        value: &T,
+    ) -> Result<()> {
+        self.push(key, value)
+    }
+
+    fn end(self) -> Result<Self::Ok> {
+        self.finish()
+    }
+}
+
+//=============================================================================
+// KeySerializer
+//-----------------------------------------------------------------------------
+/// Description of the keys rejected by [`KeySerializer`].
+const UNSUPPORTED_KEY: &str = "map keys must be strings";
+
+/// This macro implements the methods of [`KeySerializer`] that reject the
+/// value.
+macro_rules! unsupported_key_impl {
+    ($($method: ident($type: ty)),*) => {
+        $(
+            fn $method(self, _v: $type) -> Result<String> {
+                Err(ErrorKind::UnsupportedType(UNSUPPORTED_KEY))
+            }
+        )*
+    };
+}
+
+/// This struct serializes the keys of the maps. Only strings, chars and unit
+/// variants are accepted.
+struct KeySerializer;
+
+impl Serializer for KeySerializer {
+    type Ok = String;
+    type Error = ErrorKind;
+    type SerializeSeq = Impossible<String, ErrorKind>;
+    type SerializeTuple = Impossible<String, ErrorKind>;
+    type SerializeTupleStruct = Impossible<String, ErrorKind>;
+    type SerializeTupleVariant = Impossible<String, ErrorKind>;
+    type SerializeMap = Impossible<String, ErrorKind>;
+    type SerializeStruct = Impossible<String, ErrorKind>;
+    type SerializeStructVariant = Impossible<String, ErrorKind>;
+
+    unsupported_key_impl!(
+        serialize_bool(bool),
+        serialize_i8(i8),
+        serialize_i16(i16),
+        serialize_i32(i32),
+        serialize_i64(i64),
+        serialize_u8(u8),
+        serialize_u16(u16),
+        serialize_u32(u32),
+        serialize_u64(u64),
+        serialize_f32(f32),
+        serialize_f64(f64),
+        serialize_bytes(&[u8])
+    );
+
+    fn serialize_char(self, v: char) -> Result<String> {
+        Ok(v.to_string())
+    }
+
+    fn serialize_str(self, v: &str) -> Result<String> {
+        Ok(String::from(v))
+    }
+
+    fn serialize_none(self) -> Result<String> {
+        Err(ErrorKind::UnsupportedType(UNSUPPORTED_KEY))
+    }
+
+    fn serialize_some<T: Serialize + ?Sized>(self, value: &T) -> Result<String> {
+        value.serialize(self)
+    }
+
+    fn serialize_unit(self) -> Result<String> {
+        Err(ErrorKind::UnsupportedType(UNSUPPORTED_KEY))
+    }
+
+    fn serialize_unit_struct(self, _name: &'static str) -> Result<String> {
+        Err(ErrorKind::UnsupportedType(UNSUPPORTED_KEY))
+    }
+
+    fn serialize_unit_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+    ) -> Result<String> {
+        Ok(String::from(variant))
+    }
+
+    fn serialize_newtype_struct<T: Serialize + ?Sized>(
+        self,
+        _name: &'static str,
+        value: &T,
+    ) -> Result<String> {
+        value.serialize(self)
+    }
+
+    fn serialize_newtype_variant<T: Serialize + ?Sized>(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        _value: &T,
+    ) -> Result<String> {
+        Err(ErrorKind::UnsupportedType(UNSUPPORTED_KEY))
+    }
+
+    fn serialize_seq(self, _len: Option<usize>) -> Result<Self::SerializeSeq> {
+        Err(ErrorKind::UnsupportedType(UNSUPPORTED_KEY))
+    }
+
+    fn serialize_tuple(self, _len: usize) -> Result<Self::SerializeTuple> {
+        Err(ErrorKind::UnsupportedType(UNSUPPORTED_KEY))
+    }
+
+    fn serialize_tuple_struct(
+        self,
+        _name: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeTupleStruct> {
+        Err(ErrorKind::UnsupportedType(UNSUPPORTED_KEY))
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeTupleVariant> {
+        Err(ErrorKind::UnsupportedType(UNSUPPORTED_KEY))
+    }
+
+    fn serialize_map(self, _len: Option<usize>) -> Result<Self::SerializeMap> {
+        Err(ErrorKind::UnsupportedType(UNSUPPORTED_KEY))
+    }
+
+    fn serialize_struct(self, _name: &'static str, _len: usize) -> Result<Self::SerializeStruct> {
+        Err(ErrorKind::UnsupportedType(UNSUPPORTED_KEY))
+    }
+
+    fn serialize_struct_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeStructVariant> {
+        Err(ErrorKind::UnsupportedType(UNSUPPORTED_KEY))
+    }
+}
@@ -0,0 +1,351 @@
+/*
+ * BSD 3-Clause License
+ *
+ * Copyright (c) 2020, InterlockLedger Network
+ * All rights reserved.
+ *
+ * Redistribution and use in source and binary forms, with or without
+ * modification, are permitted provided that the following conditions are met:
+ *
+ * * Redistributions of source code must retain the above copyright notice, this
+ *   list of conditions and the following disclaimer.
+ *
+ * * Redistributions in binary form must reproduce the above copyright notice,
+ *   this list of conditions and the following disclaimer in the documentation
+ *   and/or other materials provided with the distribution.
+ *
+ * * Neither the name of the copyright holder nor the names of its
+ *   contributors may be used to endorse or promote products derived from
+ *   this software without specific prior written permission.
+ *
+ * THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS"
+ * AND ANY EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE
+ * IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+ * DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE
+ * FOR ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL, EXEMPLARY, OR CONSEQUENTIAL
+ * DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+ * SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER
+ * CAUSED AND ON ANY THEORY OF LIABILITY, WHETHER IN CONTRACT, STRICT LIABILITY,
+ * OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE
+ * OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+ */
+use super::*;
+use crate::io::array::{ByteArrayReader, VecWriter};
+use crate::tags::standard::*;
+use crate::tags::{tag_downcast_ref, ErrorKind as TagErrorKind};
+use ::serde::{Deserialize, Serialize, Serializer};
+use std::collections::{BTreeMap, HashMap};
+use std::error::Error;
+
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+struct Unit;
+
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+struct Newtype(u16);
+
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+struct Tuple(i8, String);
+
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+enum Variant {
+    Unit,
+    Newtype(u32),
+    Tuple(bool, f32),
+    Struct { a: i64, b: Option<String> },
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+struct Sample {
+    a: u8,
+    b: Option<u64>,
+    c: Vec<String>,
+    d: Tuple,
+    e: BTreeMap<String, Variant>,
+    f: (f64, Unit, Newtype),
+}
+
+fn create_sample() -> Sample {
+    let mut e = BTreeMap::new();
+    e.insert(String::from("u"), Variant::Unit);
+    e.insert(String::from("n"), Variant::Newtype(1));
+    e.insert(String::from("t"), Variant::Tuple(true, 1.5));
+    e.insert(
+        String::from("s"),
+        Variant::Struct {
+            a: -1,
+            b: Some(String::from("b")),
+        },
+    );
+    Sample {
+        a: 1,
+        b: None,
+        c: vec![String::from("x"), String::from("y")],
+        d: Tuple(-2, String::from("tuple")),
+        e,
+        f: (0.25, Unit, Newtype(3)),
+    }
+}
+
+struct Bytes<'a>(&'a [u8]);
+
+impl Serialize for Bytes<'_> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error> {
+        serializer.serialize_bytes(self.0)
+    }
+}
+
+#[test]
+fn test_primitives() {
+    assert_eq!(to_bytes(&true).unwrap(), [IL_BOOL_TAG_ID as u8, 1]);
+    assert_eq!(to_bytes(&-1i8).unwrap(), [IL_INT8_TAG_ID as u8, 0xFF]);
+    assert_eq!(to_bytes(&1u8).unwrap(), [IL_UINT8_TAG_ID as u8, 1]);
+    assert_eq!(to_bytes(&1i16).unwrap(), [IL_INT16_TAG_ID as u8, 0, 1]);
+    assert_eq!(to_bytes(&1u16).unwrap(), [IL_UINT16_TAG_ID as u8, 0, 1]);
+    assert_eq!(
+        to_bytes(&1i32).unwrap(),
+        [IL_INT32_TAG_ID as u8, 0, 0, 0, 1]
+    );
+    assert_eq!(
+        to_bytes(&1u32).unwrap(),
+        [IL_UINT32_TAG_ID as u8, 0, 0, 0, 1]
+    );
+    assert_eq!(to_bytes(&1i64).unwrap().len(), 9);
+    assert_eq!(to_bytes(&1u64).unwrap()[0], IL_UINT64_TAG_ID as u8);
+    assert_eq!(to_bytes(&1.0f32).unwrap()[0], IL_BIN32_TAG_ID as u8);
+    assert_eq!(to_bytes(&1.0f64).unwrap()[0], IL_BIN64_TAG_ID as u8);
+    assert_eq!(to_bytes(&'a').unwrap(), [IL_STRING_TAG_ID as u8, 1, b'a']);
+    assert_eq!(
+        to_bytes("ab").unwrap(),
+        [IL_STRING_TAG_ID as u8, 2, b'a', b'b']
+    );
+    assert_eq!(to_bytes(&()).unwrap(), [IL_NULL_TAG_ID as u8]);
+    assert_eq!(to_bytes(&Unit).unwrap(), [IL_NULL_TAG_ID as u8]);
+    assert_eq!(
+        to_bytes(&Option::<u8>::None).unwrap(),
+        [IL_NULL_TAG_ID as u8]
+    );
+    assert_eq!(to_bytes(&Some(1u8)).unwrap(), [IL_UINT8_TAG_ID as u8, 1]);
+    assert_eq!(
+        to_bytes(&Newtype(1)).unwrap(),
+        [IL_UINT16_TAG_ID as u8, 0, 1]
+    );
+    assert_eq!(
+        to_bytes(&Bytes(&[1, 2])).unwrap(),
+        [IL_BYTES_TAG_ID as u8, 2, 1, 2]
+    );
+
+    assert!(from_bytes::<bool>(&to_bytes(&true).unwrap()).unwrap());
+    assert_eq!(from_bytes::<i8>(&to_bytes(&-1i8).unwrap()).unwrap(), -1);
+    assert_eq!(from_bytes::<u8>(&to_bytes(&1u8).unwrap()).unwrap(), 1);
+    assert_eq!(from_bytes::<i16>(&to_bytes(&-1i16).unwrap()).unwrap(), -1);
+    assert_eq!(from_bytes::<u16>(&to_bytes(&1u16).unwrap()).unwrap(), 1);
+    assert_eq!(from_bytes::<i32>(&to_bytes(&-1i32).unwrap()).unwrap(), -1);
+    assert_eq!(from_bytes::<u32>(&to_bytes(&1u32).unwrap()).unwrap(), 1);
+    assert_eq!(from_bytes::<i64>(&to_bytes(&-1i64).unwrap()).unwrap(), -1);
+    assert_eq!(from_bytes::<u64>(&to_bytes(&1u64).unwrap()).unwrap(), 1);
+    assert_eq!(from_bytes::<f32>(&to_bytes(&1.5f32).unwrap()).unwrap(), 1.5);
+    assert_eq!(from_bytes::<f64>(&to_bytes(&1.5f64).unwrap()).unwrap(), 1.5);
+    assert_eq!(from_bytes::<char>(&to_bytes(&'a').unwrap()).unwrap(), 'a');
+    assert_eq!(
+        from_bytes::<String>(&to_bytes("ab").unwrap()).unwrap(),
+        "ab"
+    );
+    from_bytes::<()>(&to_bytes(&()).unwrap()).unwrap();
+    assert_eq!(from_bytes::<Unit>(&to_bytes(&Unit).unwrap()).unwrap(), Unit);
+    assert_eq!(
+        from_bytes::<Option<u8>>(&[IL_NULL_TAG_ID as u8]).unwrap(),
+        None
+    );
+    assert_eq!(
+        from_bytes::<Option<u8>>(&[IL_UINT8_TAG_ID as u8, 1]).unwrap(),
+        Some(1)
+    );
+    assert_eq!(
+        from_bytes::<Newtype>(&to_bytes(&Newtype(1)).unwrap()).unwrap(),
+        Newtype(1)
+    );
+
+    // Borrowed values
+    let tag = ILByteArrayTag::with_value(&[1, 2, 3]);
+    assert_eq!(from_tag::<&[u8]>(&tag).unwrap(), &[1, 2, 3]);
+    let tag = ILStringTag::with_value("abc");
+    assert_eq!(from_tag::<&str>(&tag).unwrap(), "abc");
+
+    // ILInt tags
+    let tag = ILILInt64Tag::with_value(1234);
+    assert_eq!(from_tag::<u64>(&tag).unwrap(), 1234);
+    let tag = ILSignedILInt64Tag::with_value(-1234);
+    assert_eq!(from_tag::<i64>(&tag).unwrap(), -1234);
+}
+
+#[test]
+fn test_collections() {
+    let v = vec![1u8, 2, 3];
+    let bytes = to_bytes(&v).unwrap();
+    assert_eq!(
+        bytes,
+        [
+            IL_ILTAGARRAY_TAG_ID as u8,
+            7,
+            3,
+            IL_UINT8_TAG_ID as u8,
+            1,
+            IL_UINT8_TAG_ID as u8,
+            2,
+            IL_UINT8_TAG_ID as u8,
+            3
+        ]
+    );
+    assert_eq!(from_bytes::<Vec<u8>>(&bytes).unwrap(), v);
+
+    let t = (1u8, String::from("a"), None::<u8>);
+    let bytes = to_bytes(&t).unwrap();
+    assert_eq!(from_bytes::<(u8, String, Option<u8>)>(&bytes).unwrap(), t);
+
+    let mut m = HashMap::new();
+    m.insert(String::from("a"), 1u32);
+    m.insert(String::from("b"), 2u32);
+    let tag = to_tag(&m, StructLayout::default()).unwrap();
+    let dict = tag_downcast_ref::<ILDictTag>(tag.as_ref()).unwrap();
+    assert_eq!(dict.len(), 2);
+    let bytes = to_bytes(&m).unwrap();
+    assert_eq!(from_bytes::<HashMap<String, u32>>(&bytes).unwrap(), m);
+
+    // Other standard containers
+    let mut tag = ILTagSeqTag::new();
+    tag.mut_value().push(Box::new(ILUInt8Tag::with_value(1)));
+    assert_eq!(from_tag::<Vec<u8>>(&tag).unwrap(), vec![1]);
+    let tag = ILILIntArrayTag::with_value(&[1, 2]);
+    assert_eq!(from_tag::<Vec<u64>>(&tag).unwrap(), vec![1, 2]);
+    let mut tag = ILStrDictTag::new();
+    tag.insert("k", "v");
+    let m: HashMap<String, String> = from_tag(&tag).unwrap();
+    assert_eq!(m.get("k").unwrap(), "v");
+}
+
+#[test]
+fn test_structs() {
+    let sample = create_sample();
+
+    let bytes = to_bytes(&sample).unwrap();
+    assert_eq!(bytes[0], IL_DICTIONARY_TAG_ID as u8);
+    assert_eq!(from_bytes::<Sample>(&bytes).unwrap(), sample);
+
+    let seq = to_bytes_with_layout(&sample, StructLayout::Sequence).unwrap();
+    assert_eq!(seq[0], IL_ILTAGARRAY_TAG_ID as u8);
+    assert!(seq.len() < bytes.len());
+    assert_eq!(from_bytes::<Sample>(&seq).unwrap(), sample);
+
+    let mut writer = VecWriter::new();
+    to_writer(&sample, &mut writer).unwrap();
+    assert_eq!(writer.as_slice(), bytes.as_slice());
+    let mut writer = VecWriter::new();
+    to_writer_with_layout(&sample, StructLayout::Sequence, &mut writer).unwrap();
+    assert_eq!(writer.as_slice(), seq.as_slice());
+
+    let mut reader = ByteArrayReader::new(&bytes);
+    assert_eq!(from_reader::<Sample>(&mut reader).unwrap(), sample);
+}
+
+#[test]
+fn test_enums() {
+    let samples = [
+        Variant::Unit,
+        Variant::Newtype(1),
+        Variant::Tuple(false, 2.0),
+        Variant::Struct { a: 1, b: None },
+    ];
+    for v in samples.iter() {
+        let bytes = to_bytes(v).unwrap();
+        assert_eq!(&from_bytes::<Variant>(&bytes).unwrap(), v);
+        let bytes = to_bytes_with_layout(v, StructLayout::Sequence).unwrap();
+        assert_eq!(&from_bytes::<Variant>(&bytes).unwrap(), v);
+    }
+
+    assert_eq!(
+        to_bytes(&Variant::Unit).unwrap(),
+        [IL_STRING_TAG_ID as u8, 4, b'U', b'n', b'i', b't']
+    );
+    assert_eq!(
+        to_bytes_with_layout(&Variant::Unit, StructLayout::Sequence).unwrap(),
+        [IL_UINT32_TAG_ID as u8, 0, 0, 0, 0]
+    );
+    let tag = to_tag(&Variant::Newtype(1), StructLayout::Dictionary).unwrap();
+    let dict = tag_downcast_ref::<ILDictTag>(tag.as_ref()).unwrap();
+    assert!(dict.get("Newtype").is_some());
+    let tag = to_tag(&Variant::Newtype(1), StructLayout::Sequence).unwrap();
+    let array = tag_downcast_ref::<ILTagArrayTag>(tag.as_ref()).unwrap();
+    assert_eq!(array.value().len(), 2);
+
+    // Unknown variants and malformed values
+    assert!(matches!(
+        from_bytes::<Variant>(&to_bytes("Other").unwrap()),
+        Err(ErrorKind::Message(_))
+    ));
+    assert!(matches!(
+        from_bytes::<Variant>(&to_bytes(&Variant::Newtype(1)).unwrap()[..0]),
+        Err(ErrorKind::TagError(_))
+    ));
+    let mut m = HashMap::new();
+    m.insert("Newtype", 1u32);
+    m.insert("Unit", 1u32);
+    assert!(matches!(
+        from_bytes::<Variant>(&to_bytes(&m).unwrap()),
+        Err(ErrorKind::Message(_))
+    ));
+    assert!(matches!(
+        from_bytes::<Variant>(&to_bytes(&vec![1u32]).unwrap()),
+        Err(ErrorKind::Message(_))
+    ));
+    assert!(matches!(
+        from_bytes::<Variant>(&to_bytes(&1u8).unwrap()),
+        Err(ErrorKind::TagError(TagErrorKind::UnexpectedTagType))
+    ));
+}
+
+#[test]
+fn test_errors() {
+    let mut m = HashMap::new();
+    m.insert(1u32, 1u32);
+    assert!(matches!(to_bytes(&m), Err(ErrorKind::UnsupportedType(_))));
+    assert!(matches!(to_bytes(&1u128), Err(ErrorKind::Message(_))));
+
+    assert!(matches!(
+        from_bytes::<u32>(&to_bytes("a").unwrap()),
+        Err(ErrorKind::Message(_))
+    ));
+    let mut bytes = to_bytes(&1u8).unwrap();
+    bytes.push(0);
+    assert!(matches!(
+        from_bytes::<u8>(&bytes),
+        Err(ErrorKind::TagError(TagErrorKind::CorruptedData))
+    ));
+    assert!(matches!(
+        from_bytes::<u8>(&[0x20, 0]),
+        Err(ErrorKind::TagError(TagErrorKind::UnknownTag))
+    ));
+    let tag = ILBin128Tag::new();
+    assert!(matches!(
+        from_tag::<u8>(&tag),
+        Err(ErrorKind::TagError(TagErrorKind::UnsupportedTag))
+    ));
+    // Standard id with a non standard implementation
+    let tag = crate::tags::ILRawTag::new(IL_STRING_TAG_ID);
+    assert!(matches!(
+        from_tag::<String>(&tag),
+        Err(ErrorKind::TagError(TagErrorKind::UnexpectedTagType))
+    ));
+}
+
+#[test]
+fn test_errorkind_error() {
+    let e = ErrorKind::UnsupportedType("x");
+    assert_eq!(format!("{}", e), "unsupported type: x");
+    assert!(e.source().is_none());
+    let e = ErrorKind::Message(String::from("message"));
+    assert_eq!(format!("{}", e), "message");
+    assert!(e.source().is_none());
+    let e = ErrorKind::from(TagErrorKind::CorruptedData);
+    assert_eq!(format!("{}", e), format!("{}", TagErrorKind::CorruptedData));
+    assert!(e.source().is_some());
+}
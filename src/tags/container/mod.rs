@@ -0,0 +1,609 @@
+/*
+ * BSD 3-Clause License
+ *
+ * Copyright (c) 2020, InterlockLedger Network
+ * All rights reserved.
+ *
+ * Redistribution and use in source and binary forms, with or without
+ * modification, are permitted provided that the following conditions are met:
+ *
+ * * Redistributions of source code must retain the above copyright notice, this
+ *   list of conditions and the following disclaimer.
+ *
+ * * Redistributions in binary form must reproduce the above copyright notice,
+ *   this list of conditions and the following disclaimer in the documentation
+ *   and/or other materials provided with the distribution.
+ *
+ * * Neither the name of the copyright holder nor the names of its
+ *   contributors may be used to endorse or promote products derived from
+ *   this software without specific prior written permission.
+ *
+ * THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS"
+ * AND ANY EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE
+ * IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+ * DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE
+ * FOR ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL, EXEMPLARY, OR CONSEQUENTIAL
+ * DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+ * SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER
+ * CAUSED AND ON ANY THEORY OF LIABILITY, WHETHER IN CONTRACT, STRICT LIABILITY,
+ * OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE
+ * OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+ */
+//! This module implements the non-recursive traversal engine used to handle
+//! container tags, in other words, tags whose values contain other tags.
+//!
+//! The naive implementation of containers serializes, computes the size and
+//! deserializes the children by calling the same methods recursively. This
+//! approach may exhaust the stack of the thread when the tag tree is deep
+//! enough. To avoid that, container tags may implement [`ILTagContainer`]
+//! and expose it through [`ILTag::as_container()`] and
+//! [`ILTag::as_mut_container()`]. By doing so, the functions of this module
+//! and [`crate::tags::standard::ILStandardTagFactory`] will handle the whole
+//! tree using an explicit stack instead of the call stack.
+//!
+//! Since the header of each container depends on the size of its value, the
+//! serialization first computes an [`ILTagSizePlan`] with the sizes of all
+//! nested containers and then streams the bytes using it. This ensures that
+//! the size of each node is computed only once.
+//!
+//! All standard containers ([`crate::tags::standard::ILTagSeqTag`],
+//! [`crate::tags::standard::ILTagArrayTag`] and
+//! [`crate::tags::standard::ILDictTag`]) are implemented using this engine.
+//!
+//! A custom container can be defined as follows:
+//!
+//! ```
+//! use il2_iltags::io::{Reader, Writer};
+//! use il2_iltags::tags::container::*;
+//! use il2_iltags::tags::{ILTag, ILTagFactory, Result};
+//! use std::any::Any;
+//!
+//! // A container that holds a single optional child.
+//! struct BoxTag {
+//!     child: Option<Box<dyn ILTag>>,
+//! }
+//!
+//! impl ILTagContainer for BoxTag {
+//!     fn value_parts(&self) -> Vec<ILTagValuePart<'_>> {
+//!         match &self.child {
+//!             Some(c) => vec![ILTagValuePart::Tag(c.as_ref())],
+//!             None => vec![],
+//!         }
+//!     }
+//!
+//!     fn begin_deserialization(
+//!         &mut self,
+//!         _factory: &dyn ILTagFactory,
+//!         value_size: usize,
+//!         _reader: &mut dyn Reader,
+//!     ) -> Result<Option<u64>> {
+//!         self.child = None;
+//!         Ok(Some(if value_size == 0 { 0 } else { 1 }))
+//!     }
+//!
+//!     fn push_child(&mut self, child: Box<dyn ILTag>) -> Result<()> {
+//!         self.child = Some(child);
+//!         Ok(())
+//!     }
+//!
+//!     fn take_children(&mut self, children: &mut Vec<Box<dyn ILTag>>) {
+//!         children.extend(self.child.take());
+//!     }
+//! }
+//!
+//! impl ILTag for BoxTag {
+//!     fn id(&self) -> u64 {
+//!         1234
+//!     }
+//!
+//!     fn value_size(&self) -> u64 {
+//!         container_value_size(self)
+//!     }
+//!
+//!     fn serialize_value(&self, writer: &mut dyn Writer) -> Result<()> {
+//!         serialize_container_value(self, writer)
+//!     }
+//!
+//!     fn deserialize_value(
+//!         &mut self,
+//!         factory: &dyn ILTagFactory,
+//!         value_size: usize,
+//!         reader: &mut dyn Reader,
+//!     ) -> Result<()> {
+//!         deserialize_container_value(self, factory, value_size, reader)
+//!     }
+//!
+//!     fn as_any(&self) -> &dyn Any {
+//!         self
+//!     }
+//!
+//!     fn as_mut_any(&mut self) -> &mut dyn Any {
+//!         self
+//!     }
+//!
+//!     fn as_container(&self) -> Option<&dyn ILTagContainer> {
+//!         Some(self)
+//!     }
+//!
+//!     fn as_mut_container(&mut self) -> Option<&mut dyn ILTagContainer> {
+//!         Some(self)
+//!     }
+//! }
+//!
+//! impl Drop for BoxTag {
+//!     fn drop(&mut self) {
+//!         drop_container_children(self);
+//!     }
+//! }
+//! ```
+//!
+//! New since 2.0.0.
+#[cfg(test)]
+mod tests;
+
+use super::serialization::*;
+use super::standard::explicit::{serialize_string_tag_from_value, string_tag_size_from_value};
+use super::util::limited_reader_ensure_empty;
+use super::{write_value_checked, ErrorKind, ILTag, ILTagFactory, Result};
+use crate::ilint::encoded_size;
+use crate::io::{LimitedReader, Reader, Writer};
+
+//=============================================================================
+// ILTagValuePart
+//-----------------------------------------------------------------------------
+/// This enum represents a part of the value of a container tag. The value of
+/// a container is always the concatenation of the serialization of its parts.
+///
+/// New since 2.0.0.
+pub enum ILTagValuePart<'a> {
+    /// An ILInt value.
+    ILInt(u64),
+    /// A sequence of bytes written as is.
+    Bytes(&'a [u8]),
+    /// A string serialized as a standard string tag.
+    StringTag(&'a str),
+    /// A child tag.
+    Tag(&'a dyn ILTag),
+}
+
+impl<'a> ILTagValuePart<'a> {
+    /// Returns the serialized size of this part if it is not a child tag.
+    ///
+    /// Returns:
+    /// - `Some(size)`: The size of this part;
+    /// - `None`: If this part is a child tag;
+    pub fn data_size(&self) -> Option<u64> {
+        match self {
+            Self::ILInt(v) => Some(encoded_size(*v) as u64),
+            Self::Bytes(b) => Some(b.len() as u64),
+            Self::StringTag(s) => Some(string_tag_size_from_value(s)),
+            Self::Tag(_) => None,
+        }
+    }
+
+    /// Serializes this part if it is not a child tag.
+    ///
+    /// Arguments:
+    /// - `writer`: The writer;
+    ///
+    /// Returns:
+    /// - `Ok(())`: On success;
+    /// - `Err(ErrorKind::UnableToSerialize)`: If this part is a child tag;
+    /// - `Err(_)`: In case of error;
+    pub fn serialize_data(&self, writer: &mut dyn Writer) -> Result<()> {
+        match self {
+            Self::ILInt(v) => writer.serialize_ilint(*v),
+            Self::Bytes(b) => writer.serialize_bytes(b),
+            Self::StringTag(s) => serialize_string_tag_from_value(s, writer),
+            Self::Tag(_) => Err(ErrorKind::UnableToSerialize),
+        }
+    }
+}
+
+//=============================================================================
+// ILTagContainer
+//-----------------------------------------------------------------------------
+/// This trait must be implemented by container tags that want to be handled
+/// by the non-recursive traversal engine.
+///
+/// The serialization is described by [`Self::value_parts()`] while the
+/// deserialization is performed in steps. First
+/// [`Self::begin_deserialization()`] reads the data that precedes the
+/// children, then, for each child, [`Self::deserialize_child_prefix()`]
+/// reads the data that precedes it and [`Self::push_child()`] receives the
+/// deserialized child. Finally, [`Self::end_deserialization()`] is called
+/// to finish the process.
+///
+/// New since 2.0.0.
+pub trait ILTagContainer {
+    /// Returns the parts of the value in the order they must be serialized.
+    fn value_parts(&self) -> Vec<ILTagValuePart<'_>>;
+
+    /// Resets this container and reads the data that precedes the children.
+    ///
+    /// Arguments:
+    /// - `factory`: The current tag factory;
+    /// - `value_size`: Size of the value in bytes;
+    /// - `reader`: The reader limited to the value of the tag;
+    ///
+    /// Returns:
+    /// - `Ok(Some(n))`: The number of children expected;
+    /// - `Ok(None)`: If the children use all the remaining bytes of the value;
+    /// - `Err(_)`: In case of error;
+    fn begin_deserialization(
+        &mut self,
+        factory: &dyn ILTagFactory,
+        value_size: usize,
+        reader: &mut dyn Reader,
+    ) -> Result<Option<u64>>;
+
+    /// Reads the data that precedes the next child. The default implementation
+    /// does nothing.
+    ///
+    /// Arguments:
+    /// - `factory`: The current tag factory;
+    /// - `reader`: The reader limited to the remaining of the value;
+    fn deserialize_child_prefix(
+        &mut self,
+        _factory: &dyn ILTagFactory,
+        _reader: &mut dyn Reader,
+    ) -> Result<()> {
+        Ok(())
+    }
+
+    /// Returns the key associated with the child being deserialized, if any.
+    /// It is called after [`Self::deserialize_child_prefix()`] and is used to
+    /// describe the location of deserialization errors. The default
+    /// implementation returns `None`.
+    fn child_key(&self) -> Option<&str> {
+        None
+    }
+
+    /// Adds the next deserialized child to this container.
+    ///
+    /// Arguments:
+    /// - `child`: The child;
+    fn push_child(&mut self, child: Box<dyn ILTag>) -> Result<()>;
+
+    /// Reads the data that follows the last child. The default implementation
+    /// does nothing.
+    ///
+    /// Arguments:
+    /// - `factory`: The current tag factory;
+    /// - `reader`: The reader limited to the remaining of the value;
+    fn end_deserialization(
+        &mut self,
+        _factory: &dyn ILTagFactory,
+        _reader: &mut dyn Reader,
+    ) -> Result<()> {
+        Ok(())
+    }
+
+    /// Moves all children of this container into the given vector. It is used
+    /// to drop deep trees without recursion.
+    ///
+    /// Arguments:
+    /// - `children`: The vector that will receive the children;
+    fn take_children(&mut self, children: &mut Vec<Box<dyn ILTag>>);
+}
+
+/// Returns the size of the tag header, in other words, the size of the id and
+/// the size of the value size when it is explicit.
+#[inline]
+pub(crate) fn tag_header_size(id: u64, value_size: u64) -> u64 {
+    let mut size = encoded_size(id) as u64;
+    if !super::is_implicit_tag(id) {
+        size += encoded_size(value_size) as u64;
+    }
+    size
+}
+
+//=============================================================================
+// ILTagSizePlan
+//-----------------------------------------------------------------------------
+/// This struct holds the value sizes of a container and all containers
+/// nested inside it. It is computed at once without recursion and allows the
+/// serialization of the whole tree without computing the size of the same
+/// node more than once.
+///
+/// The sizes are stored in the order the containers are visited during the
+/// serialization, thus a plan is valid only as long as the tree it was created
+/// from is not modified.
+///
+/// New since 2.0.0.
+pub struct ILTagSizePlan {
+    sizes: Vec<u64>,
+}
+
+impl ILTagSizePlan {
+    /// Creates the size plan of the given container.
+    ///
+    /// Arguments:
+    /// - `container`: The container;
+    pub fn new(container: &dyn ILTagContainer) -> Self {
+        struct Frame<'a> {
+            id: u64,
+            index: usize,
+            parts: Vec<ILTagValuePart<'a>>,
+            next: usize,
+            size: u64,
+        }
+
+        let mut sizes: Vec<u64> = vec![0];
+        let mut stack: Vec<Frame> = vec![Frame {
+            id: 0,
+            index: 0,
+            parts: container.value_parts(),
+            next: 0,
+            size: 0,
+        }];
+        while let Some(frame) = stack.last_mut() {
+            if frame.next == frame.parts.len() {
+                let done = stack.pop().unwrap();
+                sizes[done.index] = done.size;
+                if let Some(parent) = stack.last_mut() {
+                    parent.size += tag_header_size(done.id, done.size) + done.size;
+                }
+                continue;
+            }
+            let part = &frame.parts[frame.next];
+            frame.next += 1;
+            if let ILTagValuePart::Tag(tag) = part {
+                let tag: &dyn ILTag = *tag;
+                match tag.as_container() {
+                    Some(c) => {
+                        sizes.push(0);
+                        stack.push(Frame {
+                            id: tag.id(),
+                            index: sizes.len() - 1,
+                            parts: c.value_parts(),
+                            next: 0,
+                            size: 0,
+                        });
+                    }
+                    None => frame.size += tag.size(),
+                }
+            } else {
+                frame.size += part.data_size().unwrap();
+            }
+        }
+        Self { sizes }
+    }
+
+    /// Returns the value size of the container used to create this plan.
+    pub fn value_size(&self) -> u64 {
+        self.sizes[0]
+    }
+
+    /// Returns the total size of the container used to create this plan.
+    ///
+    /// Arguments:
+    /// - `id`: The id of the container;
+    pub fn tag_size(&self, id: u64) -> u64 {
+        tag_header_size(id, self.value_size()) + self.value_size()
+    }
+
+    /// Returns the number of containers inside this plan, including the
+    /// root container.
+    pub fn len(&self) -> usize {
+        self.sizes.len()
+    }
+
+    /// Returns true if this plan is empty. It is always false as a plan
+    /// always holds the size of the root container.
+    pub fn is_empty(&self) -> bool {
+        self.sizes.is_empty()
+    }
+}
+
+/// Serializes the tag header, in other words, the id and the value size when
+/// it is explicit.
+#[inline]
+fn serialize_header(id: u64, value_size: u64, writer: &mut dyn Writer) -> Result<()> {
+    writer.serialize_ilint(id)?;
+    if !super::is_implicit_tag(id) {
+        writer.serialize_ilint(value_size)?;
+    }
+    Ok(())
+}
+
+/// Computes the value size of the given container without recursion.
+///
+/// Arguments:
+/// - `container`: The container;
+///
+/// Returns the value size of the container.
+pub fn container_value_size(container: &dyn ILTagContainer) -> u64 {
+    ILTagSizePlan::new(container).value_size()
+}
+
+/// Computes the total size of the given container, including its header,
+/// without recursion. It builds the size plan only once.
+///
+/// Arguments:
+/// - `id`: The id of the container;
+/// - `container`: The container;
+///
+/// Returns the total size of the container.
+pub fn container_tag_size(id: u64, container: &dyn ILTagContainer) -> u64 {
+    ILTagSizePlan::new(container).tag_size(id)
+}
+
+/// Serializes the value of the given container without recursion.
+///
+/// Arguments:
+/// - `container`: The container;
+/// - `writer`: The writer;
+///
+/// Returns:
+/// - `Ok(())`: On success;
+/// - `Err(_)`: In case of error;
+pub fn serialize_container_value(
+    container: &dyn ILTagContainer,
+    writer: &mut dyn Writer,
+) -> Result<()> {
+    let plan = ILTagSizePlan::new(container);
+    serialize_container_value_with_plan(container, &plan, writer)
+}
+
+/// Serializes the value of the given container without recursion using a
+/// previously computed [`ILTagSizePlan`].
+///
+/// Arguments:
+/// - `container`: The container;
+/// - `plan`: The size plan of the container;
+/// - `writer`: The writer;
+///
+/// Returns:
+/// - `Ok(())`: On success;
+/// - `Err(ErrorKind::UnableToSerialize)`: If the plan does not match the container;
+/// - `Err(_)`: In case of error;
+pub fn serialize_container_value_with_plan(
+    container: &dyn ILTagContainer,
+    plan: &ILTagSizePlan,
+    writer: &mut dyn Writer,
+) -> Result<()> {
+    let mut next_size: usize = 1;
+    let mut stack: Vec<(Vec<ILTagValuePart>, usize)> = vec![(container.value_parts(), 0)];
+    while let Some((parts, next)) = stack.last_mut() {
+        if *next == parts.len() {
+            stack.pop();
+            continue;
+        }
+        let part = &parts[*next];
+        *next += 1;
+        if let ILTagValuePart::Tag(tag) = part {
+            let tag: &dyn ILTag = *tag;
+            match tag.as_container() {
+                Some(c) => {
+                    let size = match plan.sizes.get(next_size) {
+                        Some(s) => *s,
+                        None => return Err(ErrorKind::UnableToSerialize),
+                    };
+                    next_size += 1;
+                    serialize_header(tag.id(), size, writer)?;
+                    let parts = c.value_parts();
+                    stack.push((parts, 0));
+                }
+                None => tag.serialize(writer)?,
+            }
+        } else {
+            part.serialize_data(writer)?;
+        }
+    }
+    if next_size != plan.len() {
+        return Err(ErrorKind::UnableToSerialize);
+    }
+    Ok(())
+}
+
+/// Serializes the given container, including its header, computing the
+/// sizes of all nested containers only once.
+///
+/// In debug builds or when the feature `verify-serialization` is enabled,
+/// the size of the value written is verified against the size plan.
+///
+/// Arguments:
+/// - `id`: The id of the container;
+/// - `container`: The container;
+/// - `writer`: The writer;
+///
+/// Returns:
+/// - `Ok(())`: On success;
+/// - `Err(ErrorKind::ValueSizeMismatch(id))`: If the size of the value does
+///   not match the size plan;
+/// - `Err(_)`: In case of error;
+pub fn serialize_container(
+    id: u64,
+    container: &dyn ILTagContainer,
+    writer: &mut dyn Writer,
+) -> Result<()> {
+    let plan = ILTagSizePlan::new(container);
+    serialize_header(id, plan.value_size(), writer)?;
+    if cfg!(any(debug_assertions, feature = "verify-serialization")) {
+        write_value_checked(id, plan.value_size(), writer, |w| {
+            serialize_container_value_with_plan(container, &plan, w)
+        })
+    } else {
+        serialize_container_value_with_plan(container, &plan, writer)
+    }
+}
+
+/// Serializes the given container into a byte array, computing the sizes of
+/// all nested containers only once.
+///
+/// Arguments:
+/// - `id`: The id of the container;
+/// - `container`: The container;
+///
+/// Returns:
+/// - `Ok(v)`: The serialized container;
+/// - `Err(_)`: In case of error;
+pub fn container_to_bytes(id: u64, container: &dyn ILTagContainer) -> Result<Vec<u8>> {
+    let plan = ILTagSizePlan::new(container);
+    let mut writer = crate::io::array::VecWriter::with_capacity(plan.tag_size(id) as usize);
+    serialize_header(id, plan.value_size(), &mut writer)?;
+    if cfg!(any(debug_assertions, feature = "verify-serialization")) {
+        write_value_checked(id, plan.value_size(), &mut writer, |w| {
+            serialize_container_value_with_plan(container, &plan, w)
+        })?;
+    } else {
+        serialize_container_value_with_plan(container, &plan, &mut writer)?;
+    }
+    Ok(writer.into())
+}
+
+/// Deserializes the value of a container. It uses
+/// [`ILTagFactory::deserialize()`] to deserialize the children, thus the
+/// deserialization of the children will not be recursive as long as the
+/// factory uses the traversal engine, just like
+/// [`crate::tags::standard::ILStandardTagFactory`] does.
+///
+/// Arguments:
+/// - `container`: The container;
+/// - `factory`: The current tag factory;
+/// - `value_size`: Size of the value in bytes;
+/// - `reader`: The reader;
+///
+/// Returns:
+/// - `Ok(())`: On success;
+/// - `Err(_)`: In case of error;
+pub fn deserialize_container_value(
+    container: &mut dyn ILTagContainer,
+    factory: &dyn ILTagFactory,
+    value_size: usize,
+    reader: &mut dyn Reader,
+) -> Result<()> {
+    let mut lreader = LimitedReader::new(reader, value_size);
+    let mut remaining = container.begin_deserialization(factory, value_size, &mut lreader)?;
+    loop {
+        match remaining {
+            Some(0) => break,
+            None if lreader.empty() => break,
+            _ => (),
+        }
+        container.deserialize_child_prefix(factory, &mut lreader)?;
+        container.push_child(factory.deserialize(&mut lreader)?)?;
+        if let Some(r) = remaining.as_mut() {
+            *r -= 1;
+        }
+    }
+    container.end_deserialization(factory, &mut lreader)?;
+    limited_reader_ensure_empty(&lreader, ErrorKind::CorruptedData)
+}
+
+/// Drops all children of the given container without recursion. This
+/// function is expected to be called by the [`Drop`] implementation of
+/// the containers.
+///
+/// Arguments:
+/// - `container`: The container;
+pub fn drop_container_children(container: &mut dyn ILTagContainer) {
+    let mut pending: Vec<Box<dyn ILTag>> = Vec::new();
+    container.take_children(&mut pending);
+    while let Some(mut tag) = pending.pop() {
+        if let Some(c) = tag.as_mut_container() {
+            c.take_children(&mut pending);
+        }
+    }
+}
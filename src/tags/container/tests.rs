@@ -0,0 +1,731 @@
+/*
+ * BSD 3-Clause License
+ *
+ * Copyright (c) 2020, InterlockLedger Network
+ * All rights reserved.
+ *
+ * Redistribution and use in source and binary forms, with or without
+ * modification, are permitted provided that the following conditions are met:
+ *
+ * * Redistributions of source code must retain the above copyright notice, this
+ *   list of conditions and the following disclaimer.
+ *
+ * * Redistributions in binary form must reproduce the above copyright notice,
+ *   this list of conditions and the following disclaimer in the documentation
+ *   and/or other materials provided with the distribution.
+ *
+ * * Neither the name of the copyright holder nor the names of its
+ *   contributors may be used to endorse or promote products derived from
+ *   this software without specific prior written permission.
+ *
+ * THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS"
+ * AND ANY EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE
+ * IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+ * DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE
+ * FOR ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL, EXEMPLARY, OR CONSEQUENTIAL
+ * DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+ * SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER
+ * CAUSED AND ON ANY THEORY OF LIABILITY, WHETHER IN CONTRACT, STRICT LIABILITY,
+ * OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE
+ * OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+ */
+use super::*;
+use crate::io::array::*;
+use crate::tags::standard::constants::*;
+use crate::tags::standard::explicit::deserialize_string_tag_from_value;
+use crate::tags::standard::*;
+use crate::tags::{tag_downcast_ref, DeserializationLimits, ILRawTag};
+use std::any::Any;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+const DEEP_LEVELS: usize = 100000;
+
+/// Stack size used to run the tests with deep trees. It is small enough to
+/// make any recursive implementation fail.
+const SMALL_STACK_SIZE: usize = 128 * 1024;
+
+fn run_with_small_stack<F: FnOnce() + Send + 'static>(f: F) {
+    std::thread::Builder::new()
+        .stack_size(SMALL_STACK_SIZE)
+        .spawn(f)
+        .unwrap()
+        .join()
+        .unwrap();
+}
+
+//=============================================================================
+// Samples
+//-----------------------------------------------------------------------------
+fn create_deep_seq(levels: usize) -> ILTagSeqTag {
+    let mut tag = ILTagSeqTag::new();
+    tag.mut_value()
+        .push(Box::new(ILStringTag::with_value("leaf")));
+    for _ in 0..levels {
+        let mut parent = ILTagSeqTag::new();
+        parent.mut_value().push(Box::new(ILUInt8Tag::with_value(1)));
+        parent.mut_value().push(Box::new(tag));
+        tag = parent;
+    }
+    tag
+}
+
+fn create_deep_array(levels: usize) -> ILTagArrayTag {
+    let mut tag = ILTagArrayTag::new();
+    for _ in 0..levels {
+        let mut parent = ILTagArrayTag::new();
+        parent.mut_value().push(Box::new(tag));
+        parent.mut_value().push(Box::new(ILNullTag::new()));
+        tag = parent;
+    }
+    tag
+}
+
+fn create_deep_dict(levels: usize) -> ILDictTag {
+    let mut tag = ILDictTag::new();
+    for _ in 0..levels {
+        let mut parent = ILDictTag::new();
+        parent.insert("b", Box::new(tag));
+        parent.insert("a", Box::new(ILStringTag::with_value("value")));
+        tag = parent;
+    }
+    tag
+}
+
+/// Computes the serialization of the tag recursively using only the
+/// public API of the standard containers.
+fn recursive_serialization(tag: &dyn ILTag, writer: &mut VecWriter) {
+    if tag.as_container().is_none() {
+        tag.serialize(writer).unwrap();
+        return;
+    }
+    writer.serialize_ilint(tag.id()).unwrap();
+    if let Some(seq) = tag_downcast_ref::<ILTagSeqTag>(tag) {
+        let mut value = VecWriter::new();
+        for t in seq.value() {
+            recursive_serialization(t.as_ref(), &mut value);
+        }
+        let value: Vec<u8> = value.into();
+        writer.serialize_ilint(value.len() as u64).unwrap();
+        writer.serialize_bytes(&value).unwrap();
+    } else if let Some(array) = tag_downcast_ref::<ILTagArrayTag>(tag) {
+        let mut value = VecWriter::new();
+        value.serialize_ilint(array.value().len() as u64).unwrap();
+        for t in array.value() {
+            recursive_serialization(t.as_ref(), &mut value);
+        }
+        let value: Vec<u8> = value.into();
+        writer.serialize_ilint(value.len() as u64).unwrap();
+        writer.serialize_bytes(&value).unwrap();
+    } else if let Some(dict) = tag_downcast_ref::<ILDictTag>(tag) {
+        let mut keys: Vec<&String> = dict.value().keys().collect();
+        keys.sort();
+        let mut value = VecWriter::new();
+        value.serialize_ilint(keys.len() as u64).unwrap();
+        for k in keys {
+            serialize_string_tag_from_value(k, &mut value).unwrap();
+            recursive_serialization(dict.get(k).unwrap(), &mut value);
+        }
+        let value: Vec<u8> = value.into();
+        writer.serialize_ilint(value.len() as u64).unwrap();
+        writer.serialize_bytes(&value).unwrap();
+    } else {
+        panic!("Not a standard container.");
+    }
+}
+
+fn create_complex_tag() -> ILDictTag {
+    let mut seq = ILTagSeqTag::new();
+    seq.mut_value()
+        .push(Box::new(ILILInt64Tag::with_value(1234)));
+    seq.mut_value().push(Box::new(create_deep_array(3)));
+    seq.mut_value()
+        .push(Box::new(ILRawTag::with_value(1234, &[1, 2, 3])));
+    let mut array = ILTagArrayTag::new();
+    array.mut_value().push(Box::new(seq));
+    array.mut_value().push(Box::new(create_deep_dict(2)));
+    let mut dict = ILDictTag::new();
+    dict.insert("z", Box::new(array));
+    dict.insert("k", Box::new(create_deep_seq(3)));
+    dict.insert("a", Box::new(ILTagSeqTag::new()));
+    dict
+}
+
+//=============================================================================
+// ILTagValuePart
+//-----------------------------------------------------------------------------
+#[test]
+fn test_iltagvaluepart_data_size() {
+    assert_eq!(ILTagValuePart::ILInt(0).data_size(), Some(1));
+    assert_eq!(ILTagValuePart::ILInt(0xFFFF).data_size(), Some(3));
+    assert_eq!(ILTagValuePart::Bytes(&[1, 2, 3]).data_size(), Some(3));
+    assert_eq!(
+        ILTagValuePart::StringTag("abc").data_size(),
+        Some(string_tag_size_from_value("abc"))
+    );
+    let tag = ILNullTag::new();
+    assert_eq!(ILTagValuePart::Tag(&tag).data_size(), None);
+}
+
+#[test]
+fn test_iltagvaluepart_serialize_data() {
+    let mut writer = VecWriter::new();
+    ILTagValuePart::ILInt(0xFFFF)
+        .serialize_data(&mut writer)
+        .unwrap();
+    ILTagValuePart::Bytes(&[1, 2, 3])
+        .serialize_data(&mut writer)
+        .unwrap();
+    ILTagValuePart::StringTag("abc")
+        .serialize_data(&mut writer)
+        .unwrap();
+    assert_eq!(
+        writer.as_slice(),
+        &[0xF9, 0xFF, 0x07, 1, 2, 3, 0x11, 0x03, 0x61, 0x62, 0x63]
+    );
+
+    let tag = ILNullTag::new();
+    assert!(matches!(
+        ILTagValuePart::Tag(&tag).serialize_data(&mut writer),
+        Err(ErrorKind::UnableToSerialize)
+    ));
+}
+
+//=============================================================================
+// Traversal
+//-----------------------------------------------------------------------------
+#[test]
+fn test_tag_header_size() {
+    assert_eq!(tag_header_size(IL_NULL_TAG_ID, 0), 1);
+    assert_eq!(tag_header_size(IL_ILINT_TAG_ID, 9), 1);
+    assert_eq!(tag_header_size(IL_ILTAGSEQ_TAG_ID, 0), 2);
+    assert_eq!(tag_header_size(IL_ILTAGSEQ_TAG_ID, 0xFFFF), 4);
+    assert_eq!(tag_header_size(0xFFFF, 0xFFFF), 6);
+}
+
+#[test]
+fn test_container_value_size() {
+    let tag = create_complex_tag();
+    let mut writer = VecWriter::new();
+    recursive_serialization(&tag, &mut writer);
+    let exp: Vec<u8> = writer.into();
+
+    let size = container_value_size(&tag);
+    assert_eq!(tag.value_size(), size);
+    assert_eq!(tag.size(), exp.len() as u64);
+    assert_eq!(tag_header_size(tag.id(), size) + size, exp.len() as u64);
+
+    let empty = ILTagSeqTag::new();
+    assert_eq!(container_value_size(&empty), 0);
+}
+
+#[test]
+fn test_container_tag_size() {
+    let tag = create_complex_tag();
+    let mut writer = VecWriter::new();
+    recursive_serialization(&tag, &mut writer);
+    let exp: Vec<u8> = writer.into();
+
+    assert_eq!(container_tag_size(tag.id(), &tag), exp.len() as u64);
+    assert_eq!(tag.size(), exp.len() as u64);
+
+    let empty = ILTagSeqTag::new();
+    assert_eq!(container_tag_size(empty.id(), &empty), 2);
+    assert_eq!(empty.size(), 2);
+}
+
+#[test]
+fn test_serialize_container_value() {
+    let tag = create_complex_tag();
+    let mut writer = VecWriter::new();
+    recursive_serialization(&tag, &mut writer);
+    let exp: Vec<u8> = writer.into();
+
+    let mut writer = VecWriter::new();
+    serialize_container_value(&tag, &mut writer).unwrap();
+    let header = tag_header_size(tag.id(), tag.value_size()) as usize;
+    assert_eq!(writer.as_slice(), &exp[header..]);
+    assert_eq!(tag.to_bytes().unwrap(), exp);
+
+    // Writer errors are propagated
+    let mut buff: [u8; 16] = [0; 16];
+    let mut writer = ByteArrayWriter::new(&mut buff);
+    assert!(serialize_container_value(&tag, &mut writer).is_err());
+}
+
+#[test]
+fn test_deserialize_container_value() {
+    let tag = create_complex_tag();
+    let serialized = tag.to_bytes().unwrap();
+    let header = tag_header_size(tag.id(), tag.value_size()) as usize;
+    let factory = ILStandardTagFactory::new(false);
+
+    let mut reader = ByteArrayReader::new(&serialized[header..]);
+    let mut ret = ILDictTag::new();
+    ret.insert("garbage", Box::new(ILNullTag::new()));
+    deserialize_container_value(&mut ret, &factory, serialized.len() - header, &mut reader)
+        .unwrap();
+    assert_eq!(ret.len(), 3);
+    assert_eq!(ret.to_bytes().unwrap(), serialized);
+
+    // Not enough children
+    let mut reader = ByteArrayReader::new(&serialized[header..]);
+    assert!(deserialize_container_value(
+        &mut ret,
+        &factory,
+        serialized.len() - header - 1,
+        &mut reader
+    )
+    .is_err());
+
+    // Trailing data
+    let mut value = serialized[header..].to_vec();
+    value.push(0);
+    let mut reader = ByteArrayReader::new(&value);
+    assert!(matches!(
+        deserialize_container_value(&mut ret, &factory, value.len(), &mut reader),
+        Err(ErrorKind::CorruptedData)
+    ));
+}
+
+#[test]
+fn test_iltagsizeplan() {
+    let tag = create_complex_tag();
+    let plan = ILTagSizePlan::new(&tag);
+    // dict, array, seq, 4 arrays, 3 dicts, 4 seqs and the empty seq
+    assert_eq!(plan.len(), 15);
+    assert!(!plan.is_empty());
+    assert_eq!(plan.value_size(), tag.value_size());
+    assert_eq!(plan.tag_size(tag.id()), tag.size());
+
+    let empty = ILTagSeqTag::new();
+    let plan = ILTagSizePlan::new(&empty);
+    assert_eq!(plan.len(), 1);
+    assert_eq!(plan.value_size(), 0);
+    assert_eq!(plan.tag_size(empty.id()), 2);
+}
+
+#[test]
+fn test_serialize_container_value_with_plan() {
+    let tag = create_complex_tag();
+    let plan = ILTagSizePlan::new(&tag);
+    let mut writer = VecWriter::new();
+    serialize_container_value_with_plan(&tag, &plan, &mut writer).unwrap();
+    let mut exp = VecWriter::new();
+    tag.serialize_value(&mut exp).unwrap();
+    assert_eq!(writer.as_slice(), exp.as_slice());
+
+    // Plan from another tree
+    let other = create_deep_seq(20);
+    let mut writer = VecWriter::new();
+    assert!(matches!(
+        serialize_container_value_with_plan(&tag, &ILTagSizePlan::new(&other), &mut writer),
+        Err(ErrorKind::UnableToSerialize)
+    ));
+    let mut writer = VecWriter::new();
+    assert!(matches!(
+        serialize_container_value_with_plan(&other, &plan, &mut writer),
+        Err(ErrorKind::UnableToSerialize)
+    ));
+}
+
+#[test]
+fn test_serialize_container() {
+    let tag = create_complex_tag();
+    let mut writer = VecWriter::new();
+    recursive_serialization(&tag, &mut writer);
+    let exp: Vec<u8> = writer.into();
+
+    let mut writer = VecWriter::new();
+    serialize_container(tag.id(), &tag, &mut writer).unwrap();
+    assert_eq!(writer.as_slice(), exp.as_slice());
+
+    let mut writer = VecWriter::new();
+    tag.serialize(&mut writer).unwrap();
+    assert_eq!(writer.as_slice(), exp.as_slice());
+}
+
+#[test]
+fn test_container_to_bytes() {
+    let tag = create_complex_tag();
+    let mut writer = VecWriter::new();
+    recursive_serialization(&tag, &mut writer);
+    let exp: Vec<u8> = writer.into();
+
+    assert_eq!(container_to_bytes(tag.id(), &tag).unwrap(), exp);
+    assert_eq!(tag.to_bytes().unwrap(), exp);
+}
+
+#[test]
+fn test_drop_container_children() {
+    let mut tag = create_complex_tag();
+    drop_container_children(&mut tag);
+    assert!(tag.is_empty());
+    assert_eq!(tag.value_size(), 1);
+}
+
+//=============================================================================
+// Deep trees
+//-----------------------------------------------------------------------------
+fn check_deep_tag(tag: &dyn ILTag) {
+    let serialized = tag.to_bytes().unwrap();
+    assert_eq!(serialized.len() as u64, tag.size());
+
+    let factory = ILStandardTagFactory::new(true);
+    let ret = factory.from_bytes(&serialized).unwrap();
+    assert_eq!(ret.id(), tag.id());
+    assert_eq!(ret.to_bytes().unwrap(), serialized);
+}
+
+#[test]
+fn test_deep_seq() {
+    run_with_small_stack(|| {
+        let tag = create_deep_seq(DEEP_LEVELS);
+        check_deep_tag(&tag);
+    });
+}
+
+#[test]
+fn test_deep_array() {
+    run_with_small_stack(|| {
+        let tag = create_deep_array(DEEP_LEVELS);
+        check_deep_tag(&tag);
+    });
+}
+
+#[test]
+fn test_deep_dict() {
+    run_with_small_stack(|| {
+        let tag = create_deep_dict(DEEP_LEVELS);
+        check_deep_tag(&tag);
+    });
+}
+
+#[test]
+fn test_deep_deserialize_into() {
+    run_with_small_stack(|| {
+        let tag = create_deep_seq(DEEP_LEVELS);
+        let serialized = tag.to_bytes().unwrap();
+
+        let factory = ILStandardTagFactory::new(true);
+        let mut ret = ILTagSeqTag::new();
+        let mut reader = ByteArrayReader::new(&serialized);
+        factory.deserialize_into(&mut reader, &mut ret).unwrap();
+        assert_eq!(ret.to_bytes().unwrap(), serialized);
+    });
+}
+
+#[test]
+fn test_deep_limits() {
+    run_with_small_stack(|| {
+        let tag = create_deep_seq(DEEP_LEVELS);
+        let serialized = tag.to_bytes().unwrap();
+
+        let mut limits = DeserializationLimits::new();
+        limits.set_max_depth(DEEP_LEVELS / 2);
+        let factory = ILStandardTagFactory::with_limits(true, limits);
+        assert!(matches!(
+            factory.from_bytes(&serialized),
+            Err(ErrorKind::DepthLimitExceeded)
+        ));
+    });
+}
+
+#[test]
+fn test_deep_corrupted() {
+    // Child container larger than its parent
+    let serialized: [u8; 4] = [IL_ILTAGSEQ_TAG_ID as u8, 2, IL_ILTAGSEQ_TAG_ID as u8, 5];
+    let factory = ILStandardTagFactory::new(true);
+    assert!(matches!(
+        factory.from_bytes(&serialized),
+        Err(ErrorKind::CorruptedData)
+    ));
+
+    // Array with missing entries
+    let serialized: [u8; 5] = [
+        IL_ILTAGSEQ_TAG_ID as u8,
+        3,
+        IL_ILTAGARRAY_TAG_ID as u8,
+        1,
+        2,
+    ];
+    assert!(factory.from_bytes(&serialized).is_err());
+
+    // Array with trailing data
+    let serialized: [u8; 7] = [
+        IL_ILTAGSEQ_TAG_ID as u8,
+        5,
+        IL_ILTAGARRAY_TAG_ID as u8,
+        3,
+        1,
+        IL_NULL_TAG_ID as u8,
+        0,
+    ];
+    assert!(matches!(
+        factory.from_bytes(&serialized),
+        Err(ErrorKind::CorruptedData)
+    ));
+
+    // Unknown child
+    let serialized: [u8; 4] = [IL_ILTAGSEQ_TAG_ID as u8, 2, 15, 0];
+    assert!(matches!(
+        factory.from_bytes(&serialized),
+        Err(ErrorKind::UnknownTag)
+    ));
+}
+
+//=============================================================================
+// Custom container
+//-----------------------------------------------------------------------------
+/// A custom container that has a fixed header and a trailer.
+struct CustomContainer {
+    header: Vec<u8>,
+    children: Vec<Box<dyn ILTag>>,
+    value_parts_calls: AtomicUsize,
+}
+
+impl CustomContainer {
+    pub fn new() -> Self {
+        Self {
+            header: vec![1, 2, 3],
+            children: Vec::new(),
+            value_parts_calls: AtomicUsize::new(0),
+        }
+    }
+}
+
+impl ILTagContainer for CustomContainer {
+    fn value_parts(&self) -> Vec<ILTagValuePart<'_>> {
+        self.value_parts_calls.fetch_add(1, Ordering::SeqCst);
+        let mut parts = vec![
+            ILTagValuePart::Bytes(&self.header),
+            ILTagValuePart::ILInt(self.children.len() as u64),
+        ];
+        for c in &self.children {
+            parts.push(ILTagValuePart::StringTag("child"));
+            parts.push(ILTagValuePart::Tag(c.as_ref()));
+        }
+        parts.push(ILTagValuePart::ILInt(0xFFFF));
+        parts
+    }
+
+    fn begin_deserialization(
+        &mut self,
+        _factory: &dyn ILTagFactory,
+        _value_size: usize,
+        reader: &mut dyn Reader,
+    ) -> Result<Option<u64>> {
+        self.header = reader.deserialize_bytes(3)?;
+        self.children.clear();
+        Ok(Some(reader.deserialize_ilint()?))
+    }
+
+    fn deserialize_child_prefix(
+        &mut self,
+        _factory: &dyn ILTagFactory,
+        reader: &mut dyn Reader,
+    ) -> Result<()> {
+        if deserialize_string_tag_from_value(reader)? != "child" {
+            return Err(ErrorKind::CorruptedData);
+        }
+        Ok(())
+    }
+
+    fn push_child(&mut self, child: Box<dyn ILTag>) -> Result<()> {
+        self.children.push(child);
+        Ok(())
+    }
+
+    fn end_deserialization(
+        &mut self,
+        _factory: &dyn ILTagFactory,
+        reader: &mut dyn Reader,
+    ) -> Result<()> {
+        if reader.deserialize_ilint()? != 0xFFFF {
+            return Err(ErrorKind::CorruptedData);
+        }
+        Ok(())
+    }
+
+    fn take_children(&mut self, children: &mut Vec<Box<dyn ILTag>>) {
+        children.append(&mut self.children);
+    }
+}
+
+impl ILTag for CustomContainer {
+    fn id(&self) -> u64 {
+        1234
+    }
+
+    iltag_as_any_impl!();
+    iltag_container_func_impl!();
+}
+
+impl Drop for CustomContainer {
+    fn drop(&mut self) {
+        drop_container_children(self);
+    }
+}
+
+fn create_custom_factory() -> ILStandardTagFactory {
+    let mut factory = ILStandardTagFactory::new(true);
+    factory.engine().register(
+        1234,
+        Box::new(crate::tags::ILDefaultTagCreator::<CustomContainer>::default()),
+    );
+    factory
+}
+
+impl Default for CustomContainer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[test]
+fn test_custom_container() {
+    let mut tag = CustomContainer::new();
+    tag.header = vec![4, 5, 6];
+    tag.children.push(Box::new(create_deep_seq(2)));
+    let mut child = CustomContainer::new();
+    child.children.push(Box::new(ILNullTag::new()));
+    tag.children.push(Box::new(child));
+
+    let serialized = tag.to_bytes().unwrap();
+    assert_eq!(serialized.len() as u64, tag.size());
+
+    let factory = create_custom_factory();
+    let ret = factory.from_bytes(&serialized).unwrap();
+    let ret = tag_downcast_ref::<CustomContainer>(ret.as_ref()).unwrap();
+    assert_eq!(ret.header, vec![4, 5, 6]);
+    assert_eq!(ret.children.len(), 2);
+    assert_eq!(ret.to_bytes().unwrap(), serialized);
+
+    // Corrupted trailer
+    let mut corrupted = serialized.clone();
+    let len = corrupted.len();
+    corrupted[len - 1] = 0;
+    assert!(matches!(
+        factory.from_bytes(&corrupted),
+        Err(ErrorKind::CorruptedData)
+    ));
+}
+
+#[test]
+fn test_custom_container_deep() {
+    run_with_small_stack(|| {
+        let mut tag = CustomContainer::new();
+        for _ in 0..DEEP_LEVELS {
+            let mut parent = CustomContainer::new();
+            parent.children.push(Box::new(tag));
+            tag = parent;
+        }
+        let serialized = tag.to_bytes().unwrap();
+        assert_eq!(serialized.len() as u64, tag.size());
+
+        let factory = create_custom_factory();
+        let ret = factory.from_bytes(&serialized).unwrap();
+        assert_eq!(ret.to_bytes().unwrap(), serialized);
+        assert!(ret.as_any().is::<CustomContainer>());
+    });
+}
+
+#[test]
+fn test_custom_container_single_size_pass() {
+    let mut tag = CustomContainer::new();
+    for _ in 0..10 {
+        let mut parent = CustomContainer::new();
+        parent.children.push(Box::new(tag));
+        tag = parent;
+    }
+
+    // One pass to compute the size plan and another to write the data
+    tag.to_bytes().unwrap();
+    let mut curr = &tag;
+    loop {
+        assert_eq!(curr.value_parts_calls.load(Ordering::SeqCst), 2);
+        match curr.children.first() {
+            Some(c) => curr = tag_downcast_ref::<CustomContainer>(c.as_ref()).unwrap(),
+            None => break,
+        }
+    }
+
+    let mut writer = VecWriter::new();
+    tag.serialize(&mut writer).unwrap();
+    assert_eq!(tag.value_parts_calls.load(Ordering::SeqCst), 4);
+}
+
+#[test]
+fn test_custom_container_size_single_plan() {
+    let mut tag = CustomContainer::new();
+    for _ in 0..10 {
+        let mut parent = CustomContainer::new();
+        parent.children.push(Box::new(tag));
+        tag = parent;
+    }
+
+    // size() builds the size plan only once
+    let size = tag.size();
+    let mut curr = &tag;
+    loop {
+        assert_eq!(curr.value_parts_calls.load(Ordering::SeqCst), 1);
+        match curr.children.first() {
+            Some(c) => curr = tag_downcast_ref::<CustomContainer>(c.as_ref()).unwrap(),
+            None => break,
+        }
+    }
+    assert_eq!(size, tag.to_bytes().unwrap().len() as u64);
+
+    tag.value_size();
+    assert_eq!(tag.value_parts_calls.load(Ordering::SeqCst), 4);
+}
+
+/// A tag that reports a size that is smaller than its serialized size.
+struct WrongSizeTag;
+
+impl ILTag for WrongSizeTag {
+    fn id(&self) -> u64 {
+        IL_NULL_TAG_ID
+    }
+
+    iltag_as_any_impl!();
+
+    fn value_size(&self) -> u64 {
+        0
+    }
+
+    fn size(&self) -> u64 {
+        0
+    }
+
+    fn serialize_value(&self, _writer: &mut dyn Writer) -> Result<()> {
+        Ok(())
+    }
+
+    fn deserialize_value(
+        &mut self,
+        _factory: &dyn ILTagFactory,
+        _value_size: usize,
+        _reader: &mut dyn Reader,
+    ) -> Result<()> {
+        Ok(())
+    }
+}
+
+#[test]
+fn test_custom_container_serialize_wrong_size() {
+    if !cfg!(any(debug_assertions, feature = "verify-serialization")) {
+        return;
+    }
+    let mut tag = CustomContainer::new();
+    tag.children.push(Box::new(WrongSizeTag));
+    assert!(matches!(
+        tag.to_bytes(),
+        Err(ErrorKind::ValueSizeMismatch(1234))
+    ));
+    let mut writer = VecWriter::new();
+    assert!(matches!(
+        tag.serialize(&mut writer),
+        Err(ErrorKind::ValueSizeMismatch(1234))
+    ));
+    assert_eq!(tag.value_parts_calls.load(Ordering::SeqCst), 4);
+}
@@ -0,0 +1,368 @@
+/*
+ * BSD 3-Clause License
+ *
+ * Copyright (c) 2020, InterlockLedger Network
+ * All rights reserved.
+ *
+ * Redistribution and use in source and binary forms, with or without
+ * modification, are permitted provided that the following conditions are met:
+ *
+ * * Redistributions of source code must retain the above copyright notice, this
+ *   list of conditions and the following disclaimer.
+ *
+ * * Redistributions in binary form must reproduce the above copyright notice,
+ *   this list of conditions and the following disclaimer in the documentation
+ *   and/or other materials provided with the distribution.
+ *
+ * * Neither the name of the copyright holder nor the names of its
+ *   contributors may be used to endorse or promote products derived from
+ *   this software without specific prior written permission.
+ *
+ * THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS"
+ * AND ANY EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE
+ * IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+ * DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE
+ * FOR ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL, EXEMPLARY, OR CONSEQUENTIAL
+ * DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+ * SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER
+ * CAUSED AND ON ANY THEORY OF LIABILITY, WHETHER IN CONTRACT, STRICT LIABILITY,
+ * OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE
+ * OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+ */
+//! This module implements an annotated hex dump of serialized tags built on
+//! top of [`crate::tags::raw`]. It shows exactly which bytes encode the id,
+//! the size and the value of each tag, descends into the known containers
+//! and decodes the values of the standard scalar tags.
+//!
+//! Corrupted regions do not stop the dump. The bytes that cannot be parsed
+//! are listed as corrupted and the dump resumes after the end of the
+//! enclosing container.
+//!
+//! Example:
+//!
+//! ```
+//! use il2_iltags::tags::dump::RawTagDumper;
+//! use il2_iltags::tags::text::text_to_bytes;
+//!
+//! let raw = text_to_bytes(r#"array[ u16 1, str"abc" ]"#).unwrap();
+//! assert_eq!(
+//!     RawTagDumper::new().dump(&raw),
+//!     "\
+//! 00000000  15                                                               id: 21 (array)
+//! 00000001  09                                                               size: 9
+//! 00000002    02                                                             count: 2
+//! 00000003    05                                                             id: 5 (u16)
+//! 00000004      00 01                                                        value: u16 1
+//! 00000006    11                                                             id: 17 (str)
+//! 00000007    03                                                             size: 3
+//! 00000008      61 62 63                                                     value: str\"abc\"
+//! "
+//! );
+//! ```
+//!
+//! New since 2.0.0.
+#[cfg(test)]
+mod tests;
+
+use crate::io::array::ByteArrayReader;
+use crate::tags::raw::{
+    standard_container_layout, RawContainerLayout, RawLayoutRegistry, RawTagOffset, RawTagScanner,
+};
+use crate::tags::standard::constants::*;
+use crate::tags::standard::ILStandardTagFactory;
+use crate::tags::text::to_text;
+use crate::tags::{is_implicit_tag, ILTagFactory};
+use ::std::fmt;
+use ::std::fmt::Write;
+
+/// Number of bytes listed in each line.
+const BYTES_PER_LINE: usize = 16;
+
+/// Width of the column with the bytes. It is large enough to hold the bytes
+/// of a line indented up to 8 levels, thus the notes of most lines are
+/// aligned.
+const BYTES_COLUMN_WIDTH: usize = BYTES_PER_LINE * 3 - 1 + 16;
+
+/// Maximum depth of the containers that will be expanded. Deeper containers
+/// are listed as plain values.
+pub const MAX_DUMP_DEPTH: usize = 64;
+
+/// Maximum length of the decoded values in characters.
+const MAX_VALUE_LEN: usize = 64;
+
+//=============================================================================
+// RawTagDumper
+//-----------------------------------------------------------------------------
+/// This struct produces annotated hex dumps of sequences of serialized tags.
+///
+/// Each line of the dump contains the offset of its first byte, the bytes
+/// indented according to the depth of the tag and a description of the
+/// bytes. The containers known by the [`RawLayoutRegistry`] are expanded up
+/// to [`MAX_DUMP_DEPTH`] levels.
+///
+/// New since 2.0.0.
+pub struct RawTagDumper<'a> {
+    layouts: Option<&'a RawLayoutRegistry>,
+}
+
+impl<'a> RawTagDumper<'a> {
+    /// Creates a new dumper that knows only the standard containers.
+    pub fn new() -> Self {
+        Self { layouts: None }
+    }
+
+    /// Creates a new dumper that uses the given registry to find the
+    /// containers.
+    ///
+    /// Arguments:
+    /// - `layouts`: The layouts of the containers;
+    pub fn with_layouts(layouts: &'a RawLayoutRegistry) -> Self {
+        Self {
+            layouts: Some(layouts),
+        }
+    }
+
+    /// Dumps a sequence of serialized tags.
+    ///
+    /// Arguments:
+    /// - `raw`: The sequence of serialized tags;
+    ///
+    /// Returns:
+    /// - The annotated hex dump.
+    pub fn dump(&self, raw: &[u8]) -> String {
+        let mut s = String::new();
+        // Writing into a String never fails.
+        self.write(raw, &mut s).unwrap();
+        s
+    }
+
+    /// Dumps a sequence of serialized tags into a [`fmt::Write`].
+    ///
+    /// Arguments:
+    /// - `raw`: The sequence of serialized tags;
+    /// - `out`: The output;
+    ///
+    /// Returns:
+    /// - `Ok(())`: On success;
+    /// - `Err(_)`: If the output fails;
+    pub fn write(&self, raw: &[u8], out: &mut dyn fmt::Write) -> fmt::Result {
+        let mut output = DumpOutput {
+            raw,
+            layouts: self.layouts,
+            out,
+        };
+        output.region(0, raw.len(), 0, None)
+    }
+}
+
+impl<'a> Default for RawTagDumper<'a> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Returns the name of the standard tags. The names are the same used by
+/// [`crate::tags::text`].
+fn type_name(id: u64) -> Option<&'static str> {
+    Some(match id {
+        IL_NULL_TAG_ID => "null",
+        IL_BOOL_TAG_ID => "bool",
+        IL_INT8_TAG_ID => "i8",
+        IL_UINT8_TAG_ID => "u8",
+        IL_INT16_TAG_ID => "i16",
+        IL_UINT16_TAG_ID => "u16",
+        IL_INT32_TAG_ID => "i32",
+        IL_UINT32_TAG_ID => "u32",
+        IL_INT64_TAG_ID => "i64",
+        IL_UINT64_TAG_ID => "u64",
+        IL_ILINT_TAG_ID => "ilint",
+        IL_BIN32_TAG_ID => "f32",
+        IL_BIN64_TAG_ID => "f64",
+        IL_BIN128_TAG_ID => "bin128",
+        IL_SIGNED_ILINT_TAG_ID => "silint",
+        IL_BYTES_TAG_ID => "bytes",
+        IL_STRING_TAG_ID => "str",
+        IL_BINT_TAG_ID => "bint",
+        IL_BDEC_TAG_ID => "bdec",
+        IL_ILINTARRAY_TAG_ID => "ilints",
+        IL_ILTAGARRAY_TAG_ID => "array",
+        IL_ILTAGSEQ_TAG_ID => "seq",
+        IL_RANGE_TAG_ID => "range",
+        IL_VERSION_TAG_ID => "version",
+        IL_OID_TAG_ID => "oid",
+        IL_DICTIONARY_TAG_ID => "dict",
+        IL_STRING_DICTIONARY_TAG_ID => "strdict",
+        _ => return None,
+    })
+}
+
+/// Returns true if the value of the tag is worth decoding. Byte arrays are
+/// excluded because the decoded value would repeat the dumped bytes.
+fn is_decodable(id: u64) -> bool {
+    match id {
+        IL_BIN128_TAG_ID | IL_BYTES_TAG_ID | IL_BINT_TAG_ID => false,
+        IL_STRING_TAG_ID | IL_BDEC_TAG_ID | IL_ILINTARRAY_TAG_ID | IL_RANGE_TAG_ID
+        | IL_VERSION_TAG_ID | IL_OID_TAG_ID => true,
+        id => is_implicit_tag(id),
+    }
+}
+
+/// Helper that writes the dump.
+struct DumpOutput<'a, 'b> {
+    raw: &'a [u8],
+    layouts: Option<&'a RawLayoutRegistry>,
+    out: &'b mut dyn fmt::Write,
+}
+
+impl<'a, 'b> DumpOutput<'a, 'b> {
+    fn layout(&self, id: u64) -> Option<&'a dyn RawContainerLayout> {
+        match self.layouts {
+            Some(layouts) => layouts.get(id),
+            None => standard_container_layout(id),
+        }
+    }
+
+    /// Writes the bytes in `[start, end)` with a note in the first line.
+    fn line(&mut self, start: usize, end: usize, depth: usize, note: &str) -> fmt::Result {
+        let bytes = &self.raw[start..end];
+        if bytes.is_empty() {
+            return self.line_core(start, depth, "", note);
+        }
+        for (i, chunk) in bytes.chunks(BYTES_PER_LINE).enumerate() {
+            let mut hex = String::with_capacity(chunk.len() * 3);
+            for b in chunk {
+                if !hex.is_empty() {
+                    hex.push(' ');
+                }
+                write!(hex, "{:02x}", b)?;
+            }
+            let note = if i == 0 { note } else { "" };
+            self.line_core(start + i * BYTES_PER_LINE, depth, &hex, note)?;
+        }
+        Ok(())
+    }
+
+    fn line_core(&mut self, offset: usize, depth: usize, hex: &str, note: &str) -> fmt::Result {
+        let bytes = format!("{:indent$}{}", "", hex, indent = depth * 2);
+        let line = format!(
+            "{:08x}  {:<width$}  {}",
+            offset,
+            bytes,
+            note,
+            width = BYTES_COLUMN_WIDTH
+        );
+        writeln!(self.out, "{}", line.trim_end())
+    }
+
+    /// Dumps the tags inside `[start, end)`.
+    ///
+    /// Arguments:
+    /// - `start`: The offset of the first tag;
+    /// - `end`: The end of the region;
+    /// - `depth`: The depth of the tags;
+    /// - `expected`: The expected number of tags if known;
+    fn region(
+        &mut self,
+        start: usize,
+        end: usize,
+        depth: usize,
+        expected: Option<u64>,
+    ) -> fmt::Result {
+        let mut pos = start;
+        let mut count: u64 = 0;
+        while pos < end {
+            let mut reader = ByteArrayReader::new(&self.raw[pos..end]);
+            let tag = match RawTagScanner::new(&mut reader).next_tag() {
+                Ok(Some(tag)) => RawTagOffset::new(
+                    tag.id(),
+                    pos as u64,
+                    tag.size() - tag.value_size(),
+                    tag.value_size(),
+                ),
+                _ => return self.line(pos, end, depth, "corrupted data"),
+            };
+            self.tag(&tag, depth)?;
+            pos = tag.tag_end();
+            count += 1;
+        }
+        match expected {
+            Some(expected) if expected != count => self.line(
+                end,
+                end,
+                depth,
+                &format!("{} tags expected but {} found", expected, count),
+            ),
+            _ => Ok(()),
+        }
+    }
+
+    /// Dumps a single tag.
+    fn tag(&mut self, tag: &RawTagOffset, depth: usize) -> fmt::Result {
+        let id = tag.id();
+        let id_end = tag.tag_start() + crate::ilint::encoded_size(id);
+        let note = match type_name(id) {
+            Some(name) => format!("id: {} ({})", id, name),
+            None => format!("id: {}", id),
+        };
+        self.line(tag.tag_start(), id_end, depth, &note)?;
+        if id_end < tag.value_start() {
+            let note = format!("size: {}", tag.value_size());
+            self.line(id_end, tag.value_start(), depth, &note)?;
+        }
+        if tag.value_size() == 0 {
+            return Ok(());
+        }
+        match self.layout(id) {
+            Some(layout) if depth < MAX_DUMP_DEPTH => self.container(tag, layout, depth + 1),
+            _ => {
+                let note = self.value_note(tag);
+                self.line(tag.value_start(), tag.value_end(), depth + 1, &note)
+            }
+        }
+    }
+
+    /// Dumps the value of a container.
+    fn container(
+        &mut self,
+        tag: &RawTagOffset,
+        layout: &dyn RawContainerLayout,
+        depth: usize,
+    ) -> fmt::Result {
+        let value = tag.value_slice(self.raw);
+        let (prefix_size, expected) = match layout.parse_prefix(value) {
+            Ok((prefix_size, expected)) if prefix_size <= value.len() as u64 => {
+                (prefix_size as usize, expected)
+            }
+            _ => return self.line(tag.value_start(), tag.value_end(), depth, "corrupted data"),
+        };
+        let children_start = tag.value_start() + prefix_size;
+        if prefix_size > 0 {
+            let note = match crate::ilint::decode_from_bytes(&value[..prefix_size]) {
+                Ok((count, size)) if size == prefix_size => format!("count: {}", count),
+                _ => String::from("prefix"),
+            };
+            self.line(tag.value_start(), children_start, depth, &note)?;
+        }
+        self.region(children_start, tag.value_end(), depth, expected)
+    }
+
+    /// Composes the note of the value of a tag.
+    fn value_note(&self, tag: &RawTagOffset) -> String {
+        if !is_decodable(tag.id()) {
+            return String::from("value");
+        }
+        let factory = ILStandardTagFactory::new(false);
+        let text = match factory.from_bytes(tag.tag_slice(self.raw)) {
+            Ok(t) => to_text(t.as_ref()),
+            Err(e) => Err(e),
+        };
+        match text {
+            Ok(text) if text.chars().count() > MAX_VALUE_LEN => {
+                let text: String = text.chars().take(MAX_VALUE_LEN - 3).collect();
+                format!("value: {}...", text)
+            }
+            Ok(text) => format!("value: {}", text),
+            Err(_) => String::from("value (invalid)"),
+        }
+    }
+}
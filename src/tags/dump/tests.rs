@@ -0,0 +1,181 @@
+/*
+ * BSD 3-Clause License
+ *
+ * Copyright (c) 2020, InterlockLedger Network
+ * All rights reserved.
+ *
+ * Redistribution and use in source and binary forms, with or without
+ * modification, are permitted provided that the following conditions are met:
+ *
+ * * Redistributions of source code must retain the above copyright notice, this
+ *   list of conditions and the following disclaimer.
+ *
+ * * Redistributions in binary form must reproduce the above copyright notice,
+ *   this list of conditions and the following disclaimer in the documentation
+ *   and/or other materials provided with the distribution.
+ *
+ * * Neither the name of the copyright holder nor the names of its
+ *   contributors may be used to endorse or promote products derived from
+ *   this software without specific prior written permission.
+ *
+ * THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS"
+ * AND ANY EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE
+ * IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+ * DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE
+ * FOR ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL, EXEMPLARY, OR CONSEQUENTIAL
+ * DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+ * SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER
+ * CAUSED AND ON ANY THEORY OF LIABILITY, WHETHER IN CONTRACT, STRICT LIABILITY,
+ * OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE
+ * OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+ */
+use super::*;
+use crate::tags::raw::RawArrayLayout;
+use crate::tags::text::text_to_bytes;
+
+fn sample() -> Vec<u8> {
+    let mut raw = text_to_bytes(
+        r#"dict{ "a": array[ bytes h'000102030405060708090a0b0c0d0e0f10', null ], "b": #1234 h'ff' }"#,
+    )
+    .unwrap();
+    raw.extend_from_slice(&text_to_bytes("ilint 1234").unwrap());
+    raw
+}
+
+#[test]
+fn test_rawtagdumper_dump() {
+    let expected = "\
+00000000  1e                                                               id: 30 (dict)
+00000001  23                                                               size: 35
+00000002    02                                                             count: 2
+00000003    11                                                             id: 17 (str)
+00000004    01                                                             size: 1
+00000005      61                                                           value: str\"a\"
+00000006    15                                                             id: 21 (array)
+00000007    15                                                             size: 21
+00000008      02                                                           count: 2
+00000009      10                                                           id: 16 (bytes)
+0000000a      11                                                           size: 17
+0000000b        00 01 02 03 04 05 06 07 08 09 0a 0b 0c 0d 0e 0f            value
+0000001b        10
+0000001c      00                                                           id: 0 (null)
+0000001d    11                                                             id: 17 (str)
+0000001e    01                                                             size: 1
+0000001f      62                                                           value: str\"b\"
+00000020    f9 03 da                                                       id: 1234
+00000023    01                                                             size: 1
+00000024      ff                                                           value
+00000025  0a                                                               id: 10 (ilint)
+00000026    f9 03 da                                                       value: ilint 1234
+";
+    let raw = sample();
+    assert_eq!(RawTagDumper::new().dump(&raw), expected);
+    assert_eq!(RawTagDumper::default().dump(&raw), expected);
+
+    let mut s = String::new();
+    RawTagDumper::new().write(&raw, &mut s).unwrap();
+    assert_eq!(s, expected);
+
+    assert_eq!(RawTagDumper::new().dump(&[]), "");
+}
+
+#[test]
+fn test_rawtagdumper_values() {
+    let raw = text_to_bytes(&format!(r#"str"{}""#, "a".repeat(100))).unwrap();
+    let dump = RawTagDumper::new().dump(&raw);
+    let first = dump.lines().nth(2).unwrap();
+    assert!(first.ends_with(&format!(r#"value: str"{}..."#, "a".repeat(57))));
+
+    // Invalid UTF-8
+    let dump = RawTagDumper::new().dump(&[0x11, 0x01, 0xFF]);
+    assert!(dump.lines().nth(2).unwrap().ends_with("value (invalid)"));
+}
+
+#[test]
+fn test_rawtagdumper_corrupted() {
+    // Truncated tag inside a container
+    let mut raw = text_to_bytes("array[ u8 1, u16 2 ]").unwrap();
+    raw[1] = 0x05;
+    raw.truncate(7);
+    assert_eq!(
+        RawTagDumper::new().dump(&raw),
+        "\
+00000000  15                                                               id: 21 (array)
+00000001  05                                                               size: 5
+00000002    02                                                             count: 2
+00000003    03                                                             id: 3 (u8)
+00000004      01                                                           value: u8 1
+00000005    05 00                                                          corrupted data
+"
+    );
+
+    // Wrong number of children followed by more tags
+    let mut raw = text_to_bytes("array[ u8 1 ]").unwrap();
+    raw[2] = 0x02;
+    raw.extend_from_slice(&text_to_bytes("null").unwrap());
+    assert_eq!(
+        RawTagDumper::new().dump(&raw),
+        "\
+00000000  15                                                               id: 21 (array)
+00000001  03                                                               size: 3
+00000002    02                                                             count: 2
+00000003    03                                                             id: 3 (u8)
+00000004      01                                                           value: u8 1
+00000005                                                                   2 tags expected but 1 found
+00000005  00                                                               id: 0 (null)
+"
+    );
+
+    // Invalid prefix
+    assert_eq!(
+        RawTagDumper::new().dump(&[0x15, 0x01, 0xFF, 0x00]),
+        "\
+00000000  15                                                               id: 21 (array)
+00000001  01                                                               size: 1
+00000002    ff                                                             corrupted data
+00000003  00                                                               id: 0 (null)
+"
+    );
+
+    // Truncated top level tag
+    assert_eq!(
+        RawTagDumper::new().dump(&[0x00, 0x11, 0x05, 0x61]),
+        "\
+00000000  00                                                               id: 0 (null)
+00000001  11 05 61                                                         corrupted data
+"
+    );
+}
+
+#[test]
+fn test_rawtagdumper_with_layouts() {
+    let raw = text_to_bytes("#1234 h'01 0000'").unwrap();
+    let mut layouts = RawLayoutRegistry::new();
+    layouts.register(1234, Box::new(RawArrayLayout));
+    assert_eq!(
+        RawTagDumper::with_layouts(&layouts).dump(&raw),
+        "\
+00000000  f9 03 da                                                         id: 1234
+00000003  03                                                               size: 3
+00000004    01                                                             count: 1
+00000005    00                                                             id: 0 (null)
+00000006    00                                                             id: 0 (null)
+00000007                                                                   1 tags expected but 2 found
+"
+    );
+}
+
+#[test]
+fn test_rawtagdumper_max_depth() {
+    let text = format!(
+        "{}null{}",
+        "seq[".repeat(MAX_DUMP_DEPTH + 1),
+        "]".repeat(MAX_DUMP_DEPTH + 1)
+    );
+    let raw = text_to_bytes(&text).unwrap();
+    let dump = RawTagDumper::new().dump(&raw);
+    // Each container uses 2 lines and the last one is dumped as a value
+    let lines: Vec<&str> = dump.lines().collect();
+    assert_eq!(lines.len(), (MAX_DUMP_DEPTH + 1) * 2 + 1);
+    assert!(lines.last().unwrap().ends_with("00  value"));
+}
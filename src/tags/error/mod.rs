@@ -0,0 +1,126 @@
+/*
+ * BSD 3-Clause License
+ *
+ * Copyright (c) 2020, InterlockLedger Network
+ * All rights reserved.
+ *
+ * Redistribution and use in source and binary forms, with or without
+ * modification, are permitted provided that the following conditions are met:
+ *
+ * * Redistributions of source code must retain the above copyright notice, this
+ *   list of conditions and the following disclaimer.
+ *
+ * * Redistributions in binary form must reproduce the above copyright notice,
+ *   this list of conditions and the following disclaimer in the documentation
+ *   and/or other materials provided with the distribution.
+ *
+ * * Neither the name of the copyright holder nor the names of its
+ *   contributors may be used to endorse or promote products derived from
+ *   this software without specific prior written permission.
+ *
+ * THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS"
+ * AND ANY EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE
+ * IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+ * DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE
+ * FOR ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL, EXEMPLARY, OR CONSEQUENTIAL
+ * DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+ * SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER
+ * CAUSED AND ON ANY THEORY OF LIABILITY, WHETHER IN CONTRACT, STRICT LIABILITY,
+ * OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE
+ * OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+ */
+//! This module defines [`DeserializationError`], an error that carries the
+//! location where the deserialization of a tag failed.
+//!
+//! New since 2.0.0.
+#[cfg(test)]
+mod tests;
+
+use crate::tags::path::{fmt_location, ILTagPath};
+use crate::tags::ErrorKind;
+use ::std::fmt;
+
+//=============================================================================
+// DeserializationError
+//-----------------------------------------------------------------------------
+/// This struct describes an error found during the deserialization of a tag
+/// along with its location. It contains the offset of the tag that could not
+/// be deserialized, its id (if it could be read) and the path of its
+/// ancestors.
+///
+/// The offset is relative to the first byte read from the reader, thus it
+/// is the absolute offset in the input if the reader is positioned at its
+/// beginning.
+///
+/// New since 2.0.0.
+#[derive(Debug)]
+pub struct DeserializationError {
+    kind: ErrorKind,
+    offset: u64,
+    id: Option<u64>,
+    path: ILTagPath,
+}
+
+impl DeserializationError {
+    /// Creates a new instance of this struct.
+    ///
+    /// Arguments:
+    /// - `kind`: The error;
+    /// - `offset`: The offset of the tag;
+    /// - `id`: The id of the tag if known;
+    /// - `path`: The path of the parent of the tag;
+    pub fn new(kind: ErrorKind, offset: u64, id: Option<u64>, path: ILTagPath) -> Self {
+        Self {
+            kind,
+            offset,
+            id,
+            path,
+        }
+    }
+
+    /// Returns the error.
+    pub fn kind(&self) -> &ErrorKind {
+        &self.kind
+    }
+
+    /// Returns the offset of the tag that could not be deserialized.
+    pub fn offset(&self) -> u64 {
+        self.offset
+    }
+
+    /// Returns the id of the tag that could not be deserialized or None if
+    /// the id itself could not be read.
+    pub fn id(&self) -> Option<u64> {
+        self.id
+    }
+
+    /// Returns the path of the parent of the tag that could not be
+    /// deserialized. It is empty if the error was found in the top level tag.
+    pub fn path(&self) -> &ILTagPath {
+        &self.path
+    }
+
+    /// Discards the location and returns the error.
+    pub fn into_kind(self) -> ErrorKind {
+        self.kind
+    }
+}
+
+impl fmt::Display for DeserializationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} at offset {}", self.kind, self.offset)?;
+        fmt_location(f, &self.path, self.id)
+    }
+}
+
+impl std::error::Error for DeserializationError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        Some(&self.kind)
+    }
+}
+
+impl From<DeserializationError> for ErrorKind {
+    fn from(error: DeserializationError) -> Self {
+        error.into_kind()
+    }
+}
@@ -0,0 +1,89 @@
+/*
+ * BSD 3-Clause License
+ *
+ * Copyright (c) 2020, InterlockLedger Network
+ * All rights reserved.
+ *
+ * Redistribution and use in source and binary forms, with or without
+ * modification, are permitted provided that the following conditions are met:
+ *
+ * * Redistributions of source code must retain the above copyright notice, this
+ *   list of conditions and the following disclaimer.
+ *
+ * * Redistributions in binary form must reproduce the above copyright notice,
+ *   this list of conditions and the following disclaimer in the documentation
+ *   and/or other materials provided with the distribution.
+ *
+ * * Neither the name of the copyright holder nor the names of its
+ *   contributors may be used to endorse or promote products derived from
+ *   this software without specific prior written permission.
+ *
+ * THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS"
+ * AND ANY EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE
+ * IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+ * DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE
+ * FOR ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL, EXEMPLARY, OR CONSEQUENTIAL
+ * DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+ * SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER
+ * CAUSED AND ON ANY THEORY OF LIABILITY, WHETHER IN CONTRACT, STRICT LIABILITY,
+ * OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE
+ * OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+ */
+use super::*;
+use crate::tags::path::ILTagPathElement;
+use crate::tags::standard::constants::*;
+use std::error::Error;
+
+#[test]
+fn test_deserializationerror() {
+    let e = DeserializationError::new(ErrorKind::UnknownTag, 10, Some(1234), ILTagPath::new());
+    assert!(matches!(e.kind(), ErrorKind::UnknownTag));
+    assert_eq!(e.offset(), 10);
+    assert_eq!(e.id(), Some(1234));
+    assert!(e.path().is_empty());
+    assert!(matches!(e.into_kind(), ErrorKind::UnknownTag));
+
+    let e = DeserializationError::new(ErrorKind::CorruptedData, 0, None, ILTagPath::new());
+    assert_eq!(e.id(), None);
+    let kind: ErrorKind = e.into();
+    assert!(matches!(kind, ErrorKind::CorruptedData));
+}
+
+#[test]
+fn test_deserializationerror_display() {
+    let e = DeserializationError::new(ErrorKind::CorruptedData, 3, None, ILTagPath::new());
+    assert_eq!(format!("{}", e), "corrupted tag data at offset 3");
+
+    let e = DeserializationError::new(ErrorKind::UnknownTag, 4, Some(1234), ILTagPath::new());
+    assert_eq!(format!("{}", e), "unknown tag at offset 4 in tag(1234)");
+
+    let mut path = ILTagPath::new();
+    path.push(ILTagPathElement::new(
+        IL_DICTIONARY_TAG_ID,
+        0,
+        Some(String::from("payload")),
+    ));
+    path.push(ILTagPathElement::new(IL_ILTAGARRAY_TAG_ID, 17, None));
+    let e = DeserializationError::new(ErrorKind::CorruptedData, 1234, Some(IL_STRING_TAG_ID), path);
+    assert_eq!(
+        format!("{}", e),
+        "corrupted tag data at offset 1234 in dict[\"payload\"] > array[17] > string"
+    );
+    assert_eq!(e.path().len(), 2);
+}
+
+#[test]
+fn test_deserializationerror_source() {
+    let e = DeserializationError::new(
+        ErrorKind::IOError(crate::io::ErrorKind::EndOfData),
+        0,
+        None,
+        ILTagPath::new(),
+    );
+    let source = e.source().unwrap();
+    assert_eq!(format!("{}", source), "I/O error");
+    assert_eq!(format!("{}", source.source().unwrap()), "end of data");
+
+    fn assert_send_sync<T: Send + Sync + 'static>() {}
+    assert_send_sync::<DeserializationError>();
+}
@@ -0,0 +1,402 @@
+/*
+ * BSD 3-Clause License
+ *
+ * Copyright (c) 2020, InterlockLedger Network
+ * All rights reserved.
+ *
+ * Redistribution and use in source and binary forms, with or without
+ * modification, are permitted provided that the following conditions are met:
+ *
+ * * Redistributions of source code must retain the above copyright notice, this
+ *   list of conditions and the following disclaimer.
+ *
+ * * Redistributions in binary form must reproduce the above copyright notice,
+ *   this list of conditions and the following disclaimer in the documentation
+ *   and/or other materials provided with the distribution.
+ *
+ * * Neither the name of the copyright holder nor the names of its
+ *   contributors may be used to endorse or promote products derived from
+ *   this software without specific prior written permission.
+ *
+ * THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS"
+ * AND ANY EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE
+ * IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+ * DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE
+ * FOR ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL, EXEMPLARY, OR CONSEQUENTIAL
+ * DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+ * SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER
+ * CAUSED AND ON ANY THEORY OF LIABILITY, WHETHER IN CONTRACT, STRICT LIABILITY,
+ * OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE
+ * OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+ */
+//! This module defines the trait [`ILTagField`] that maps Rust values into
+//! standard tags. It is the runtime support used by the derive macros
+//! `ILTag` and `ILTagPayload` provided by the feature `derive`, but it can
+//! also be used to implement custom tags manually.
+//!
+//! The values are mapped as follows:
+//! - `bool`, `i8`, `u8`, `i16`, `u16`, `i32`, `u32`, `i64`, `u64`, `f32` and
+//!   `f64`: The corresponding implicit standard tags;
+//! - `String`: [`ILStringTag`];
+//! - `Option<T>`: [`ILNullTag`] if `None` or the tag of `T` otherwise;
+//! - `Vec<T>`: [`ILTagArrayTag`] with the tags of each element;
+//!
+//! New since 2.0.0.
+#[cfg(test)]
+mod tests;
+
+use crate::io::{LimitedReader, Reader, Writer};
+use crate::tags::serialization::*;
+use crate::tags::standard::constants::*;
+use crate::tags::standard::explicit::string_tag_size_from_value;
+use crate::tags::standard::implicit::implicit_tag_size;
+use crate::tags::standard::*;
+use crate::tags::util::limited_reader_ensure_empty;
+use crate::tags::{ErrorKind, ILTag, ILTagFactory, Result};
+
+//=============================================================================
+// ILTagField
+//-----------------------------------------------------------------------------
+/// This trait must be implemented by all values that can be serialized as a
+/// field of a tag. Each field is serialized as a complete tag, including its
+/// header.
+///
+/// The `depth` passed to the deserialization methods is the nesting depth of
+/// the field inside the outermost tag. It must be incremented by values that
+/// contain other fields and verified against the
+/// [`crate::tags::DeserializationLimits`] of the factory.
+///
+/// New since 2.0.0.
+pub trait ILTagField: Sized {
+    /// Returns the size of this field serialized as a tag, including its
+    /// header.
+    fn field_size(&self) -> u64;
+
+    /// Serializes this field as a tag.
+    ///
+    /// Arguments:
+    /// - `writer`: The writer;
+    ///
+    /// Returns:
+    /// - `Ok(())`: For success;
+    /// - `Err(_)`: For failure;
+    fn serialize_field(&self, writer: &mut dyn Writer) -> Result<()>;
+
+    /// Deserializes this field from a tag whose id has already been read.
+    ///
+    /// Arguments:
+    /// - `factory`: The tag factory;
+    /// - `depth`: The current nesting depth;
+    /// - `id`: The tag id already read from the reader;
+    /// - `reader`: The reader positioned right after the tag id;
+    ///
+    /// Returns:
+    /// - `Ok(v)`: The deserialized value;
+    /// - `Err(ErrorKind::UnexpectedTagType)`: If the tag id is not compatible
+    ///   with this type;
+    /// - `Err(_)`: For other failures;
+    fn deserialize_field_with_id(
+        factory: &dyn ILTagFactory,
+        depth: usize,
+        id: u64,
+        reader: &mut dyn Reader,
+    ) -> Result<Self>;
+
+    /// Deserializes this field from a tag.
+    ///
+    /// Arguments:
+    /// - `factory`: The tag factory;
+    /// - `depth`: The current nesting depth;
+    /// - `reader`: The reader;
+    ///
+    /// Returns:
+    /// - `Ok(v)`: The deserialized value;
+    /// - `Err(_)`: For failure;
+    fn deserialize_field(
+        factory: &dyn ILTagFactory,
+        depth: usize,
+        reader: &mut dyn Reader,
+    ) -> Result<Self> {
+        let id = reader.deserialize_ilint_with(factory.canonical())?;
+        Self::deserialize_field_with_id(factory, depth, id, reader)
+    }
+}
+
+//=============================================================================
+// Helpers
+//-----------------------------------------------------------------------------
+/// Computes the size of an explicit tag from its id and the size of its
+/// value.
+///
+/// Arguments:
+/// - `id`: The tag id;
+/// - `value_size`: The size of the value;
+///
+/// Returns:
+/// - The size of the tag in bytes.
+pub fn explicit_field_size(id: u64, value_size: u64) -> u64 {
+    (crate::ilint::encoded_size(id) + crate::ilint::encoded_size(value_size)) as u64 + value_size
+}
+
+/// Verifies if the tag id read from the data stream is the expected one.
+///
+/// Arguments:
+/// - `id`: The tag id;
+/// - `expected`: The expected tag id;
+///
+/// Returns:
+/// - `Ok(())`: If the ids match;
+/// - `Err(ErrorKind::UnexpectedTagType)`: If the ids do not match;
+#[inline]
+pub fn check_field_id(id: u64, expected: u64) -> Result<()> {
+    if id == expected {
+        Ok(())
+    } else {
+        Err(ErrorKind::UnexpectedTagType)
+    }
+}
+
+/// Deserializes a sequence of fields stored inside a value of a given size.
+/// The function `f` reads the fields from a reader limited to `value_size`
+/// bytes that must be fully consumed.
+///
+/// Arguments:
+/// - `factory`: The tag factory;
+/// - `depth`: The nesting depth of the value;
+/// - `value_size`: The size of the value;
+/// - `reader`: The reader;
+/// - `f`: The function that deserializes the fields;
+///
+/// Returns:
+/// - `Ok(v)`: The value returned by `f`;
+/// - `Err(ErrorKind::DepthLimitExceeded)`: If `depth` is not allowed;
+/// - `Err(ErrorKind::CorruptedData)`: If the value was not fully consumed;
+/// - `Err(_)`: For other failures;
+pub fn deserialize_fields<T, F>(
+    factory: &dyn ILTagFactory,
+    depth: usize,
+    value_size: usize,
+    reader: &mut dyn Reader,
+    f: F,
+) -> Result<T>
+where
+    F: FnOnce(&mut dyn Reader) -> Result<T>,
+{
+    factory.limits().check_depth(depth)?;
+    let mut lreader = LimitedReader::new(reader, value_size);
+    let ret = f(&mut lreader)?;
+    limited_reader_ensure_empty(&lreader, ErrorKind::CorruptedData)?;
+    Ok(ret)
+}
+
+/// Deserializes the size and the value of an explicit tag whose id has
+/// already been read. The value is handled by [`deserialize_fields()`].
+///
+/// Arguments:
+/// - `factory`: The tag factory;
+/// - `depth`: The nesting depth of the value;
+/// - `reader`: The reader positioned right after the tag id;
+/// - `f`: The function that deserializes the fields;
+///
+/// Returns:
+/// - `Ok(v)`: The value returned by `f`;
+/// - `Err(_)`: For failure;
+pub fn deserialize_explicit_field<T, F>(
+    factory: &dyn ILTagFactory,
+    depth: usize,
+    reader: &mut dyn Reader,
+    f: F,
+) -> Result<T>
+where
+    F: FnOnce(&mut dyn Reader) -> Result<T>,
+{
+    let value_size = reader.deserialize_ilint_with(factory.canonical())?;
+    let value_size = factory.limits().check_tag_size(value_size)?;
+    deserialize_fields(factory, depth, value_size, reader, f)
+}
+
+/// Serializes the index of an enum variant. Enums are serialized as tagged
+/// unions whose value starts with the index of the variant encoded as an
+/// ILInt.
+///
+/// Arguments:
+/// - `index`: The index of the variant;
+/// - `writer`: The writer;
+///
+/// Returns:
+/// - `Ok(())`: For success;
+/// - `Err(_)`: For failure;
+#[inline]
+pub fn serialize_variant_index(index: u64, writer: &mut dyn Writer) -> Result<()> {
+    serialize_ilint(index, writer)
+}
+
+/// Deserializes the index of an enum variant. See
+/// [`serialize_variant_index()`].
+///
+/// Arguments:
+/// - `factory`: The tag factory;
+/// - `reader`: The reader;
+///
+/// Returns:
+/// - `Ok(index)`: The index of the variant;
+/// - `Err(_)`: For failure;
+#[inline]
+pub fn deserialize_variant_index(
+    factory: &dyn ILTagFactory,
+    reader: &mut dyn Reader,
+) -> Result<u64> {
+    reader.deserialize_ilint_with(factory.canonical())
+}
+
+//=============================================================================
+// Implementations
+//-----------------------------------------------------------------------------
+/// This macro implements [`ILTagField`] for primitive types mapped into the
+/// implicit standard tags.
+///
+/// Arguments:
+/// - `$type`: The primitive type;
+/// - `$tag_type`: The implicit tag type;
+/// - `$tag_id`: The id of the implicit tag;
+macro_rules! implicit_field_impl {
+    ($type: ty, $tag_type: ty, $tag_id: expr) => {
+        impl ILTagField for $type {
+            #[inline]
+            fn field_size(&self) -> u64 {
+                crate::ilint::encoded_size($tag_id) as u64 + implicit_tag_size($tag_id)
+            }
+
+            fn serialize_field(&self, writer: &mut dyn Writer) -> Result<()> {
+                <$tag_type>::with_value(*self).serialize(writer)
+            }
+
+            fn deserialize_field_with_id(
+                factory: &dyn ILTagFactory,
+                _depth: usize,
+                id: u64,
+                reader: &mut dyn Reader,
+            ) -> Result<Self> {
+                check_field_id(id, $tag_id)?;
+                let mut tag = <$tag_type>::new();
+                tag.deserialize_value(factory, implicit_tag_size($tag_id) as usize, reader)?;
+                Ok(tag.value())
+            }
+        }
+    };
+}
+
+implicit_field_impl!(bool, ILBoolTag, IL_BOOL_TAG_ID);
+implicit_field_impl!(i8, ILInt8Tag, IL_INT8_TAG_ID);
+implicit_field_impl!(u8, ILUInt8Tag, IL_UINT8_TAG_ID);
+implicit_field_impl!(i16, ILInt16Tag, IL_INT16_TAG_ID);
+implicit_field_impl!(u16, ILUInt16Tag, IL_UINT16_TAG_ID);
+implicit_field_impl!(i32, ILInt32Tag, IL_INT32_TAG_ID);
+implicit_field_impl!(u32, ILUInt32Tag, IL_UINT32_TAG_ID);
+implicit_field_impl!(i64, ILInt64Tag, IL_INT64_TAG_ID);
+implicit_field_impl!(u64, ILUInt64Tag, IL_UINT64_TAG_ID);
+implicit_field_impl!(f32, ILBin32Tag, IL_BIN32_TAG_ID);
+implicit_field_impl!(f64, ILBin64Tag, IL_BIN64_TAG_ID);
+
+impl ILTagField for String {
+    #[inline]
+    fn field_size(&self) -> u64 {
+        string_tag_size_from_value(self)
+    }
+
+    fn serialize_field(&self, writer: &mut dyn Writer) -> Result<()> {
+        writer.serialize_ilint(IL_STRING_TAG_ID)?;
+        writer.serialize_ilint(self.len() as u64)?;
+        writer.serialize_bytes(self.as_bytes())
+    }
+
+    fn deserialize_field_with_id(
+        factory: &dyn ILTagFactory,
+        _depth: usize,
+        id: u64,
+        reader: &mut dyn Reader,
+    ) -> Result<Self> {
+        check_field_id(id, IL_STRING_TAG_ID)?;
+        let value_size = reader.deserialize_ilint_with(factory.canonical())?;
+        let value_size = factory.limits().check_tag_size(value_size)?;
+        let mut tag = ILStringTag::new();
+        tag.deserialize_value(factory, value_size, reader)?;
+        Ok(String::from(tag.value()))
+    }
+}
+
+/// `None` is mapped into [`ILNullTag`]. Since the null tag is used as the
+/// marker of `None`, `Option<Option<T>>` cannot distinguish `Some(None)` from
+/// `None`.
+impl<T: ILTagField> ILTagField for Option<T> {
+    fn field_size(&self) -> u64 {
+        match self {
+            Some(v) => v.field_size(),
+            None => crate::ilint::encoded_size(IL_NULL_TAG_ID) as u64,
+        }
+    }
+
+    fn serialize_field(&self, writer: &mut dyn Writer) -> Result<()> {
+        match self {
+            Some(v) => v.serialize_field(writer),
+            None => ILNullTag::new().serialize(writer),
+        }
+    }
+
+    fn deserialize_field_with_id(
+        factory: &dyn ILTagFactory,
+        depth: usize,
+        id: u64,
+        reader: &mut dyn Reader,
+    ) -> Result<Self> {
+        if id == IL_NULL_TAG_ID {
+            Ok(None)
+        } else {
+            Ok(Some(T::deserialize_field_with_id(
+                factory, depth, id, reader,
+            )?))
+        }
+    }
+}
+
+/// Computes the size of the value of the [`ILTagArrayTag`] that holds the
+/// given fields.
+fn tag_array_value_size<T: ILTagField>(v: &[T]) -> u64 {
+    crate::ilint::encoded_size(v.len() as u64) as u64
+        + v.iter().map(|e| e.field_size()).sum::<u64>()
+}
+
+/// The vector is mapped into [`ILTagArrayTag`].
+impl<T: ILTagField> ILTagField for Vec<T> {
+    fn field_size(&self) -> u64 {
+        explicit_field_size(IL_ILTAGARRAY_TAG_ID, tag_array_value_size(self))
+    }
+
+    fn serialize_field(&self, writer: &mut dyn Writer) -> Result<()> {
+        writer.serialize_ilint(IL_ILTAGARRAY_TAG_ID)?;
+        writer.serialize_ilint(tag_array_value_size(self))?;
+        writer.serialize_ilint(self.len() as u64)?;
+        for v in self {
+            v.serialize_field(writer)?;
+        }
+        Ok(())
+    }
+
+    fn deserialize_field_with_id(
+        factory: &dyn ILTagFactory,
+        depth: usize,
+        id: u64,
+        reader: &mut dyn Reader,
+    ) -> Result<Self> {
+        check_field_id(id, IL_ILTAGARRAY_TAG_ID)?;
+        deserialize_explicit_field(factory, depth, reader, |reader| {
+            let count = reader.deserialize_ilint_with(factory.canonical())?;
+            factory.limits().check_elements(count)?;
+            let mut ret = Vec::new();
+            for _ in 0..count {
+                ret.push(T::deserialize_field(factory, depth + 1, reader)?);
+            }
+            Ok(ret)
+        })
+    }
+}
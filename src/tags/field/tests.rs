@@ -0,0 +1,380 @@
+/*
+ * BSD 3-Clause License
+ *
+ * Copyright (c) 2020, InterlockLedger Network
+ * All rights reserved.
+ *
+ * Redistribution and use in source and binary forms, with or without
+ * modification, are permitted provided that the following conditions are met:
+ *
+ * * Redistributions of source code must retain the above copyright notice, this
+ *   list of conditions and the following disclaimer.
+ *
+ * * Redistributions in binary form must reproduce the above copyright notice,
+ *   this list of conditions and the following disclaimer in the documentation
+ *   and/or other materials provided with the distribution.
+ *
+ * * Neither the name of the copyright holder nor the names of its
+ *   contributors may be used to endorse or promote products derived from
+ *   this software without specific prior written permission.
+ *
+ * THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS"
+ * AND ANY EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE
+ * IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+ * DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE
+ * FOR ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL, EXEMPLARY, OR CONSEQUENTIAL
+ * DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+ * SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER
+ * CAUSED AND ON ANY THEORY OF LIABILITY, WHETHER IN CONTRACT, STRICT LIABILITY,
+ * OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE
+ * OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+ */
+use super::*;
+use crate::io::array::ByteArrayReader;
+use crate::io::array::{MemoryReader, VecWriter};
+use crate::tags::{CanonicalRule, DeserializationLimits};
+
+fn field_to_bytes<T: ILTagField>(v: &T) -> Vec<u8> {
+    let mut writer = VecWriter::new();
+    v.serialize_field(&mut writer).unwrap();
+    let bytes = writer.as_slice().to_vec();
+    assert_eq!(bytes.len() as u64, v.field_size());
+    bytes
+}
+
+fn field_from_bytes<T: ILTagField>(factory: &dyn ILTagFactory, bytes: &[u8]) -> Result<T> {
+    let mut reader = ByteArrayReader::new(bytes);
+    let v = T::deserialize_field(factory, 0, &mut reader)?;
+    assert_eq!(reader.available(), 0);
+    Ok(v)
+}
+
+#[test]
+fn test_explicit_field_size() {
+    assert_eq!(explicit_field_size(IL_STRING_TAG_ID, 0), 2);
+    assert_eq!(explicit_field_size(IL_STRING_TAG_ID, 247), 249);
+    assert_eq!(explicit_field_size(IL_STRING_TAG_ID, 248), 251);
+    assert_eq!(explicit_field_size(1234, 248), 253);
+}
+
+#[test]
+fn test_check_field_id() {
+    assert!(check_field_id(1, 1).is_ok());
+    assert!(matches!(
+        check_field_id(1, 2),
+        Err(ErrorKind::UnexpectedTagType)
+    ));
+}
+
+#[test]
+fn test_deserialize_fields() {
+    let factory = ILStandardTagFactory::new(false);
+    let sample: [u8; 4] = [1, 2, 3, 4];
+
+    let mut reader = ByteArrayReader::new(&sample);
+    let v = deserialize_fields(&factory, 0, 2, &mut reader, |r| {
+        Ok(r.deserialize_ilint()? + r.deserialize_ilint()?)
+    })
+    .unwrap();
+    assert_eq!(v, 3);
+    assert_eq!(reader.available(), 2);
+
+    let mut reader = ByteArrayReader::new(&sample);
+    assert!(matches!(
+        deserialize_fields(&factory, 0, 3, &mut reader, |r| r.deserialize_ilint()),
+        Err(ErrorKind::CorruptedData)
+    ));
+
+    let mut reader = ByteArrayReader::new(&sample);
+    assert!(matches!(
+        deserialize_fields(&factory, 0, 1, &mut reader, |r| {
+            r.deserialize_ilint()?;
+            r.deserialize_ilint()
+        }),
+        Err(ErrorKind::IOError(_))
+    ));
+
+    let mut limits = DeserializationLimits::new();
+    limits.set_max_depth(1);
+    let factory = ILStandardTagFactory::with_limits(false, limits);
+    let mut reader = ByteArrayReader::new(&sample);
+    assert!(deserialize_fields(&factory, 1, 0, &mut reader, |_| Ok(())).is_ok());
+    assert!(matches!(
+        deserialize_fields(&factory, 2, 0, &mut reader, |_| Ok(())),
+        Err(ErrorKind::DepthLimitExceeded)
+    ));
+}
+
+#[test]
+fn test_deserialize_explicit_field() {
+    let factory = ILStandardTagFactory::new(false);
+    let sample: [u8; 4] = [2, 1, 2, 3];
+
+    let mut reader = ByteArrayReader::new(&sample);
+    let v = deserialize_explicit_field(&factory, 0, &mut reader, |r| {
+        Ok(r.deserialize_ilint()? + r.deserialize_ilint()?)
+    })
+    .unwrap();
+    assert_eq!(v, 3);
+    assert_eq!(reader.available(), 1);
+
+    let mut limits = DeserializationLimits::new();
+    limits.set_max_tag_size(1);
+    let factory = ILStandardTagFactory::with_limits(false, limits);
+    let mut reader = ByteArrayReader::new(&sample);
+    assert!(matches!(
+        deserialize_explicit_field(&factory, 0, &mut reader, |_| Ok(())),
+        Err(ErrorKind::TagTooLarge)
+    ));
+}
+
+#[test]
+fn test_variant_index() {
+    let factory = ILStandardTagFactory::new(false);
+    let mut writer = VecWriter::new();
+    serialize_variant_index(1234, &mut writer).unwrap();
+    assert_eq!(writer.as_slice(), &[0xF9, 0x03, 0xDA]);
+
+    let mut reader = ByteArrayReader::new(writer.as_slice());
+    assert_eq!(
+        deserialize_variant_index(&factory, &mut reader).unwrap(),
+        1234
+    );
+
+    let mut factory = ILStandardTagFactory::new(false);
+    factory.set_canonical(true);
+    let mut reader = ByteArrayReader::new(&[0xF9, 0x00, 0x00]);
+    assert!(matches!(
+        deserialize_variant_index(&factory, &mut reader),
+        Err(ErrorKind::NonCanonical(CanonicalRule::ILInt))
+    ));
+}
+
+#[test]
+fn test_iltagfield_primitives() {
+    let factory = ILStandardTagFactory::new(false);
+
+    assert_eq!(field_to_bytes(&true), [IL_BOOL_TAG_ID as u8, 1]);
+    assert_eq!(field_to_bytes(&-2i8), [IL_INT8_TAG_ID as u8, 0xFE]);
+    assert_eq!(field_to_bytes(&2u8), [IL_UINT8_TAG_ID as u8, 2]);
+    assert_eq!(field_to_bytes(&-2i16), [IL_INT16_TAG_ID as u8, 0xFF, 0xFE]);
+    assert_eq!(
+        field_to_bytes(&0x1234u16),
+        [IL_UINT16_TAG_ID as u8, 0x12, 0x34]
+    );
+    assert_eq!(
+        field_to_bytes(&0x12345678i32),
+        [IL_INT32_TAG_ID as u8, 0x12, 0x34, 0x56, 0x78]
+    );
+    assert_eq!(
+        field_to_bytes(&0x12345678u32),
+        [IL_UINT32_TAG_ID as u8, 0x12, 0x34, 0x56, 0x78]
+    );
+    assert_eq!(
+        field_to_bytes(&-1i64),
+        [
+            IL_INT64_TAG_ID as u8,
+            0xFF,
+            0xFF,
+            0xFF,
+            0xFF,
+            0xFF,
+            0xFF,
+            0xFF,
+            0xFF
+        ]
+    );
+    assert_eq!(
+        field_to_bytes(&1u64),
+        [IL_UINT64_TAG_ID as u8, 0, 0, 0, 0, 0, 0, 0, 1]
+    );
+    assert_eq!(
+        field_to_bytes(&1.0f32),
+        [IL_BIN32_TAG_ID as u8, 0x3F, 0x80, 0x00, 0x00]
+    );
+    assert_eq!(
+        field_to_bytes(&1.0f64),
+        [IL_BIN64_TAG_ID as u8, 0x3F, 0xF0, 0, 0, 0, 0, 0, 0]
+    );
+
+    assert!(field_from_bytes::<bool>(&factory, &field_to_bytes(&true)).unwrap());
+    assert_eq!(
+        field_from_bytes::<i8>(&factory, &field_to_bytes(&-2i8)).unwrap(),
+        -2
+    );
+    assert_eq!(
+        field_from_bytes::<u8>(&factory, &field_to_bytes(&2u8)).unwrap(),
+        2
+    );
+    assert_eq!(
+        field_from_bytes::<i16>(&factory, &field_to_bytes(&-2i16)).unwrap(),
+        -2
+    );
+    assert_eq!(
+        field_from_bytes::<u16>(&factory, &field_to_bytes(&2u16)).unwrap(),
+        2
+    );
+    assert_eq!(
+        field_from_bytes::<i32>(&factory, &field_to_bytes(&-2i32)).unwrap(),
+        -2
+    );
+    assert_eq!(
+        field_from_bytes::<u32>(&factory, &field_to_bytes(&2u32)).unwrap(),
+        2
+    );
+    assert_eq!(
+        field_from_bytes::<i64>(&factory, &field_to_bytes(&-2i64)).unwrap(),
+        -2
+    );
+    assert_eq!(
+        field_from_bytes::<u64>(&factory, &field_to_bytes(&2u64)).unwrap(),
+        2
+    );
+    assert_eq!(
+        field_from_bytes::<f32>(&factory, &field_to_bytes(&1.5f32)).unwrap(),
+        1.5
+    );
+    assert_eq!(
+        field_from_bytes::<f64>(&factory, &field_to_bytes(&1.5f64)).unwrap(),
+        1.5
+    );
+
+    // Wrong type
+    assert!(matches!(
+        field_from_bytes::<u16>(&factory, &field_to_bytes(&2u32)),
+        Err(ErrorKind::UnexpectedTagType)
+    ));
+    // Truncated
+    assert!(matches!(
+        field_from_bytes::<u32>(&factory, &[IL_UINT32_TAG_ID as u8, 0, 0]),
+        Err(ErrorKind::IOError(_))
+    ));
+    // Canonical rules of the tags are enforced
+    let mut factory = ILStandardTagFactory::new(false);
+    factory.set_canonical(true);
+    assert!(matches!(
+        field_from_bytes::<bool>(&factory, &[IL_BOOL_TAG_ID as u8, 2]),
+        Err(ErrorKind::NonCanonical(CanonicalRule::Bool))
+    ));
+}
+
+#[test]
+fn test_iltagfield_string() {
+    let factory = ILStandardTagFactory::new(false);
+
+    let s = String::from("abc");
+    let bytes = field_to_bytes(&s);
+    assert_eq!(bytes, [IL_STRING_TAG_ID as u8, 3, b'a', b'b', b'c']);
+    assert_eq!(field_from_bytes::<String>(&factory, &bytes).unwrap(), s);
+
+    let s = String::new();
+    let bytes = field_to_bytes(&s);
+    assert_eq!(bytes, [IL_STRING_TAG_ID as u8, 0]);
+    assert_eq!(field_from_bytes::<String>(&factory, &bytes).unwrap(), s);
+
+    assert!(matches!(
+        field_from_bytes::<String>(&factory, &[IL_BYTES_TAG_ID as u8, 0]),
+        Err(ErrorKind::UnexpectedTagType)
+    ));
+    assert!(matches!(
+        field_from_bytes::<String>(&factory, &[IL_STRING_TAG_ID as u8, 1, 0xFF]),
+        Err(ErrorKind::CorruptedData)
+    ));
+
+    let mut limits = DeserializationLimits::new();
+    limits.set_max_value_length(2);
+    let factory = ILStandardTagFactory::with_limits(false, limits);
+    assert!(matches!(
+        field_from_bytes::<String>(&factory, &field_to_bytes(&String::from("abc"))),
+        Err(ErrorKind::ValueLengthLimitExceeded)
+    ));
+}
+
+#[test]
+fn test_iltagfield_option() {
+    let factory = ILStandardTagFactory::new(false);
+
+    let v: Option<u8> = None;
+    let bytes = field_to_bytes(&v);
+    assert_eq!(bytes, [IL_NULL_TAG_ID as u8]);
+    assert_eq!(
+        field_from_bytes::<Option<u8>>(&factory, &bytes).unwrap(),
+        None
+    );
+
+    let v: Option<u8> = Some(1);
+    let bytes = field_to_bytes(&v);
+    assert_eq!(bytes, [IL_UINT8_TAG_ID as u8, 1]);
+    assert_eq!(field_from_bytes::<Option<u8>>(&factory, &bytes).unwrap(), v);
+
+    assert!(matches!(
+        field_from_bytes::<Option<u8>>(&factory, &[IL_BOOL_TAG_ID as u8, 1]),
+        Err(ErrorKind::UnexpectedTagType)
+    ));
+}
+
+#[test]
+fn test_iltagfield_vec() {
+    let factory = ILStandardTagFactory::new(false);
+
+    let v: Vec<u8> = vec![];
+    let bytes = field_to_bytes(&v);
+    assert_eq!(bytes, [IL_ILTAGARRAY_TAG_ID as u8, 1, 0]);
+    assert_eq!(field_from_bytes::<Vec<u8>>(&factory, &bytes).unwrap(), v);
+
+    let v: Vec<Option<u8>> = vec![Some(1), None, Some(2)];
+    let bytes = field_to_bytes(&v);
+    assert_eq!(
+        bytes,
+        [
+            IL_ILTAGARRAY_TAG_ID as u8,
+            6,
+            3,
+            IL_UINT8_TAG_ID as u8,
+            1,
+            IL_NULL_TAG_ID as u8,
+            IL_UINT8_TAG_ID as u8,
+            2
+        ]
+    );
+    assert_eq!(
+        field_from_bytes::<Vec<Option<u8>>>(&factory, &bytes).unwrap(),
+        v
+    );
+
+    // The standard factory reads the same data
+    let tag = factory.from_bytes(&bytes).unwrap();
+    let tag = tag.as_any().downcast_ref::<ILTagArrayTag>().unwrap();
+    assert_eq!(tag.value().len(), 3);
+
+    // Trailing data inside the value
+    let bytes = [IL_ILTAGARRAY_TAG_ID as u8, 3, 1, IL_NULL_TAG_ID as u8, 0];
+    assert!(matches!(
+        field_from_bytes::<Vec<Option<u8>>>(&factory, &bytes),
+        Err(ErrorKind::CorruptedData)
+    ));
+
+    // Limits
+    let v: Vec<Vec<u8>> = vec![vec![1, 2], vec![]];
+    let bytes = field_to_bytes(&v);
+    assert_eq!(
+        field_from_bytes::<Vec<Vec<u8>>>(&factory, &bytes).unwrap(),
+        v
+    );
+
+    let mut limits = DeserializationLimits::new();
+    limits.set_max_elements(1);
+    let factory = ILStandardTagFactory::with_limits(false, limits);
+    assert!(matches!(
+        field_from_bytes::<Vec<Vec<u8>>>(&factory, &bytes),
+        Err(ErrorKind::ElementCountLimitExceeded)
+    ));
+
+    let mut limits = DeserializationLimits::new();
+    limits.set_max_depth(0);
+    let factory = ILStandardTagFactory::with_limits(false, limits);
+    assert!(matches!(
+        field_from_bytes::<Vec<Vec<u8>>>(&factory, &bytes),
+        Err(ErrorKind::DepthLimitExceeded)
+    ));
+}
@@ -0,0 +1,985 @@
+/*
+ * BSD 3-Clause License
+ *
+ * Copyright (c) 2020, InterlockLedger Network
+ * All rights reserved.
+ *
+ * Redistribution and use in source and binary forms, with or without
+ * modification, are permitted provided that the following conditions are met:
+ *
+ * * Redistributions of source code must retain the above copyright notice, this
+ *   list of conditions and the following disclaimer.
+ *
+ * * Redistributions in binary form must reproduce the above copyright notice,
+ *   this list of conditions and the following disclaimer in the documentation
+ *   and/or other materials provided with the distribution.
+ *
+ * * Neither the name of the copyright holder nor the names of its
+ *   contributors may be used to endorse or promote products derived from
+ *   this software without specific prior written permission.
+ *
+ * THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS"
+ * AND ANY EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE
+ * IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+ * DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE
+ * FOR ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL, EXEMPLARY, OR CONSEQUENTIAL
+ * DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+ * SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER
+ * CAUSED AND ON ANY THEORY OF LIABILITY, WHETHER IN CONTRACT, STRICT LIABILITY,
+ * OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE
+ * OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+ */
+//! This module implements a bidirectional mapping between the standard tags
+//! and JSON. It does not depend on any external JSON library.
+//!
+//! Each tag is mapped into a JSON object with the following members:
+//!
+//! - `"type"`: The name of the tag type (see the table below);
+//! - `"id"`: The id of the tag. It is omitted if the tag uses the default id
+//!   of its type, except for `raw` where it is always present;
+//! - `"value"`: The value of the tag;
+//!
+//! | Type           | Tag                    | Value                                        |
+//! |----------------|------------------------|----------------------------------------------|
+//! | `null`         | [`ILNullTag`]          | `null`                                       |
+//! | `bool`         | [`ILBoolTag`]          | `true` or `false`                            |
+//! | `int8`         | [`ILInt8Tag`]          | number                                       |
+//! | `uint8`        | [`ILUInt8Tag`]         | number                                       |
+//! | `int16`        | [`ILInt16Tag`]         | number                                       |
+//! | `uint16`       | [`ILUInt16Tag`]        | number                                       |
+//! | `int32`        | [`ILInt32Tag`]         | number                                       |
+//! | `uint32`       | [`ILUInt32Tag`]        | number                                       |
+//! | `int64`        | [`ILInt64Tag`]         | number                                       |
+//! | `uint64`       | [`ILUInt64Tag`]        | number                                       |
+//! | `ilint`        | [`ILILInt64Tag`]       | number                                       |
+//! | `signed_ilint` | [`ILSignedILInt64Tag`] | number                                       |
+//! | `bin32`        | [`ILBin32Tag`]         | number, `"NaN"`, `"Infinity"` or `"-Infinity"` |
+//! | `bin64`        | [`ILBin64Tag`]         | number, `"NaN"`, `"Infinity"` or `"-Infinity"` |
+//! | `bin128`       | [`ILBin128Tag`]        | hexadecimal string                           |
+//! | `bytes`        | [`ILByteArrayTag`]     | hexadecimal string                           |
+//! | `string`       | [`ILStringTag`]        | string                                       |
+//! | `bint`         | [`ILBigIntTag`]        | hexadecimal string                           |
+//! | `bdec`         | [`ILBigDecTag`]        | `{"scale": number, "value": hexadecimal string}` |
+//! | `ilint_array`  | [`ILILIntArrayTag`]    | array of numbers                             |
+//! | `array`        | [`ILTagArrayTag`]      | array of tags                                |
+//! | `seq`          | [`ILTagSeqTag`]        | array of tags                                |
+//! | `range`        | [`ILRangeTag`]         | `{"start": number, "count": number}`         |
+//! | `version`      | [`ILVersionTag`]       | array with 4 numbers                         |
+//! | `oid`          | [`ILOIDTag`]           | array of numbers                             |
+//! | `dict`         | [`ILDictTag`]          | object of tags                               |
+//! | `str_dict`     | [`ILStrDictTag`]       | object of strings                            |
+//! | `raw`          | [`ILRawTag`]           | hexadecimal string with the payload          |
+//!
+//! Explicit tags implemented by other structs are mapped as `raw`, thus the
+//! conversion never loses information. The entries of dictionaries are
+//! written in the order of their keys in order to produce a deterministic
+//! output.
+//!
+//! The output is written directly into a [`Writer`], thus large trees can be
+//! exported without holding the whole JSON document in memory.
+//! [`JsonArrayWriter`] goes one step further and allows the export of large
+//! arrays whose elements are produced one at a time.
+//!
+//! Example:
+//!
+//! ```
+//! use il2_iltags::tags::json::{from_json, to_json};
+//! use il2_iltags::tags::standard::*;
+//! use il2_iltags::tags::util::iltag_are_equal;
+//!
+//! let mut tag = ILTagArrayTag::new();
+//! tag.mut_value().push(Box::new(ILUInt16Tag::with_value(1)));
+//! tag.mut_value().push(Box::new(ILStringTag::with_id_value(1234, "abc")));
+//! let json = to_json(&tag).unwrap();
+//! assert_eq!(
+//!     json,
+//!     r#"{"type":"array","value":[{"type":"uint16","value":1},{"type":"string","id":1234,"value":"abc"}]}"#
+//! );
+//! let parsed = from_json(&json).unwrap();
+//! assert!(iltag_are_equal(&tag, parsed.as_ref()));
+//! ```
+//!
+//! New since 2.0.0.
+#[cfg(test)]
+mod tests;
+
+mod parser;
+
+use crate::io::array::BorrowedVecWriter;
+use crate::io::Writer;
+use crate::tags::standard::constants::*;
+use crate::tags::standard::*;
+use crate::tags::util::{hex_decode, hex_encode};
+use crate::tags::{is_implicit_tag, tag_downcast_ref, ErrorKind, ILRawTag, ILTag, Result};
+use parser::{JsonNode, JsonValue};
+use std::fmt;
+use std::str::FromStr;
+
+//=============================================================================
+// JsonError
+//-----------------------------------------------------------------------------
+/// This struct describes an error found while converting a JSON document into
+/// tags. It is reported by [`from_json()`] wrapped in [`ErrorKind::Boxed`].
+///
+/// New since 2.0.0.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct JsonError {
+    offset: usize,
+    message: String,
+}
+
+impl JsonError {
+    /// Creates a new instance of this struct.
+    ///
+    /// Arguments:
+    /// - `offset`: The offset in bytes of the error in the JSON document;
+    /// - `message`: The description of the error;
+    pub fn new(offset: usize, message: &str) -> Self {
+        Self {
+            offset,
+            message: String::from(message),
+        }
+    }
+
+    /// Returns the offset in bytes of the error in the JSON document.
+    pub fn offset(&self) -> usize {
+        self.offset
+    }
+
+    /// Returns the description of the error.
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+}
+
+impl fmt::Display for JsonError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} at offset {}", self.message, self.offset)
+    }
+}
+
+impl std::error::Error for JsonError {}
+
+impl From<JsonError> for ErrorKind {
+    fn from(error: JsonError) -> Self {
+        ErrorKind::Boxed(Box::new(error))
+    }
+}
+
+type JsonResult<T> = std::result::Result<T, JsonError>;
+
+//=============================================================================
+// Output
+//-----------------------------------------------------------------------------
+/// Converts a tag into a compact JSON document. Trees of any depth can be
+/// converted.
+///
+/// Arguments:
+/// - `tag`: The tag to be converted;
+///
+/// Returns:
+/// - `Ok(json)`: The JSON document;
+/// - `Err(ErrorKind::UnsupportedTag)`: If the tag contains an implicit tag
+///   that is not implemented by one of the standard structs;
+/// - `Err(_)`: If the payload of a raw tag could not be serialized;
+pub fn to_json(tag: &dyn ILTag) -> Result<String> {
+    json_to_string(tag, false)
+}
+
+/// Converts a tag into an indented JSON document. Containers are written
+/// with one element per line while the other tags are written in a single
+/// line.
+///
+/// Arguments:
+/// - `tag`: The tag to be converted;
+///
+/// Returns:
+/// - `Ok(json)`: The JSON document;
+/// - `Err(_)`: See [`to_json()`];
+pub fn to_json_pretty(tag: &dyn ILTag) -> Result<String> {
+    json_to_string(tag, true)
+}
+
+fn json_to_string(tag: &dyn ILTag, pretty: bool) -> Result<String> {
+    let mut json = Vec::new();
+    write_json(tag, pretty, &mut BorrowedVecWriter::new(&mut json))?;
+    // The output is always valid UTF-8.
+    Ok(String::from_utf8(json).unwrap())
+}
+
+/// Writes a tag as a JSON document into a writer. The document is written
+/// as it is generated.
+///
+/// Arguments:
+/// - `tag`: The tag to be converted;
+/// - `pretty`: If true, the document is indented like in [`to_json_pretty()`];
+/// - `writer`: The writer;
+///
+/// Returns:
+/// - `Ok(())`: On success;
+/// - `Err(_)`: See [`to_json()`] or if the writer fails;
+pub fn write_json(tag: &dyn ILTag, pretty: bool, writer: &mut dyn Writer) -> Result<()> {
+    JsonOutput::new(writer, pretty).tag(tag)
+}
+
+/// Formats a float according to the rules of this module.
+macro_rules! float_to_json {
+    ($v: expr) => {
+        if $v.is_nan() {
+            String::from("\"NaN\"")
+        } else if $v.is_infinite() {
+            if $v > 0.0 {
+                String::from("\"Infinity\"")
+            } else {
+                String::from("\"-Infinity\"")
+            }
+        } else {
+            format!("{:?}", $v)
+        }
+    };
+}
+
+/// Helper that writes the JSON tokens into a [`Writer`].
+struct JsonOutput<'a> {
+    writer: &'a mut dyn Writer,
+    pretty: bool,
+    level: usize,
+}
+
+impl<'a> JsonOutput<'a> {
+    fn new(writer: &'a mut dyn Writer, pretty: bool) -> Self {
+        Self {
+            writer,
+            pretty,
+            level: 0,
+        }
+    }
+
+    fn raw(&mut self, s: &str) -> Result<()> {
+        self.writer.write_all(s.as_bytes())?;
+        Ok(())
+    }
+
+    fn separator(&mut self) -> Result<()> {
+        self.raw(if self.pretty { ", " } else { "," })
+    }
+
+    fn name(&mut self, name: &str) -> Result<()> {
+        self.string(name)?;
+        self.raw(if self.pretty { ": " } else { ":" })
+    }
+
+    fn new_line(&mut self) -> Result<()> {
+        if self.pretty {
+            self.raw("\n")?;
+            for _ in 0..self.level {
+                self.raw("  ")?;
+            }
+        }
+        Ok(())
+    }
+
+    fn string(&mut self, s: &str) -> Result<()> {
+        self.raw("\"")?;
+        let mut start = 0;
+        for (i, c) in s.char_indices() {
+            let escaped = match c {
+                '"' => String::from("\\\""),
+                '\\' => String::from("\\\\"),
+                '\n' => String::from("\\n"),
+                '\r' => String::from("\\r"),
+                '\t' => String::from("\\t"),
+                c if (c as u32) < 0x20 => format!("\\u{:04x}", c as u32),
+                _ => continue,
+            };
+            self.raw(&s[start..i])?;
+            self.raw(&escaped)?;
+            start = i + c.len_utf8();
+        }
+        self.raw(&s[start..])?;
+        self.raw("\"")
+    }
+
+    fn numbers<T: fmt::Display>(&mut self, values: &[T]) -> Result<()> {
+        self.raw("[")?;
+        for (i, v) in values.iter().enumerate() {
+            if i > 0 {
+                self.separator()?;
+            }
+            self.raw(&v.to_string())?;
+        }
+        self.raw("]")
+    }
+
+    /// Writes the beginning of a tag object up to the name of the value.
+    fn begin(&mut self, type_name: &str, id: u64, default_id: u64) -> Result<()> {
+        self.raw("{")?;
+        self.name("type")?;
+        self.string(type_name)?;
+        if id != default_id {
+            self.separator()?;
+            self.name("id")?;
+            self.raw(&id.to_string())?;
+        }
+        self.separator()?;
+        self.name("value")
+    }
+
+    fn simple(&mut self, type_name: &str, id: u64, default_id: u64, value: &str) -> Result<()> {
+        self.begin(type_name, id, default_id)?;
+        self.raw(value)?;
+        self.raw("}")
+    }
+
+    /// Writes a tag. Containers are written using an explicit stack, thus
+    /// trees of any depth can be written.
+    fn tag(&mut self, tag: &dyn ILTag) -> Result<()> {
+        let mut stack: Vec<JsonFrame> = Vec::new();
+        let mut next = Some(tag);
+        loop {
+            if let Some(t) = next.take() {
+                if let Some(frame) = self.tag_node(t)? {
+                    self.level += 1;
+                    stack.push(frame);
+                }
+            }
+            let frame = match stack.last_mut() {
+                Some(frame) => frame,
+                None => return Ok(()),
+            };
+            match frame.next_child() {
+                Some((key, child)) => {
+                    if frame.count > 1 {
+                        self.raw(",")?;
+                    }
+                    self.new_line()?;
+                    if let Some(key) = key {
+                        self.name(key)?;
+                    }
+                    next = Some(child);
+                }
+                None => {
+                    self.level -= 1;
+                    if frame.count > 0 {
+                        self.new_line()?;
+                    }
+                    let close = frame.close;
+                    stack.pop();
+                    self.raw(close)?;
+                }
+            }
+        }
+    }
+
+    /// Writes a tag that is not a container or the beginning of a container.
+    ///
+    /// Returns:
+    /// - `Ok(None)`: If the tag was written;
+    /// - `Ok(Some(frame))`: If the tag is a container. Its children must be
+    ///   written by [`Self::tag()`];
+    /// - `Err(_)`: In case of error;
+    fn tag_node<'t>(&mut self, tag: &'t dyn ILTag) -> Result<Option<JsonFrame<'t>>> {
+        let id = tag.id();
+        if let Some(t) = tag_downcast_ref::<ILTagArrayTag>(tag) {
+            self.begin("array", id, IL_ILTAGARRAY_TAG_ID)?;
+            self.raw("[")?;
+            Ok(Some(JsonFrame::list(t.value())))
+        } else if let Some(t) = tag_downcast_ref::<ILTagSeqTag>(tag) {
+            self.begin("seq", id, IL_ILTAGSEQ_TAG_ID)?;
+            self.raw("[")?;
+            Ok(Some(JsonFrame::list(t.value())))
+        } else if let Some(t) = tag_downcast_ref::<ILDictTag>(tag) {
+            self.begin("dict", id, IL_DICTIONARY_TAG_ID)?;
+            self.raw("{")?;
+            Ok(Some(JsonFrame::dict(t)))
+        } else {
+            self.simple_tag(tag)?;
+            Ok(None)
+        }
+    }
+
+    /// Writes a tag that is not a container.
+    fn simple_tag(&mut self, tag: &dyn ILTag) -> Result<()> {
+        let id = tag.id();
+        if tag_downcast_ref::<ILNullTag>(tag).is_some() {
+            self.simple("null", id, IL_NULL_TAG_ID, "null")
+        } else if let Some(t) = tag_downcast_ref::<ILBoolTag>(tag) {
+            self.simple("bool", id, IL_BOOL_TAG_ID, &t.value().to_string())
+        } else if let Some(t) = tag_downcast_ref::<ILInt8Tag>(tag) {
+            self.simple("int8", id, IL_INT8_TAG_ID, &t.value().to_string())
+        } else if let Some(t) = tag_downcast_ref::<ILUInt8Tag>(tag) {
+            self.simple("uint8", id, IL_UINT8_TAG_ID, &t.value().to_string())
+        } else if let Some(t) = tag_downcast_ref::<ILInt16Tag>(tag) {
+            self.simple("int16", id, IL_INT16_TAG_ID, &t.value().to_string())
+        } else if let Some(t) = tag_downcast_ref::<ILUInt16Tag>(tag) {
+            self.simple("uint16", id, IL_UINT16_TAG_ID, &t.value().to_string())
+        } else if let Some(t) = tag_downcast_ref::<ILInt32Tag>(tag) {
+            self.simple("int32", id, IL_INT32_TAG_ID, &t.value().to_string())
+        } else if let Some(t) = tag_downcast_ref::<ILUInt32Tag>(tag) {
+            self.simple("uint32", id, IL_UINT32_TAG_ID, &t.value().to_string())
+        } else if let Some(t) = tag_downcast_ref::<ILInt64Tag>(tag) {
+            self.simple("int64", id, IL_INT64_TAG_ID, &t.value().to_string())
+        } else if let Some(t) = tag_downcast_ref::<ILUInt64Tag>(tag) {
+            self.simple("uint64", id, IL_UINT64_TAG_ID, &t.value().to_string())
+        } else if let Some(t) = tag_downcast_ref::<ILILInt64Tag>(tag) {
+            self.simple("ilint", id, IL_ILINT_TAG_ID, &t.value().to_string())
+        } else if let Some(t) = tag_downcast_ref::<ILSignedILInt64Tag>(tag) {
+            self.simple(
+                "signed_ilint",
+                id,
+                IL_SIGNED_ILINT_TAG_ID,
+                &t.value().to_string(),
+            )
+        } else if let Some(t) = tag_downcast_ref::<ILBin32Tag>(tag) {
+            self.simple("bin32", id, IL_BIN32_TAG_ID, &float_to_json!(t.value()))
+        } else if let Some(t) = tag_downcast_ref::<ILBin64Tag>(tag) {
+            self.simple("bin64", id, IL_BIN64_TAG_ID, &float_to_json!(t.value()))
+        } else if let Some(t) = tag_downcast_ref::<ILBin128Tag>(tag) {
+            self.begin("bin128", id, IL_BIN128_TAG_ID)?;
+            self.string(&hex_encode(t.value()))?;
+            self.raw("}")
+        } else if let Some(t) = tag_downcast_ref::<ILByteArrayTag>(tag) {
+            self.begin("bytes", id, IL_BYTES_TAG_ID)?;
+            self.string(&hex_encode(t.value()))?;
+            self.raw("}")
+        } else if let Some(t) = tag_downcast_ref::<ILStringTag>(tag) {
+            self.begin("string", id, IL_STRING_TAG_ID)?;
+            self.string(t.value())?;
+            self.raw("}")
+        } else if let Some(t) = tag_downcast_ref::<ILBigIntTag>(tag) {
+            self.begin("bint", id, IL_BINT_TAG_ID)?;
+            self.string(&hex_encode(t.value()))?;
+            self.raw("}")
+        } else if let Some(t) = tag_downcast_ref::<ILBigDecTag>(tag) {
+            self.begin("bdec", id, IL_BDEC_TAG_ID)?;
+            self.raw("{")?;
+            self.name("scale")?;
+            self.raw(&t.scale().to_string())?;
+            self.separator()?;
+            self.name("value")?;
+            self.string(&hex_encode(t.value()))?;
+            self.raw("}}")
+        } else if let Some(t) = tag_downcast_ref::<ILILIntArrayTag>(tag) {
+            self.begin("ilint_array", id, IL_ILINTARRAY_TAG_ID)?;
+            self.numbers(t.value())?;
+            self.raw("}")
+        } else if let Some(t) = tag_downcast_ref::<ILRangeTag>(tag) {
+            self.begin("range", id, IL_RANGE_TAG_ID)?;
+            self.raw("{")?;
+            self.name("start")?;
+            self.raw(&t.start().to_string())?;
+            self.separator()?;
+            self.name("count")?;
+            self.raw(&t.count().to_string())?;
+            self.raw("}}")
+        } else if let Some(t) = tag_downcast_ref::<ILVersionTag>(tag) {
+            self.begin("version", id, IL_VERSION_TAG_ID)?;
+            self.numbers(t.value())?;
+            self.raw("}")
+        } else if let Some(t) = tag_downcast_ref::<ILOIDTag>(tag) {
+            self.begin("oid", id, IL_OID_TAG_ID)?;
+            self.numbers(t.value())?;
+            self.raw("}")
+        } else if let Some(t) = tag_downcast_ref::<ILStrDictTag>(tag) {
+            self.begin("str_dict", id, IL_STRING_DICTIONARY_TAG_ID)?;
+            self.str_dict(t)?;
+            self.raw("}")
+        } else if is_implicit_tag(id) {
+            Err(ErrorKind::UnsupportedTag)
+        } else {
+            // ILRawTag and any other explicit tag
+            let value = match tag_downcast_ref::<ILRawTag>(tag) {
+                Some(t) => hex_encode(t.value()),
+                None => {
+                    let mut value = Vec::with_capacity(tag.value_size() as usize);
+                    tag.serialize_value(&mut BorrowedVecWriter::new(&mut value))?;
+                    hex_encode(&value)
+                }
+            };
+            self.raw("{")?;
+            self.name("type")?;
+            self.string("raw")?;
+            self.separator()?;
+            self.name("id")?;
+            self.raw(&id.to_string())?;
+            self.separator()?;
+            self.name("value")?;
+            self.string(&value)?;
+            self.raw("}")
+        }
+    }
+
+    fn str_dict(&mut self, tag: &ILStrDictTag) -> Result<()> {
+        let mut entries: Vec<_> = tag
+            .value()
+            .iter()
+            .map(|(k, t)| (k.as_str(), t.as_ref()))
+            .collect();
+        entries.sort_unstable_by(|a, b| a.0.cmp(b.0));
+        self.raw("{")?;
+        for (i, (k, v)) in entries.iter().enumerate() {
+            if i > 0 {
+                self.separator()?;
+            }
+            self.name(k)?;
+            self.string(v)?;
+        }
+        self.raw("}")
+    }
+}
+
+/// The children of a container being written by [`JsonOutput::tag()`].
+enum JsonChildren<'t> {
+    List(std::slice::Iter<'t, Box<dyn ILTag>>),
+    Dict(std::vec::IntoIter<(&'t str, &'t dyn ILTag)>),
+}
+
+/// A container being written by [`JsonOutput::tag()`].
+struct JsonFrame<'t> {
+    children: JsonChildren<'t>,
+    /// Number of children returned so far.
+    count: usize,
+    /// The text that closes the container.
+    close: &'static str,
+}
+
+impl<'t> JsonFrame<'t> {
+    fn list(tags: &'t [Box<dyn ILTag>]) -> Self {
+        Self {
+            children: JsonChildren::List(tags.iter()),
+            count: 0,
+            close: "]}",
+        }
+    }
+
+    fn dict(tag: &'t ILDictTag) -> Self {
+        let mut entries: Vec<_> = tag
+            .value()
+            .iter()
+            .map(|(k, t)| (k.as_str(), t.as_ref()))
+            .collect();
+        entries.sort_unstable_by(|a, b| a.0.cmp(b.0));
+        Self {
+            children: JsonChildren::Dict(entries.into_iter()),
+            count: 0,
+            close: "}}",
+        }
+    }
+
+    /// Returns the next child and its key if the container is a dictionary.
+    fn next_child(&mut self) -> Option<(Option<&'t str>, &'t dyn ILTag)> {
+        let child = match &mut self.children {
+            JsonChildren::List(tags) => tags.next().map(|t| (None, t.as_ref())),
+            JsonChildren::Dict(entries) => entries.next().map(|(k, t)| (Some(k), t)),
+        };
+        if child.is_some() {
+            self.count += 1;
+        }
+        child
+    }
+}
+
+//=============================================================================
+// JsonArrayWriter
+//-----------------------------------------------------------------------------
+/// This struct writes an [`ILTagArrayTag`] as JSON one element at a time. It
+/// allows the export of arrays that are too large to be kept in memory. The
+/// output is exactly the same produced by [`write_json()`] for the
+/// equivalent [`ILTagArrayTag`].
+///
+/// Example:
+///
+/// ```
+/// use il2_iltags::io::array::VecWriter;
+/// use il2_iltags::tags::json::JsonArrayWriter;
+/// use il2_iltags::tags::standard::ILUInt8Tag;
+///
+/// let mut writer = VecWriter::new();
+/// let mut array = JsonArrayWriter::new(&mut writer, false).unwrap();
+/// for i in 0..2 {
+///     array.write(&ILUInt8Tag::with_value(i)).unwrap();
+/// }
+/// array.finish().unwrap();
+/// assert_eq!(
+///     writer.as_slice(),
+///     br#"{"type":"array","value":[{"type":"uint8","value":0},{"type":"uint8","value":1}]}"#
+/// );
+/// ```
+///
+/// New since 2.0.0.
+pub struct JsonArrayWriter<'a> {
+    output: JsonOutput<'a>,
+    count: usize,
+}
+
+impl<'a> JsonArrayWriter<'a> {
+    /// Creates a new instance of this struct for an array with the default
+    /// id. The beginning of the array is written immediately.
+    ///
+    /// Arguments:
+    /// - `writer`: The writer;
+    /// - `pretty`: If true, the document is indented like in [`to_json_pretty()`];
+    ///
+    /// Returns:
+    /// - `Ok(writer)`: The new instance;
+    /// - `Err(_)`: If the writer fails;
+    pub fn new(writer: &'a mut dyn Writer, pretty: bool) -> Result<Self> {
+        Self::with_id(writer, IL_ILTAGARRAY_TAG_ID, pretty)
+    }
+
+    /// Creates a new instance of this struct for an array with a custom id.
+    /// The beginning of the array is written immediately.
+    ///
+    /// Arguments:
+    /// - `writer`: The writer;
+    /// - `id`: The id of the array;
+    /// - `pretty`: If true, the document is indented like in [`to_json_pretty()`];
+    ///
+    /// Returns:
+    /// - `Ok(writer)`: The new instance;
+    /// - `Err(_)`: If the writer fails;
+    pub fn with_id(writer: &'a mut dyn Writer, id: u64, pretty: bool) -> Result<Self> {
+        let mut output = JsonOutput::new(writer, pretty);
+        output.begin("array", id, IL_ILTAGARRAY_TAG_ID)?;
+        output.raw("[")?;
+        output.level += 1;
+        Ok(Self { output, count: 0 })
+    }
+
+    /// Writes the next element of the array.
+    ///
+    /// Arguments:
+    /// - `tag`: The element;
+    ///
+    /// Returns:
+    /// - `Ok(())`: On success;
+    /// - `Err(_)`: See [`to_json()`] or if the writer fails;
+    pub fn write(&mut self, tag: &dyn ILTag) -> Result<()> {
+        if self.count > 0 {
+            self.output.raw(",")?;
+        }
+        self.output.new_line()?;
+        self.output.tag(tag)?;
+        self.count += 1;
+        Ok(())
+    }
+
+    /// Returns the number of elements written so far.
+    pub fn count(&self) -> usize {
+        self.count
+    }
+
+    /// Writes the end of the array.
+    ///
+    /// Returns:
+    /// - `Ok(())`: On success;
+    /// - `Err(_)`: If the writer fails;
+    pub fn finish(mut self) -> Result<()> {
+        self.output.level -= 1;
+        if self.count > 0 {
+            self.output.new_line()?;
+        }
+        self.output.raw("]}")
+    }
+}
+
+//=============================================================================
+// Input
+//-----------------------------------------------------------------------------
+/// Converts a JSON document produced by [`to_json()`] or [`to_json_pretty()`]
+/// back into tags. The conversion is strict: unknown or duplicated members,
+/// values out of the range of the tag type and custom ids on types that do
+/// not support them are reported as errors. Documents nested deeper than 256
+/// arrays and objects are rejected.
+///
+/// Arguments:
+/// - `json`: The JSON document;
+///
+/// Returns:
+/// - `Ok(tag)`: The tag;
+/// - `Err(ErrorKind::Boxed(_))`: If the document is invalid. The boxed error
+///   is a [`JsonError`] with the location of the problem;
+pub fn from_json(json: &str) -> Result<Box<dyn ILTag>> {
+    let root = parser::parse(json)?;
+    Ok(tag_from_node(&root)?)
+}
+
+fn tag_from_node(node: &JsonNode) -> JsonResult<Box<dyn ILTag>> {
+    let m = members(node, &["type", "id", "value"])?;
+    let type_name = string_from_node(required(node, m[0], "type")?)?;
+    let id = match m[1] {
+        Some(n) => Some(number_from_node::<u64>(n)?),
+        None => None,
+    };
+    let value = required(node, m[2], "value")?;
+    let id_or = |default_id: u64| id.unwrap_or(default_id);
+    let tag: Box<dyn ILTag> = match type_name {
+        "null" => match value.value {
+            JsonValue::Null => Box::new(ILNullTag::with_id(id_or(IL_NULL_TAG_ID))),
+            _ => return Err(unexpected(value, "null")),
+        },
+        "bool" => match value.value {
+            JsonValue::Bool(v) => Box::new(ILBoolTag::with_id_value(id_or(IL_BOOL_TAG_ID), v)),
+            _ => return Err(unexpected(value, "boolean")),
+        },
+        "int8" => Box::new(ILInt8Tag::with_id_value(
+            id_or(IL_INT8_TAG_ID),
+            number_from_node(value)?,
+        )),
+        "uint8" => Box::new(ILUInt8Tag::with_id_value(
+            id_or(IL_UINT8_TAG_ID),
+            number_from_node(value)?,
+        )),
+        "int16" => Box::new(ILInt16Tag::with_id_value(
+            id_or(IL_INT16_TAG_ID),
+            number_from_node(value)?,
+        )),
+        "uint16" => Box::new(ILUInt16Tag::with_id_value(
+            id_or(IL_UINT16_TAG_ID),
+            number_from_node(value)?,
+        )),
+        "int32" => Box::new(ILInt32Tag::with_id_value(
+            id_or(IL_INT32_TAG_ID),
+            number_from_node(value)?,
+        )),
+        "uint32" => Box::new(ILUInt32Tag::with_id_value(
+            id_or(IL_UINT32_TAG_ID),
+            number_from_node(value)?,
+        )),
+        "int64" => Box::new(ILInt64Tag::with_id_value(
+            id_or(IL_INT64_TAG_ID),
+            number_from_node(value)?,
+        )),
+        "uint64" => Box::new(ILUInt64Tag::with_id_value(
+            id_or(IL_UINT64_TAG_ID),
+            number_from_node(value)?,
+        )),
+        "ilint" => Box::new(ILILInt64Tag::with_id_value(
+            id_or(IL_ILINT_TAG_ID),
+            number_from_node(value)?,
+        )),
+        "signed_ilint" => Box::new(ILSignedILInt64Tag::with_id_value(
+            id_or(IL_SIGNED_ILINT_TAG_ID),
+            number_from_node(value)?,
+        )),
+        "bin32" => Box::new(ILBin32Tag::with_id_value(
+            id_or(IL_BIN32_TAG_ID),
+            float_from_node(value)?,
+        )),
+        "bin64" => Box::new(ILBin64Tag::with_id_value(
+            id_or(IL_BIN64_TAG_ID),
+            float_from_node(value)?,
+        )),
+        "bin128" => {
+            let bytes = hex_from_node(value)?;
+            if bytes.len() != 16 {
+                return Err(JsonError::new(value.offset, "16 bytes expected"));
+            }
+            let mut v = [0u8; 16];
+            v.copy_from_slice(&bytes);
+            Box::new(ILBin128Tag::with_id_value(id_or(IL_BIN128_TAG_ID), &v))
+        }
+        "bytes" => {
+            check_default_id(node, id, IL_BYTES_TAG_ID)?;
+            Box::new(ILByteArrayTag::with_value(&hex_from_node(value)?))
+        }
+        "string" => Box::new(ILStringTag::with_id_value(
+            id_or(IL_STRING_TAG_ID),
+            string_from_node(value)?,
+        )),
+        "bint" => {
+            check_default_id(node, id, IL_BINT_TAG_ID)?;
+            Box::new(ILBigIntTag::with_value(&hex_from_node(value)?))
+        }
+        "bdec" => {
+            let v = members(value, &["scale", "value"])?;
+            Box::new(ILBigDecTag::with_id_value(
+                id_or(IL_BDEC_TAG_ID),
+                number_from_node(required(value, v[0], "scale")?)?,
+                &hex_from_node(required(value, v[1], "value")?)?,
+            ))
+        }
+        "ilint_array" => Box::new(ILILIntArrayTag::with_id_value(
+            id_or(IL_ILINTARRAY_TAG_ID),
+            &numbers_from_node(value)?,
+        )),
+        "array" => {
+            let mut tag = ILTagArrayTag::with_id(id_or(IL_ILTAGARRAY_TAG_ID));
+            for e in array_from_node(value)? {
+                tag.mut_value().push(tag_from_node(e)?);
+            }
+            Box::new(tag)
+        }
+        "seq" => {
+            let mut tag = ILTagSeqTag::with_id(id_or(IL_ILTAGSEQ_TAG_ID));
+            for e in array_from_node(value)? {
+                tag.mut_value().push(tag_from_node(e)?);
+            }
+            Box::new(tag)
+        }
+        "range" => {
+            let v = members(value, &["start", "count"])?;
+            Box::new(ILRangeTag::with_id_value(
+                id_or(IL_RANGE_TAG_ID),
+                number_from_node(required(value, v[0], "start")?)?,
+                number_from_node(required(value, v[1], "count")?)?,
+            ))
+        }
+        "version" => {
+            let v: Vec<i32> = numbers_from_node(value)?;
+            if v.len() != 4 {
+                return Err(JsonError::new(value.offset, "4 numbers expected"));
+            }
+            Box::new(ILVersionTag::with_id_value_from_slice(
+                id_or(IL_VERSION_TAG_ID),
+                &[v[0], v[1], v[2], v[3]],
+            ))
+        }
+        "oid" => Box::new(ILOIDTag::with_id_value(
+            id_or(IL_OID_TAG_ID),
+            &numbers_from_node(value)?,
+        )),
+        "dict" => {
+            let mut tag = ILDictTag::with_id(id_or(IL_DICTIONARY_TAG_ID));
+            for (k, v) in object_from_node(value)? {
+                if tag.insert(k, tag_from_node(v)?).is_some() {
+                    return Err(JsonError::new(v.offset, "duplicated key"));
+                }
+            }
+            Box::new(tag)
+        }
+        "str_dict" => {
+            let mut tag = ILStrDictTag::with_id(id_or(IL_STRING_DICTIONARY_TAG_ID));
+            for (k, v) in object_from_node(value)? {
+                if tag.insert(k, string_from_node(v)?).is_some() {
+                    return Err(JsonError::new(v.offset, "duplicated key"));
+                }
+            }
+            Box::new(tag)
+        }
+        "raw" => {
+            let id = match id {
+                Some(id) => id,
+                None => return Err(JsonError::new(node.offset, "member \"id\" expected")),
+            };
+            if is_implicit_tag(id) {
+                return Err(JsonError::new(
+                    node.offset,
+                    "raw tags cannot have implicit ids",
+                ));
+            }
+            Box::new(ILRawTag::with_value(id, &hex_from_node(value)?))
+        }
+        _ => return Err(JsonError::new(node.offset, "unknown tag type")),
+    };
+    Ok(tag)
+}
+
+/// Extracts the members of an object. It fails if the object contains
+/// members that are not listed in `names` or if a member is repeated.
+///
+/// Returns:
+/// - `Ok(members)`: The value of each member listed in `names`, in the same
+///   order;
+/// - `Err(_)`: If the node is not a valid object;
+fn members<'a>(node: &'a JsonNode, names: &[&str]) -> JsonResult<Vec<Option<&'a JsonNode>>> {
+    let mut ret = vec![None; names.len()];
+    for (name, value) in object_from_node(node)? {
+        match names.iter().position(|n| *n == name) {
+            Some(i) if ret[i].is_none() => ret[i] = Some(value),
+            Some(_) => return Err(JsonError::new(value.offset, "duplicated member")),
+            None => return Err(JsonError::new(value.offset, "unknown member")),
+        }
+    }
+    Ok(ret)
+}
+
+fn required<'a>(
+    node: &JsonNode,
+    member: Option<&'a JsonNode>,
+    name: &str,
+) -> JsonResult<&'a JsonNode> {
+    match member {
+        Some(m) => Ok(m),
+        None => Err(JsonError::new(
+            node.offset,
+            &format!("member \"{}\" expected", name),
+        )),
+    }
+}
+
+fn check_default_id(node: &JsonNode, id: Option<u64>, default_id: u64) -> JsonResult<()> {
+    match id {
+        Some(id) if id != default_id => Err(JsonError::new(
+            node.offset,
+            "this tag type does not support custom ids",
+        )),
+        _ => Ok(()),
+    }
+}
+
+fn unexpected(node: &JsonNode, expected: &str) -> JsonError {
+    JsonError::new(
+        node.offset,
+        &format!("{} expected but {} found", expected, node.type_name()),
+    )
+}
+
+fn object_from_node(node: &JsonNode) -> JsonResult<impl Iterator<Item = (&str, &JsonNode)>> {
+    match &node.value {
+        JsonValue::Object(m) => Ok(m.iter().map(|(k, v)| (k.as_str(), v))),
+        _ => Err(unexpected(node, "object")),
+    }
+}
+
+fn array_from_node(node: &JsonNode) -> JsonResult<&[JsonNode]> {
+    match &node.value {
+        JsonValue::Array(v) => Ok(v),
+        _ => Err(unexpected(node, "array")),
+    }
+}
+
+fn string_from_node(node: &JsonNode) -> JsonResult<&str> {
+    match &node.value {
+        JsonValue::String(s) => Ok(s),
+        _ => Err(unexpected(node, "string")),
+    }
+}
+
+fn hex_from_node(node: &JsonNode) -> JsonResult<Vec<u8>> {
+    match hex_decode(string_from_node(node)?) {
+        Some(v) => Ok(v),
+        None => Err(JsonError::new(node.offset, "invalid hexadecimal string")),
+    }
+}
+
+/// Parses an integer. Numbers with fractions or exponents and numbers out of
+/// the range of `T` are rejected.
+fn number_from_node<T: FromStr>(node: &JsonNode) -> JsonResult<T> {
+    match &node.value {
+        JsonValue::Number(n) => match n.parse() {
+            Ok(v) => Ok(v),
+            Err(_) => Err(JsonError::new(node.offset, "invalid integer")),
+        },
+        _ => Err(unexpected(node, "number")),
+    }
+}
+
+fn numbers_from_node<T: FromStr>(node: &JsonNode) -> JsonResult<Vec<T>> {
+    array_from_node(node)?
+        .iter()
+        .map(number_from_node)
+        .collect()
+}
+
+fn float_from_node<T: FromStr>(node: &JsonNode) -> JsonResult<T> {
+    let s = match &node.value {
+        JsonValue::Number(n) => n.as_str(),
+        JsonValue::String(s) if s == "NaN" || s == "Infinity" || s == "-Infinity" => s.as_str(),
+        _ => return Err(unexpected(node, "number")),
+    };
+    match s.parse() {
+        Ok(v) => Ok(v),
+        Err(_) => Err(JsonError::new(node.offset, "invalid number")),
+    }
+}
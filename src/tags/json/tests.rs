@@ -0,0 +1,463 @@
+/*
+ * BSD 3-Clause License
+ *
+ * Copyright (c) 2020, InterlockLedger Network
+ * All rights reserved.
+ *
+ * Redistribution and use in source and binary forms, with or without
+ * modification, are permitted provided that the following conditions are met:
+ *
+ * * Redistributions of source code must retain the above copyright notice, this
+ *   list of conditions and the following disclaimer.
+ *
+ * * Redistributions in binary form must reproduce the above copyright notice,
+ *   this list of conditions and the following disclaimer in the documentation
+ *   and/or other materials provided with the distribution.
+ *
+ * * Neither the name of the copyright holder nor the names of its
+ *   contributors may be used to endorse or promote products derived from
+ *   this software without specific prior written permission.
+ *
+ * THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS"
+ * AND ANY EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE
+ * IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+ * DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE
+ * FOR ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL, EXEMPLARY, OR CONSEQUENTIAL
+ * DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+ * SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER
+ * CAUSED AND ON ANY THEORY OF LIABILITY, WHETHER IN CONTRACT, STRICT LIABILITY,
+ * OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE
+ * OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+ */
+use super::*;
+use crate::io::array::VecWriter;
+use crate::tags::util::iltag_are_equal;
+
+fn create_sample() -> ILTagArrayTag {
+    let mut dict = ILDictTag::new();
+    dict.insert("null", Box::new(ILNullTag::new()));
+    dict.insert("bool", Box::new(ILBoolTag::with_value(true)));
+    dict.insert("int8", Box::new(ILInt8Tag::with_value(-128)));
+    dict.insert("uint8", Box::new(ILUInt8Tag::with_value(255)));
+    dict.insert("int16", Box::new(ILInt16Tag::with_value(-2)));
+    dict.insert("uint16", Box::new(ILUInt16Tag::with_value(2)));
+    dict.insert("int32", Box::new(ILInt32Tag::with_value(i32::MIN)));
+    dict.insert("uint32", Box::new(ILUInt32Tag::with_value(u32::MAX)));
+    dict.insert("int64", Box::new(ILInt64Tag::with_value(i64::MIN)));
+    dict.insert("uint64", Box::new(ILUInt64Tag::with_value(u64::MAX)));
+    dict.insert("ilint", Box::new(ILILInt64Tag::with_value(u64::MAX)));
+    dict.insert("signed_ilint", Box::new(ILSignedILInt64Tag::with_value(-5)));
+    dict.insert("bin32", Box::new(ILBin32Tag::with_value(0.1)));
+    dict.insert("bin64", Box::new(ILBin64Tag::with_value(-1e300)));
+    dict.insert("bin128", Box::new(ILBin128Tag::with_value(&[0xAB; 16])));
+    dict.insert("bytes", Box::new(ILByteArrayTag::with_value(&[1, 2, 0xFF])));
+    dict.insert(
+        "string",
+        Box::new(ILStringTag::with_value("\"a\\b\"\n\u{1}é")),
+    );
+    dict.insert("bint", Box::new(ILBigIntTag::with_value(&[0x80])));
+    dict.insert("bdec", Box::new(ILBigDecTag::with_value(-2, &[0x7F])));
+    dict.insert(
+        "ilint_array",
+        Box::new(ILILIntArrayTag::with_value(&[0, u64::MAX])),
+    );
+    dict.insert("range", Box::new(ILRangeTag::with_value(1234, 10)));
+    dict.insert("version", Box::new(ILVersionTag::with_value(1, 2, -3, 4)));
+    dict.insert("oid", Box::new(ILOIDTag::with_value(&[1, 2, 3])));
+    let mut str_dict = ILStrDictTag::new();
+    str_dict.insert("b", "2");
+    str_dict.insert("a", "1");
+    dict.insert("str_dict", Box::new(str_dict));
+    let mut seq = ILTagSeqTag::new();
+    seq.mut_value()
+        .push(Box::new(ILRawTag::with_value(1234, &[1, 2])));
+    dict.insert("seq", Box::new(seq));
+    dict.insert("empty", Box::new(ILTagArrayTag::new()));
+
+    let mut tag = ILTagArrayTag::new();
+    tag.mut_value().push(Box::new(dict));
+    tag.mut_value()
+        .push(Box::new(ILUInt16Tag::with_id_value(16, 7)));
+    tag.mut_value()
+        .push(Box::new(ILInt64Tag::with_id_value(17, 7)));
+    tag.mut_value()
+        .push(Box::new(ILStringTag::with_id_value(1234, "x")));
+    tag.mut_value().push(Box::new(ILTagArrayTag::with_id(1235)));
+    tag
+}
+
+#[test]
+fn test_to_json() {
+    let mut tag = ILTagArrayTag::new();
+    assert_eq!(to_json(&tag).unwrap(), r#"{"type":"array","value":[]}"#);
+    assert_eq!(
+        to_json_pretty(&tag).unwrap(),
+        r#"{"type": "array", "value": []}"#
+    );
+
+    let mut dict = ILDictTag::with_id(1234);
+    dict.insert("b", Box::new(ILUInt8Tag::with_value(2)));
+    dict.insert("a", Box::new(ILBin32Tag::with_value(f32::NAN)));
+    tag.mut_value().push(Box::new(dict));
+    tag.mut_value()
+        .push(Box::new(ILBigDecTag::with_value(2, &[1])));
+    tag.mut_value()
+        .push(Box::new(ILRawTag::with_value(100, &[0xAB])));
+    assert_eq!(
+        to_json(&tag).unwrap(),
+        concat!(
+            r#"{"type":"array","value":["#,
+            r#"{"type":"dict","id":1234,"value":{"a":{"type":"bin32","value":"NaN"},"b":{"type":"uint8","value":2}}},"#,
+            r#"{"type":"bdec","value":{"scale":2,"value":"01"}},"#,
+            r#"{"type":"raw","id":100,"value":"ab"}]}"#
+        )
+    );
+    assert_eq!(
+        to_json_pretty(&tag).unwrap(),
+        r#"{"type": "array", "value": [
+  {"type": "dict", "id": 1234, "value": {
+    "a": {"type": "bin32", "value": "NaN"},
+    "b": {"type": "uint8", "value": 2}
+  }},
+  {"type": "bdec", "value": {"scale": 2, "value": "01"}},
+  {"type": "raw", "id": 100, "value": "ab"}
+]}"#
+    );
+
+    // Explicit tags implemented by other structs are mapped as raw
+    let tag = ILValueTag::new();
+    assert_eq!(
+        to_json(&tag).unwrap(),
+        r#"{"type":"raw","id":17,"value":"03616263"}"#
+    );
+
+    // Unknown implicit tags cannot be mapped
+    assert!(matches!(
+        to_json(&ILValueTag { id: 1 }),
+        Err(ErrorKind::UnsupportedTag)
+    ));
+}
+
+/// An explicit tag that is not implemented by a standard struct.
+struct ILValueTag {
+    id: u64,
+}
+
+impl ILValueTag {
+    fn new() -> Self {
+        Self {
+            id: IL_STRING_TAG_ID,
+        }
+    }
+}
+
+impl ILTag for ILValueTag {
+    fn id(&self) -> u64 {
+        self.id
+    }
+
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+
+    fn as_mut_any(&mut self) -> &mut dyn std::any::Any {
+        self
+    }
+
+    fn value_size(&self) -> u64 {
+        4
+    }
+
+    fn serialize_value(&self, writer: &mut dyn Writer) -> Result<()> {
+        writer.write_all(&[3, b'a', b'b', b'c'])?;
+        Ok(())
+    }
+
+    fn deserialize_value(
+        &mut self,
+        _factory: &dyn crate::tags::ILTagFactory,
+        _value_size: usize,
+        _reader: &mut dyn crate::io::Reader,
+    ) -> Result<()> {
+        Err(ErrorKind::UnsupportedTag)
+    }
+}
+
+#[test]
+fn test_from_json_round_trip() {
+    let tag = create_sample();
+    for json in [to_json(&tag).unwrap(), to_json_pretty(&tag).unwrap()] {
+        let parsed = from_json(&json).unwrap();
+        assert!(iltag_are_equal(&tag, parsed.as_ref()));
+        // The concrete types are preserved
+        let parsed = tag_downcast_ref::<ILTagArrayTag>(parsed.as_ref()).unwrap();
+        assert!(tag_downcast_ref::<ILUInt16Tag>(parsed.value()[1].as_ref()).is_some());
+        assert!(tag_downcast_ref::<ILInt64Tag>(parsed.value()[2].as_ref()).is_some());
+        let dict = tag_downcast_ref::<ILDictTag>(parsed.value()[0].as_ref()).unwrap();
+        let seq = tag_downcast_ref::<ILTagSeqTag>(dict.get("seq").unwrap()).unwrap();
+        assert!(tag_downcast_ref::<ILRawTag>(seq.value()[0].as_ref()).is_some());
+        assert_eq!(to_json(parsed).unwrap(), to_json(&tag).unwrap());
+    }
+
+    for v in [f64::INFINITY, f64::NEG_INFINITY, f64::MIN_POSITIVE, -0.0] {
+        let tag = ILBin64Tag::with_value(v);
+        let parsed = from_json(&to_json(&tag).unwrap()).unwrap();
+        assert!(iltag_are_equal(&tag, parsed.as_ref()));
+    }
+    let parsed = from_json(r#"{"type":"bin32","value":"NaN"}"#).unwrap();
+    assert!(tag_downcast_ref::<ILBin32Tag>(parsed.as_ref())
+        .unwrap()
+        .value()
+        .is_nan());
+}
+
+#[test]
+fn test_from_json_escapes() {
+    let parsed =
+        from_json(r#" { "value" : "\"\\\/\b\f\n\r\t\u00e9\ud83d\ude00" , "type" : "string" } "#)
+            .unwrap();
+    assert_eq!(
+        tag_downcast_ref::<ILStringTag>(parsed.as_ref())
+            .unwrap()
+            .value(),
+        "\"\\/\u{8}\u{c}\n\r\t\u{e9}\u{1F600}"
+    );
+}
+
+fn assert_json_error(json: &str, offset: usize, message: &str) {
+    match from_json(json) {
+        Err(ErrorKind::Boxed(e)) => {
+            let e = e.downcast_ref::<JsonError>().unwrap();
+            assert_eq!(e.offset(), offset, "{}", json);
+            assert_eq!(e.message(), message, "{}", json);
+        }
+        _ => panic!("Error expected for {}", json),
+    }
+}
+
+#[test]
+fn test_from_json_errors() {
+    // Syntax
+    assert_json_error("", 0, "unexpected end of the JSON document");
+    assert_json_error("{} {}", 3, "trailing characters after the JSON value");
+    assert_json_error("[1,]", 3, "invalid value");
+    assert_json_error("[1 2]", 3, "',' or ']' expected");
+    assert_json_error("{\"a\" 1}", 5, "':' expected");
+    assert_json_error("{1:1}", 1, "member name expected");
+    assert_json_error("\"abc", 4, "unterminated string");
+    assert_json_error("\"\\x\"", 2, "invalid escape sequence");
+    assert_json_error("\"\\ud83d\"", 7, "invalid surrogate pair");
+    assert_json_error("01", 1, "trailing characters after the JSON value");
+    assert_json_error("1.", 2, "invalid number");
+    assert_json_error("tru", 0, "invalid value");
+    let deep = "[".repeat(parser::MAX_JSON_DEPTH + 1);
+    assert_json_error(
+        &deep,
+        parser::MAX_JSON_DEPTH,
+        "maximum nesting level exceeded",
+    );
+
+    // Mapping
+    assert_json_error("[]", 0, "object expected but array found");
+    assert_json_error(r#"{"value":1}"#, 0, "member \"type\" expected");
+    assert_json_error(r#"{"type":"int8"}"#, 0, "member \"value\" expected");
+    assert_json_error(r#"{"type":"x","value":1}"#, 0, "unknown tag type");
+    assert_json_error(r#"{"type":"int8","value":1,"x":1}"#, 29, "unknown member");
+    assert_json_error(
+        r#"{"type":"int8","value":1,"value":1}"#,
+        33,
+        "duplicated member",
+    );
+    assert_json_error(r#"{"type":"int8","value":128}"#, 23, "invalid integer");
+    assert_json_error(r#"{"type":"int8","value":1.0}"#, 23, "invalid integer");
+    assert_json_error(
+        r#"{"type":"int8","value":"1"}"#,
+        23,
+        "number expected but string found",
+    );
+    assert_json_error(
+        r#"{"type":"bin32","value":"nan"}"#,
+        24,
+        "number expected but string found",
+    );
+    assert_json_error(r#"{"type":"bin128","value":"00"}"#, 25, "16 bytes expected");
+    assert_json_error(
+        r#"{"type":"bytes","value":"0"}"#,
+        24,
+        "invalid hexadecimal string",
+    );
+    assert_json_error(
+        r#"{"type":"bytes","id":1234,"value":""}"#,
+        0,
+        "this tag type does not support custom ids",
+    );
+    assert_json_error(
+        r#"{"type":"version","value":[1,2,3]}"#,
+        26,
+        "4 numbers expected",
+    );
+    assert_json_error(
+        r#"{"type":"range","value":{"start":1}}"#,
+        24,
+        "member \"count\" expected",
+    );
+    assert_json_error(r#"{"type":"raw","value":""}"#, 0, "member \"id\" expected");
+    assert_json_error(
+        r#"{"type":"raw","id":15,"value":""}"#,
+        0,
+        "raw tags cannot have implicit ids",
+    );
+    assert_json_error(
+        r#"{"type":"str_dict","value":{"a":"1","a":"2"}}"#,
+        40,
+        "duplicated key",
+    );
+    assert_json_error(
+        r#"{"type":"array","value":[{"type":"null","value":0}]}"#,
+        48,
+        "null expected but number found",
+    );
+}
+
+#[test]
+fn test_write_json() {
+    let tag = create_sample();
+    let mut writer = VecWriter::new();
+    write_json(&tag, true, &mut writer).unwrap();
+    assert_eq!(writer.as_slice(), to_json_pretty(&tag).unwrap().as_bytes());
+
+    let mut buff = [0u8; 8];
+    let mut writer = crate::io::array::ByteArrayWriter::new(&mut buff);
+    assert!(matches!(
+        write_json(&tag, false, &mut writer),
+        Err(ErrorKind::IOError(_))
+    ));
+}
+
+#[test]
+fn test_json_array_writer() {
+    let tag = create_sample();
+    for pretty in [false, true] {
+        let mut writer = VecWriter::new();
+        let mut array = JsonArrayWriter::new(&mut writer, pretty).unwrap();
+        for t in tag.value() {
+            array.write(t.as_ref()).unwrap();
+        }
+        assert_eq!(array.count(), tag.value().len());
+        array.finish().unwrap();
+        let expected = if pretty {
+            to_json_pretty(&tag).unwrap()
+        } else {
+            to_json(&tag).unwrap()
+        };
+        assert_eq!(writer.as_slice(), expected.as_bytes());
+    }
+
+    let tag = ILTagArrayTag::with_id(1234);
+    for pretty in [false, true] {
+        let mut writer = VecWriter::new();
+        JsonArrayWriter::with_id(&mut writer, 1234, pretty)
+            .unwrap()
+            .finish()
+            .unwrap();
+        let expected = if pretty {
+            to_json_pretty(&tag).unwrap()
+        } else {
+            to_json(&tag).unwrap()
+        };
+        assert_eq!(writer.as_slice(), expected.as_bytes());
+    }
+}
+
+#[test]
+fn test_json_deep_tree() {
+    // Each tag uses 2 nesting levels
+    let levels = parser::MAX_JSON_DEPTH / 2;
+    let mut tag = ILTagArrayTag::new();
+    for _ in 1..levels {
+        let mut parent = ILTagArrayTag::new();
+        parent.mut_value().push(Box::new(tag));
+        tag = parent;
+    }
+    let json = to_json(&tag).unwrap();
+    let parsed = from_json(&json).unwrap();
+    assert!(iltag_are_equal(&tag, parsed.as_ref()));
+
+    let json = format!(r#"{{"type":"array","value":[{}]}}"#, json);
+    let offset = json
+        .match_indices(['[', '{'])
+        .nth(parser::MAX_JSON_DEPTH)
+        .unwrap()
+        .0;
+    assert_json_error(&json, offset, "maximum nesting level exceeded");
+}
+
+const DEEP_LEVELS: usize = 200000;
+
+/// Stack size used to run the tests with very deep trees. It is small enough
+/// to make any recursive implementation fail.
+const SMALL_STACK_SIZE: usize = 128 * 1024;
+
+fn run_with_small_stack<F: FnOnce() + Send + 'static>(f: F) {
+    std::thread::Builder::new()
+        .stack_size(SMALL_STACK_SIZE)
+        .spawn(f)
+        .unwrap()
+        .join()
+        .unwrap();
+}
+
+/// Creates a tree that alternates between sequences, arrays and
+/// dictionaries.
+fn create_very_deep_tag(levels: usize) -> Box<dyn ILTag> {
+    let mut tag: Box<dyn ILTag> = Box::new(ILNullTag::new());
+    for i in 0..levels {
+        tag = match i % 3 {
+            0 => {
+                let mut parent = ILTagSeqTag::new();
+                parent.mut_value().push(tag);
+                Box::new(parent)
+            }
+            1 => {
+                let mut parent = ILTagArrayTag::new();
+                parent.mut_value().push(tag);
+                Box::new(parent)
+            }
+            _ => {
+                let mut parent = ILDictTag::new();
+                parent.insert("a", tag);
+                Box::new(parent)
+            }
+        };
+    }
+    tag
+}
+
+#[test]
+fn test_to_json_very_deep_tree() {
+    run_with_small_stack(|| {
+        let tag = create_very_deep_tag(DEEP_LEVELS);
+        let json = to_json(tag.as_ref()).unwrap();
+        assert!(json.starts_with(r#"{"type":"array","value":[{"type":"seq","value":[{"#));
+        assert!(json.contains(r#"{"type":"null","value":null}"#));
+        assert!(json.ends_with(r#"}}]}]}"#));
+        assert_eq!(json.matches(r#""type":"#).count(), DEEP_LEVELS + 1);
+        let lists = (0..DEEP_LEVELS).filter(|i| i % 3 != 2).count();
+        assert_eq!(json.matches("]}").count(), lists);
+    });
+}
+
+#[test]
+fn test_to_json_pretty_very_deep_tree() {
+    // The indentation makes the output grow with the square of the depth
+    const LEVELS: usize = 5000;
+
+    run_with_small_stack(|| {
+        let tag = create_very_deep_tag(LEVELS);
+        let json = to_json(tag.as_ref()).unwrap();
+        let pretty = to_json_pretty(tag.as_ref()).unwrap();
+        assert!(pretty.lines().count() > LEVELS);
+        let compact: String = pretty.lines().map(|l| l.trim_start()).collect();
+        assert_eq!(compact.replace(": ", ":").replace(", ", ","), json);
+    });
+}
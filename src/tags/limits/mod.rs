@@ -0,0 +1,229 @@
+/*
+ * BSD 3-Clause License
+ *
+ * Copyright (c) 2020, InterlockLedger Network
+ * All rights reserved.
+ *
+ * Redistribution and use in source and binary forms, with or without
+ * modification, are permitted provided that the following conditions are met:
+ *
+ * * Redistributions of source code must retain the above copyright notice, this
+ *   list of conditions and the following disclaimer.
+ *
+ * * Redistributions in binary form must reproduce the above copyright notice,
+ *   this list of conditions and the following disclaimer in the documentation
+ *   and/or other materials provided with the distribution.
+ *
+ * * Neither the name of the copyright holder nor the names of its
+ *   contributors may be used to endorse or promote products derived from
+ *   this software without specific prior written permission.
+ *
+ * THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS"
+ * AND ANY EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE
+ * IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+ * DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE
+ * FOR ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL, EXEMPLARY, OR CONSEQUENTIAL
+ * DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+ * SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER
+ * CAUSED AND ON ANY THEORY OF LIABILITY, WHETHER IN CONTRACT, STRICT LIABILITY,
+ * OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE
+ * OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+ */
+//! This module defines the deserialization limits that can be attached to a
+//! [`crate::tags::ILTagFactory`] in order to protect the application against
+//! hostile or corrupted payloads.
+//!
+//! The limits are queried by the tag implementations during the
+//! deserialization by calling [`crate::tags::ILTagFactory::limits()`] on the
+//! factory they receive as argument.
+//!
+//! New since 2.0.0.
+#[cfg(test)]
+mod tests;
+
+use super::{ErrorKind, Result, MAX_TAG_SIZE};
+
+//=============================================================================
+// DeserializationLimits
+//-----------------------------------------------------------------------------
+/// This struct defines the limits that must be enforced during the
+/// deserialization of tags.
+///
+/// The default instance preserves the behavior of the previous versions of
+/// this library, in other words, it only limits the size of the tags to
+/// [`MAX_TAG_SIZE`].
+///
+/// New since 2.0.0.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct DeserializationLimits {
+    max_depth: usize,
+    max_total_size: u64,
+    max_elements: u64,
+    max_value_length: u64,
+    max_tag_size: u64,
+}
+
+impl DeserializationLimits {
+    /// Creates a new instance of this struct with the default limits.
+    pub const fn new() -> Self {
+        Self {
+            max_depth: usize::MAX,
+            max_total_size: MAX_TAG_SIZE,
+            max_elements: u64::MAX,
+            max_value_length: u64::MAX,
+            max_tag_size: MAX_TAG_SIZE,
+        }
+    }
+
+    /// Returns the maximum nesting depth. The top level tag is at depth 0,
+    /// its children are at depth 1 and so on.
+    pub fn max_depth(&self) -> usize {
+        self.max_depth
+    }
+
+    /// Sets the maximum nesting depth.
+    ///
+    /// Note that [`crate::tags::validation::validate()`] supports only up to
+    /// [`crate::tags::validation::MAX_VALIDATION_DEPTH`] levels. Use
+    /// [`crate::tags::validation::validate_with_stack()`] to validate data
+    /// that is deeper than that.
+    ///
+    /// Arguments:
+    /// - `max_depth`: The new value;
+    pub fn set_max_depth(&mut self, max_depth: usize) {
+        self.max_depth = max_depth;
+    }
+
+    /// Returns the maximum number of bytes, including the header, of the top
+    /// level tag. It limits the total amount of data consumed by a single call
+    /// to [`crate::tags::ILTagFactory::deserialize()`].
+    pub fn max_total_size(&self) -> u64 {
+        self.max_total_size
+    }
+
+    /// Sets the maximum number of bytes of the top level tag.
+    ///
+    /// Arguments:
+    /// - `max_total_size`: The new value;
+    pub fn set_max_total_size(&mut self, max_total_size: u64) {
+        self.max_total_size = max_total_size;
+    }
+
+    /// Returns the maximum number of elements of a single container, such as
+    /// entries of arrays, sequences and dictionaries.
+    pub fn max_elements(&self) -> u64 {
+        self.max_elements
+    }
+
+    /// Sets the maximum number of elements of a single container.
+    ///
+    /// Arguments:
+    /// - `max_elements`: The new value;
+    pub fn set_max_elements(&mut self, max_elements: u64) {
+        self.max_elements = max_elements;
+    }
+
+    /// Returns the maximum length in bytes of a string or byte array value.
+    pub fn max_value_length(&self) -> u64 {
+        self.max_value_length
+    }
+
+    /// Sets the maximum length in bytes of a string or byte array value.
+    ///
+    /// Arguments:
+    /// - `max_value_length`: The new value;
+    pub fn set_max_value_length(&mut self, max_value_length: u64) {
+        self.max_value_length = max_value_length;
+    }
+
+    /// Returns the maximum value size of each individual tag. It can never
+    /// be larger than [`MAX_TAG_SIZE`].
+    pub fn max_tag_size(&self) -> u64 {
+        self.max_tag_size
+    }
+
+    /// Sets the maximum value size of each individual tag. Values larger
+    /// than [`MAX_TAG_SIZE`] are capped to [`MAX_TAG_SIZE`].
+    ///
+    /// Arguments:
+    /// - `max_tag_size`: The new value;
+    pub fn set_max_tag_size(&mut self, max_tag_size: u64) {
+        self.max_tag_size = std::cmp::min(max_tag_size, MAX_TAG_SIZE);
+    }
+
+    /// Verifies if the given depth is allowed.
+    ///
+    /// Returns:
+    /// - `Ok(())`: If the depth is within the limit;
+    /// - `Err(ErrorKind::DepthLimitExceeded)`: If the limit was exceeded;
+    pub fn check_depth(&self, depth: usize) -> Result<()> {
+        if depth > self.max_depth {
+            Err(ErrorKind::DepthLimitExceeded)
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Verifies if the given size of the top level tag is allowed.
+    ///
+    /// Returns:
+    /// - `Ok(())`: If the size is within the limit;
+    /// - `Err(ErrorKind::TotalSizeLimitExceeded)`: If the limit was exceeded;
+    pub fn check_total_size(&self, size: u64) -> Result<()> {
+        if size > self.max_total_size {
+            Err(ErrorKind::TotalSizeLimitExceeded)
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Verifies if the given number of elements is allowed.
+    ///
+    /// Returns:
+    /// - `Ok(())`: If the number of elements is within the limit;
+    /// - `Err(ErrorKind::ElementCountLimitExceeded)`: If the limit was exceeded;
+    pub fn check_elements(&self, count: u64) -> Result<()> {
+        if count > self.max_elements {
+            Err(ErrorKind::ElementCountLimitExceeded)
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Verifies if the given string or byte array length is allowed.
+    ///
+    /// Returns:
+    /// - `Ok(())`: If the length is within the limit;
+    /// - `Err(ErrorKind::ValueLengthLimitExceeded)`: If the limit was exceeded;
+    pub fn check_value_length(&self, length: u64) -> Result<()> {
+        if length > self.max_value_length {
+            Err(ErrorKind::ValueLengthLimitExceeded)
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Verifies if the given tag value size is allowed and converts it into
+    /// an usize. It is equivalent to [`crate::tags::tag_size_to_usize()`]
+    /// but uses the configured limit.
+    ///
+    /// Returns:
+    /// - `Ok(size)`: The size as usize;
+    /// - `Err(ErrorKind::TagTooLarge)`: If the limit was exceeded;
+    pub fn check_tag_size(&self, size: u64) -> Result<usize> {
+        if size > self.max_tag_size {
+            Err(ErrorKind::TagTooLarge)
+        } else {
+            Ok(size as usize)
+        }
+    }
+}
+
+impl Default for DeserializationLimits {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Default limits used by [`crate::tags::ILTagFactory::limits()`].
+pub(crate) static DEFAULT_LIMITS: DeserializationLimits = DeserializationLimits::new();
@@ -0,0 +1,100 @@
+/*
+ * BSD 3-Clause License
+ *
+ * Copyright (c) 2020, InterlockLedger Network
+ * All rights reserved.
+ *
+ * Redistribution and use in source and binary forms, with or without
+ * modification, are permitted provided that the following conditions are met:
+ *
+ * * Redistributions of source code must retain the above copyright notice, this
+ *   list of conditions and the following disclaimer.
+ *
+ * * Redistributions in binary form must reproduce the above copyright notice,
+ *   this list of conditions and the following disclaimer in the documentation
+ *   and/or other materials provided with the distribution.
+ *
+ * * Neither the name of the copyright holder nor the names of its
+ *   contributors may be used to endorse or promote products derived from
+ *   this software without specific prior written permission.
+ *
+ * THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS"
+ * AND ANY EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE
+ * IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+ * DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE
+ * FOR ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL, EXEMPLARY, OR CONSEQUENTIAL
+ * DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+ * SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER
+ * CAUSED AND ON ANY THEORY OF LIABILITY, WHETHER IN CONTRACT, STRICT LIABILITY,
+ * OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE
+ * OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+ */
+use super::*;
+
+#[test]
+fn test_deserializationlimits_new() {
+    let l = DeserializationLimits::new();
+    assert_eq!(l.max_depth(), usize::MAX);
+    assert_eq!(l.max_total_size(), MAX_TAG_SIZE);
+    assert_eq!(l.max_elements(), u64::MAX);
+    assert_eq!(l.max_value_length(), u64::MAX);
+    assert_eq!(l.max_tag_size(), MAX_TAG_SIZE);
+    assert_eq!(l, DeserializationLimits::default());
+    assert_eq!(l, DEFAULT_LIMITS);
+}
+
+#[test]
+fn test_deserializationlimits_set() {
+    let mut l = DeserializationLimits::new();
+
+    l.set_max_depth(1);
+    assert_eq!(l.max_depth(), 1);
+    l.set_max_total_size(2);
+    assert_eq!(l.max_total_size(), 2);
+    l.set_max_elements(3);
+    assert_eq!(l.max_elements(), 3);
+    l.set_max_value_length(4);
+    assert_eq!(l.max_value_length(), 4);
+    l.set_max_tag_size(5);
+    assert_eq!(l.max_tag_size(), 5);
+    l.set_max_tag_size(MAX_TAG_SIZE + 1);
+    assert_eq!(l.max_tag_size(), MAX_TAG_SIZE);
+}
+
+#[test]
+fn test_deserializationlimits_check() {
+    let mut l = DeserializationLimits::new();
+    l.set_max_depth(1);
+    l.set_max_total_size(2);
+    l.set_max_elements(3);
+    l.set_max_value_length(4);
+    l.set_max_tag_size(5);
+
+    assert!(l.check_depth(0).is_ok());
+    assert!(l.check_depth(1).is_ok());
+    assert!(matches!(
+        l.check_depth(2),
+        Err(ErrorKind::DepthLimitExceeded)
+    ));
+
+    assert!(l.check_total_size(2).is_ok());
+    assert!(matches!(
+        l.check_total_size(3),
+        Err(ErrorKind::TotalSizeLimitExceeded)
+    ));
+
+    assert!(l.check_elements(3).is_ok());
+    assert!(matches!(
+        l.check_elements(4),
+        Err(ErrorKind::ElementCountLimitExceeded)
+    ));
+
+    assert!(l.check_value_length(4).is_ok());
+    assert!(matches!(
+        l.check_value_length(5),
+        Err(ErrorKind::ValueLengthLimitExceeded)
+    ));
+
+    assert_eq!(l.check_tag_size(5).unwrap(), 5);
+    assert!(matches!(l.check_tag_size(6), Err(ErrorKind::TagTooLarge)));
+}
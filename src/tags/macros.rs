@@ -37,7 +37,7 @@
 /// tag.
 ///
 /// Example:
-/// ```
+/// ```ignore
 /// pub struct SampleTag {
 ///     id: u64,
 ///     ...
@@ -72,7 +72,7 @@ macro_rules! iltag_as_any_impl {
 /// tag.
 ///
 /// Example:
-/// ```
+/// ```ignore
 /// pub struct SampleTag {
 ///     id: u64,
 ///     ...
@@ -105,7 +105,7 @@ macro_rules! iltag_base_func_impl {
 /// constructor called `new()` that takes no arguments.
 ///
 /// Example:
-/// ```
+/// ```ignore
 /// pub struct SampleTag{...}
 ///
 /// impl SampleTag {
@@ -139,7 +139,7 @@ macro_rules! iltag_default_impl {
 ///
 /// Example:
 ///
-/// ```
+/// ```ignore
 /// pub struct NewTag{
 ///     inner: ILRawTag,
 /// }
@@ -168,7 +168,7 @@ macro_rules! inner_iltag_base_func_impl {
 ///
 /// Example:
 ///
-/// ```
+/// ```ignore
 /// pub struct NewTag{
 ///     inner: ILRawTag,
 /// }
@@ -202,3 +202,70 @@ macro_rules! inner_iltag_default_impl {
         }
     };
 }
+
+/// This macro implements the methods of `ILTag` required by the tags that
+/// implement the trait `ILTagContainer`. All of them delegate their work to
+/// the non-recursive traversal engine defined in `tags::container`.
+///
+/// Example:
+///
+/// ```ignore
+/// impl ILTagContainer for NewTag {
+///     ...
+/// }
+///
+/// impl ILTag for NewTag {
+///     iltag_base_func_impl!();
+///     iltag_container_func_impl!();
+/// }
+/// ```
+///
+/// It defines the following methods:
+/// - `fn value_size(&self) -> u64`;
+/// - `fn size(&self) -> u64`;
+/// - `fn serialize_value(&self, writer: &mut dyn Writer) -> Result<()>`;
+/// - `fn deserialize_value(&mut self, factory: &dyn ILTagFactory, value_size: usize, reader: &mut dyn Reader) -> Result<()>`;
+/// - `fn serialize(&self, writer: &mut dyn Writer) -> Result<()>`;
+/// - `fn to_bytes(&self) -> Result<Vec<u8>>`;
+/// - `fn as_container(&self) -> Option<&dyn ILTagContainer>`;
+/// - `fn as_mut_container(&mut self) -> Option<&mut dyn ILTagContainer>`;
+macro_rules! iltag_container_func_impl {
+    () => {
+        fn value_size(&self) -> u64 {
+            container_value_size(self)
+        }
+
+        fn size(&self) -> u64 {
+            container_tag_size(self.id(), self)
+        }
+
+        fn serialize(&self, writer: &mut dyn Writer) -> Result<()> {
+            serialize_container(self.id(), self, writer)
+        }
+
+        fn to_bytes(&self) -> Result<Vec<u8>> {
+            container_to_bytes(self.id(), self)
+        }
+
+        fn serialize_value(&self, writer: &mut dyn Writer) -> Result<()> {
+            serialize_container_value(self, writer)
+        }
+
+        fn deserialize_value(
+            &mut self,
+            factory: &dyn ILTagFactory,
+            value_size: usize,
+            reader: &mut dyn Reader,
+        ) -> Result<()> {
+            deserialize_container_value(self, factory, value_size, reader)
+        }
+
+        fn as_container(&self) -> Option<&dyn ILTagContainer> {
+            Some(self)
+        }
+
+        fn as_mut_container(&mut self) -> Option<&mut dyn ILTagContainer> {
+            Some(self)
+        }
+    };
+}
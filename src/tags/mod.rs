@@ -60,17 +60,28 @@
 #[macro_use]
 mod macros;
 
+pub mod container;
+pub mod dump;
+pub mod error;
+pub mod field;
+pub mod json;
+pub mod limits;
+pub mod path;
 pub mod payload;
 pub mod raw;
 pub mod serialization;
 pub mod standard;
+pub mod text;
 pub mod util;
+pub mod validation;
+pub mod value;
+pub mod view;
 
 #[cfg(test)]
 mod tests;
 
 use crate::ilint::encoded_size;
-use crate::io::{Reader, Writer};
+use crate::io::{Digest, HashingWriter, NullWriter, Reader, Writer};
 use ::std::any::Any;
 use ::std::collections::HashMap;
 use serialization::*;
@@ -80,11 +91,24 @@ pub use serialization::{
 };
 use std::convert::From;
 
+pub use limits::DeserializationLimits;
+
+/// Derive macro that implements [`ILTag`] and [`field::ILTagField`] for
+/// structs and enums. See [`field`] for further details.
+///
+/// New since 2.0.0.
+#[cfg(feature = "derive")]
+pub use il2_iltags_derive::ILTag;
+
 /// Maximum tag size that can be handled by this library. It in this version it is set to 512MB.
 pub const MAX_TAG_SIZE: u64 = 1024 * 1024 * 512;
 
 /// Definition of the errors from this package.
+///
+/// Since 2.0.0 this enum is marked as `#[non_exhaustive]`, so new errors
+/// can be added without breaking existing code.
 #[derive(Debug)]
+#[non_exhaustive]
 pub enum ErrorKind {
     /// If an unknown tag is found.
     UnknownTag,
@@ -98,10 +122,120 @@ pub enum ErrorKind {
     UnexpectedTagType,
     /// If the tag cannot be serialized.
     UnableToSerialize,
+    /// If a tag writes a value whose size differs from [`ILTag::value_size()`].
+    /// It contains the id of the tag.
+    /// New since 2.0.0.
+    ValueSizeMismatch(u64),
+    /// If the maximum nesting depth defined by [`DeserializationLimits`] was exceeded.
+    /// New since 2.0.0.
+    DepthLimitExceeded,
+    /// If the maximum size of the top level tag defined by [`DeserializationLimits`]
+    /// was exceeded.
+    /// New since 2.0.0.
+    TotalSizeLimitExceeded,
+    /// If the maximum number of elements of a container defined by
+    /// [`DeserializationLimits`] was exceeded.
+    /// New since 2.0.0.
+    ElementCountLimitExceeded,
+    /// If the maximum length of a string or byte array defined by
+    /// [`DeserializationLimits`] was exceeded.
+    /// New since 2.0.0.
+    ValueLengthLimitExceeded,
+    /// If the data is not encoded in its canonical form. It is reported only
+    /// when the factory is in canonical mode. The [`CanonicalRule`] indicates
+    /// the rule that was violated.
+    /// New since 2.0.0.
+    NonCanonical(CanonicalRule),
+    /// If a dictionary contains the same key more than once and the factory
+    /// uses [`DuplicateKeyPolicy::Reject`].
+    /// New since 2.0.0.
+    DuplicateKey,
+    /// If the keys of a dictionary are not sorted and the factory requires
+    /// them to be sorted.
+    /// New since 2.0.0.
+    UnsortedKeys,
     /// Wraper to I/O errors.
     IOError(crate::io::ErrorKind),
-    /// Wrapper to boxed errors.
-    Boxed(Box<dyn ::std::error::Error>),
+    /// Wrapper to boxed errors. Since 2.0.0 the boxed error must be [`Send`]
+    /// and [`Sync`]. It is displayed as the boxed error itself.
+    Boxed(Box<dyn ::std::error::Error + Send + Sync>),
+}
+
+/// Rules enforced by the canonical mode of the factories. See
+/// [`ILTagFactory::canonical()`] for further details.
+///
+/// New since 2.0.0.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum CanonicalRule {
+    /// ILInt values must be encoded using the smallest possible number of bytes.
+    ILInt,
+    /// Boolean values must be encoded as 0 or 1.
+    Bool,
+    /// Dictionary keys must be sorted in lexicographic order.
+    DictKeyOrder,
+    /// Dictionary keys must be unique.
+    DictDuplicateKey,
+    /// Big integers must be encoded as the shortest two's complement value.
+    TwosComplement,
+    /// NaN values must be encoded as the canonical quiet NaN.
+    NaN,
+}
+
+/// Defines how the dictionaries must handle duplicate keys during the
+/// deserialization. See [`ILTagFactory::duplicate_key_policy()`] for further
+/// details.
+///
+/// New since 2.0.0.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Default)]
+pub enum DuplicateKeyPolicy {
+    /// Duplicate keys are reported as [`ErrorKind::DuplicateKey`]. This is
+    /// the default policy.
+    #[default]
+    Reject,
+    /// The first value associated with the key is kept.
+    FirstWins,
+    /// The last value associated with the key is kept.
+    LastWins,
+}
+
+impl std::fmt::Display for ErrorKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ErrorKind::UnknownTag => write!(f, "unknown tag"),
+            ErrorKind::UnsupportedTag => write!(f, "unsupported tag"),
+            ErrorKind::CorruptedData => write!(f, "corrupted tag data"),
+            ErrorKind::TagTooLarge => write!(f, "tag too large"),
+            ErrorKind::UnexpectedTagType => write!(f, "unexpected tag type"),
+            ErrorKind::UnableToSerialize => write!(f, "unable to serialize the tag"),
+            ErrorKind::ValueSizeMismatch(id) => write!(
+                f,
+                "the value of the tag with id {} does not match its size",
+                id
+            ),
+            ErrorKind::DepthLimitExceeded => write!(f, "nesting depth limit exceeded"),
+            ErrorKind::TotalSizeLimitExceeded => write!(f, "total size limit exceeded"),
+            ErrorKind::ElementCountLimitExceeded => write!(f, "element count limit exceeded"),
+            ErrorKind::ValueLengthLimitExceeded => write!(f, "value length limit exceeded"),
+            ErrorKind::NonCanonical(rule) => {
+                write!(f, "data is not in its canonical form ({:?})", rule)
+            }
+            ErrorKind::DuplicateKey => write!(f, "duplicate dictionary key"),
+            ErrorKind::UnsortedKeys => write!(f, "dictionary keys are not sorted"),
+            ErrorKind::IOError(_) => write!(f, "I/O error"),
+            ErrorKind::Boxed(e) => e.fmt(f),
+        }
+    }
+}
+
+/// New since 2.0.0.
+impl std::error::Error for ErrorKind {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            ErrorKind::IOError(e) => Some(e),
+            ErrorKind::Boxed(e) => e.source(),
+            _ => None,
+        }
+    }
 }
 
 impl From<crate::io::ErrorKind> for ErrorKind {
@@ -110,6 +244,28 @@ impl From<crate::io::ErrorKind> for ErrorKind {
     }
 }
 
+/// Converts the errors from [`crate::ilint`]. Non canonical values are
+/// mapped to [`ErrorKind::NonCanonical`] with [`CanonicalRule::ILInt`] and
+/// malformed values to [`crate::io::ErrorKind::CorruptedData`].
+///
+/// New since 2.0.0.
+impl From<crate::ilint::ErrorKind> for ErrorKind {
+    fn from(error: crate::ilint::ErrorKind) -> Self {
+        match error {
+            crate::ilint::ErrorKind::IOError(e) => ErrorKind::IOError(e),
+            crate::ilint::ErrorKind::NonCanonical => ErrorKind::NonCanonical(CanonicalRule::ILInt),
+            _ => ErrorKind::IOError(crate::io::ErrorKind::CorruptedData),
+        }
+    }
+}
+
+/// New since 2.0.0.
+impl From<std::io::Error> for ErrorKind {
+    fn from(error: std::io::Error) -> Self {
+        ErrorKind::IOError(crate::io::ErrorKind::from(error))
+    }
+}
+
 /// A specialized [`std::result::Result`] generated by functions and methods from this package.
 pub type Result<T> = ::std::result::Result<T, ErrorKind>;
 
@@ -147,6 +303,62 @@ pub fn is_reserved_tag(id: u64) -> bool {
     id <= RESERVED_ID_MAX
 }
 
+/// Serializes the value of the given tag and verifies that exactly
+/// [`ILTag::value_size()`] bytes were written. It is used by
+/// [`ILTag::serialize()`] to detect tags that report wrong value sizes,
+/// which would otherwise produce corrupted streams.
+///
+/// The value is written through a [`crate::io::LimitedWriter`], thus a tag
+/// that tries to write more bytes than expected is stopped before the
+/// extra bytes reach the writer.
+///
+/// Arguments:
+///
+/// * `tag`: The tag;
+/// * `writer`: The writer that will receive the encoded value;
+///
+/// Returns:
+///
+/// * `Ok()`: On success.
+/// * `Err(ErrorKind::ValueSizeMismatch(id))`: If the tag wrote more or less
+///   bytes than its value size;
+/// * `Err(_)`: In case of other errors.
+///
+/// New since 2.0.0.
+pub fn serialize_value_checked<T: ILTag + ?Sized>(tag: &T, writer: &mut dyn Writer) -> Result<()> {
+    write_value_checked(tag.id(), tag.value_size(), writer, |w| {
+        tag.serialize_value(w)
+    })
+}
+
+/// Writes the value of a tag using `serialize` and verifies that exactly
+/// `value_size` bytes were written. See [`serialize_value_checked()`] for
+/// further details.
+///
+/// Arguments:
+///
+/// * `id`: The id of the tag;
+/// * `value_size`: The expected size of the value;
+/// * `writer`: The writer that will receive the encoded value;
+/// * `serialize`: The function that writes the value;
+pub(crate) fn write_value_checked<F>(
+    id: u64,
+    value_size: u64,
+    writer: &mut dyn Writer,
+    serialize: F,
+) -> Result<()>
+where
+    F: FnOnce(&mut dyn Writer) -> Result<()>,
+{
+    let mut limited = crate::io::LimitedWriter::new(writer, value_size);
+    match serialize(&mut limited) {
+        Ok(()) if limited.full() => Ok(()),
+        Ok(()) => Err(ErrorKind::ValueSizeMismatch(id)),
+        Err(_) if limited.exceeded() => Err(ErrorKind::ValueSizeMismatch(id)),
+        Err(e) => Err(e),
+    }
+}
+
 /// This function converts the tag size as u64 into
 /// a usize value. It checks if the tag size falls within
 /// the maximum size of a tag that this library accepts.
@@ -218,6 +430,14 @@ pub trait ILTag: Any + Send + Sync {
 
     /// Serializes this tag.
     ///
+    /// Since 2.0.0, the number of bytes written by [`Self::serialize_value()`]
+    /// is verified against [`Self::value_size()`] in debug builds or when the
+    /// feature `verify-serialization` is enabled. See
+    /// [`serialize_value_checked()`] for further details. Containers are not
+    /// verified here as they are verified against their
+    /// [`crate::tags::container::ILTagSizePlan`] when serialized by
+    /// [`crate::tags::container::serialize_container()`].
+    ///
     /// Arguments:
     ///
     /// * `writer`: The writer that will receive the encoded value;
@@ -225,14 +445,23 @@ pub trait ILTag: Any + Send + Sync {
     /// Returns:
     ///
     /// * `Ok()`: On success.
+    /// * `Err(ErrorKind::ValueSizeMismatch(_))`: If the size of the value
+    ///   does not match [`Self::value_size()`];
     /// * `Err(())`: If the buffer is too small to hold the encoded value.
     ///
     fn serialize(&self, writer: &mut dyn Writer) -> Result<()> {
+        let value_size = self.value_size();
         writer.serialize_ilint(self.id())?;
         if !self.is_implicity() {
-            writer.serialize_ilint(self.value_size())?;
+            writer.serialize_ilint(value_size)?;
+        }
+        if cfg!(any(debug_assertions, feature = "verify-serialization"))
+            && self.as_container().is_none()
+        {
+            write_value_checked(self.id(), value_size, writer, |w| self.serialize_value(w))
+        } else {
+            self.serialize_value(writer)
         }
-        self.serialize_value(writer)
     }
 
     /// Deserializes the value.
@@ -268,6 +497,62 @@ pub trait ILTag: Any + Send + Sync {
         self.serialize(&mut writer)?;
         Ok(writer.into())
     }
+
+    /// Adds the serialization of this tag to the given digest. The tag is
+    /// hashed while it is serialized, thus the serialization is never held
+    /// in memory.
+    ///
+    /// Arguments:
+    ///
+    /// * `digest`: The digest;
+    ///
+    /// Returns:
+    ///
+    /// * `Ok()`: On success.
+    /// * `Err(())`: If the tag cannot be serialized.
+    ///
+    /// New since 2.0.0.
+    fn update_digest(&self, digest: &mut dyn Digest) -> Result<()> {
+        let mut null = NullWriter::new();
+        let mut writer = HashingWriter::new(&mut null, digest);
+        self.serialize(&mut writer)
+    }
+
+    /// Computes the digest of the serialization of this tag using the hash
+    /// function `D`. See [`Self::update_digest()`] for further details.
+    ///
+    /// Use [`util::iltag_digest()`] to compute the digest of a `dyn ILTag`.
+    ///
+    /// Returns:
+    ///
+    /// * `Ok(digest)`: The digest.
+    /// * `Err(())`: If the tag cannot be serialized.
+    ///
+    /// New since 2.0.0.
+    fn digest<D: Digest + Default>(&self) -> Result<Vec<u8>>
+    where
+        Self: Sized,
+    {
+        util::iltag_digest::<D>(self)
+    }
+
+    /// Returns this tag as a [`container::ILTagContainer`] if it is a
+    /// container that must be handled by the non-recursive traversal engine.
+    /// The default implementation returns `None`.
+    ///
+    /// New since 2.0.0.
+    fn as_container(&self) -> Option<&dyn container::ILTagContainer> {
+        None
+    }
+
+    /// Returns this tag as a mutable [`container::ILTagContainer`] if it is
+    /// a container that must be handled by the non-recursive traversal engine.
+    /// The default implementation returns `None`.
+    ///
+    /// New since 2.0.0.
+    fn as_mut_container(&mut self) -> Option<&mut dyn container::ILTagContainer> {
+        None
+    }
 }
 
 /// Downcasts a [`ILTag`] into a reference to its concrete type.
@@ -486,6 +771,7 @@ pub trait ILTagFactory: Send + Sync {
     /// not possible or the deserialization does not use all bytes from raw.
     ///
     /// New since 1.4.0.
+    #[allow(clippy::wrong_self_convention)]
     fn from_bytes(&self, raw_tag: &[u8]) -> Result<Box<dyn ILTag>> {
         let mut reader = crate::io::array::ByteArrayReader::new(raw_tag);
         let ret = self.deserialize(&mut reader)?;
@@ -495,6 +781,73 @@ pub trait ILTagFactory: Send + Sync {
             Err(ErrorKind::CorruptedData)
         }
     }
+
+    /// Returns the deserialization limits that must be enforced by the tags
+    /// deserialized by this factory. Implementations of
+    /// [`ILTag::deserialize_value()`] are expected to check those limits
+    /// before allocating resources based on the data read.
+    ///
+    /// The default implementation returns the default [`DeserializationLimits`].
+    ///
+    /// New since 2.0.0.
+    fn limits(&self) -> &DeserializationLimits {
+        &limits::DEFAULT_LIMITS
+    }
+
+    /// Returns true if the tags deserialized by this factory must reject
+    /// values that are not encoded in their canonical form. Implementations
+    /// of [`ILTag::deserialize_value()`] are expected to use
+    /// [`CanonicalILIntDeserializer::deserialize_ilint_with()`] to read
+    /// ILInt values and to verify all other rules listed by
+    /// [`CanonicalRule`] when this mode is enabled.
+    ///
+    /// Since every value has a single valid encoding in this mode, two tags
+    /// deserialized in canonical mode are equal if and only if their
+    /// serializations are equal. This makes [`util::iltag_are_equal()`]
+    /// reliable for signed payloads.
+    ///
+    /// The default implementation returns false.
+    ///
+    /// New since 2.0.0.
+    fn canonical(&self) -> bool {
+        false
+    }
+
+    /// Returns how the dictionaries deserialized by this factory must handle
+    /// duplicate keys. The canonical mode always rejects duplicate keys,
+    /// regardless of this policy.
+    ///
+    /// The default implementation returns [`DuplicateKeyPolicy::Reject`].
+    ///
+    /// New since 2.0.0.
+    fn duplicate_key_policy(&self) -> DuplicateKeyPolicy {
+        DuplicateKeyPolicy::Reject
+    }
+
+    /// Returns true if the dictionaries deserialized by this factory must
+    /// have their keys in the same order used by their serialization. If
+    /// true, unsorted keys are reported as [`ErrorKind::UnsortedKeys`]. The
+    /// canonical mode always verifies the order of the keys.
+    ///
+    /// The default implementation returns false.
+    ///
+    /// New since 2.0.0.
+    fn sorted_keys(&self) -> bool {
+        false
+    }
+
+    /// Returns the nesting depth of the tags deserialized by this factory.
+    /// The factory passed to [`ILTag::deserialize_value()`] of a tag at
+    /// depth `d` reports `d + 1`, allowing implementations that deserialize
+    /// nested values by themselves to enforce
+    /// [`DeserializationLimits::max_depth`].
+    ///
+    /// The default implementation returns 0.
+    ///
+    /// New since 2.0.0.
+    fn depth(&self) -> usize {
+        0
+    }
 }
 
 //=============================================================================
@@ -592,7 +945,7 @@ impl ILTagCreatorEngine {
     ///
     /// Arguments:
     /// * `strict`: Strict mode. If false, unknown tags will be mapped to RawTag
-    /// instances.
+    ///   instances.
     pub fn new(strict: bool) -> ILTagCreatorEngine {
         ILTagCreatorEngine {
             creators: HashMap::new(),
@@ -749,10 +1102,11 @@ impl ILTag for ILRawTag {
 
     fn deserialize_value(
         &mut self,
-        _factory: &dyn ILTagFactory,
+        factory: &dyn ILTagFactory,
         value_size: usize,
         reader: &mut dyn Reader,
     ) -> Result<()> {
+        factory.limits().check_value_length(value_size as u64)?;
         reader.deserialize_bytes_into_vec(value_size, &mut self.value)
     }
 }
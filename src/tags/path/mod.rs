@@ -0,0 +1,240 @@
+/*
+ * BSD 3-Clause License
+ *
+ * Copyright (c) 2020, InterlockLedger Network
+ * All rights reserved.
+ *
+ * Redistribution and use in source and binary forms, with or without
+ * modification, are permitted provided that the following conditions are met:
+ *
+ * * Redistributions of source code must retain the above copyright notice, this
+ *   list of conditions and the following disclaimer.
+ *
+ * * Redistributions in binary form must reproduce the above copyright notice,
+ *   this list of conditions and the following disclaimer in the documentation
+ *   and/or other materials provided with the distribution.
+ *
+ * * Neither the name of the copyright holder nor the names of its
+ *   contributors may be used to endorse or promote products derived from
+ *   this software without specific prior written permission.
+ *
+ * THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS"
+ * AND ANY EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE
+ * IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+ * DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE
+ * FOR ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL, EXEMPLARY, OR CONSEQUENTIAL
+ * DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+ * SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER
+ * CAUSED AND ON ANY THEORY OF LIABILITY, WHETHER IN CONTRACT, STRICT LIABILITY,
+ * OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE
+ * OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+ */
+//! This module defines the path of a tag inside a tree of tags. It is used
+//! to report the location of errors found inside nested containers.
+//!
+//! New since 2.0.0.
+#[cfg(test)]
+mod tests;
+
+use crate::tags::standard::constants::*;
+use ::std::fmt;
+
+/// Returns the short name of a standard tag.
+///
+/// Arguments:
+/// - `id`: The tag id;
+///
+/// Returns the name of the tag or None if the tag is not a standard tag.
+///
+/// New since 2.0.0.
+pub fn standard_tag_name(id: u64) -> Option<&'static str> {
+    Some(match id {
+        IL_NULL_TAG_ID => "null",
+        IL_BOOL_TAG_ID => "bool",
+        IL_INT8_TAG_ID => "int8",
+        IL_UINT8_TAG_ID => "uint8",
+        IL_INT16_TAG_ID => "int16",
+        IL_UINT16_TAG_ID => "uint16",
+        IL_INT32_TAG_ID => "int32",
+        IL_UINT32_TAG_ID => "uint32",
+        IL_INT64_TAG_ID => "int64",
+        IL_UINT64_TAG_ID => "uint64",
+        IL_ILINT_TAG_ID => "ilint",
+        IL_BIN32_TAG_ID => "bin32",
+        IL_BIN64_TAG_ID => "bin64",
+        IL_BIN128_TAG_ID => "bin128",
+        IL_SIGNED_ILINT_TAG_ID => "signed_ilint",
+        IL_BYTES_TAG_ID => "bytes",
+        IL_STRING_TAG_ID => "string",
+        IL_BINT_TAG_ID => "bigint",
+        IL_BDEC_TAG_ID => "bigdec",
+        IL_ILINTARRAY_TAG_ID => "ilint_array",
+        IL_ILTAGARRAY_TAG_ID => "array",
+        IL_ILTAGSEQ_TAG_ID => "seq",
+        IL_RANGE_TAG_ID => "range",
+        IL_VERSION_TAG_ID => "version",
+        IL_OID_TAG_ID => "oid",
+        IL_DICTIONARY_TAG_ID => "dict",
+        IL_STRING_DICTIONARY_TAG_ID => "strdict",
+        _ => return None,
+    })
+}
+
+/// Writes the name of the tag into the formatter. Non standard tags are
+/// written as `tag(<id>)`.
+///
+/// Arguments:
+/// - `f`: The formatter;
+/// - `id`: The tag id;
+pub(crate) fn fmt_tag_name(f: &mut fmt::Formatter<'_>, id: u64) -> fmt::Result {
+    match standard_tag_name(id) {
+        Some(name) => write!(f, "{}", name),
+        None => write!(f, "tag({})", id),
+    }
+}
+
+/// Writes the location of an error into the formatter as
+/// ` in <path> > <tag name>`. Nothing is written if both the path and the
+/// tag id are not available.
+///
+/// Arguments:
+/// - `f`: The formatter;
+/// - `path`: The path of the parent of the tag;
+/// - `id`: The tag id if known;
+pub(crate) fn fmt_location(
+    f: &mut fmt::Formatter<'_>,
+    path: &ILTagPath,
+    id: Option<u64>,
+) -> fmt::Result {
+    if path.is_empty() && id.is_none() {
+        return Ok(());
+    }
+    write!(f, " in {}", path)?;
+    if let Some(id) = id {
+        if !path.is_empty() {
+            write!(f, " > ")?;
+        }
+        fmt_tag_name(f, id)?;
+    }
+    Ok(())
+}
+
+//=============================================================================
+// ILTagPathElement
+//-----------------------------------------------------------------------------
+/// This struct represents a single step of a [`ILTagPath`]. It identifies a
+/// container and the position of the child inside it.
+///
+/// New since 2.0.0.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ILTagPathElement {
+    id: u64,
+    index: u64,
+    key: Option<String>,
+}
+
+impl ILTagPathElement {
+    /// Creates a new instance of this struct.
+    ///
+    /// Arguments:
+    /// - `id`: The id of the container;
+    /// - `index`: The index of the child inside the container;
+    /// - `key`: The key associated with the child if the container is a
+    ///   dictionary;
+    pub fn new(id: u64, index: u64, key: Option<String>) -> Self {
+        Self { id, index, key }
+    }
+
+    /// Returns the id of the container.
+    pub fn id(&self) -> u64 {
+        self.id
+    }
+
+    /// Returns the index of the child inside the container.
+    pub fn index(&self) -> u64 {
+        self.index
+    }
+
+    /// Returns the key associated with the child, if any.
+    pub fn key(&self) -> Option<&str> {
+        self.key.as_deref()
+    }
+}
+
+impl fmt::Display for ILTagPathElement {
+    /// Formats this element as `name[index]` or `name["key"]`.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt_tag_name(f, self.id)?;
+        match &self.key {
+            Some(key) => write!(f, "[{:?}]", key),
+            None => write!(f, "[{}]", self.index),
+        }
+    }
+}
+
+//=============================================================================
+// ILTagPath
+//-----------------------------------------------------------------------------
+/// This struct represents the path from the top level tag to a nested tag.
+/// Each element identifies one of the ancestor containers, starting from the
+/// outermost one.
+///
+/// It is formatted as `dict["payload"] > array[17]`.
+///
+/// New since 2.0.0.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct ILTagPath {
+    elements: Vec<ILTagPathElement>,
+}
+
+impl ILTagPath {
+    /// Creates a new empty path.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the elements of this path, from the outermost to the
+    /// innermost.
+    pub fn elements(&self) -> &[ILTagPathElement] {
+        self.elements.as_slice()
+    }
+
+    /// Adds a new element at the end of the path.
+    ///
+    /// Arguments:
+    /// - `element`: The new element;
+    pub fn push(&mut self, element: ILTagPathElement) {
+        self.elements.push(element);
+    }
+
+    /// Adds a new element at the beginning of the path. It is used to
+    /// collect the path as the error unwinds through the containers.
+    ///
+    /// Arguments:
+    /// - `element`: The new element;
+    pub fn push_front(&mut self, element: ILTagPathElement) {
+        self.elements.insert(0, element);
+    }
+
+    /// Returns the number of elements.
+    pub fn len(&self) -> usize {
+        self.elements.len()
+    }
+
+    /// Returns true if the path is empty.
+    pub fn is_empty(&self) -> bool {
+        self.elements.is_empty()
+    }
+}
+
+impl fmt::Display for ILTagPath {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, e) in self.elements.iter().enumerate() {
+            if i > 0 {
+                write!(f, " > ")?;
+            }
+            write!(f, "{}", e)?;
+        }
+        Ok(())
+    }
+}
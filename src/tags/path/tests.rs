@@ -0,0 +1,89 @@
+/*
+ * BSD 3-Clause License
+ *
+ * Copyright (c) 2020, InterlockLedger Network
+ * All rights reserved.
+ *
+ * Redistribution and use in source and binary forms, with or without
+ * modification, are permitted provided that the following conditions are met:
+ *
+ * * Redistributions of source code must retain the above copyright notice, this
+ *   list of conditions and the following disclaimer.
+ *
+ * * Redistributions in binary form must reproduce the above copyright notice,
+ *   this list of conditions and the following disclaimer in the documentation
+ *   and/or other materials provided with the distribution.
+ *
+ * * Neither the name of the copyright holder nor the names of its
+ *   contributors may be used to endorse or promote products derived from
+ *   this software without specific prior written permission.
+ *
+ * THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS"
+ * AND ANY EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE
+ * IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+ * DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE
+ * FOR ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL, EXEMPLARY, OR CONSEQUENTIAL
+ * DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+ * SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER
+ * CAUSED AND ON ANY THEORY OF LIABILITY, WHETHER IN CONTRACT, STRICT LIABILITY,
+ * OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE
+ * OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+ */
+use super::*;
+
+#[test]
+fn test_standard_tag_name() {
+    assert_eq!(standard_tag_name(IL_NULL_TAG_ID), Some("null"));
+    assert_eq!(standard_tag_name(IL_STRING_TAG_ID), Some("string"));
+    assert_eq!(standard_tag_name(IL_ILTAGARRAY_TAG_ID), Some("array"));
+    assert_eq!(standard_tag_name(IL_DICTIONARY_TAG_ID), Some("dict"));
+    assert_eq!(
+        standard_tag_name(IL_STRING_DICTIONARY_TAG_ID),
+        Some("strdict")
+    );
+    assert_eq!(standard_tag_name(15), None);
+    assert_eq!(standard_tag_name(1234), None);
+    for id in 0..32 {
+        assert_eq!(
+            standard_tag_name(id).is_some(),
+            id != 15 && !(26..30).contains(&id)
+        );
+    }
+}
+
+#[test]
+fn test_iltagpathelement() {
+    let e = ILTagPathElement::new(IL_ILTAGARRAY_TAG_ID, 17, None);
+    assert_eq!(e.id(), IL_ILTAGARRAY_TAG_ID);
+    assert_eq!(e.index(), 17);
+    assert_eq!(e.key(), None);
+    assert_eq!(format!("{}", e), "array[17]");
+
+    let e = ILTagPathElement::new(IL_DICTIONARY_TAG_ID, 3, Some(String::from("payload")));
+    assert_eq!(e.key(), Some("payload"));
+    assert_eq!(format!("{}", e), "dict[\"payload\"]");
+
+    let e = ILTagPathElement::new(1234, 0, None);
+    assert_eq!(format!("{}", e), "tag(1234)[0]");
+}
+
+#[test]
+fn test_iltagpath() {
+    let mut p = ILTagPath::new();
+    assert!(p.is_empty());
+    assert_eq!(p.len(), 0);
+    assert_eq!(format!("{}", p), "");
+
+    p.push(ILTagPathElement::new(IL_ILTAGARRAY_TAG_ID, 17, None));
+    p.push_front(ILTagPathElement::new(
+        IL_DICTIONARY_TAG_ID,
+        0,
+        Some(String::from("payload")),
+    ));
+    p.push(ILTagPathElement::new(IL_ILTAGSEQ_TAG_ID, 2, None));
+    assert!(!p.is_empty());
+    assert_eq!(p.len(), 3);
+    assert_eq!(p.elements()[0].id(), IL_DICTIONARY_TAG_ID);
+    assert_eq!(p.elements()[2].id(), IL_ILTAGSEQ_TAG_ID);
+    assert_eq!(format!("{}", p), "dict[\"payload\"] > array[17] > seq[2]");
+}
@@ -40,6 +40,13 @@ use crate::tags::{DefaultWithId, ILTag, ILTagFactory, Result};
 use std::any::Any;
 use std::ops::{Deref, DerefMut};
 
+/// Derive macro that implements [`ILTagPayload`] for structs and enums. See
+/// [`crate::tags::field`] for further details.
+///
+/// New since 2.0.0.
+#[cfg(feature = "derive")]
+pub use il2_iltags_derive::ILTagPayload;
+
 //=============================================================================
 // ILTagPayload
 //-----------------------------------------------------------------------------
@@ -206,7 +213,6 @@ impl<T: ILTagPayload + Send + Default> DefaultWithId for ILGenericPayloadTag<T>
 impl<T: ILTagPayload + Send + Default> Deref for ILGenericPayloadTag<T> {
     type Target = T;
 
-    #[must_use]
     fn deref(&self) -> &Self::Target {
         self.payload()
     }
@@ -37,13 +37,18 @@
 //!
 //! It is expected that
 //!
+//! Since 2.0.0, [`RawTagWalker`] is also able to descend into the standard
+//! containers and into custom containers registered in a
+//! [`RawLayoutRegistry`].
+//!
 //! New since 1.4.0.
 #[cfg(test)]
 mod tests;
+use crate::io::array::ByteArrayReader;
 use crate::io::Reader;
-use crate::tags::serialization::*;
 use crate::tags::standard::constants::*;
 use crate::tags::{is_implicit_tag, ErrorKind, Result};
+use ::std::collections::HashMap;
 
 //=============================================================================
 // RawTagOffset
@@ -54,7 +59,7 @@ use crate::tags::{is_implicit_tag, ErrorKind, Result};
 ///
 /// It does not store references to the original byte stream, just the
 /// information about a tags inside it.
-#[derive(Debug, Copy, Clone, PartialEq)]
+#[derive(Debug, Copy, Clone, PartialEq, Default)]
 pub struct RawTagOffset {
     id: u64,
     offset: u64,
@@ -188,53 +193,86 @@ impl RawTagOffset {
     }
 }
 
-impl Default for RawTagOffset {
-    fn default() -> Self {
-        Self {
-            id: 0,
-            offset: 0,
-            header_size: 0,
-            value_size: 0,
-        }
-    }
-}
-
 //=============================================================================
 // RawTagScanner
 //-----------------------------------------------------------------------------
+/// Verifies if the given error indicates the end of the data. Readers based
+/// on [`std::io::Read`] report it as [`std::io::ErrorKind::UnexpectedEof`]
+/// instead of [`crate::io::ErrorKind::EndOfData`].
+fn is_end_of_data(e: &crate::io::ErrorKind) -> bool {
+    match e {
+        crate::io::ErrorKind::EndOfData => true,
+        crate::io::ErrorKind::IOError(e) => e.kind() == std::io::ErrorKind::UnexpectedEof,
+        _ => false,
+    }
+}
+
 /// This struct implements a Raw tag scanner that can scan a reader and find
 /// the information about the tags inside it.
 ///
 /// Only the top level tags will be considered as it does not parse the
 /// value of the tags.
-pub struct RawTagScanner<'a, T: Reader> {
+///
+/// Since 2.0.0, the end of the data is detected properly and it can also be
+/// used as an [`Iterator`]. It also accepts `dyn Reader` as its reader.
+pub struct RawTagScanner<'a, T: Reader + ?Sized> {
     offset: u64,
     reader: &'a mut T,
+    failed: bool,
 }
 
-impl<'a, T: Reader> RawTagScanner<'a, T> {
+impl<'a, T: Reader + ?Sized> RawTagScanner<'a, T> {
     /// Creates a new instance of [`RawTagScanner`]. It assumes the current
     /// offset of the provided reader as being 0.
     pub fn new(reader: &'a mut T) -> Self {
-        Self { offset: 0, reader }
+        Self {
+            offset: 0,
+            reader,
+            failed: false,
+        }
     }
 
     /// Returns the information about the next tag.
+    ///
+    /// Returns:
+    /// - Ok(Some(offset)): The information about the next tag;
+    /// - Ok(None): If the end of the data was reached exactly at the end of
+    ///   the last tag;
+    /// - Err(ErrorKind::CorruptedData): If the data ends in the middle of a
+    ///   tag or the tag is corrupted;
+    /// - Err(_): In case of other errors;
     pub fn next_tag(&mut self) -> Result<Option<RawTagOffset>> {
-        let id: u64 = self.reader.deserialize_ilint()?;
+        let header = match self.reader.read() {
+            Ok(header) => header,
+            Err(e) if is_end_of_data(&e) => return Ok(None),
+            Err(e) => return Err(ErrorKind::IOError(e)),
+        };
+        match self.next_tag_core(header) {
+            Ok(t) => Ok(Some(t)),
+            Err(ErrorKind::IOError(crate::io::ErrorKind::UnableToReadData)) => {
+                Err(ErrorKind::CorruptedData)
+            }
+            Err(ErrorKind::IOError(e)) if is_end_of_data(&e) => Err(ErrorKind::CorruptedData),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Reads the remaining of the tag after the first byte of its id.
+    fn next_tag_core(&mut self, header: u8) -> Result<RawTagOffset> {
+        let id: u64 = self.read_ilint_body(header)?;
         let size_info = self.extract_tag_size(id)?;
         let header_size: u64 = crate::ilint::encoded_size(id) as u64 + size_info.0;
         let value_size: u64 = size_info.1;
         let bytes_to_skip: u64 = size_info.2;
         let offset = self.offset;
-        self.offset += header_size + value_size;
         self.reader.skip_u64(bytes_to_skip)?;
-        Ok(Some(RawTagOffset {
+        self.offset += header_size + value_size;
+        Ok(RawTagOffset {
             id,
             offset,
             header_size,
             value_size,
-        }))
+        })
     }
 
     /// Returns the information about the next tag. It works just like
@@ -253,6 +291,30 @@ impl<'a, T: Reader> RawTagScanner<'a, T> {
         }
     }
 
+    /// Reads an ILInt value from the reader.
+    fn read_ilint(&mut self) -> Result<u64> {
+        let header = self.reader.read()?;
+        self.read_ilint_body(header)
+    }
+
+    /// Reads the body of an ILInt value from the reader.
+    ///
+    /// Arguments:
+    /// - `header`: The first byte of the ILInt;
+    fn read_ilint_body(&mut self, header: u8) -> Result<u64> {
+        let size = crate::ilint::decoded_size(header);
+        if size == 1 {
+            return Ok(header as u64);
+        }
+        let mut body = [0_u8; 8];
+        let body = &mut body[..size - 1];
+        self.reader.read_all(body)?;
+        match crate::ilint::decode_body(body) {
+            Ok(v) => Ok(v),
+            Err(_) => Err(ErrorKind::CorruptedData),
+        }
+    }
+
     /// Extracts the size of the tag from the reader.
     ///
     /// Returns (u64, u64, u64) where:
@@ -272,7 +334,7 @@ impl<'a, T: Reader> RawTagScanner<'a, T> {
                 }
             }
         } else {
-            let value_size = self.reader.deserialize_ilint()?;
+            let value_size = self.read_ilint()?;
             (
                 crate::ilint::encoded_size(value_size) as u64,
                 value_size,
@@ -293,3 +355,357 @@ impl<'a, T: Reader> RawTagScanner<'a, T> {
         Ok((0, total_size as u64, (total_size - 1) as u64))
     }
 }
+
+impl<'a, T: Reader + ?Sized> Iterator for RawTagScanner<'a, T> {
+    type Item = Result<RawTagOffset>;
+
+    /// Returns the information about the next tag. Once an error is
+    /// returned, the iteration stops.
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.failed {
+            return None;
+        }
+        match self.next_tag() {
+            Ok(Some(t)) => Some(Ok(t)),
+            Ok(None) => None,
+            Err(e) => {
+                self.failed = true;
+                Some(Err(e))
+            }
+        }
+    }
+}
+
+//=============================================================================
+// RawContainerLayout
+//-----------------------------------------------------------------------------
+/// This trait must be implemented by the layout handlers used by
+/// [`RawTagWalker`] to descend into container tags. It assumes that the value
+/// of the container is composed by an optional prefix followed by a sequence
+/// of serialized tags that extends up to the end of the value.
+///
+/// New since 2.0.0.
+pub trait RawContainerLayout: Send + Sync {
+    /// Parses the prefix of the value of the container.
+    ///
+    /// Arguments:
+    /// - `value`: The value of the container;
+    ///
+    /// Returns `(u64, Option<u64>)` where:
+    /// - 0: The size of the prefix that must be skipped to reach the first
+    ///   child;
+    /// - 1: The expected number of children if known;
+    fn parse_prefix(&self, value: &[u8]) -> Result<(u64, Option<u64>)>;
+}
+
+/// Reads the ILInt count at the beginning of the value of a container.
+///
+/// Returns the count and its size in bytes.
+fn parse_count_prefix(value: &[u8]) -> Result<(u64, u64)> {
+    match crate::ilint::decode_from_bytes(value) {
+        Ok((count, size)) => Ok((count, size as u64)),
+        Err(_) => Err(ErrorKind::CorruptedData),
+    }
+}
+
+/// Layout of [`crate::tags::standard::ILTagSeqTag`]. The value is just a
+/// sequence of tags.
+///
+/// New since 2.0.0.
+pub struct RawSeqLayout;
+
+impl RawContainerLayout for RawSeqLayout {
+    fn parse_prefix(&self, _value: &[u8]) -> Result<(u64, Option<u64>)> {
+        Ok((0, None))
+    }
+}
+
+/// Layout of [`crate::tags::standard::ILTagArrayTag`]. The value starts with
+/// the number of elements encoded as an ILInt followed by the elements.
+///
+/// New since 2.0.0.
+pub struct RawArrayLayout;
+
+impl RawContainerLayout for RawArrayLayout {
+    fn parse_prefix(&self, value: &[u8]) -> Result<(u64, Option<u64>)> {
+        let (count, size) = parse_count_prefix(value)?;
+        Ok((size, Some(count)))
+    }
+}
+
+/// Layout of [`crate::tags::standard::ILDictTag`] and
+/// [`crate::tags::standard::ILStrDictTag`]. The value starts with the number
+/// of entries encoded as an ILInt followed by the alternating keys and
+/// values. Thus, the keys are the children with even indexes while the values
+/// are the children with odd indexes.
+///
+/// New since 2.0.0.
+pub struct RawDictLayout;
+
+impl RawContainerLayout for RawDictLayout {
+    fn parse_prefix(&self, value: &[u8]) -> Result<(u64, Option<u64>)> {
+        let (count, size) = parse_count_prefix(value)?;
+        match count.checked_mul(2) {
+            Some(count) => Ok((size, Some(count))),
+            None => Err(ErrorKind::CorruptedData),
+        }
+    }
+}
+
+static RAW_SEQ_LAYOUT: RawSeqLayout = RawSeqLayout;
+static RAW_ARRAY_LAYOUT: RawArrayLayout = RawArrayLayout;
+static RAW_DICT_LAYOUT: RawDictLayout = RawDictLayout;
+
+/// Returns the layout of the standard container tags.
+///
+/// Arguments:
+/// - `id`: The tag id;
+///
+/// Returns the layout or None if the tag is not a standard container.
+///
+/// New since 2.0.0.
+pub fn standard_container_layout(id: u64) -> Option<&'static dyn RawContainerLayout> {
+    match id {
+        IL_ILTAGSEQ_TAG_ID => Some(&RAW_SEQ_LAYOUT),
+        IL_ILTAGARRAY_TAG_ID => Some(&RAW_ARRAY_LAYOUT),
+        IL_DICTIONARY_TAG_ID | IL_STRING_DICTIONARY_TAG_ID => Some(&RAW_DICT_LAYOUT),
+        _ => None,
+    }
+}
+
+//=============================================================================
+// RawLayoutRegistry
+//-----------------------------------------------------------------------------
+/// This struct holds the [`RawContainerLayout`]s of custom container tags.
+/// The layouts of the standard containers are always available unless they
+/// are overridden by a custom layout.
+///
+/// New since 2.0.0.
+#[derive(Default)]
+pub struct RawLayoutRegistry {
+    layouts: HashMap<u64, Box<dyn RawContainerLayout>>,
+}
+
+impl RawLayoutRegistry {
+    /// Creates a new empty registry.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Registers the layout of a given tag id. It replaces any layout
+    /// previously registered for the same id.
+    ///
+    /// Arguments:
+    /// - `id`: The tag id;
+    /// - `layout`: The layout;
+    pub fn register(&mut self, id: u64, layout: Box<dyn RawContainerLayout>) {
+        self.layouts.insert(id, layout);
+    }
+
+    /// Returns the layout of the given tag id.
+    ///
+    /// Arguments:
+    /// - `id`: The tag id;
+    ///
+    /// Returns the layout or None if the tag is not a known container.
+    pub fn get(&self, id: u64) -> Option<&dyn RawContainerLayout> {
+        match self.layouts.get(&id) {
+            Some(layout) => Some(layout.as_ref()),
+            None => standard_container_layout(id),
+        }
+    }
+}
+
+//=============================================================================
+// RawTagNode
+//-----------------------------------------------------------------------------
+/// This struct holds the information about a tag found by [`RawTagWalker`].
+///
+/// New since 2.0.0.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct RawTagNode {
+    offset: RawTagOffset,
+    depth: usize,
+    parent: Option<usize>,
+    index: u64,
+}
+
+impl RawTagNode {
+    /// Returns the offset of the tag. It is always relative to the beginning
+    /// of the data being walked.
+    pub fn offset(&self) -> &RawTagOffset {
+        &self.offset
+    }
+
+    /// Returns the depth of the tag. Top level tags have depth 0.
+    pub fn depth(&self) -> usize {
+        self.depth
+    }
+
+    /// Returns the position of the parent in the sequence of nodes returned
+    /// by the walker or None if this is a top level tag.
+    pub fn parent(&self) -> Option<usize> {
+        self.parent
+    }
+
+    /// Returns the index of this tag among the children of its parent or
+    /// among the top level tags.
+    pub fn index(&self) -> u64 {
+        self.index
+    }
+}
+
+//=============================================================================
+// RawTagWalker
+//-----------------------------------------------------------------------------
+/// A frame of the explicit stack used by [`RawTagWalker`].
+struct RawWalkFrame {
+    end: u64,
+    parent: Option<usize>,
+    remaining: Option<u64>,
+    next_index: u64,
+}
+
+/// This struct implements a raw tree walker. It visits all tags inside a
+/// byte slice in pre-order, descending into the containers known by its
+/// [`RawLayoutRegistry`] and yielding a [`RawTagNode`] for each tag found.
+///
+/// It does not use recursion, thus arbitrarily deep trees can be walked.
+/// Once an error is returned, the iteration stops.
+///
+/// New since 2.0.0.
+pub struct RawTagWalker<'a> {
+    raw: &'a [u8],
+    layouts: Option<&'a RawLayoutRegistry>,
+    stack: Vec<RawWalkFrame>,
+    pos: u64,
+    count: usize,
+    failed: bool,
+}
+
+impl<'a> RawTagWalker<'a> {
+    /// Creates a new walker that knows only the standard containers.
+    ///
+    /// Arguments:
+    /// - `raw`: The sequence of serialized tags;
+    pub fn new(raw: &'a [u8]) -> Self {
+        Self::create(raw, None)
+    }
+
+    /// Creates a new walker that uses the given registry to find the
+    /// containers.
+    ///
+    /// Arguments:
+    /// - `raw`: The sequence of serialized tags;
+    /// - `layouts`: The layouts of the containers;
+    pub fn with_layouts(raw: &'a [u8], layouts: &'a RawLayoutRegistry) -> Self {
+        Self::create(raw, Some(layouts))
+    }
+
+    fn create(raw: &'a [u8], layouts: Option<&'a RawLayoutRegistry>) -> Self {
+        Self {
+            raw,
+            layouts,
+            stack: vec![RawWalkFrame {
+                end: raw.len() as u64,
+                parent: None,
+                remaining: None,
+                next_index: 0,
+            }],
+            pos: 0,
+            count: 0,
+            failed: false,
+        }
+    }
+
+    /// Returns the layout of the given tag if it is a container.
+    fn layout(&self, id: u64) -> Option<&'a dyn RawContainerLayout> {
+        match self.layouts {
+            Some(layouts) => layouts.get(id),
+            None => standard_container_layout(id),
+        }
+    }
+
+    /// Reads the header of the tag at the current position.
+    fn read_tag(&self, end: u64) -> Result<RawTagOffset> {
+        let mut reader = ByteArrayReader::new(&self.raw[self.pos as usize..end as usize]);
+        let mut scanner = RawTagScanner::new(&mut reader);
+        match scanner.next_tag() {
+            Ok(Some(offset)) => Ok(RawTagOffset {
+                offset: self.pos,
+                ..offset
+            }),
+            Ok(None) => Err(ErrorKind::CorruptedData),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Returns the next node.
+    fn next_node(&mut self) -> Result<Option<RawTagNode>> {
+        loop {
+            let frame = match self.stack.last_mut() {
+                Some(frame) => frame,
+                None => return Ok(None),
+            };
+            if self.pos == frame.end {
+                if matches!(frame.remaining, Some(r) if r > 0) {
+                    return Err(ErrorKind::CorruptedData);
+                }
+                self.stack.pop();
+                continue;
+            }
+            if frame.remaining == Some(0) {
+                return Err(ErrorKind::CorruptedData);
+            }
+            let end = frame.end;
+            let offset = self.read_tag(end)?;
+            let depth = self.stack.len() - 1;
+            let frame = self.stack.last_mut().unwrap();
+            let node = RawTagNode {
+                offset,
+                depth,
+                parent: frame.parent,
+                index: frame.next_index,
+            };
+            frame.next_index += 1;
+            if let Some(remaining) = frame.remaining.as_mut() {
+                *remaining -= 1;
+            }
+            let node_index = self.count;
+            self.count += 1;
+            self.pos = offset.next_tag_offset();
+            if let Some(layout) = self.layout(offset.id()) {
+                let (prefix_size, remaining) = layout.parse_prefix(offset.value_slice(self.raw))?;
+                if prefix_size > offset.value_size() {
+                    return Err(ErrorKind::CorruptedData);
+                }
+                self.pos = offset.value_offset() + prefix_size;
+                self.stack.push(RawWalkFrame {
+                    end: offset.next_tag_offset(),
+                    parent: Some(node_index),
+                    remaining,
+                    next_index: 0,
+                });
+            }
+            return Ok(Some(node));
+        }
+    }
+}
+
+impl<'a> Iterator for RawTagWalker<'a> {
+    type Item = Result<RawTagNode>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.failed {
+            return None;
+        }
+        match self.next_node() {
+            Ok(Some(node)) => Some(Ok(node)),
+            Ok(None) => None,
+            Err(e) => {
+                self.failed = true;
+                Some(Err(e))
+            }
+        }
+    }
+}
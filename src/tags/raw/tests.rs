@@ -132,21 +132,14 @@ fn test_rawragoffset_impl_value_slice() {
 //=============================================================================
 // Samples
 //-----------------------------------------------------------------------------
-macro_rules! vec_push_new_tag {
-    ($v: expr, $tag_type: ty) => {
-        $v.push(Box::new(<$tag_type>::new()));
-    };
-    ($v: expr, $tag_type: ty, $($next_tags: ty), +) => {
-        vec_push_new_tag!($v, $tag_type);
-        vec_push_new_tag!($v, $($next_tags), +);
+macro_rules! vec_new_tags {
+    ($($tag_type: ty), +) => {
+        vec![$(Box::new(<$tag_type>::new()) as Box<dyn ILTag>), +]
     };
 }
 
 fn create_sample_tag_seq() -> Vec<Box<dyn ILTag>> {
-    let mut resp = Vec::<Box<dyn ILTag>>::new();
-
-    vec_push_new_tag!(
-        resp,
+    let mut resp = vec_new_tags!(
         ILBin128Tag,
         ILBin32Tag,
         ILBin64Tag,
@@ -180,7 +173,7 @@ fn test_serialize_tag_seq() {
 
     let serialized = serialize_tag_seq(&tags);
 
-    let mut size = 0 as u64;
+    let mut size = 0_u64;
     for t in tags {
         size += t.size();
     }
@@ -197,7 +190,7 @@ fn test_rawragscanner_next_tag() {
     let mut reader = ByteArrayReader::new(serialized.as_slice());
 
     let mut scanner = RawTagScanner::new(&mut reader);
-    let mut offs = 0 as u64;
+    let mut offs = 0_u64;
     for exp in tags {
         // Collect the values
         let next_offs = offs + exp.size();
@@ -221,7 +214,7 @@ fn test_rawragscanner_next_tag_if_id() {
     let mut reader = ByteArrayReader::new(serialized.as_slice());
 
     let mut scanner = RawTagScanner::new(&mut reader);
-    let mut offs = 0 as u64;
+    let mut offs = 0_u64;
     for exp in tags {
         // Collect the values
         let next_offs = offs + exp.size();
@@ -245,3 +238,463 @@ fn test_rawragscanner_next_tag_if_id() {
         Err(ErrorKind::UnexpectedTagType)
     ));
 }
+
+//=============================================================================
+// RawContainerLayout
+//-----------------------------------------------------------------------------
+#[test]
+fn test_standard_container_layout() {
+    assert_eq!(
+        standard_container_layout(IL_ILTAGSEQ_TAG_ID)
+            .unwrap()
+            .parse_prefix(&[])
+            .unwrap(),
+        (0, None)
+    );
+    assert_eq!(
+        standard_container_layout(IL_ILTAGARRAY_TAG_ID)
+            .unwrap()
+            .parse_prefix(&[0xF8, 0x01])
+            .unwrap(),
+        (2, Some(0xF9))
+    );
+    assert_eq!(
+        standard_container_layout(IL_DICTIONARY_TAG_ID)
+            .unwrap()
+            .parse_prefix(&[3])
+            .unwrap(),
+        (1, Some(6))
+    );
+    assert_eq!(
+        standard_container_layout(IL_STRING_DICTIONARY_TAG_ID)
+            .unwrap()
+            .parse_prefix(&[3])
+            .unwrap(),
+        (1, Some(6))
+    );
+    assert!(matches!(
+        standard_container_layout(IL_ILTAGARRAY_TAG_ID)
+            .unwrap()
+            .parse_prefix(&[]),
+        Err(ErrorKind::CorruptedData)
+    ));
+    assert!(matches!(
+        standard_container_layout(IL_DICTIONARY_TAG_ID)
+            .unwrap()
+            .parse_prefix(&[0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0x00]),
+        Err(ErrorKind::CorruptedData)
+    ));
+    assert!(standard_container_layout(IL_STRING_TAG_ID).is_none());
+    assert!(standard_container_layout(IL_ILINTARRAY_TAG_ID).is_none());
+}
+
+struct CustomLayout;
+
+impl RawContainerLayout for CustomLayout {
+    fn parse_prefix(&self, value: &[u8]) -> Result<(u64, Option<u64>)> {
+        if value.is_empty() {
+            Err(ErrorKind::CorruptedData)
+        } else {
+            Ok((1, Some(value[0] as u64)))
+        }
+    }
+}
+
+#[test]
+fn test_rawlayoutregistry() {
+    let mut r = RawLayoutRegistry::new();
+    assert!(r.get(IL_ILTAGSEQ_TAG_ID).is_some());
+    assert!(r.get(1234).is_none());
+
+    r.register(1234, Box::new(CustomLayout));
+    assert_eq!(
+        r.get(1234).unwrap().parse_prefix(&[2]).unwrap(),
+        (1, Some(2))
+    );
+
+    // Override
+    r.register(IL_ILTAGSEQ_TAG_ID, Box::new(CustomLayout));
+    assert_eq!(
+        r.get(IL_ILTAGSEQ_TAG_ID)
+            .unwrap()
+            .parse_prefix(&[2])
+            .unwrap(),
+        (1, Some(2))
+    );
+}
+
+//=============================================================================
+// RawTagWalker
+//-----------------------------------------------------------------------------
+fn create_sample_tree() -> Vec<u8> {
+    let mut dict = ILDictTag::new();
+    dict.insert("k", Box::new(ILUInt8Tag::with_value(1)));
+
+    let mut array = ILTagArrayTag::new();
+    array.mut_value().push(Box::new(dict));
+    array
+        .mut_value()
+        .push(Box::new(ILStringTag::with_value("x")));
+
+    let mut seq = ILTagSeqTag::new();
+    seq.mut_value().push(Box::new(array));
+    seq.mut_value().push(Box::new(ILTagSeqTag::new()));
+    seq.mut_value().push(Box::new(ILNullTag::new()));
+
+    let mut raw = seq.to_bytes().unwrap();
+    raw.extend(ILBoolTag::with_value(true).to_bytes().unwrap());
+    raw
+}
+
+#[test]
+fn test_rawtagwalker() {
+    let raw = create_sample_tree();
+    let nodes: Vec<RawTagNode> = RawTagWalker::new(&raw).map(|n| n.unwrap()).collect();
+
+    // (id, depth, parent, index)
+    let exp: [(u64, usize, Option<usize>, u64); 9] = [
+        (IL_ILTAGSEQ_TAG_ID, 0, None, 0),
+        (IL_ILTAGARRAY_TAG_ID, 1, Some(0), 0),
+        (IL_DICTIONARY_TAG_ID, 2, Some(1), 0),
+        (IL_STRING_TAG_ID, 3, Some(2), 0),
+        (IL_UINT8_TAG_ID, 3, Some(2), 1),
+        (IL_STRING_TAG_ID, 2, Some(1), 1),
+        (IL_ILTAGSEQ_TAG_ID, 1, Some(0), 1),
+        (IL_NULL_TAG_ID, 1, Some(0), 2),
+        (IL_BOOL_TAG_ID, 0, None, 1),
+    ];
+    assert_eq!(nodes.len(), 9);
+    for (node, exp) in nodes.iter().zip(exp.iter()) {
+        assert_eq!(node.offset().id(), exp.0);
+        assert_eq!(node.depth(), exp.1);
+        assert_eq!(node.parent(), exp.2);
+        assert_eq!(node.index(), exp.3);
+        if let Some(parent) = node.parent() {
+            let parent = nodes[parent].offset();
+            assert!(node.offset().offset() >= parent.value_offset());
+            assert!(node.offset().next_tag_offset() <= parent.next_tag_offset());
+        }
+    }
+
+    // Check the slices
+    assert_eq!(nodes[0].offset().tag_slice(&raw), &raw[..raw.len() - 2]);
+    assert_eq!(nodes[3].offset().value_slice(&raw), b"k");
+    assert_eq!(nodes[4].offset().value_slice(&raw), &[1]);
+    assert_eq!(nodes[5].offset().value_slice(&raw), b"x");
+    assert_eq!(nodes[8].offset().tag_slice(&raw), &raw[raw.len() - 2..]);
+
+    assert_eq!(RawTagWalker::new(&[]).count(), 0);
+}
+
+#[test]
+fn test_rawtagwalker_flat() {
+    let tags = create_sample_tag_seq();
+    let serialized = serialize_tag_seq(&tags);
+    let mut offs = 0_u64;
+    let mut count = 0;
+    for (node, exp) in RawTagWalker::new(&serialized).zip(tags.iter()) {
+        let node = node.unwrap();
+        assert_eq!(node.offset().id(), exp.id());
+        assert_eq!(node.offset().offset(), offs);
+        assert_eq!(node.depth(), 0);
+        assert_eq!(node.parent(), None);
+        assert_eq!(node.index(), count);
+        offs += exp.size();
+        count += 1;
+    }
+    assert_eq!(count, tags.len() as u64);
+}
+
+#[test]
+fn test_rawtagwalker_with_layouts() {
+    // Custom container with a prefix that holds the number of children.
+    let mut value: Vec<u8> = vec![2];
+    value.extend(ILUInt8Tag::with_value(1).to_bytes().unwrap());
+    value.extend(ILUInt8Tag::with_value(2).to_bytes().unwrap());
+    let raw = ILRawTag::with_value(1234, &value).to_bytes().unwrap();
+
+    // Unknown by default
+    let nodes: Vec<RawTagNode> = RawTagWalker::new(&raw).map(|n| n.unwrap()).collect();
+    assert_eq!(nodes.len(), 1);
+
+    let mut layouts = RawLayoutRegistry::new();
+    layouts.register(1234, Box::new(CustomLayout));
+    let nodes: Vec<RawTagNode> = RawTagWalker::with_layouts(&raw, &layouts)
+        .map(|n| n.unwrap())
+        .collect();
+    assert_eq!(nodes.len(), 3);
+    assert_eq!(nodes[0].offset().id(), 1234);
+    for (i, n) in nodes[1..].iter().enumerate() {
+        assert_eq!(n.offset().id(), IL_UINT8_TAG_ID);
+        assert_eq!(n.depth(), 1);
+        assert_eq!(n.parent(), Some(0));
+        assert_eq!(n.index(), i as u64);
+        assert_eq!(n.offset().value_slice(&raw), &[i as u8 + 1]);
+    }
+
+    // Wrong number of children
+    let mut value = value.clone();
+    value[0] = 3;
+    let raw = ILRawTag::with_value(1234, &value).to_bytes().unwrap();
+    let mut walker = RawTagWalker::with_layouts(&raw, &layouts);
+    for _ in 0..3 {
+        assert!(walker.next().unwrap().is_ok());
+    }
+    assert!(matches!(walker.next(), Some(Err(ErrorKind::CorruptedData))));
+    assert!(walker.next().is_none());
+}
+
+#[test]
+fn test_rawtagwalker_corrupted() {
+    // Array with less elements than expected
+    let raw: [u8; 5] = [IL_ILTAGARRAY_TAG_ID as u8, 3, 2, IL_NULL_TAG_ID as u8, 0];
+    let mut walker = RawTagWalker::new(&raw[..4]);
+    assert!(matches!(walker.next(), Some(Err(ErrorKind::CorruptedData))));
+    assert!(walker.next().is_none());
+
+    let raw: [u8; 4] = [IL_ILTAGARRAY_TAG_ID as u8, 2, 2, IL_NULL_TAG_ID as u8];
+    let mut walker = RawTagWalker::new(&raw);
+    assert!(walker.next().unwrap().is_ok());
+    assert!(walker.next().unwrap().is_ok());
+    assert!(matches!(walker.next(), Some(Err(ErrorKind::CorruptedData))));
+    assert!(walker.next().is_none());
+
+    // Array with more elements than expected
+    let raw: [u8; 5] = [
+        IL_ILTAGARRAY_TAG_ID as u8,
+        3,
+        1,
+        IL_NULL_TAG_ID as u8,
+        IL_NULL_TAG_ID as u8,
+    ];
+    let mut walker = RawTagWalker::new(&raw);
+    assert!(walker.next().unwrap().is_ok());
+    assert!(walker.next().unwrap().is_ok());
+    assert!(matches!(walker.next(), Some(Err(ErrorKind::CorruptedData))));
+    assert!(walker.next().is_none());
+
+    // Array without the count
+    let raw: [u8; 2] = [IL_ILTAGARRAY_TAG_ID as u8, 0];
+    let mut walker = RawTagWalker::new(&raw);
+    assert!(matches!(walker.next(), Some(Err(ErrorKind::CorruptedData))));
+    assert!(walker.next().is_none());
+
+    // Child crosses the end of the parent
+    let raw: [u8; 5] = [IL_ILTAGSEQ_TAG_ID as u8, 2, IL_STRING_TAG_ID as u8, 1, b'a'];
+    let mut walker = RawTagWalker::new(&raw);
+    assert!(walker.next().unwrap().is_ok());
+    assert!(matches!(walker.next(), Some(Err(ErrorKind::CorruptedData))));
+    assert!(walker.next().is_none());
+
+    // Truncated top level tag
+    let raw: [u8; 2] = [IL_UINT16_TAG_ID as u8, 0];
+    let mut walker = RawTagWalker::new(&raw);
+    assert!(matches!(walker.next(), Some(Err(ErrorKind::CorruptedData))));
+    assert!(walker.next().is_none());
+}
+
+#[test]
+fn test_rawtagwalker_deep() {
+    const LEVELS: usize = 100000;
+
+    // Compute the value size of each level, from the innermost to the outermost
+    let mut value_sizes: Vec<u64> = Vec::with_capacity(LEVELS);
+    let mut value_size = 0;
+    for _ in 0..LEVELS {
+        value_sizes.push(value_size);
+        value_size = (crate::ilint::encoded_size(IL_ILTAGSEQ_TAG_ID)
+            + crate::ilint::encoded_size(value_size)) as u64
+            + value_size;
+    }
+    let mut writer = VecWriter::new();
+    for value_size in value_sizes.iter().rev() {
+        serialize_ilint(IL_ILTAGSEQ_TAG_ID, &mut writer).unwrap();
+        serialize_ilint(*value_size, &mut writer).unwrap();
+    }
+    let raw: Vec<u8> = writer.into();
+    assert_eq!(raw.len() as u64, value_size);
+
+    let mut depth = 0;
+    for (i, node) in RawTagWalker::new(&raw).enumerate() {
+        let node = node.unwrap();
+        assert_eq!(node.depth(), depth);
+        assert_eq!(node.parent(), if i == 0 { None } else { Some(i - 1) });
+        depth += 1;
+    }
+    assert_eq!(depth, LEVELS);
+}
+
+#[test]
+fn test_rawragscanner_next_tag_eof() {
+    let tags = create_sample_tag_seq();
+    let serialized = serialize_tag_seq(&tags);
+
+    // Ends exactly at the end of the last tag
+    let mut reader = ByteArrayReader::new(serialized.as_slice());
+    let mut scanner = RawTagScanner::new(&mut reader);
+    for _ in 0..tags.len() {
+        assert!(scanner.next_tag().unwrap().is_some());
+    }
+    assert!(scanner.next_tag().unwrap().is_none());
+    assert!(scanner.next_tag().unwrap().is_none());
+
+    // Empty
+    let mut reader = ByteArrayReader::new(&[]);
+    let mut scanner = RawTagScanner::new(&mut reader);
+    assert!(scanner.next_tag().unwrap().is_none());
+
+    // Truncated in every possible position of the last tag
+    let last_size = tags.last().unwrap().size() as usize;
+    for cut in 1..last_size {
+        let mut reader = ByteArrayReader::new(&serialized[..serialized.len() - cut]);
+        let mut scanner = RawTagScanner::new(&mut reader);
+        for _ in 0..tags.len() - 1 {
+            assert!(scanner.next_tag().unwrap().is_some());
+        }
+        assert!(matches!(scanner.next_tag(), Err(ErrorKind::CorruptedData)));
+    }
+}
+
+#[test]
+fn test_rawragscanner_next_tag_truncated_header() {
+    // Multibyte tag id
+    let raw: [u8; 2] = [0xF9, 0x01];
+    let mut reader = ByteArrayReader::new(&raw);
+    let mut scanner = RawTagScanner::new(&mut reader);
+    assert!(matches!(scanner.next_tag(), Err(ErrorKind::CorruptedData)));
+
+    // Multibyte size
+    let raw: [u8; 2] = [IL_STRING_TAG_ID as u8, 0xF9];
+    let mut reader = ByteArrayReader::new(&raw);
+    let mut scanner = RawTagScanner::new(&mut reader);
+    assert!(matches!(scanner.next_tag(), Err(ErrorKind::CorruptedData)));
+
+    // Implicit ILInt
+    let raw: [u8; 2] = [IL_ILINT_TAG_ID as u8, 0xF9];
+    let mut reader = ByteArrayReader::new(&raw);
+    let mut scanner = RawTagScanner::new(&mut reader);
+    assert!(matches!(scanner.next_tag(), Err(ErrorKind::CorruptedData)));
+
+    // Reserved
+    let raw: [u8; 2] = [15, 0];
+    let mut reader = ByteArrayReader::new(&raw);
+    let mut scanner = RawTagScanner::new(&mut reader);
+    assert!(matches!(scanner.next_tag(), Err(ErrorKind::CorruptedData)));
+}
+
+#[test]
+fn test_rawragscanner_iterator() {
+    let tags = create_sample_tag_seq();
+    let serialized = serialize_tag_seq(&tags);
+
+    let mut reader = ByteArrayReader::new(serialized.as_slice());
+    let scanner = RawTagScanner::new(&mut reader);
+    let offsets: Vec<RawTagOffset> = scanner.map(|t| t.unwrap()).collect();
+    assert_eq!(offsets.len(), tags.len());
+    let mut offs = 0_u64;
+    for (t, exp) in offsets.iter().zip(tags.iter()) {
+        assert_eq!(t.id(), exp.id());
+        assert_eq!(t.offset(), offs);
+        offs += exp.size();
+    }
+
+    // Stops after the first error
+    let mut reader = ByteArrayReader::new(&serialized[..serialized.len() - 1]);
+    let mut scanner = RawTagScanner::new(&mut reader);
+    for _ in 0..tags.len() - 1 {
+        assert!(scanner.next().unwrap().is_ok());
+    }
+    assert!(matches!(
+        scanner.next(),
+        Some(Err(ErrorKind::CorruptedData))
+    ));
+    assert!(scanner.next().is_none());
+}
+
+#[test]
+fn test_rawragscanner_dyn_reader() {
+    let tags = create_sample_tag_seq();
+    let serialized = serialize_tag_seq(&tags);
+
+    let mut reader = ByteArrayReader::new(serialized.as_slice());
+    let reader: &mut dyn Reader = &mut reader;
+    let mut scanner: RawTagScanner<dyn Reader> = RawTagScanner::new(reader);
+    for exp in tags.iter() {
+        assert_eq!(scanner.next_tag().unwrap().unwrap().id(), exp.id());
+    }
+    assert!(scanner.next_tag().unwrap().is_none());
+}
+
+#[test]
+fn test_rawragscanner_next_tag_eof_std_readers() {
+    use crate::io::std_io::{BufferedReader, ReadReader};
+
+    let tags = create_sample_tag_seq();
+    let serialized = serialize_tag_seq(&tags);
+    let last_size = tags.last().unwrap().size() as usize;
+
+    // ReadReader
+    let mut src = std::io::Cursor::new(serialized.as_slice());
+    let mut reader = ReadReader::new(&mut src);
+    let mut scanner = RawTagScanner::new(&mut reader);
+    for _ in 0..tags.len() {
+        assert!(scanner.next_tag().unwrap().is_some());
+    }
+    assert!(scanner.next_tag().unwrap().is_none());
+    for cut in 1..last_size {
+        let mut src = std::io::Cursor::new(&serialized[..serialized.len() - cut]);
+        let mut reader = ReadReader::new(&mut src);
+        let mut scanner = RawTagScanner::new(&mut reader);
+        for _ in 0..tags.len() - 1 {
+            assert!(scanner.next_tag().unwrap().is_some());
+        }
+        assert!(matches!(scanner.next_tag(), Err(ErrorKind::CorruptedData)));
+    }
+
+    // BufferedReader
+    let mut src = std::io::Cursor::new(serialized.as_slice());
+    let mut reader = BufferedReader::with_capacity(&mut src, 3);
+    let mut scanner = RawTagScanner::new(&mut reader);
+    for _ in 0..tags.len() {
+        assert!(scanner.next_tag().unwrap().is_some());
+    }
+    assert!(scanner.next_tag().unwrap().is_none());
+    for cut in 1..last_size {
+        let mut src = std::io::Cursor::new(&serialized[..serialized.len() - cut]);
+        let mut reader = BufferedReader::with_capacity(&mut src, 3);
+        let mut scanner = RawTagScanner::new(&mut reader);
+        for _ in 0..tags.len() - 1 {
+            assert!(scanner.next_tag().unwrap().is_some());
+        }
+        assert!(matches!(scanner.next_tag(), Err(ErrorKind::CorruptedData)));
+    }
+}
+
+#[test]
+fn test_rawragscanner_next_tag_eof_read_seek() {
+    let tags = create_sample_tag_seq();
+    let serialized = serialize_tag_seq(&tags);
+    let last_size = tags.last().unwrap().size() as usize;
+
+    // Single tag
+    let single = tags[0].to_bytes().unwrap();
+    let mut src = std::io::Cursor::new(single.as_slice());
+    let mut scanner = RawTagScanner::new(&mut src);
+    assert_eq!(scanner.next_tag().unwrap().unwrap().id(), tags[0].id());
+    assert!(scanner.next_tag().unwrap().is_none());
+
+    let mut src = std::io::Cursor::new(serialized.as_slice());
+    let mut scanner = RawTagScanner::new(&mut src);
+    for _ in 0..tags.len() {
+        assert!(scanner.next_tag().unwrap().is_some());
+    }
+    assert!(scanner.next_tag().unwrap().is_none());
+    for cut in 1..last_size {
+        let mut src = std::io::Cursor::new(&serialized[..serialized.len() - cut]);
+        let mut scanner = RawTagScanner::new(&mut src);
+        for _ in 0..tags.len() - 1 {
+            assert!(scanner.next_tag().unwrap().is_some());
+        }
+        assert!(matches!(scanner.next_tag(), Err(ErrorKind::CorruptedData)));
+    }
+}
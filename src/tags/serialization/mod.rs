@@ -512,6 +512,91 @@ impl SignedILIntDeserializer for dyn Reader + '_ {
     }
 }
 
+//=============================================================================
+// CanonicalILIntDeserializer
+//-----------------------------------------------------------------------------
+/// This trait adds the ability to deserialize ILInt values that must be
+/// encoded in their canonical form. Non canonical values are reported as
+/// [`super::ErrorKind::NonCanonical`] with [`super::CanonicalRule::ILInt`].
+///
+/// New since 2.0.0.
+pub trait CanonicalILIntDeserializer {
+    /// Deserializes an ILInt value in its canonical form.
+    ///
+    /// Returns:
+    /// - Ok(v): For success;
+    /// - Err(_): For failure;
+    fn deserialize_canonical_ilint(&mut self) -> Result<u64>;
+
+    /// Deserializes a signed ILInt value in its canonical form.
+    ///
+    /// Returns:
+    /// - Ok(v): For success;
+    /// - Err(_): For failure;
+    fn deserialize_signed_canonical_ilint(&mut self) -> Result<i64>;
+
+    /// Deserializes an ILInt value, optionally rejecting non canonical forms.
+    ///
+    /// Arguments:
+    /// - `canonical`: If true, only the canonical form is accepted;
+    ///
+    /// Returns:
+    /// - Ok(v): For success;
+    /// - Err(_): For failure;
+    fn deserialize_ilint_with(&mut self, canonical: bool) -> Result<u64>;
+
+    /// Deserializes a signed ILInt value, optionally rejecting non canonical
+    /// forms.
+    ///
+    /// Arguments:
+    /// - `canonical`: If true, only the canonical form is accepted;
+    ///
+    /// Returns:
+    /// - Ok(v): For success;
+    /// - Err(_): For failure;
+    fn deserialize_signed_ilint_with(&mut self, canonical: bool) -> Result<i64>;
+}
+
+macro_rules! canonicalilintdeserializer_impl {
+    () => {
+        #[inline]
+        fn deserialize_canonical_ilint(&mut self) -> Result<u64> {
+            crate::ilint::canonical_decode(self).map_err(super::ErrorKind::from)
+        }
+
+        #[inline]
+        fn deserialize_signed_canonical_ilint(&mut self) -> Result<i64> {
+            crate::ilint::signed_canonical_decode(self).map_err(super::ErrorKind::from)
+        }
+
+        #[inline]
+        fn deserialize_ilint_with(&mut self, canonical: bool) -> Result<u64> {
+            if canonical {
+                self.deserialize_canonical_ilint()
+            } else {
+                Ok(read_ilint(self)?)
+            }
+        }
+
+        #[inline]
+        fn deserialize_signed_ilint_with(&mut self, canonical: bool) -> Result<i64> {
+            if canonical {
+                self.deserialize_signed_canonical_ilint()
+            } else {
+                Ok(read_signed_ilint(self)?)
+            }
+        }
+    };
+}
+
+impl<R: Reader> CanonicalILIntDeserializer for R {
+    canonicalilintdeserializer_impl!();
+}
+
+impl CanonicalILIntDeserializer for dyn Reader + '_ {
+    canonicalilintdeserializer_impl!();
+}
+
 //=============================================================================
 // SignedILIntSerializer
 //-----------------------------------------------------------------------------
@@ -32,7 +32,7 @@
 use super::*;
 use crate::io::array::{ByteArrayReader, VecWriter};
 use crate::io::data::test_samples::*;
-use crate::tags::ErrorKind;
+use crate::tags::{CanonicalRule, ErrorKind};
 
 //=============================================================================
 // Deserializer Tests
@@ -413,6 +413,97 @@ fn test_signedilintdeserializer_dyn_reader() {
     }
 }
 
+//=============================================================================
+// CanonicalILIntDeserializer
+//-----------------------------------------------------------------------------
+const NON_CANONICAL_ILINT: [u8; 3] = [0xF9, 0x00, 0x01];
+
+#[test]
+fn test_canonicalilintdeserializer_reader() {
+    let mut writer = VecWriter::new();
+    crate::ilint::encode(0x1234, &mut writer).unwrap();
+    crate::ilint::signed_encode(-0x1234, &mut writer).unwrap();
+
+    let mut reader = ByteArrayReader::new(writer.as_slice());
+    assert_eq!(reader.deserialize_canonical_ilint().unwrap(), 0x1234);
+    assert_eq!(
+        reader.deserialize_signed_canonical_ilint().unwrap(),
+        -0x1234
+    );
+    let mut reader = ByteArrayReader::new(writer.as_slice());
+    assert_eq!(reader.deserialize_ilint_with(true).unwrap(), 0x1234);
+    assert_eq!(reader.deserialize_signed_ilint_with(true).unwrap(), -0x1234);
+    let mut reader = ByteArrayReader::new(writer.as_slice());
+    assert_eq!(reader.deserialize_ilint_with(false).unwrap(), 0x1234);
+    assert_eq!(
+        reader.deserialize_signed_ilint_with(false).unwrap(),
+        -0x1234
+    );
+
+    let mut reader = ByteArrayReader::new(&NON_CANONICAL_ILINT);
+    assert!(matches!(
+        reader.deserialize_canonical_ilint(),
+        Err(ErrorKind::NonCanonical(CanonicalRule::ILInt))
+    ));
+    let mut reader = ByteArrayReader::new(&NON_CANONICAL_ILINT);
+    assert!(matches!(
+        reader.deserialize_signed_canonical_ilint(),
+        Err(ErrorKind::NonCanonical(CanonicalRule::ILInt))
+    ));
+    let mut reader = ByteArrayReader::new(&NON_CANONICAL_ILINT);
+    assert!(matches!(
+        reader.deserialize_ilint_with(true),
+        Err(ErrorKind::NonCanonical(CanonicalRule::ILInt))
+    ));
+    let mut reader = ByteArrayReader::new(&NON_CANONICAL_ILINT);
+    assert!(matches!(
+        reader.deserialize_signed_ilint_with(true),
+        Err(ErrorKind::NonCanonical(CanonicalRule::ILInt))
+    ));
+    let mut reader = ByteArrayReader::new(&NON_CANONICAL_ILINT);
+    assert_eq!(reader.deserialize_ilint_with(false).unwrap(), 0xF9);
+    let mut reader = ByteArrayReader::new(&NON_CANONICAL_ILINT);
+    assert_eq!(reader.deserialize_signed_ilint_with(false).unwrap(), -125);
+
+    // Other errors
+    let mut reader = ByteArrayReader::new(&NON_CANONICAL_ILINT[..2]);
+    assert!(matches!(
+        reader.deserialize_canonical_ilint(),
+        Err(ErrorKind::IOError(_))
+    ));
+    let overflow: [u8; 9] = [0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF];
+    let mut reader = ByteArrayReader::new(&overflow);
+    assert!(matches!(
+        reader.deserialize_canonical_ilint(),
+        Err(ErrorKind::IOError(crate::io::ErrorKind::CorruptedData))
+    ));
+}
+
+#[test]
+fn test_canonicalilintdeserializer_dyn_reader() {
+    let mut writer = VecWriter::new();
+    crate::ilint::encode(0x1234, &mut writer).unwrap();
+    crate::ilint::signed_encode(-0x1234, &mut writer).unwrap();
+
+    let mut inner_reader = ByteArrayReader::new(writer.as_slice());
+    let reader: &mut dyn Reader = &mut inner_reader;
+    assert_eq!(reader.deserialize_ilint_with(true).unwrap(), 0x1234);
+    assert_eq!(reader.deserialize_signed_ilint_with(true).unwrap(), -0x1234);
+
+    let mut inner_reader = ByteArrayReader::new(&NON_CANONICAL_ILINT);
+    let reader: &mut dyn Reader = &mut inner_reader;
+    assert!(matches!(
+        reader.deserialize_ilint_with(true),
+        Err(ErrorKind::NonCanonical(CanonicalRule::ILInt))
+    ));
+    let mut inner_reader = ByteArrayReader::new(&NON_CANONICAL_ILINT);
+    let reader: &mut dyn Reader = &mut inner_reader;
+    assert!(matches!(
+        reader.deserialize_signed_canonical_ilint(),
+        Err(ErrorKind::NonCanonical(CanonicalRule::ILInt))
+    ));
+}
+
 //=============================================================================
 // SignedILIntSerializer
 //-----------------------------------------------------------------------------
@@ -37,9 +37,11 @@ mod tests;
 use super::constants::*;
 use super::{DefaultWithId, ErrorKind, ILTag, ILTagFactory, Result};
 use crate::io::{LimitedReader, Reader, Writer};
+use crate::tags::container::*;
+use crate::tags::limits::DEFAULT_LIMITS;
 use crate::tags::serialization::*;
 use crate::tags::util::limited_reader_ensure_empty;
-use crate::tags::{tag_size_to_usize, ILRawTag};
+use crate::tags::{CanonicalRule, DeserializationLimits, DuplicateKeyPolicy, ILRawTag};
 use ::std::any::Any;
 use ::std::collections::HashMap;
 
@@ -49,7 +51,7 @@ use ::std::collections::HashMap;
 /// This struct must contain a single field called inner using the type
 /// ILRawTag. Example:
 ///
-/// ```
+/// ```ignore
 /// pub struct NewStruct {
 ///     inner: ILRawTag,
 /// }
@@ -213,10 +215,11 @@ impl ILTag for ILStringTag {
 
     fn deserialize_value(
         &mut self,
-        _factory: &dyn ILTagFactory,
+        factory: &dyn ILTagFactory,
         value_size: usize,
         reader: &mut dyn Reader,
     ) -> Result<()> {
+        factory.limits().check_value_length(value_size as u64)?;
         let tmp = reader.deserialize_bytes(value_size)?;
         match ::std::str::from_utf8(tmp.as_slice()) {
             Ok(v) => {
@@ -278,13 +281,48 @@ pub fn deserialize_string_tag_from_value_into(
     reader: &mut dyn Reader,
     output: &mut String,
 ) -> Result<()> {
-    let id = reader.deserialize_ilint()?;
+    deserialize_string_tag_from_value_with(reader, &DEFAULT_LIMITS, false, output)
+}
+
+/// Extracts a string value from a standard string tag directly from
+/// the data stream and put it inside an existing [`std::string::String`]
+/// while enforcing the [`crate::tags::DeserializationLimits`] and the
+/// canonical mode of the given factory.
+///
+/// Arguments:
+/// - `reader`: The reader;
+/// - `factory`: The current tag factory;
+/// - `output`: The string instance that will hold the result;
+///
+/// Returns:
+/// - `Ok(String)`: The string extracted from the data stream;
+/// - `Err(e)`: In case of error.
+///
+/// New since 2.0.0.
+pub fn deserialize_checked_string_tag_from_value_into(
+    reader: &mut dyn Reader,
+    factory: &dyn ILTagFactory,
+    output: &mut String,
+) -> Result<()> {
+    deserialize_string_tag_from_value_with(reader, factory.limits(), factory.canonical(), output)
+}
+
+/// Extracts a string value from a standard string tag while enforcing the
+/// given limits and, optionally, the canonical form of the ILInt values.
+fn deserialize_string_tag_from_value_with(
+    reader: &mut dyn Reader,
+    limits: &DeserializationLimits,
+    canonical: bool,
+    output: &mut String,
+) -> Result<()> {
+    let id = reader.deserialize_ilint_with(canonical)?;
     if id != IL_STRING_TAG_ID {
         return Err(ErrorKind::CorruptedData);
     }
-    let len = reader.deserialize_ilint()?;
-    // Performs this conversion to ensure that
-    let usize_len = tag_size_to_usize(len)?;
+    let len = reader.deserialize_ilint_with(canonical)?;
+    limits.check_value_length(len)?;
+    // Performs this conversion to ensure that the size is acceptable
+    let usize_len = limits.check_tag_size(len)?;
     let tmp = reader.deserialize_bytes(usize_len)?;
     let s = match ::std::str::from_utf8(tmp.as_slice()) {
         Ok(v) => v,
@@ -309,6 +347,122 @@ pub fn deserialize_string_tag_from_value(reader: &mut dyn Reader) -> Result<Stri
     Ok(ret)
 }
 
+/// Extracts a string value from a standard string tag directly from
+/// the data stream while enforcing the
+/// [`crate::tags::DeserializationLimits`] and the canonical mode of the
+/// given factory.
+///
+/// Arguments:
+/// - `reader`: The reader;
+/// - `factory`: The current tag factory;
+///
+/// Returns:
+/// - `Ok(std::string::String)`: The string extracted from the data stream;
+/// - `Err(e)`: In case of error.
+///
+/// New since 2.0.0.
+pub fn deserialize_checked_string_tag_from_value(
+    reader: &mut dyn Reader,
+    factory: &dyn ILTagFactory,
+) -> Result<String> {
+    let mut ret = String::default();
+    deserialize_checked_string_tag_from_value_into(reader, factory, &mut ret)?;
+    Ok(ret)
+}
+
+/// Verifies if the given big endian two's complement value is encoded using
+/// the smallest possible number of bytes. Since at least one byte is always
+/// required, zero must be encoded as a single 0x00.
+///
+/// Arguments:
+/// - `value`: The value to be verified;
+///
+/// Returns true if the value is in its minimal form or false otherwise.
+///
+/// New since 2.0.0.
+pub fn is_minimal_twos_complement(value: &[u8]) -> bool {
+    match value {
+        [] => false,
+        [0x00, next, ..] => next & 0x80 != 0,
+        [0xFF, next, ..] => next & 0x80 == 0,
+        _ => true,
+    }
+}
+
+/// Verifies if the given two's complement value is minimal when the factory
+/// is in canonical mode.
+#[inline]
+fn check_canonical_twos_complement(factory: &dyn ILTagFactory, value: &[u8]) -> Result<()> {
+    if factory.canonical() && !is_minimal_twos_complement(value) {
+        Err(ErrorKind::NonCanonical(CanonicalRule::TwosComplement))
+    } else {
+        Ok(())
+    }
+}
+
+/// Verifies if a dictionary key follows the previous one when the factory
+/// requires sorted keys or is in canonical mode. In canonical mode, repeated
+/// keys are also rejected.
+///
+/// Arguments:
+/// - `factory`: The factory used to deserialize the dictionary;
+/// - `first`: Indicates if the key is the first one of the dictionary;
+/// - `previous`: The previous key. It is updated with the current key;
+/// - `key`: The current key;
+fn check_dict_key_order(
+    factory: &dyn ILTagFactory,
+    first: bool,
+    previous: &mut String,
+    key: &str,
+) -> Result<()> {
+    let canonical = factory.canonical();
+    if !canonical && !factory.sorted_keys() {
+        return Ok(());
+    }
+    if !first {
+        match previous.as_str().cmp(key) {
+            std::cmp::Ordering::Less => (),
+            std::cmp::Ordering::Equal => {
+                if canonical {
+                    return Err(ErrorKind::NonCanonical(CanonicalRule::DictDuplicateKey));
+                }
+            }
+            std::cmp::Ordering::Greater => {
+                return Err(if canonical {
+                    ErrorKind::NonCanonical(CanonicalRule::DictKeyOrder)
+                } else {
+                    ErrorKind::UnsortedKeys
+                })
+            }
+        }
+    }
+    previous.clear();
+    previous.push_str(key);
+    Ok(())
+}
+
+/// Applies the duplicate key policy of the factory to a new dictionary entry.
+///
+/// Arguments:
+/// - `factory`: The factory used to deserialize the dictionary;
+/// - `duplicate`: Indicates if the key of the entry is already in use;
+///
+/// Returns:
+/// - Ok(true): If the entry must be inserted;
+/// - Ok(false): If the entry must be discarded;
+/// - Err(ErrorKind::DuplicateKey): If the duplicate key must be rejected;
+#[inline]
+fn apply_duplicate_key_policy(factory: &dyn ILTagFactory, duplicate: bool) -> Result<bool> {
+    if !duplicate {
+        return Ok(true);
+    }
+    match factory.duplicate_key_policy() {
+        DuplicateKeyPolicy::Reject => Err(ErrorKind::DuplicateKey),
+        DuplicateKeyPolicy::FirstWins => Ok(false),
+        DuplicateKeyPolicy::LastWins => Ok(true),
+    }
+}
+
 //=============================================================================
 // ILBigIntTag
 //-----------------------------------------------------------------------------
@@ -323,7 +477,27 @@ impl ILBigIntTag {
     std_byte_array_tag_func_impl!(IL_BINT_TAG_ID);
 }
 
-inner_iltag_default_impl!(ILBigIntTag);
+impl ILTag for ILBigIntTag {
+    inner_iltag_base_func_impl!();
+
+    fn value_size(&self) -> u64 {
+        self.inner.value_size()
+    }
+
+    fn serialize_value(&self, writer: &mut dyn Writer) -> Result<()> {
+        self.inner.serialize_value(writer)
+    }
+
+    fn deserialize_value(
+        &mut self,
+        factory: &dyn ILTagFactory,
+        value_size: usize,
+        reader: &mut dyn Reader,
+    ) -> Result<()> {
+        self.inner.deserialize_value(factory, value_size, reader)?;
+        check_canonical_twos_complement(factory, self.inner.value())
+    }
+}
 
 impl Default for ILBigIntTag {
     fn default() -> Self {
@@ -430,7 +604,8 @@ impl ILTag for ILBigDecTag {
         }
         self.scale = reader.deserialize_value()?;
         self.inner
-            .deserialize_value(factory, value_size - 4, reader)
+            .deserialize_value(factory, value_size - 4, reader)?;
+        check_canonical_twos_complement(factory, self.inner.value())
     }
 }
 
@@ -517,26 +692,28 @@ impl ILTag for ILILIntArrayTag {
     fn serialize_value(&self, writer: &mut dyn Writer) -> Result<()> {
         writer.serialize_ilint(self.value.len() as u64)?;
         for v in self.value.as_slice() {
-            writer.serialize_ilint(*v as u64)?;
+            writer.serialize_ilint(*v)?;
         }
         Ok(())
     }
 
     fn deserialize_value(
         &mut self,
-        _factory: &dyn ILTagFactory,
+        factory: &dyn ILTagFactory,
         value_size: usize,
         reader: &mut dyn Reader,
     ) -> Result<()> {
+        let canonical = factory.canonical();
         let mut lreader = LimitedReader::new(reader, value_size);
-        let count = lreader.deserialize_ilint()?;
+        let count = lreader.deserialize_ilint_with(canonical)?;
         if count > value_size as u64 {
             return Err(ErrorKind::CorruptedData);
         }
+        factory.limits().check_elements(count)?;
         self.value.clear();
         self.value.reserve(count as usize);
         for _i in 0..count {
-            self.value.push(lreader.deserialize_ilint()?);
+            self.value.push(lreader.deserialize_ilint_with(canonical)?);
         }
         limited_reader_ensure_empty(&lreader, ErrorKind::CorruptedData)
     }
@@ -584,42 +761,55 @@ impl ILTagSeqTag {
     }
 }
 
-impl ILTag for ILTagSeqTag {
-    iltag_base_func_impl!();
-
-    fn value_size(&self) -> u64 {
-        let mut size: u64 = 0;
-        for v in self.value.as_slice() {
-            size += v.size();
-        }
-        size
+impl ILTagContainer for ILTagSeqTag {
+    fn value_parts(&self) -> Vec<ILTagValuePart<'_>> {
+        self.value
+            .iter()
+            .map(|v| ILTagValuePart::Tag(v.as_ref()))
+            .collect()
     }
 
-    fn serialize_value(&self, writer: &mut dyn Writer) -> Result<()> {
-        for v in self.value.as_slice() {
-            v.serialize(writer)?;
-        }
-        Ok(())
+    fn begin_deserialization(
+        &mut self,
+        _factory: &dyn ILTagFactory,
+        _value_size: usize,
+        _reader: &mut dyn Reader,
+    ) -> Result<Option<u64>> {
+        self.value.clear();
+        Ok(None)
     }
 
-    fn deserialize_value(
+    fn deserialize_child_prefix(
         &mut self,
         factory: &dyn ILTagFactory,
-        value_size: usize,
-        reader: &mut dyn Reader,
+        _reader: &mut dyn Reader,
     ) -> Result<()> {
-        let mut lreader = LimitedReader::new(reader, value_size);
-        self.value.clear();
-        while !lreader.empty() {
-            let t = factory.deserialize(&mut lreader)?;
-            self.value.push(t);
-        }
+        factory.limits().check_elements(self.value.len() as u64 + 1)
+    }
+
+    fn push_child(&mut self, child: Box<dyn ILTag>) -> Result<()> {
+        self.value.push(child);
         Ok(())
     }
+
+    fn take_children(&mut self, children: &mut Vec<Box<dyn ILTag>>) {
+        children.append(&mut self.value);
+    }
+}
+
+impl ILTag for ILTagSeqTag {
+    iltag_base_func_impl!();
+    iltag_container_func_impl!();
 }
 
 iltag_default_impl!(ILTagSeqTag);
 
+impl Drop for ILTagSeqTag {
+    fn drop(&mut self) {
+        drop_container_children(self);
+    }
+}
+
 //=============================================================================
 // ILTagArrayTag
 //-----------------------------------------------------------------------------
@@ -660,40 +850,50 @@ impl ILTagArrayTag {
     }
 }
 
-impl ILTag for ILTagArrayTag {
-    inner_iltag_base_func_impl!();
-
-    fn value_size(&self) -> u64 {
-        let size: u64 = crate::ilint::encoded_size(self.inner.value.len() as u64) as u64;
-        size + self.inner.value_size()
-    }
-
-    fn serialize_value(&self, writer: &mut dyn Writer) -> Result<()> {
-        writer.serialize_ilint(self.inner.value.len() as u64)?;
-        self.inner.serialize_value(writer)
+impl ILTagContainer for ILTagArrayTag {
+    fn value_parts(&self) -> Vec<ILTagValuePart<'_>> {
+        let mut parts = Vec::with_capacity(self.inner.value.len() + 1);
+        parts.push(ILTagValuePart::ILInt(self.inner.value.len() as u64));
+        parts.extend(
+            self.inner
+                .value
+                .iter()
+                .map(|v| ILTagValuePart::Tag(v.as_ref())),
+        );
+        parts
     }
 
-    fn deserialize_value(
+    fn begin_deserialization(
         &mut self,
         factory: &dyn ILTagFactory,
         value_size: usize,
         reader: &mut dyn Reader,
-    ) -> Result<()> {
-        let mut lreader = LimitedReader::new(reader, value_size);
-
-        let count = lreader.deserialize_ilint()?;
+    ) -> Result<Option<u64>> {
+        let count = reader.deserialize_ilint_with(factory.canonical())?;
         if count > value_size as u64 {
             return Err(ErrorKind::CorruptedData);
         }
+        factory.limits().check_elements(count)?;
         self.inner.value.clear();
         self.inner.value.reserve(count as usize);
-        for _i in 0..count {
-            self.inner.value.push(factory.deserialize(&mut lreader)?);
-        }
-        limited_reader_ensure_empty(&lreader, ErrorKind::CorruptedData)
+        Ok(Some(count))
+    }
+
+    fn push_child(&mut self, child: Box<dyn ILTag>) -> Result<()> {
+        self.inner.value.push(child);
+        Ok(())
+    }
+
+    fn take_children(&mut self, children: &mut Vec<Box<dyn ILTag>>) {
+        self.inner.take_children(children);
     }
 }
 
+impl ILTag for ILTagArrayTag {
+    inner_iltag_base_func_impl!();
+    iltag_container_func_impl!();
+}
+
 iltag_default_impl!(ILTagArrayTag);
 
 //=============================================================================
@@ -772,12 +972,12 @@ impl ILTag for ILRangeTag {
 
     fn deserialize_value(
         &mut self,
-        _factory: &dyn ILTagFactory,
+        factory: &dyn ILTagFactory,
         value_size: usize,
         reader: &mut dyn Reader,
     ) -> Result<()> {
         let mut lreader = LimitedReader::new(reader, value_size);
-        self.start = lreader.deserialize_ilint()?;
+        self.start = lreader.deserialize_ilint_with(factory.canonical())?;
         self.count = lreader.deserialize_value()?;
         limited_reader_ensure_empty(&lreader, ErrorKind::CorruptedData)
     }
@@ -986,10 +1186,17 @@ iltag_default_impl!(ILOIDTag);
 /// To ensure maximum the stability of the serialized data, the keys are sorted
 /// according to the lexicographic order before the serialization.
 ///
+/// Since 2.0.0, duplicate keys found during the deserialization are handled
+/// according to [`ILTagFactory::duplicate_key_policy()`] and the order of the
+/// keys is verified if [`ILTagFactory::sorted_keys()`] is true.
+///
 /// By default it sets the tag id to [`IL_DICTIONARY_TAG_ID`].
 pub struct ILDictTag {
     id: u64,
     value: HashMap<String, Box<dyn ILTag>>,
+    pending_key: String,
+    previous_key: String,
+    discard_pending: bool,
 }
 
 impl ILDictTag {
@@ -1007,6 +1214,9 @@ impl ILDictTag {
         Self {
             id,
             value: HashMap::default(),
+            pending_key: String::new(),
+            previous_key: String::new(),
+            discard_pending: false,
         }
     }
 
@@ -1073,57 +1283,78 @@ impl ILDictTag {
     }
 }
 
-impl ILTag for ILDictTag {
-    iltag_base_func_impl!();
-
-    fn value_size(&self) -> u64 {
-        (crate::ilint::encoded_size(self.value.len() as u64) as u64)
-            + self
-                .value
-                .iter()
-                .map(|(k, v)| string_tag_size_from_value(k) + v.size())
-                .sum::<u64>()
+impl ILTagContainer for ILDictTag {
+    fn value_parts(&self) -> Vec<ILTagValuePart<'_>> {
+        let mut entries: Vec<(&String, &Box<dyn ILTag>)> = self.value.iter().collect();
+        entries.sort_unstable_by(|a, b| a.0.cmp(b.0));
+        let mut parts = Vec::with_capacity(2 * entries.len() + 1);
+        parts.push(ILTagValuePart::ILInt(entries.len() as u64));
+        for (k, v) in entries {
+            parts.push(ILTagValuePart::StringTag(k));
+            parts.push(ILTagValuePart::Tag(v.as_ref()));
+        }
+        parts
     }
 
-    fn serialize_value(&self, writer: &mut dyn Writer) -> Result<()> {
-        let mut keys: Vec<&str> = Vec::with_capacity(self.value.len());
-        for key in self.value.keys() {
-            keys.push(key);
-        }
-        keys.sort_unstable();
-        writer.serialize_ilint(self.value.len() as u64)?;
-        for key in keys {
-            let value = match self.value.get(key) {
-                Some(s) => s,
-                None => return Err(ErrorKind::UnableToSerialize),
-            };
-            serialize_string_tag_from_value(key, writer)?;
-            value.serialize(writer)?;
-        }
-        Ok(())
+    fn begin_deserialization(
+        &mut self,
+        factory: &dyn ILTagFactory,
+        _value_size: usize,
+        reader: &mut dyn Reader,
+    ) -> Result<Option<u64>> {
+        let count = reader.deserialize_ilint_with(factory.canonical())?;
+        factory.limits().check_elements(count)?;
+        self.value.clear();
+        Ok(Some(count))
     }
 
-    fn deserialize_value(
+    fn deserialize_child_prefix(
         &mut self,
         factory: &dyn ILTagFactory,
-        value_size: usize,
         reader: &mut dyn Reader,
     ) -> Result<()> {
-        let mut lreader = LimitedReader::new(reader, value_size);
-        let count = lreader.deserialize_ilint()?;
-        self.value.clear();
-        for _ in 0..count {
-            self.value.insert(
-                deserialize_string_tag_from_value(&mut lreader)?,
-                factory.deserialize(&mut lreader)?,
-            );
+        deserialize_checked_string_tag_from_value_into(reader, factory, &mut self.pending_key)?;
+        check_dict_key_order(
+            factory,
+            self.value.is_empty(),
+            &mut self.previous_key,
+            &self.pending_key,
+        )?;
+        self.discard_pending =
+            !apply_duplicate_key_policy(factory, self.value.contains_key(&self.pending_key))?;
+        Ok(())
+    }
+
+    fn child_key(&self) -> Option<&str> {
+        Some(&self.pending_key)
+    }
+
+    fn push_child(&mut self, child: Box<dyn ILTag>) -> Result<()> {
+        let key = std::mem::take(&mut self.pending_key);
+        if !self.discard_pending {
+            self.value.insert(key, child);
         }
-        limited_reader_ensure_empty(&lreader, ErrorKind::CorruptedData)
+        Ok(())
+    }
+
+    fn take_children(&mut self, children: &mut Vec<Box<dyn ILTag>>) {
+        children.extend(self.value.drain().map(|(_, v)| v));
     }
 }
 
+impl ILTag for ILDictTag {
+    iltag_base_func_impl!();
+    iltag_container_func_impl!();
+}
+
 iltag_default_impl!(ILDictTag);
 
+impl Drop for ILDictTag {
+    fn drop(&mut self) {
+        drop_container_children(self);
+    }
+}
+
 //=============================================================================
 // ILStrDictTag
 //-----------------------------------------------------------------------------
@@ -1134,6 +1365,9 @@ iltag_default_impl!(ILDictTag);
 /// however, this implementation is optimized to handle strings and offer
 /// easier ways to deal with them.
 ///
+/// Since 2.0.0, it handles duplicate and unsorted keys exactly like
+/// [`ILDictTag`].
+///
 /// By default it sets the tag id to [`IL_STRING_DICTIONARY_TAG_ID`].
 pub struct ILStrDictTag {
     id: u64,
@@ -1253,18 +1487,22 @@ impl ILTag for ILStrDictTag {
 
     fn deserialize_value(
         &mut self,
-        _factory: &dyn ILTagFactory,
+        factory: &dyn ILTagFactory,
         value_size: usize,
         reader: &mut dyn Reader,
     ) -> Result<()> {
         let mut lreader = LimitedReader::new(reader, value_size);
-        let count = lreader.deserialize_ilint()?;
+        let count = lreader.deserialize_ilint_with(factory.canonical())?;
+        factory.limits().check_elements(count)?;
         self.value.clear();
-        for _ in 0..count {
-            self.value.insert(
-                deserialize_string_tag_from_value(&mut lreader)?,
-                deserialize_string_tag_from_value(&mut lreader)?,
-            );
+        let mut previous_key = String::new();
+        for i in 0..count {
+            let key = deserialize_checked_string_tag_from_value(&mut lreader, factory)?;
+            check_dict_key_order(factory, i == 0, &mut previous_key, &key)?;
+            let value = deserialize_checked_string_tag_from_value(&mut lreader, factory)?;
+            if apply_duplicate_key_policy(factory, self.value.contains_key(&key))? {
+                self.value.insert(key, value);
+            }
         }
         limited_reader_ensure_empty(&lreader, ErrorKind::CorruptedData)
     }
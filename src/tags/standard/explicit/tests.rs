@@ -37,6 +37,7 @@ use crate::tags::standard::factory::ILStandardTagFactory;
 use crate::tags::standard::implicit::*;
 use crate::tags::util::UntouchbleTagFactory;
 use crate::tags::util::*;
+use crate::tags::{tag_downcast_ref, CanonicalRule, DuplicateKeyPolicy};
 
 /// Test the functions defined by std_byte_array_tag_func_impl macro.
 macro_rules! test_std_byte_array_tag_func_impl {
@@ -466,11 +467,63 @@ fn test_ilbiginttag_iltag_deserialize() {
     test_inner_iltag_default_impl_deserialize!(ILBigIntTag);
 }
 
+#[test]
+fn test_ilbiginttag_iltag_deserialize_canonical() {
+    let mut f = ILStandardTagFactory::new(false);
+    let samples: [&[u8]; 3] = [&[0x00, 0x7F], &[0xFF, 0x80], &[]];
+
+    for sample in samples {
+        let mut t = ILBigIntTag::new();
+        let mut reader = ByteArrayReader::new(sample);
+        match t.deserialize_value(&f, sample.len(), &mut reader) {
+            Ok(()) => (),
+            _ => panic!("Unable to deserialize the tag."),
+        }
+        assert_eq!(t.value().as_slice(), sample);
+    }
+
+    f.set_canonical(true);
+    for sample in samples {
+        let mut t = ILBigIntTag::new();
+        let mut reader = ByteArrayReader::new(sample);
+        match t.deserialize_value(&f, sample.len(), &mut reader) {
+            Err(ErrorKind::NonCanonical(CanonicalRule::TwosComplement)) => (),
+            _ => panic!("Non canonical value not detected."),
+        }
+    }
+
+    let sample: [u8; 3] = [0x00, 0x80, 0x00];
+    let mut t = ILBigIntTag::new();
+    let mut reader = ByteArrayReader::new(&sample);
+    match t.deserialize_value(&f, sample.len(), &mut reader) {
+        Ok(()) => (),
+        _ => panic!("Unable to deserialize the tag."),
+    }
+    assert_eq!(t.value().as_slice(), &sample);
+}
+
 #[test]
 fn test_ilbiginttag_default() {
     test_default_impl!(ILBigIntTag, IL_BINT_TAG_ID);
 }
 
+#[test]
+fn test_is_minimal_twos_complement() {
+    assert!(!is_minimal_twos_complement(&[]));
+    assert!(is_minimal_twos_complement(&[0x00]));
+    assert!(is_minimal_twos_complement(&[0xFF]));
+    assert!(is_minimal_twos_complement(&[0x7F]));
+    assert!(is_minimal_twos_complement(&[0x80]));
+    assert!(is_minimal_twos_complement(&[0x00, 0x80]));
+    assert!(is_minimal_twos_complement(&[0xFF, 0x7F]));
+    assert!(is_minimal_twos_complement(&[0x01, 0x00, 0x00]));
+    assert!(is_minimal_twos_complement(&[0xFE, 0xFF, 0xFF]));
+    assert!(!is_minimal_twos_complement(&[0x00, 0x00]));
+    assert!(!is_minimal_twos_complement(&[0x00, 0x7F]));
+    assert!(!is_minimal_twos_complement(&[0xFF, 0xFF]));
+    assert!(!is_minimal_twos_complement(&[0xFF, 0x80, 0x00]));
+}
+
 //=============================================================================
 // ILBigDecTag
 //-----------------------------------------------------------------------------
@@ -512,7 +565,7 @@ fn test_ilbigdectag_impl() {
 fn test_ilbigdectag_iltag_value_size() {
     // new
     let t = ILBigDecTag::new();
-    assert_eq!(t.value_size(), 4 + 0);
+    assert_eq!(t.value_size(), 4);
 
     // with_value
     let t = ILBigDecTag::with_value(-1, &SAMPLE_STRING_BIN);
@@ -578,6 +631,38 @@ fn test_ilbigdectag_iltag_deserialize_value() {
     }
 }
 
+#[test]
+fn test_ilbigdectag_iltag_deserialize_value_canonical() {
+    let mut f = ILStandardTagFactory::new(false);
+    f.set_canonical(true);
+
+    let exp: [u8; 6] = [0x00, 0x00, 0x00, 0x02, 0xFF, 0x00];
+    let mut reader = ByteArrayReader::new(&exp);
+    let mut t = ILBigDecTag::default();
+    match t.deserialize_value(&f, exp.len(), &mut reader) {
+        Ok(()) => (),
+        _ => panic!("Unable to deserialize the tag."),
+    }
+    assert_eq!(t.scale(), 2);
+    assert_eq!(t.value().as_slice(), &exp[4..]);
+
+    let exp: [u8; 6] = [0x00, 0x00, 0x00, 0x02, 0xFF, 0xFF];
+    let mut reader = ByteArrayReader::new(&exp);
+    let mut t = ILBigDecTag::default();
+    match t.deserialize_value(&f, exp.len(), &mut reader) {
+        Err(ErrorKind::NonCanonical(CanonicalRule::TwosComplement)) => (),
+        _ => panic!("Non canonical value not detected."),
+    }
+
+    let exp: [u8; 4] = [0x00, 0x00, 0x00, 0x02];
+    let mut reader = ByteArrayReader::new(&exp);
+    let mut t = ILBigDecTag::default();
+    match t.deserialize_value(&f, exp.len(), &mut reader) {
+        Err(ErrorKind::NonCanonical(CanonicalRule::TwosComplement)) => (),
+        _ => panic!("Non canonical value not detected."),
+    }
+}
+
 //=============================================================================
 // ILILIntArrayTag
 //-----------------------------------------------------------------------------
@@ -846,8 +931,8 @@ fn test_iltagseqtag_iltag_deserialize_value_non_strict() {
         _ => panic!("Unable to serialize the tag."),
     }
     assert_eq!(t.value().len(), sample.len());
-    for i in 0..sample.len() {
-        assert!(iltag_are_equal(t.value()[i].as_ref(), sample[i].as_ref()));
+    for (v, s) in t.value().iter().zip(sample.iter()) {
+        assert!(iltag_are_equal(v.as_ref(), s.as_ref()));
     }
 
     // with multiple tags
@@ -863,8 +948,8 @@ fn test_iltagseqtag_iltag_deserialize_value_non_strict() {
         _ => panic!("Unable to deserialize the tag."),
     }
     assert_eq!(t.value().len(), sample.len());
-    for i in 0..sample.len() {
-        assert!(iltag_are_equal(t.value()[i].as_ref(), sample[i].as_ref()));
+    for (v, s) in t.value().iter().zip(sample.iter()) {
+        assert!(iltag_are_equal(v.as_ref(), s.as_ref()));
     }
 
     // Errors
@@ -904,8 +989,8 @@ fn test_iltagseqtag_iltag_deserialize_value_strict() {
         _ => panic!("Unable to deserialize the tag."),
     }
     assert_eq!(t.value().len(), sample.len());
-    for i in 0..sample.len() {
-        assert!(iltag_are_equal(t.value()[i].as_ref(), sample[i].as_ref()));
+    for (v, s) in t.value().iter().zip(sample.iter()) {
+        assert!(iltag_are_equal(v.as_ref(), s.as_ref()));
     }
 
     // with multiple tags
@@ -920,8 +1005,8 @@ fn test_iltagseqtag_iltag_deserialize_value_strict() {
         _ => panic!("Unable to deserialize the tag."),
     }
     assert_eq!(t.value().len(), sample.len());
-    for i in 0..sample.len() {
-        assert!(iltag_are_equal(t.value()[i].as_ref(), sample[i].as_ref()));
+    for (v, s) in t.value().iter().zip(sample.iter()) {
+        assert!(iltag_are_equal(v.as_ref(), s.as_ref()));
     }
 
     // Errors
@@ -1059,8 +1144,8 @@ fn test_iltagarraytag_iltag_deserialize_value_non_strict() {
         _ => panic!("Unable to serialize the tag."),
     }
     assert_eq!(t.value().len(), sample.len());
-    for i in 0..sample.len() {
-        assert!(iltag_are_equal(t.value()[i].as_ref(), sample[i].as_ref()));
+    for (v, s) in t.value().iter().zip(sample.iter()) {
+        assert!(iltag_are_equal(v.as_ref(), s.as_ref()));
     }
 
     // with multiple tags
@@ -1077,8 +1162,8 @@ fn test_iltagarraytag_iltag_deserialize_value_non_strict() {
         _ => panic!("Unable to serialize the tag."),
     }
     assert_eq!(t.value().len(), sample.len());
-    for i in 0..sample.len() {
-        assert!(iltag_are_equal(t.value()[i].as_ref(), sample[i].as_ref()));
+    for (v, s) in t.value().iter().zip(sample.iter()) {
+        assert!(iltag_are_equal(v.as_ref(), s.as_ref()));
     }
 
     // Error - last is corrupted
@@ -1125,8 +1210,8 @@ fn test_iltagarraytag_iltag_deserialize_value_strict() {
         _ => panic!("Unable to deserialize the tag."),
     }
     assert_eq!(t.value().len(), sample.len());
-    for i in 0..sample.len() {
-        assert!(iltag_are_equal(t.value()[i].as_ref(), sample[i].as_ref()));
+    for (v, s) in t.value().iter().zip(sample.iter()) {
+        assert!(iltag_are_equal(v.as_ref(), s.as_ref()));
     }
 
     // with multiple tags
@@ -1142,8 +1227,8 @@ fn test_iltagarraytag_iltag_deserialize_value_strict() {
         _ => panic!("Unable to deserialize the tag."),
     }
     assert_eq!(t.value().len(), sample.len());
-    for i in 0..sample.len() {
-        assert!(iltag_are_equal(t.value()[i].as_ref(), sample[i].as_ref()));
+    for (v, s) in t.value().iter().zip(sample.iter()) {
+        assert!(iltag_are_equal(v.as_ref(), s.as_ref()));
     }
 
     // Error - last is corrupted
@@ -1685,7 +1770,7 @@ fn test_ildicttag_iltag_value_size() {
     let exp = (crate::ilint::encoded_size(t.len() as u64) as u64)
         + t.value()
             .iter()
-            .map(|(k, v)| (string_tag_size_from_value(k) + v.size()) as u64)
+            .map(|(k, v)| string_tag_size_from_value(k) + v.size())
             .sum::<u64>();
     assert_eq!(t.value_size(), exp);
 }
@@ -1932,7 +2017,7 @@ fn test_ilstrdicttag_iltag_value_size() {
     let exp = (crate::ilint::encoded_size(t.len() as u64) as u64)
         + t.value()
             .iter()
-            .map(|(k, v)| (string_tag_size_from_value(k) + string_tag_size_from_value(v)) as u64)
+            .map(|(k, v)| string_tag_size_from_value(k) + string_tag_size_from_value(v))
             .sum::<u64>();
     assert_eq!(t.value_size(), exp);
 }
@@ -2044,3 +2129,283 @@ fn test_ilstrdicttag_iltag_deserialze_value() {
         _ => panic!("Unable to detect issues."),
     }
 }
+
+fn serialize_ildicttag_keys_for_testing(keys: &[&str]) -> VecWriter {
+    let mut exp = VecWriter::default();
+    write_value_for_testing!(write_ilint, keys.len() as u64, &mut exp);
+    for k in keys {
+        write_value_for_testing!(serialize_string_tag_from_value, k, &mut exp);
+        serialize_tag_for_testing(&ILNullTag::new(), &mut exp);
+    }
+    exp
+}
+
+fn serialize_ilstrdicttag_keys_for_testing(keys: &[&str]) -> VecWriter {
+    let mut exp = VecWriter::default();
+    write_value_for_testing!(write_ilint, keys.len() as u64, &mut exp);
+    for k in keys {
+        write_value_for_testing!(serialize_string_tag_from_value, k, &mut exp);
+        write_value_for_testing!(serialize_string_tag_from_value, k, &mut exp);
+    }
+    exp
+}
+
+#[test]
+fn test_ildicttag_iltag_deserialze_value_canonical() {
+    let mut f = ILStandardTagFactory::new(false);
+    f.set_canonical(true);
+
+    let exp = serialize_ildicttag_keys_for_testing(&["", "a", "aa", "b"]);
+    let mut t = ILDictTag::new();
+    let mut reader = ByteArrayReader::new(exp.as_slice());
+    match t.deserialize_value(&f, exp.as_slice().len(), &mut reader) {
+        Ok(()) => (),
+        _ => panic!("Unable to deserialize the tag."),
+    }
+    assert_eq!(t.len(), 4);
+
+    let exp = serialize_ildicttag_keys_for_testing(&["a", "c", "b"]);
+    let mut t = ILDictTag::new();
+    let mut reader = ByteArrayReader::new(exp.as_slice());
+    match t.deserialize_value(&f, exp.as_slice().len(), &mut reader) {
+        Err(ErrorKind::NonCanonical(CanonicalRule::DictKeyOrder)) => (),
+        _ => panic!("Unsorted keys not detected."),
+    }
+
+    let exp = serialize_ildicttag_keys_for_testing(&["a", "b", "b"]);
+    let mut t = ILDictTag::new();
+    let mut reader = ByteArrayReader::new(exp.as_slice());
+    match t.deserialize_value(&f, exp.as_slice().len(), &mut reader) {
+        Err(ErrorKind::NonCanonical(CanonicalRule::DictDuplicateKey)) => (),
+        _ => panic!("Duplicated keys not detected."),
+    }
+
+    // Not canonical
+    f.set_canonical(false);
+    let mut t = ILDictTag::new();
+    let exp = serialize_ildicttag_keys_for_testing(&["a", "c", "b"]);
+    let mut reader = ByteArrayReader::new(exp.as_slice());
+    match t.deserialize_value(&f, exp.as_slice().len(), &mut reader) {
+        Ok(()) => (),
+        _ => panic!("Unable to deserialize the tag."),
+    }
+    assert_eq!(t.len(), 3);
+}
+
+#[test]
+fn test_ilstrdicttag_iltag_deserialze_value_canonical() {
+    let mut f = ILStandardTagFactory::new(false);
+    f.set_canonical(true);
+
+    let exp = serialize_ilstrdicttag_keys_for_testing(&["", "a", "aa", "b"]);
+    let mut t = ILStrDictTag::new();
+    let mut reader = ByteArrayReader::new(exp.as_slice());
+    match t.deserialize_value(&f, exp.as_slice().len(), &mut reader) {
+        Ok(()) => (),
+        _ => panic!("Unable to deserialize the tag."),
+    }
+    assert_eq!(t.len(), 4);
+
+    let exp = serialize_ilstrdicttag_keys_for_testing(&["a", "c", "b"]);
+    let mut t = ILStrDictTag::new();
+    let mut reader = ByteArrayReader::new(exp.as_slice());
+    match t.deserialize_value(&f, exp.as_slice().len(), &mut reader) {
+        Err(ErrorKind::NonCanonical(CanonicalRule::DictKeyOrder)) => (),
+        _ => panic!("Unsorted keys not detected."),
+    }
+
+    let exp = serialize_ilstrdicttag_keys_for_testing(&["a", "b", "b"]);
+    let mut t = ILStrDictTag::new();
+    let mut reader = ByteArrayReader::new(exp.as_slice());
+    match t.deserialize_value(&f, exp.as_slice().len(), &mut reader) {
+        Err(ErrorKind::NonCanonical(CanonicalRule::DictDuplicateKey)) => (),
+        _ => panic!("Duplicated keys not detected."),
+    }
+
+    // Not canonical
+    f.set_canonical(false);
+    let mut t = ILStrDictTag::new();
+    let exp = serialize_ilstrdicttag_keys_for_testing(&["a", "c", "b"]);
+    let mut reader = ByteArrayReader::new(exp.as_slice());
+    match t.deserialize_value(&f, exp.as_slice().len(), &mut reader) {
+        Ok(()) => (),
+        _ => panic!("Unable to deserialize the tag."),
+    }
+    assert_eq!(t.len(), 3);
+}
+
+fn serialize_ildicttag_entries_for_testing(entries: &[(&str, u8)]) -> VecWriter {
+    let mut exp = VecWriter::default();
+    write_value_for_testing!(write_ilint, entries.len() as u64, &mut exp);
+    for (k, v) in entries {
+        write_value_for_testing!(serialize_string_tag_from_value, k, &mut exp);
+        serialize_tag_for_testing(&ILUInt8Tag::with_value(*v), &mut exp);
+    }
+    exp
+}
+
+fn serialize_ilstrdicttag_entries_for_testing(entries: &[(&str, &str)]) -> VecWriter {
+    let mut exp = VecWriter::default();
+    write_value_for_testing!(write_ilint, entries.len() as u64, &mut exp);
+    for (k, v) in entries {
+        write_value_for_testing!(serialize_string_tag_from_value, k, &mut exp);
+        write_value_for_testing!(serialize_string_tag_from_value, v, &mut exp);
+    }
+    exp
+}
+
+fn ildicttag_get_u8(t: &ILDictTag, key: &str) -> u8 {
+    tag_downcast_ref::<ILUInt8Tag>(t.get(key).unwrap())
+        .unwrap()
+        .value()
+}
+
+#[test]
+fn test_ildicttag_iltag_deserialze_value_duplicate_keys() {
+    let mut f = ILStandardTagFactory::new(false);
+    let exp = serialize_ildicttag_entries_for_testing(&[("b", 1), ("a", 2), ("b", 3)]);
+
+    // Reject
+    let mut t = ILDictTag::new();
+    let mut reader = ByteArrayReader::new(exp.as_slice());
+    match t.deserialize_value(&f, exp.as_slice().len(), &mut reader) {
+        Err(ErrorKind::DuplicateKey) => (),
+        _ => panic!("Duplicated keys not detected."),
+    }
+
+    // First wins
+    f.set_duplicate_key_policy(DuplicateKeyPolicy::FirstWins);
+    let mut t = ILDictTag::new();
+    let mut reader = ByteArrayReader::new(exp.as_slice());
+    match t.deserialize_value(&f, exp.as_slice().len(), &mut reader) {
+        Ok(()) => (),
+        _ => panic!("Unable to deserialize the tag."),
+    }
+    assert_eq!(t.len(), 2);
+    assert_eq!(ildicttag_get_u8(&t, "a"), 2);
+    assert_eq!(ildicttag_get_u8(&t, "b"), 1);
+
+    // Last wins
+    f.set_duplicate_key_policy(DuplicateKeyPolicy::LastWins);
+    let mut t = ILDictTag::new();
+    let mut reader = ByteArrayReader::new(exp.as_slice());
+    match t.deserialize_value(&f, exp.as_slice().len(), &mut reader) {
+        Ok(()) => (),
+        _ => panic!("Unable to deserialize the tag."),
+    }
+    assert_eq!(t.len(), 2);
+    assert_eq!(ildicttag_get_u8(&t, "a"), 2);
+    assert_eq!(ildicttag_get_u8(&t, "b"), 3);
+
+    // Canonical always rejects
+    f.set_canonical(true);
+    let exp = serialize_ildicttag_entries_for_testing(&[("a", 1), ("a", 2)]);
+    let mut t = ILDictTag::new();
+    let mut reader = ByteArrayReader::new(exp.as_slice());
+    match t.deserialize_value(&f, exp.as_slice().len(), &mut reader) {
+        Err(ErrorKind::NonCanonical(CanonicalRule::DictDuplicateKey)) => (),
+        _ => panic!("Duplicated keys not detected."),
+    }
+}
+
+#[test]
+fn test_ildicttag_iltag_deserialze_value_sorted_keys() {
+    let mut f = ILStandardTagFactory::new(false);
+    f.set_sorted_keys(true);
+
+    let exp = serialize_ildicttag_entries_for_testing(&[("a", 1), ("b", 2), ("c", 3)]);
+    let mut t = ILDictTag::new();
+    let mut reader = ByteArrayReader::new(exp.as_slice());
+    match t.deserialize_value(&f, exp.as_slice().len(), &mut reader) {
+        Ok(()) => (),
+        _ => panic!("Unable to deserialize the tag."),
+    }
+    assert_eq!(t.len(), 3);
+
+    let exp = serialize_ildicttag_entries_for_testing(&[("a", 1), ("c", 2), ("b", 3)]);
+    let mut t = ILDictTag::new();
+    let mut reader = ByteArrayReader::new(exp.as_slice());
+    match t.deserialize_value(&f, exp.as_slice().len(), &mut reader) {
+        Err(ErrorKind::UnsortedKeys) => (),
+        _ => panic!("Unsorted keys not detected."),
+    }
+
+    // Duplicates are still handled by the policy
+    let exp = serialize_ildicttag_entries_for_testing(&[("a", 1), ("a", 2), ("b", 3)]);
+    let mut t = ILDictTag::new();
+    let mut reader = ByteArrayReader::new(exp.as_slice());
+    match t.deserialize_value(&f, exp.as_slice().len(), &mut reader) {
+        Err(ErrorKind::DuplicateKey) => (),
+        _ => panic!("Duplicated keys not detected."),
+    }
+    f.set_duplicate_key_policy(DuplicateKeyPolicy::LastWins);
+    let mut t = ILDictTag::new();
+    let mut reader = ByteArrayReader::new(exp.as_slice());
+    match t.deserialize_value(&f, exp.as_slice().len(), &mut reader) {
+        Ok(()) => (),
+        _ => panic!("Unable to deserialize the tag."),
+    }
+    assert_eq!(t.len(), 2);
+    assert_eq!(ildicttag_get_u8(&t, "a"), 2);
+}
+
+#[test]
+fn test_ilstrdicttag_iltag_deserialze_value_duplicate_keys() {
+    let mut f = ILStandardTagFactory::new(false);
+    let exp = serialize_ilstrdicttag_entries_for_testing(&[("b", "1"), ("a", "2"), ("b", "3")]);
+
+    // Reject
+    let mut t = ILStrDictTag::new();
+    let mut reader = ByteArrayReader::new(exp.as_slice());
+    match t.deserialize_value(&f, exp.as_slice().len(), &mut reader) {
+        Err(ErrorKind::DuplicateKey) => (),
+        _ => panic!("Duplicated keys not detected."),
+    }
+
+    // First wins
+    f.set_duplicate_key_policy(DuplicateKeyPolicy::FirstWins);
+    let mut t = ILStrDictTag::new();
+    let mut reader = ByteArrayReader::new(exp.as_slice());
+    match t.deserialize_value(&f, exp.as_slice().len(), &mut reader) {
+        Ok(()) => (),
+        _ => panic!("Unable to deserialize the tag."),
+    }
+    assert_eq!(t.len(), 2);
+    assert_eq!(t.get("a").unwrap(), "2");
+    assert_eq!(t.get("b").unwrap(), "1");
+
+    // Last wins
+    f.set_duplicate_key_policy(DuplicateKeyPolicy::LastWins);
+    let mut t = ILStrDictTag::new();
+    let mut reader = ByteArrayReader::new(exp.as_slice());
+    match t.deserialize_value(&f, exp.as_slice().len(), &mut reader) {
+        Ok(()) => (),
+        _ => panic!("Unable to deserialize the tag."),
+    }
+    assert_eq!(t.len(), 2);
+    assert_eq!(t.get("a").unwrap(), "2");
+    assert_eq!(t.get("b").unwrap(), "3");
+}
+
+#[test]
+fn test_ilstrdicttag_iltag_deserialze_value_sorted_keys() {
+    let mut f = ILStandardTagFactory::new(false);
+    f.set_sorted_keys(true);
+
+    let exp = serialize_ilstrdicttag_entries_for_testing(&[("a", "1"), ("b", "2"), ("c", "3")]);
+    let mut t = ILStrDictTag::new();
+    let mut reader = ByteArrayReader::new(exp.as_slice());
+    match t.deserialize_value(&f, exp.as_slice().len(), &mut reader) {
+        Ok(()) => (),
+        _ => panic!("Unable to deserialize the tag."),
+    }
+    assert_eq!(t.len(), 3);
+
+    let exp = serialize_ilstrdicttag_entries_for_testing(&[("a", "1"), ("c", "2"), ("b", "3")]);
+    let mut t = ILStrDictTag::new();
+    let mut reader = ByteArrayReader::new(exp.as_slice());
+    match t.deserialize_value(&f, exp.as_slice().len(), &mut reader) {
+        Err(ErrorKind::UnsortedKeys) => (),
+        _ => panic!("Unsorted keys not detected."),
+    }
+}
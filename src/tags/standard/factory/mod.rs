@@ -51,12 +51,18 @@ use super::implicit::{
     ILInt32Tag, ILInt64Tag, ILInt8Tag, ILNullTag, ILSignedILInt64Tag, ILUInt16Tag, ILUInt32Tag,
     ILUInt64Tag, ILUInt8Tag,
 };
-use crate::io::{LimitedReader, Reader};
+use crate::io::array::{ByteArrayReader, MemoryReader};
+use crate::io::{LimitedReader, OffsetReader, Reader};
+use crate::tags::error::DeserializationError;
+use crate::tags::path::{ILTagPath, ILTagPathElement};
 use crate::tags::serialization::*;
+use crate::tags::util::limited_reader_ensure_empty;
 use crate::tags::{
-    is_implicit_tag, tag_size_to_usize, ErrorKind, ILDefaultTagCreator, ILTag, ILTagCreatorEngine,
-    ILTagFactory, Result,
+    is_implicit_tag, tag_size_to_usize, DeserializationLimits, DuplicateKeyPolicy, ErrorKind,
+    ILDefaultTagCreator, ILRawTag, ILTag, ILTagCreatorEngine, ILTagFactory, Result,
 };
+use ::std::sync::atomic::{AtomicU64, Ordering};
+use ::std::sync::Mutex;
 
 /// This macro is a shortcut to the registration of a new [`ILDefaultTagCreator`] for
 /// the specified tag id and struct.
@@ -118,6 +124,23 @@ pub fn create_std_engine(strict: bool) -> ILTagCreatorEngine {
     engine
 }
 
+//=============================================================================
+// RecoveryPolicy
+//-----------------------------------------------------------------------------
+/// Defines how [`ILStandardTagFactory::from_bytes_recovering()`] handles the
+/// tags that cannot be deserialized.
+///
+/// New since 2.0.0.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Default)]
+pub enum RecoveryPolicy {
+    /// The tag is replaced by an [`ILRawTag`] that contains its original
+    /// value. This is the default policy.
+    #[default]
+    Placeholder,
+    /// The tag is skipped.
+    Skip,
+}
+
 //=============================================================================
 // ILStandardTagFactory
 //-----------------------------------------------------------------------------
@@ -125,8 +148,20 @@ pub fn create_std_engine(strict: bool) -> ILTagCreatorEngine {
 ///
 /// It can be extended to include custom tags if necessary by registering new
 /// tag creators for the desired tag id.
+///
+/// Since 2.0.0, it also enforces the [`DeserializationLimits`] associated with
+/// it. Those limits are enforced for all nested tags deserialized by the
+/// containers through [`ILTagFactory::deserialize()`].
+///
+/// Since 2.0.0, all tags that implement [`crate::tags::container::ILTagContainer`]
+/// are deserialized without recursion, thus arbitrarily deep trees can be
+/// handled as long as they fit inside the [`DeserializationLimits`].
 pub struct ILStandardTagFactory {
     engine: ILTagCreatorEngine,
+    limits: DeserializationLimits,
+    canonical: bool,
+    duplicate_key_policy: DuplicateKeyPolicy,
+    sorted_keys: bool,
 }
 
 impl ILStandardTagFactory {
@@ -135,8 +170,24 @@ impl ILStandardTagFactory {
     /// Arguments:
     /// - `strict`: If true, this factory will work in strict mode.
     pub fn new(strict: bool) -> Self {
+        Self::with_limits(strict, DeserializationLimits::default())
+    }
+
+    /// Creates a new instance of the ILStandardTagFactory with the given
+    /// deserialization limits.
+    ///
+    /// Arguments:
+    /// - `strict`: If true, this factory will work in strict mode.
+    /// - `limits`: The deserialization limits;
+    ///
+    /// New since 2.0.0.
+    pub fn with_limits(strict: bool, limits: DeserializationLimits) -> Self {
         Self {
             engine: create_std_engine(strict),
+            limits,
+            canonical: false,
+            duplicate_key_policy: DuplicateKeyPolicy::default(),
+            sorted_keys: false,
         }
     }
 
@@ -149,31 +200,570 @@ impl ILStandardTagFactory {
         &mut self.engine
     }
 
+    /// Sets the deserialization limits.
+    ///
+    /// Arguments:
+    /// - `limits`: The new deserialization limits;
+    ///
+    /// New since 2.0.0.
+    pub fn set_limits(&mut self, limits: DeserializationLimits) {
+        self.limits = limits;
+    }
+
+    /// Enables or disables the canonical mode. When enabled, all values that
+    /// are not encoded in their canonical form are rejected with
+    /// [`ErrorKind::NonCanonical`]. It is disabled by default.
+    ///
+    /// Arguments:
+    /// - `canonical`: The new canonical mode;
+    ///
+    /// New since 2.0.0.
+    pub fn set_canonical(&mut self, canonical: bool) {
+        self.canonical = canonical;
+    }
+
+    /// Sets how the dictionaries must handle duplicate keys. It is set to
+    /// [`DuplicateKeyPolicy::Reject`] by default.
+    ///
+    /// Arguments:
+    /// - `policy`: The new policy;
+    ///
+    /// New since 2.0.0.
+    pub fn set_duplicate_key_policy(&mut self, policy: DuplicateKeyPolicy) {
+        self.duplicate_key_policy = policy;
+    }
+
+    /// Enables or disables the verification of the order of the keys of the
+    /// dictionaries. When enabled, unsorted keys are rejected with
+    /// [`ErrorKind::UnsortedKeys`]. It is disabled by default.
+    ///
+    /// Arguments:
+    /// - `sorted_keys`: The new value;
+    ///
+    /// New since 2.0.0.
+    pub fn set_sorted_keys(&mut self, sorted_keys: bool) {
+        self.sorted_keys = sorted_keys;
+    }
+
     #[inline]
-    fn deserialize_tag_size(tag_id: u64, reader: &mut dyn Reader) -> Result<usize> {
+    fn deserialize_tag_size(
+        tag_id: u64,
+        canonical: bool,
+        reader: &mut dyn Reader,
+    ) -> Result<usize> {
         let tag_size = if is_implicit_tag(tag_id) {
             implicit_tag_size(tag_id)
         } else {
-            reader.deserialize_ilint()?
+            reader.deserialize_ilint_with(canonical)?
         };
         let size = tag_size_to_usize(tag_size)?;
         Ok(size)
     }
 
+    /// Reads the header of a tag and verifies the limits associated with it.
+    ///
+    /// Arguments:
+    /// - `depth`: The depth of the tag;
+    /// - `reader`: The reader;
+    /// - `expected_id`: The expected tag id if any;
+    ///
+    /// Returns:
+    /// - `Ok((id, size))`: The tag id and the size of the value;
+    /// - `Err(_)`: In case of error;
+    fn deserialize_header(
+        &self,
+        depth: usize,
+        reader: &mut OffsetReader,
+        expected_id: Option<u64>,
+    ) -> ContextResult<(u64, usize)> {
+        let start = reader.offset();
+        let error = |kind, id| DeserializationError::new(kind, start, id, ILTagPath::new());
+        self.limits.check_depth(depth).map_err(|e| error(e, None))?;
+        let tag_id = reader
+            .deserialize_ilint_with(self.canonical)
+            .map_err(|e| error(e, None))?;
+        if matches!(expected_id, Some(id) if id != tag_id) {
+            return Err(error(ErrorKind::UnexpectedTagType, Some(tag_id)));
+        }
+        let tag_size = Self::deserialize_tag_size(tag_id, self.canonical, reader)
+            .map_err(|e| error(e, Some(tag_id)))?;
+        self.limits
+            .check_tag_size(tag_size as u64)
+            .map_err(|e| error(e, Some(tag_id)))?;
+        Ok((tag_id, tag_size))
+    }
+
     #[inline]
     fn deserialize_tag_value_into(
         &self,
+        depth: usize,
+        start: u64,
+        tag_size: usize,
+        reader: &mut OffsetReader,
+        tag: &mut dyn ILTag,
+        recovery: Option<&mut Recovery>,
+    ) -> ContextResult<()> {
+        let tag_id = tag.id();
+        let error = |kind| DeserializationError::new(kind, start, Some(tag_id), ILTagPath::new());
+        if depth == 0 {
+            let mut total_size = crate::ilint::encoded_size(tag_id) + tag_size;
+            if !is_implicit_tag(tag_id) {
+                total_size += crate::ilint::encoded_size(tag_size as u64);
+            }
+            self.limits
+                .check_total_size(total_size as u64)
+                .map_err(error)?;
+        }
+        if tag.as_container().is_some() {
+            let mut state = ContainerState::new(start, tag_id);
+            match self
+                .deserialize_container_into(depth, tag_size, reader, tag, &mut state, recovery)
+            {
+                Ok(()) => Ok(()),
+                Err(e) => Err(state.build_error(e, tag)),
+            }
+        } else {
+            let nested = NestedTags::new(tag_id, reader.offset());
+            match self.deserialize_leaf_value_into(depth, tag_size, reader, tag, &nested) {
+                Ok(()) => Ok(()),
+                Err(e) => match nested.into_error() {
+                    Some(n) => Err(DeserializationError::new(e, n.offset, n.id, n.path)),
+                    None => Err(error(e)),
+                },
+            }
+        }
+    }
+
+    /// Deserializes the value of a tag that is not a container.
+    ///
+    /// The tags deserialized by `tag` through the factory are tracked by
+    /// `nested`, so the errors found inside them can be located.
+    fn deserialize_leaf_value_into(
+        &self,
+        depth: usize,
         tag_size: usize,
         reader: &mut dyn Reader,
         tag: &mut dyn ILTag,
+        nested: &NestedTags,
     ) -> Result<()> {
-        let mut lreader = LimitedReader::new(reader, tag_size);
-        tag.deserialize_value(self, tag_size, &mut lreader)?;
+        let context = ILStandardTagFactoryContext {
+            factory: self,
+            depth: depth + 1,
+            nested: Some(nested),
+        };
+        let mut nreader = NestedTagsReader {
+            source: reader,
+            nested,
+        };
+        let mut lreader = LimitedReader::new(&mut nreader, tag_size);
+        tag.deserialize_value(&context, tag_size, &mut lreader)?;
         if tag.id() != IL_ILINT_TAG_ID && tag.id() != IL_SIGNED_ILINT_TAG_ID && !lreader.empty() {
             return Err(ErrorKind::CorruptedData);
         }
         Ok(())
     }
+
+    fn begin_container(
+        &self,
+        depth: usize,
+        tag_size: usize,
+        reader: &mut dyn Reader,
+        tag: &mut dyn ILTag,
+    ) -> Result<Option<u64>> {
+        let context = ILStandardTagFactoryContext {
+            factory: self,
+            depth: depth + 1,
+            nested: None,
+        };
+        let mut lreader = LimitedReader::new(reader, tag_size);
+        match tag.as_mut_container() {
+            Some(c) => c.begin_deserialization(&context, tag_size, &mut lreader),
+            None => Err(ErrorKind::UnexpectedTagType),
+        }
+    }
+
+    /// Deserializes the value of a container and all its descendants using
+    /// an explicit stack instead of recursion. Only the tags that do not
+    /// implement [`crate::tags::container::ILTagContainer`] are deserialized
+    /// by [`ILTag::deserialize_value()`].
+    ///
+    /// The location of the tag being deserialized is kept in `state`, thus
+    /// the caller can locate the error if something goes wrong. If
+    /// `recovery` is set, the tags that cannot be deserialized are handled
+    /// by [`Self::recover()`].
+    fn deserialize_container_into(
+        &self,
+        depth: usize,
+        tag_size: usize,
+        reader: &mut OffsetReader,
+        root: &mut dyn ILTag,
+        state: &mut ContainerState,
+        mut recovery: Option<&mut Recovery>,
+    ) -> Result<()> {
+        let root_start = state.cursor.offset;
+        let value_start = reader.offset();
+        let root_remaining = self.begin_container(depth, tag_size, reader, root)?;
+        state.levels.push(ContainerLevel {
+            id: root.id(),
+            start: root_start,
+            value_start,
+            end: value_start + tag_size as u64,
+            remaining: root_remaining,
+            next_index: 0,
+        });
+        loop {
+            match self.deserialize_container_step(depth, reader, root, state) {
+                Ok(true) => return Ok(()),
+                Ok(false) => (),
+                Err(e) => match recovery.as_deref_mut() {
+                    Some(r) => Self::recover(e, reader, root, state, r)?,
+                    None => return Err(e),
+                },
+            }
+        }
+    }
+
+    /// Performs a single step of [`Self::deserialize_container_into()`]. It
+    /// either deserializes the next child of the current container or
+    /// finishes it.
+    ///
+    /// Returns:
+    /// - `Ok(true)`: If the root container is finished;
+    /// - `Ok(false)`: If there is more work to do;
+    /// - `Err(_)`: In case of error;
+    fn deserialize_container_step(
+        &self,
+        depth: usize,
+        reader: &mut OffsetReader,
+        root: &mut dyn ILTag,
+        state: &mut ContainerState,
+    ) -> Result<bool> {
+        let level_count = state.levels.len();
+        let level = depth + level_count - 1;
+        let frame = match state.levels.last_mut() {
+            Some(f) => f,
+            None => return Ok(true),
+        };
+        let container = match state.tags.last_mut() {
+            Some(t) => t.as_mut_container(),
+            None => root.as_mut_container(),
+        };
+        let container = container.ok_or(ErrorKind::UnexpectedTagType)?;
+        let context = ILStandardTagFactoryContext {
+            factory: self,
+            depth: level + 1,
+            nested: None,
+        };
+        let available = (frame.end - reader.offset()) as usize;
+        let finished = match frame.remaining {
+            Some(n) => n == 0,
+            None => available == 0,
+        };
+        if finished {
+            state.cursor = ErrorCursor {
+                offset: frame.start,
+                id: Some(frame.id),
+                levels: level_count - 1,
+                key: true,
+                value: Some((frame.value_start, frame.end)),
+                nested: None,
+            };
+            let mut lreader = LimitedReader::new(reader, available);
+            container.end_deserialization(&context, &mut lreader)?;
+            limited_reader_ensure_empty(&lreader, ErrorKind::CorruptedData)?;
+            state.levels.pop();
+            let done = match state.tags.pop() {
+                Some(t) => t,
+                None => return Ok(true),
+            };
+            let parent = match state.tags.last_mut() {
+                Some(t) => t.as_mut_container(),
+                None => root.as_mut_container(),
+            };
+            parent
+                .ok_or(ErrorKind::UnexpectedTagType)?
+                .push_child(done)?;
+            return Ok(false);
+        }
+        if let Some(n) = frame.remaining.as_mut() {
+            *n -= 1;
+        }
+        frame.next_index += 1;
+
+        // Child header
+        state.cursor = ErrorCursor {
+            offset: reader.offset(),
+            id: None,
+            levels: level_count,
+            key: false,
+            value: None,
+            nested: None,
+        };
+        container.deserialize_child_prefix(&context, &mut LimitedReader::new(reader, available))?;
+        state.cursor.offset = reader.offset();
+        state.cursor.key = true;
+        self.limits.check_depth(level + 1)?;
+        let available = (frame.end - reader.offset()) as usize;
+        let mut lreader = LimitedReader::new(reader, available);
+        let tag_id = lreader.deserialize_ilint_with(self.canonical)?;
+        state.cursor.id = Some(tag_id);
+        let child_size = Self::deserialize_tag_size(tag_id, self.canonical, &mut lreader)?;
+        let available = (frame.end - reader.offset()) as usize;
+        let value_start = reader.offset();
+        if child_size <= available {
+            state.cursor.value = Some((value_start, value_start + child_size as u64));
+        }
+        self.limits.check_tag_size(child_size as u64)?;
+        let mut child = match self.create_tag(tag_id) {
+            Some(t) => t,
+            None => return Err(ErrorKind::UnknownTag),
+        };
+
+        // Child value
+        if child.as_container().is_some() {
+            if child_size > available {
+                return Err(ErrorKind::CorruptedData);
+            }
+            let child_remaining =
+                self.begin_container(level + 1, child_size, reader, child.as_mut())?;
+            state.levels.push(ContainerLevel {
+                id: tag_id,
+                start: state.cursor.offset,
+                value_start,
+                end: value_start + child_size as u64,
+                remaining: child_remaining,
+                next_index: 0,
+            });
+            state.tags.push(child);
+        } else {
+            let nested = NestedTags::new(tag_id, value_start);
+            let mut lreader = LimitedReader::new(reader, std::cmp::min(child_size, available));
+            if let Err(e) = self.deserialize_leaf_value_into(
+                level + 1,
+                child_size,
+                &mut lreader,
+                child.as_mut(),
+                &nested,
+            ) {
+                state.cursor.nested = nested.into_error();
+                return Err(e);
+            }
+            container.push_child(child)?;
+        }
+        Ok(false)
+    }
+
+    /// Recovers from an error found by [`Self::deserialize_container_step()`].
+    /// The tag pointed by the cursor is replaced by a placeholder if its
+    /// header is known and it fits inside its parent. Otherwise, the
+    /// innermost container is replaced. The error is added to the
+    /// diagnostics.
+    ///
+    /// Returns:
+    /// - `Ok(())`: If the error was recovered;
+    /// - `Err(_)`: If the error cannot be recovered;
+    fn recover(
+        kind: ErrorKind,
+        reader: &mut OffsetReader,
+        root: &mut dyn ILTag,
+        state: &mut ContainerState,
+        recovery: &mut Recovery,
+    ) -> Result<()> {
+        let child = match (state.cursor.id, state.cursor.value) {
+            (Some(id), Some((start, end))) if state.cursor.levels == state.levels.len() => {
+                Some((id, start, end))
+            }
+            _ => None,
+        };
+        if child.is_none() && state.levels.len() < 2 {
+            return Err(kind);
+        }
+        recovery.diagnostics.push(state.build_error(kind, root));
+        let (id, value_start, value_end) = match child {
+            Some(c) => c,
+            None => {
+                state.tags.pop();
+                match state.levels.pop() {
+                    Some(l) => (l.id, l.value_start, l.end),
+                    None => return Err(ErrorKind::CorruptedData),
+                }
+            }
+        };
+        reader.skip((value_end - reader.offset()) as usize)?;
+        if recovery.policy == RecoveryPolicy::Placeholder {
+            let value = &recovery.raw[value_start as usize..value_end as usize];
+            let parent = match state.tags.last_mut() {
+                Some(t) => t.as_mut_container(),
+                None => root.as_mut_container(),
+            };
+            parent
+                .ok_or(ErrorKind::UnexpectedTagType)?
+                .push_child(Box::new(ILRawTag::with_value(id, value)))?;
+        }
+        Ok(())
+    }
+
+    fn deserialize_at_depth(
+        &self,
+        depth: usize,
+        reader: &mut OffsetReader,
+        recovery: Option<&mut Recovery>,
+    ) -> ContextResult<Box<dyn ILTag>> {
+        let start = reader.offset();
+        let (tag_id, tag_size) = self.deserialize_header(depth, reader, None)?;
+        let mut tag = match self.create_tag(tag_id) {
+            Some(t) => t,
+            None => {
+                return Err(DeserializationError::new(
+                    ErrorKind::UnknownTag,
+                    start,
+                    Some(tag_id),
+                    ILTagPath::new(),
+                ))
+            }
+        };
+        self.deserialize_tag_value_into(depth, start, tag_size, reader, tag.as_mut(), recovery)?;
+        Ok(tag)
+    }
+
+    fn deserialize_into_at_depth(
+        &self,
+        depth: usize,
+        reader: &mut OffsetReader,
+        tag: &mut dyn ILTag,
+    ) -> ContextResult<()> {
+        let start = reader.offset();
+        let (_, tag_size) = self.deserialize_header(depth, reader, Some(tag.id()))?;
+        self.deserialize_tag_value_into(depth, start, tag_size, reader, tag, None)
+    }
+
+    /// Deserializes a tag from a reader just like
+    /// [`ILTagFactory::deserialize()`] but, in case of failure, reports the
+    /// offset, the id and the path of the tag that could not be deserialized.
+    /// The path is collected from [`ILTagSeqTag`], [`ILTagArrayTag`],
+    /// [`ILDictTag`] and any other tag that implements
+    /// [`crate::tags::container::ILTagContainer`].
+    ///
+    /// The offsets are relative to the position of the reader when this
+    /// method is called.
+    ///
+    /// The errors found inside the tags deserialized through the factory by
+    /// a tag that is not a container are located as well. Their paths
+    /// include the tag that deserialized them as `tag(id)[n]`, where `n` is
+    /// the index of the nested tag.
+    ///
+    /// Arguments:
+    /// - `reader`: The reader that contains the tag;
+    ///
+    /// Returns:
+    /// - `Ok(tag)`: The deserialized tag;
+    /// - `Err(e)`: The error and its location;
+    ///
+    /// New since 2.0.0.
+    pub fn deserialize_with_context(
+        &self,
+        reader: &mut dyn Reader,
+    ) -> std::result::Result<Box<dyn ILTag>, DeserializationError> {
+        self.deserialize_at_depth(0, &mut OffsetReader::new(reader), None)
+    }
+
+    /// Deserializes a tag from a reader into an existing tag just like
+    /// [`ILTagFactory::deserialize_into()`]. See
+    /// [`Self::deserialize_with_context()`] for further details about the
+    /// errors.
+    ///
+    /// Arguments:
+    /// - `reader`: The reader that contains the tag;
+    /// - `tag`: The tag instance that will receive the deserialized tag;
+    ///
+    /// Returns:
+    /// - `Ok(())`: On success;
+    /// - `Err(e)`: The error and its location;
+    ///
+    /// New since 2.0.0.
+    pub fn deserialize_into_with_context(
+        &self,
+        reader: &mut dyn Reader,
+        tag: &mut dyn ILTag,
+    ) -> std::result::Result<(), DeserializationError> {
+        self.deserialize_into_at_depth(0, &mut OffsetReader::new(reader), tag)
+    }
+
+    /// Deserializes a tag from a byte slice just like
+    /// [`ILTagFactory::from_bytes()`]. See
+    /// [`Self::deserialize_with_context()`] for further details about the
+    /// errors. The offsets are the absolute offsets inside `raw_tag`.
+    ///
+    /// Arguments:
+    /// - `raw_tag`: The serialized tag;
+    ///
+    /// Returns:
+    /// - `Ok(tag)`: The deserialized tag;
+    /// - `Err(e)`: The error and its location;
+    ///
+    /// New since 2.0.0.
+    pub fn from_bytes_with_context(
+        &self,
+        raw_tag: &[u8],
+    ) -> std::result::Result<Box<dyn ILTag>, DeserializationError> {
+        let mut reader = ByteArrayReader::new(raw_tag);
+        let tag = self.deserialize_with_context(&mut reader)?;
+        if reader.available() == 0 {
+            Ok(tag)
+        } else {
+            Err(DeserializationError::new(
+                ErrorKind::CorruptedData,
+                reader.offset() as u64,
+                None,
+                ILTagPath::new(),
+            ))
+        }
+    }
+
+    /// Deserializes a tag from a byte slice in recovery mode. Instead of
+    /// failing, the children of the containers that cannot be deserialized
+    /// are handled according to `policy` and the deserialization continues
+    /// with their siblings. Each error found is added to `diagnostics`.
+    ///
+    /// A child can be recovered only if its header can be read and its
+    /// value fits inside its parent. Otherwise the innermost container that
+    /// contains it is recovered instead. Errors found in the top level tag
+    /// itself cannot be recovered.
+    ///
+    /// Arguments:
+    /// - `raw_tag`: The serialized tag;
+    /// - `policy`: How the tags that cannot be deserialized are handled;
+    /// - `diagnostics`: The vector that will receive the errors found;
+    ///
+    /// Returns:
+    /// - `Ok(tag)`: The best-effort deserialized tag;
+    /// - `Err(e)`: If the top level tag cannot be deserialized;
+    ///
+    /// New since 2.0.0.
+    pub fn from_bytes_recovering(
+        &self,
+        raw_tag: &[u8],
+        policy: RecoveryPolicy,
+        diagnostics: &mut Vec<DeserializationError>,
+    ) -> std::result::Result<Box<dyn ILTag>, DeserializationError> {
+        let mut recovery = Recovery {
+            raw: raw_tag,
+            policy,
+            diagnostics,
+        };
+        let mut reader = ByteArrayReader::new(raw_tag);
+        let tag =
+            self.deserialize_at_depth(0, &mut OffsetReader::new(&mut reader), Some(&mut recovery))?;
+        if reader.available() != 0 {
+            recovery.diagnostics.push(DeserializationError::new(
+                ErrorKind::CorruptedData,
+                reader.offset() as u64,
+                None,
+                ILTagPath::new(),
+            ));
+        }
+        Ok(tag)
+    }
 }
 
 impl ILTagFactory for ILStandardTagFactory {
@@ -182,23 +772,312 @@ impl ILTagFactory for ILStandardTagFactory {
     }
 
     fn deserialize(&self, reader: &mut dyn Reader) -> Result<Box<dyn ILTag>> {
-        let tag_id = reader.deserialize_ilint()?;
-        let tag_size = Self::deserialize_tag_size(tag_id, reader)?;
-        let mut tag = match self.create_tag(tag_id) {
-            Some(t) => t,
-            None => return Err(ErrorKind::UnknownTag),
+        self.deserialize_with_context(reader)
+            .map_err(ErrorKind::from)
+    }
+
+    fn deserialize_into(&self, reader: &mut dyn Reader, tag: &mut dyn ILTag) -> Result<()> {
+        self.deserialize_into_with_context(reader, tag)
+            .map_err(ErrorKind::from)
+    }
+
+    fn limits(&self) -> &DeserializationLimits {
+        &self.limits
+    }
+
+    fn canonical(&self) -> bool {
+        self.canonical
+    }
+
+    fn duplicate_key_policy(&self) -> DuplicateKeyPolicy {
+        self.duplicate_key_policy
+    }
+
+    fn sorted_keys(&self) -> bool {
+        self.sorted_keys
+    }
+}
+
+//=============================================================================
+// ILStandardTagFactoryContext
+//-----------------------------------------------------------------------------
+/// This struct is the [`ILTagFactory`] passed by [`ILStandardTagFactory`] to
+/// [`ILTag::deserialize_value()`]. It keeps track of the current nesting
+/// depth in order to enforce the [`DeserializationLimits`] and, if `nested`
+/// is set, of the location of the tags deserialized through it.
+struct ILStandardTagFactoryContext<'a> {
+    factory: &'a ILStandardTagFactory,
+    depth: usize,
+    nested: Option<&'a NestedTags>,
+}
+
+impl<'a> ILStandardTagFactoryContext<'a> {
+    /// Creates the [`OffsetReader`] used to deserialize a nested tag. Its
+    /// offset is the absolute offset of the next byte if it is known.
+    fn offset_reader<'r>(&self, reader: &'r mut dyn Reader) -> OffsetReader<'r> {
+        match self.nested {
+            Some(n) => OffsetReader::with_offset(reader, n.offset.load(Ordering::Relaxed)),
+            None => OffsetReader::new(reader),
+        }
+    }
+
+    /// Converts the result of the deserialization of a nested tag. The
+    /// location of the error is kept by `nested` if it is set. It is
+    /// discarded if a later nested tag is deserialized successfully.
+    fn map_result<T>(&self, result: ContextResult<T>) -> Result<T> {
+        let n = match self.nested {
+            Some(n) => n,
+            None => return result.map_err(ErrorKind::from),
         };
-        self.deserialize_tag_value_into(tag_size, reader, tag.as_mut())?;
-        Ok(tag)
+        let (ret, location) = match result {
+            Ok(v) => (Ok(v), None),
+            Err(e) => {
+                let mut path = e.path().clone();
+                path.push_front(ILTagPathElement::new(
+                    n.id,
+                    n.count.load(Ordering::Relaxed).saturating_sub(1),
+                    None,
+                ));
+                let location = NestedError {
+                    offset: e.offset(),
+                    id: e.id(),
+                    path,
+                };
+                (Err(e.into_kind()), Some(location))
+            }
+        };
+        if let Ok(mut error) = n.error.lock() {
+            *error = location;
+        }
+        ret
+    }
+}
+
+impl<'a> ILTagFactory for ILStandardTagFactoryContext<'a> {
+    fn create_tag(&self, tag_id: u64) -> Option<Box<dyn ILTag>> {
+        self.factory.create_tag(tag_id)
+    }
+
+    fn deserialize(&self, reader: &mut dyn Reader) -> Result<Box<dyn ILTag>> {
+        if let Some(n) = self.nested {
+            n.count.fetch_add(1, Ordering::Relaxed);
+        }
+        let result =
+            self.factory
+                .deserialize_at_depth(self.depth, &mut self.offset_reader(reader), None);
+        self.map_result(result)
     }
 
     fn deserialize_into(&self, reader: &mut dyn Reader, tag: &mut dyn ILTag) -> Result<()> {
-        let tag_id = reader.deserialize_ilint()?;
-        if tag_id != tag.id() {
-            return Err(ErrorKind::UnexpectedTagType);
+        if let Some(n) = self.nested {
+            n.count.fetch_add(1, Ordering::Relaxed);
         }
-        let tag_size = Self::deserialize_tag_size(tag_id, reader)?;
-        self.deserialize_tag_value_into(tag_size, reader, tag)?;
+        let result = self.factory.deserialize_into_at_depth(
+            self.depth,
+            &mut self.offset_reader(reader),
+            tag,
+        );
+        self.map_result(result)
+    }
+
+    fn limits(&self) -> &DeserializationLimits {
+        self.factory.limits()
+    }
+
+    fn canonical(&self) -> bool {
+        self.factory.canonical()
+    }
+
+    fn duplicate_key_policy(&self) -> DuplicateKeyPolicy {
+        self.factory.duplicate_key_policy()
+    }
+
+    fn sorted_keys(&self) -> bool {
+        self.factory.sorted_keys()
+    }
+
+    fn depth(&self) -> usize {
+        self.depth
+    }
+}
+
+//=============================================================================
+// NestedTags
+//-----------------------------------------------------------------------------
+/// Location of an error found inside a tag deserialized by a tag that is not
+/// a container.
+struct NestedError {
+    offset: u64,
+    id: Option<u64>,
+    /// Path relative to the tag that is not a container, including it.
+    path: ILTagPath,
+}
+
+/// Tracks the tags deserialized through [`ILStandardTagFactoryContext`] by a
+/// tag that is not a container, so the errors found inside them report their
+/// absolute offsets and their paths.
+struct NestedTags {
+    /// Id of the tag that is not a container.
+    id: u64,
+    /// Absolute offset of the next byte read by the tag.
+    offset: AtomicU64,
+    /// Number of tags deserialized so far.
+    count: AtomicU64,
+    /// Location of the last error found inside the nested tags.
+    error: Mutex<Option<NestedError>>,
+}
+
+impl NestedTags {
+    fn new(id: u64, offset: u64) -> Self {
+        Self {
+            id,
+            offset: AtomicU64::new(offset),
+            count: AtomicU64::new(0),
+            error: Mutex::new(None),
+        }
+    }
+
+    /// Returns the location of the last error found inside the nested tags.
+    fn into_error(self) -> Option<NestedError> {
+        match self.error.into_inner() {
+            Ok(e) => e,
+            Err(e) => e.into_inner(),
+        }
+    }
+}
+
+/// A [`Reader`] that updates the offset of [`NestedTags`].
+struct NestedTagsReader<'a> {
+    source: &'a mut dyn Reader,
+    nested: &'a NestedTags,
+}
+
+impl<'a> NestedTagsReader<'a> {
+    fn advance(&self, count: usize) {
+        self.nested
+            .offset
+            .fetch_add(count as u64, Ordering::Relaxed);
+    }
+}
+
+impl<'a> Reader for NestedTagsReader<'a> {
+    fn read(&mut self) -> crate::io::Result<u8> {
+        let b = self.source.read()?;
+        self.advance(1);
+        Ok(b)
+    }
+
+    fn read_all(&mut self, buff: &mut [u8]) -> crate::io::Result<()> {
+        self.source.read_all(buff)?;
+        self.advance(buff.len());
+        Ok(())
+    }
+
+    fn skip(&mut self, count: usize) -> crate::io::Result<()> {
+        self.source.skip(count)?;
+        self.advance(count);
         Ok(())
     }
 }
+
+//=============================================================================
+// ContainerState
+//-----------------------------------------------------------------------------
+/// A specialized [`std::result::Result`] that reports the location of the
+/// errors.
+type ContextResult<T> = std::result::Result<T, DeserializationError>;
+
+/// A container being deserialized by [`ILStandardTagFactory`].
+struct ContainerLevel {
+    id: u64,
+    start: u64,
+    value_start: u64,
+    end: u64,
+    remaining: Option<u64>,
+    next_index: u64,
+}
+
+/// Location of the tag being deserialized by [`ILStandardTagFactory`].
+struct ErrorCursor {
+    /// Offset of the tag.
+    offset: u64,
+    /// The tag id if already known.
+    id: Option<u64>,
+    /// Number of containers that are ancestors of the tag.
+    levels: usize,
+    /// Whether the key of the tag inside its parent is already known.
+    key: bool,
+    /// The bounds of the value of the tag if it is known to fit inside its
+    /// parent.
+    value: Option<(u64, u64)>,
+    /// The location of the error if it was found inside a tag deserialized
+    /// by the tag, which is not a container.
+    nested: Option<NestedError>,
+}
+
+/// State of the recovery mode of [`ILStandardTagFactory`].
+struct Recovery<'a> {
+    raw: &'a [u8],
+    policy: RecoveryPolicy,
+    diagnostics: &'a mut Vec<DeserializationError>,
+}
+
+/// The explicit stack used by [`ILStandardTagFactory`] to deserialize the
+/// containers. The first level is the root container, which is not stored
+/// in `tags`.
+struct ContainerState {
+    levels: Vec<ContainerLevel>,
+    tags: Vec<Box<dyn ILTag>>,
+    cursor: ErrorCursor,
+}
+
+impl ContainerState {
+    fn new(start: u64, id: u64) -> Self {
+        Self {
+            levels: Vec::new(),
+            tags: Vec::new(),
+            cursor: ErrorCursor {
+                offset: start,
+                id: Some(id),
+                levels: 0,
+                key: false,
+                value: None,
+                nested: None,
+            },
+        }
+    }
+
+    /// Creates the [`DeserializationError`] that points to the current tag.
+    ///
+    /// Arguments:
+    /// - `kind`: The error;
+    /// - `root`: The root container;
+    fn build_error(&self, kind: ErrorKind, root: &dyn ILTag) -> DeserializationError {
+        let mut path = ILTagPath::new();
+        let count = self.cursor.levels;
+        for (i, level) in self.levels.iter().take(count).enumerate() {
+            let container = match i {
+                0 => root.as_container(),
+                _ => self.tags[i - 1].as_container(),
+            };
+            let key = match container {
+                Some(c) if i + 1 < count || self.cursor.key => c.child_key().map(String::from),
+                _ => None,
+            };
+            path.push(ILTagPathElement::new(
+                level.id,
+                level.next_index.saturating_sub(1),
+                key,
+            ));
+        }
+        match &self.cursor.nested {
+            Some(n) => {
+                for element in n.path.elements() {
+                    path.push(element.clone());
+                }
+                DeserializationError::new(kind, n.offset, n.id, path)
+            }
+            None => DeserializationError::new(kind, self.cursor.offset, self.cursor.id, path),
+        }
+    }
+}
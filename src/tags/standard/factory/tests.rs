@@ -31,8 +31,11 @@
  */
 use super::*;
 use crate::io::array::*;
-use crate::tags::ErrorKind;
+use crate::io::OffsetReader;
+use crate::tags::error::DeserializationError;
 use crate::tags::ILRawTag;
+use crate::tags::{tag_downcast_ref, CanonicalRule, DuplicateKeyPolicy, ErrorKind};
+use std::any::Any;
 
 macro_rules! test_tag_type {
     ($tag: expr, $tag_id: expr, $tag_type: ty) => {
@@ -208,7 +211,7 @@ fn test_ilstandardtagfactory_iltagfactory_deserialize_tag_size() {
 
     // Implicit
     for tag_id in 0..16 {
-        let size = match ILStandardTagFactory::deserialize_tag_size(tag_id, &mut reader) {
+        let size = match ILStandardTagFactory::deserialize_tag_size(tag_id, false, &mut reader) {
             Ok(v) => v,
             _ => panic!("Unexpected error."),
         };
@@ -221,14 +224,14 @@ fn test_ilstandardtagfactory_iltagfactory_deserialize_tag_size() {
         let mut writer = VecWriter::new();
         assert!(writer.serialize_ilint(exp_size).is_ok());
         let mut reader = ByteArrayReader::new(writer.as_slice());
-        let size = match ILStandardTagFactory::deserialize_tag_size(12345, &mut reader) {
+        let size = match ILStandardTagFactory::deserialize_tag_size(12345, false, &mut reader) {
             Ok(v) => v,
             _ => panic!("Unexpected error {:?}.", exp_size),
         };
         assert_eq!(exp_size, size as u64);
 
         let mut reader = ByteArrayReader::new(&writer.as_slice()[0..writer.as_slice().len() - 1]);
-        match ILStandardTagFactory::deserialize_tag_size(12345, &mut reader) {
+        match ILStandardTagFactory::deserialize_tag_size(12345, false, &mut reader) {
             Err(_) => (),
             _ => panic!("Error expected."),
         };
@@ -240,7 +243,7 @@ fn test_ilstandardtagfactory_iltagfactory_deserialize_tag_size() {
         .serialize_ilint(crate::tags::MAX_TAG_SIZE + 1)
         .is_ok());
     let mut reader = ByteArrayReader::new(writer.as_slice());
-    match ILStandardTagFactory::deserialize_tag_size(12345, &mut reader) {
+    match ILStandardTagFactory::deserialize_tag_size(12345, false, &mut reader) {
         Err(ErrorKind::TagTooLarge) => (),
         _ => panic!("Error expected."),
     };
@@ -254,25 +257,39 @@ fn test_ilstandardtagfactory_iltagfactory_deserialize_tag_value_into_fixed() {
     let mut tr = ILInt64Tag::default();
     let mut writer = VecWriter::new();
     assert!(t.serialize_value(&mut writer).is_ok());
-    assert!(writer.serialize_value(0 as u8).is_ok());
+    assert!(writer.serialize_value(0_u8).is_ok());
 
     // Correct
     let mut reader = ByteArrayReader::new(writer.as_slice());
     assert!(factory
-        .deserialize_tag_value_into(8, &mut reader, &mut tr)
+        .deserialize_tag_value_into(0, 0, 8, &mut OffsetReader::new(&mut reader), &mut tr, None)
         .is_ok());
     assert!(crate::tags::util::iltag_are_equal(&t, &tr));
 
     // Too large
     let mut reader = ByteArrayReader::new(writer.as_slice());
-    match factory.deserialize_tag_value_into(9, &mut reader, &mut tr) {
-        Err(ErrorKind::CorruptedData) => (),
+    match factory.deserialize_tag_value_into(
+        0,
+        0,
+        9,
+        &mut OffsetReader::new(&mut reader),
+        &mut tr,
+        None,
+    ) {
+        Err(e) if matches!(e.kind(), ErrorKind::CorruptedData) => (),
         _ => panic!("Error expected."),
     }
 
     // Too small
     let mut reader = ByteArrayReader::new(&writer.as_slice()[0..7]);
-    match factory.deserialize_tag_value_into(8, &mut reader, &mut tr) {
+    match factory.deserialize_tag_value_into(
+        0,
+        0,
+        8,
+        &mut OffsetReader::new(&mut reader),
+        &mut tr,
+        None,
+    ) {
         Err(_) => (),
         _ => panic!("Error expected."),
     }
@@ -286,26 +303,47 @@ fn test_ilstandardtagfactory_iltagfactory_deserialize_tag_value_into_ilint() {
     let mut tr = ILILInt64Tag::default();
     let mut writer = VecWriter::new();
     assert!(t.serialize_value(&mut writer).is_ok());
-    assert!(writer.serialize_value(0 as u8).is_ok());
+    assert!(writer.serialize_value(0_u8).is_ok());
     let value_size = writer.as_slice().len();
 
     // Correct
     let mut reader = ByteArrayReader::new(writer.as_slice());
     assert!(factory
-        .deserialize_tag_value_into(value_size - 1, &mut reader, &mut tr)
+        .deserialize_tag_value_into(
+            0,
+            0,
+            value_size - 1,
+            &mut OffsetReader::new(&mut reader),
+            &mut tr,
+            None
+        )
         .is_ok());
     assert!(crate::tags::util::iltag_are_equal(&t, &tr));
 
     // Too large
     let mut reader = ByteArrayReader::new(writer.as_slice());
     assert!(factory
-        .deserialize_tag_value_into(value_size, &mut reader, &mut tr)
+        .deserialize_tag_value_into(
+            0,
+            0,
+            value_size,
+            &mut OffsetReader::new(&mut reader),
+            &mut tr,
+            None
+        )
         .is_ok());
     assert!(crate::tags::util::iltag_are_equal(&t, &tr));
 
     // Too small
     let mut reader = ByteArrayReader::new(&writer.as_slice()[0..value_size - 2]);
-    match factory.deserialize_tag_value_into(value_size, &mut reader, &mut tr) {
+    match factory.deserialize_tag_value_into(
+        0,
+        0,
+        value_size,
+        &mut OffsetReader::new(&mut reader),
+        &mut tr,
+        None,
+    ) {
         Err(_) => (),
         _ => panic!("Error expected."),
     }
@@ -319,26 +357,47 @@ fn test_ilstandardtagfactory_iltagfactory_deserialize_tag_value_into_signed_ilin
     let mut tr = ILSignedILInt64Tag::default();
     let mut writer = VecWriter::new();
     assert!(t.serialize_value(&mut writer).is_ok());
-    assert!(writer.serialize_value(0 as u8).is_ok());
+    assert!(writer.serialize_value(0_u8).is_ok());
     let value_size = writer.as_slice().len();
 
     // Correct
     let mut reader = ByteArrayReader::new(writer.as_slice());
     assert!(factory
-        .deserialize_tag_value_into(value_size - 1, &mut reader, &mut tr)
+        .deserialize_tag_value_into(
+            0,
+            0,
+            value_size - 1,
+            &mut OffsetReader::new(&mut reader),
+            &mut tr,
+            None
+        )
         .is_ok());
     assert!(crate::tags::util::iltag_are_equal(&t, &tr));
 
     // Too large
     let mut reader = ByteArrayReader::new(writer.as_slice());
     assert!(factory
-        .deserialize_tag_value_into(value_size, &mut reader, &mut tr)
+        .deserialize_tag_value_into(
+            0,
+            0,
+            value_size,
+            &mut OffsetReader::new(&mut reader),
+            &mut tr,
+            None
+        )
         .is_ok());
     assert!(crate::tags::util::iltag_are_equal(&t, &tr));
 
     // Too small
     let mut reader = ByteArrayReader::new(&writer.as_slice()[0..value_size - 2]);
-    match factory.deserialize_tag_value_into(value_size, &mut reader, &mut tr) {
+    match factory.deserialize_tag_value_into(
+        0,
+        0,
+        value_size,
+        &mut OffsetReader::new(&mut reader),
+        &mut tr,
+        None,
+    ) {
         Err(_) => (),
         _ => panic!("Error expected."),
     }
@@ -545,3 +604,798 @@ fn test_ilstandardtagfactory_iltagfactory_from_bytes() {
     let t2 = f.from_bytes(s.as_slice()).unwrap();
     assert!(crate::tags::util::iltag_are_equal(&t, t2.as_ref()));
 }
+
+#[test]
+fn test_ilstandardtagfactory_limits() {
+    let mut f = ILStandardTagFactory::new(true);
+    assert_eq!(*f.limits(), DeserializationLimits::default());
+
+    let mut limits = DeserializationLimits::default();
+    limits.set_max_depth(10);
+    f.set_limits(limits);
+    assert_eq!(*f.limits(), limits);
+
+    let f = ILStandardTagFactory::with_limits(true, limits);
+    assert_eq!(*f.limits(), limits);
+}
+
+fn create_nested_seq(depth: usize) -> ILTagSeqTag {
+    let mut t = ILTagSeqTag::new();
+    t.mut_value().push(Box::new(ILNullTag::new()));
+    for _ in 0..depth {
+        let mut parent = ILTagSeqTag::new();
+        parent.mut_value().push(Box::new(t));
+        t = parent;
+    }
+    t
+}
+
+#[test]
+fn test_ilstandardtagfactory_limits_depth() {
+    // The null tag is at depth 4
+    let s = create_nested_seq(3).to_bytes().unwrap();
+
+    let mut limits = DeserializationLimits::default();
+    limits.set_max_depth(4);
+    let f = ILStandardTagFactory::with_limits(true, limits);
+    assert!(f.from_bytes(&s).is_ok());
+    let mut t = ILTagSeqTag::new();
+    assert!(f
+        .deserialize_into(&mut ByteArrayReader::new(&s), &mut t)
+        .is_ok());
+
+    limits.set_max_depth(3);
+    let f = ILStandardTagFactory::with_limits(true, limits);
+    assert!(matches!(
+        f.from_bytes(&s),
+        Err(ErrorKind::DepthLimitExceeded)
+    ));
+    let mut t = ILTagSeqTag::new();
+    assert!(matches!(
+        f.deserialize_into(&mut ByteArrayReader::new(&s), &mut t),
+        Err(ErrorKind::DepthLimitExceeded)
+    ));
+}
+
+#[test]
+fn test_ilstandardtagfactory_limits_total_size() {
+    let s = create_nested_seq(3).to_bytes().unwrap();
+
+    let mut limits = DeserializationLimits::default();
+    limits.set_max_total_size(s.len() as u64);
+    let f = ILStandardTagFactory::with_limits(true, limits);
+    assert!(f.from_bytes(&s).is_ok());
+
+    limits.set_max_total_size(s.len() as u64 - 1);
+    let f = ILStandardTagFactory::with_limits(true, limits);
+    assert!(matches!(
+        f.from_bytes(&s),
+        Err(ErrorKind::TotalSizeLimitExceeded)
+    ));
+}
+
+#[test]
+fn test_ilstandardtagfactory_limits_tag_size() {
+    let s = ILStringTag::with_value("12345").to_bytes().unwrap();
+
+    let mut limits = DeserializationLimits::default();
+    limits.set_max_tag_size(5);
+    let f = ILStandardTagFactory::with_limits(true, limits);
+    assert!(f.from_bytes(&s).is_ok());
+
+    limits.set_max_tag_size(4);
+    let f = ILStandardTagFactory::with_limits(true, limits);
+    assert!(matches!(f.from_bytes(&s), Err(ErrorKind::TagTooLarge)));
+}
+
+#[test]
+fn test_ilstandardtagfactory_limits_elements() {
+    let mut samples: Vec<Box<dyn ILTag>> = Vec::new();
+    let mut seq = ILTagSeqTag::new();
+    let mut array = ILTagArrayTag::new();
+    let mut dict = ILDictTag::new();
+    let mut str_dict = ILStrDictTag::new();
+    for i in 0..3 {
+        let key = format!("{}", i);
+        seq.mut_value().push(Box::new(ILNullTag::new()));
+        array.mut_value().push(Box::new(ILNullTag::new()));
+        dict.insert(&key, Box::new(ILNullTag::new()));
+        str_dict.insert(&key, &key);
+    }
+    samples.push(Box::new(seq));
+    samples.push(Box::new(array));
+    samples.push(Box::new(dict));
+    samples.push(Box::new(str_dict));
+    samples.push(Box::new(ILILIntArrayTag::with_value(&[1, 2, 3])));
+    samples.push(Box::new(ILOIDTag::with_value(&[1, 2, 3])));
+
+    let mut limits = DeserializationLimits::default();
+    for sample in samples {
+        let s = sample.to_bytes().unwrap();
+
+        limits.set_max_elements(3);
+        let f = ILStandardTagFactory::with_limits(true, limits);
+        assert!(f.from_bytes(&s).is_ok());
+
+        limits.set_max_elements(2);
+        let f = ILStandardTagFactory::with_limits(true, limits);
+        assert!(matches!(
+            f.from_bytes(&s),
+            Err(ErrorKind::ElementCountLimitExceeded)
+        ));
+    }
+}
+
+#[test]
+fn test_ilstandardtagfactory_limits_value_length() {
+    let mut samples: Vec<Box<dyn ILTag>> = Vec::new();
+    let mut dict = ILDictTag::new();
+    dict.insert("12345", Box::new(ILNullTag::new()));
+    let mut str_dict_key = ILStrDictTag::new();
+    str_dict_key.insert("12345", "");
+    let mut str_dict_value = ILStrDictTag::new();
+    str_dict_value.insert("", "12345");
+    samples.push(Box::new(ILStringTag::with_value("12345")));
+    samples.push(Box::new(ILByteArrayTag::with_value(b"12345")));
+    samples.push(Box::new(ILBigIntTag::with_value(b"12345")));
+    samples.push(Box::new(ILBigDecTag::with_value(0, b"12345")));
+    samples.push(Box::new(ILRawTag::with_value(1234, b"12345")));
+    samples.push(Box::new(dict));
+    samples.push(Box::new(str_dict_key));
+    samples.push(Box::new(str_dict_value));
+
+    let mut limits = DeserializationLimits::default();
+    for sample in samples {
+        let s = sample.to_bytes().unwrap();
+
+        limits.set_max_value_length(5);
+        let f = ILStandardTagFactory::with_limits(false, limits);
+        assert!(f.from_bytes(&s).is_ok());
+
+        limits.set_max_value_length(4);
+        let f = ILStandardTagFactory::with_limits(false, limits);
+        assert!(matches!(
+            f.from_bytes(&s),
+            Err(ErrorKind::ValueLengthLimitExceeded)
+        ));
+    }
+}
+
+#[test]
+fn test_ilstandardtagfactory_canonical() {
+    let mut f = ILStandardTagFactory::new(true);
+    assert!(!f.canonical());
+    f.set_canonical(true);
+    assert!(f.canonical());
+    f.set_canonical(false);
+    assert!(!f.canonical());
+}
+
+#[test]
+fn test_ilstandardtagfactory_dict_keys() {
+    let mut f = ILStandardTagFactory::new(true);
+    assert_eq!(f.duplicate_key_policy(), DuplicateKeyPolicy::Reject);
+    assert!(!f.sorted_keys());
+    f.set_duplicate_key_policy(DuplicateKeyPolicy::FirstWins);
+    assert_eq!(f.duplicate_key_policy(), DuplicateKeyPolicy::FirstWins);
+    f.set_duplicate_key_policy(DuplicateKeyPolicy::LastWins);
+    assert_eq!(f.duplicate_key_policy(), DuplicateKeyPolicy::LastWins);
+    f.set_sorted_keys(true);
+    assert!(f.sorted_keys());
+    f.set_sorted_keys(false);
+    assert!(!f.sorted_keys());
+}
+
+/// Verifies that the given tag is accepted when the canonical mode is off and
+/// rejected with [`ErrorKind::NonCanonical`] when it is on.
+fn assert_non_canonical(serialized: &[u8]) {
+    let mut f = ILStandardTagFactory::new(false);
+    assert!(f.from_bytes(serialized).is_ok());
+    f.set_canonical(true);
+    assert!(matches!(
+        f.from_bytes(serialized),
+        Err(ErrorKind::NonCanonical(CanonicalRule::ILInt))
+    ));
+}
+
+#[test]
+fn test_ilstandardtagfactory_canonical_ilint() {
+    // Non canonical form of 0xF8
+    let nc: [u8; 3] = [0xF9, 0x00, 0x00];
+    // Non canonical form of 0x1F8
+    let nc_1f8: [u8; 4] = [0xFA, 0x00, 0x01, 0x00];
+
+    // Canonical values are accepted
+    let mut f = ILStandardTagFactory::new(false);
+    f.set_canonical(true);
+    let mut seq = ILTagSeqTag::new();
+    seq.mut_value()
+        .push(Box::new(ILILInt64Tag::with_value(0xFFFF)));
+    seq.mut_value()
+        .push(Box::new(ILSignedILInt64Tag::with_value(-0xFFFF)));
+    seq.mut_value()
+        .push(Box::new(ILByteArrayTag::with_value(&[0; 0x1F8])));
+    seq.mut_value()
+        .push(Box::new(ILRawTag::with_value(0x1F8, &[1, 2, 3])));
+    let mut dict = ILDictTag::new();
+    dict.insert(&"k".repeat(0x1F8), Box::new(ILNullTag::new()));
+    seq.mut_value().push(Box::new(dict));
+    let s = seq.to_bytes().unwrap();
+    assert!(f.from_bytes(&s).is_ok());
+
+    // Tag id
+    let mut s: Vec<u8> = nc_1f8.to_vec();
+    s.push(0);
+    assert_non_canonical(&s);
+
+    // Tag size
+    let mut s: Vec<u8> = vec![IL_BYTES_TAG_ID as u8];
+    s.extend_from_slice(&nc_1f8);
+    s.resize(s.len() + 0x1F8, 0);
+    assert_non_canonical(&s);
+
+    // ILInt tags
+    let mut s: Vec<u8> = vec![IL_ILINT_TAG_ID as u8];
+    s.extend_from_slice(&nc);
+    assert_non_canonical(&s);
+    let mut s: Vec<u8> = vec![IL_SIGNED_ILINT_TAG_ID as u8];
+    s.extend_from_slice(&nc);
+    assert_non_canonical(&s);
+
+    // ILInt array count and values
+    let mut s: Vec<u8> = vec![IL_ILINTARRAY_TAG_ID as u8, 4, 1];
+    s.extend_from_slice(&nc);
+    assert_non_canonical(&s);
+    let mut s: Vec<u8> = vec![IL_ILINTARRAY_TAG_ID as u8, 3];
+    s.extend_from_slice(&nc);
+    let f = ILStandardTagFactory::new(false);
+    assert!(f.from_bytes(&s).is_err());
+    let mut f = ILStandardTagFactory::new(false);
+    f.set_canonical(true);
+    assert!(matches!(
+        f.from_bytes(&s),
+        Err(ErrorKind::NonCanonical(CanonicalRule::ILInt))
+    ));
+
+    // Range start
+    let mut s: Vec<u8> = vec![IL_RANGE_TAG_ID as u8, 5];
+    s.extend_from_slice(&nc);
+    s.extend_from_slice(&[0, 1]);
+    assert_non_canonical(&s);
+
+    // Tag array and dictionary counts
+    for id in &[
+        IL_ILTAGARRAY_TAG_ID,
+        IL_DICTIONARY_TAG_ID,
+        IL_STRING_DICTIONARY_TAG_ID,
+    ] {
+        let mut s: Vec<u8> = vec![*id as u8, 3];
+        s.extend_from_slice(&nc);
+        let mut f = ILStandardTagFactory::new(false);
+        f.set_canonical(true);
+        assert!(matches!(
+            f.from_bytes(&s),
+            Err(ErrorKind::NonCanonical(CanonicalRule::ILInt))
+        ));
+    }
+
+    // Child id inside a container
+    let mut s: Vec<u8> = vec![IL_ILTAGSEQ_TAG_ID as u8, 5];
+    s.extend_from_slice(&nc_1f8);
+    s.push(0);
+    assert_non_canonical(&s);
+
+    // Dictionary key length
+    let mut value = VecWriter::new();
+    value.serialize_ilint(1).unwrap();
+    value.serialize_ilint(IL_STRING_TAG_ID).unwrap();
+    value.serialize_bytes(&nc_1f8).unwrap();
+    value.serialize_bytes(&[b'k'; 0x1F8]).unwrap();
+    value.serialize_ilint(IL_NULL_TAG_ID).unwrap();
+    let mut writer = VecWriter::new();
+    writer.serialize_ilint(IL_DICTIONARY_TAG_ID).unwrap();
+    writer
+        .serialize_ilint(value.as_slice().len() as u64)
+        .unwrap();
+    writer.serialize_bytes(value.as_slice()).unwrap();
+    assert_non_canonical(writer.as_slice());
+}
+
+fn create_context_sample(bad_string: &[u8]) -> Vec<u8> {
+    // dict{"a": null, "payload": array[string, string]}
+    let mut array = VecWriter::new();
+    array.serialize_ilint(2).unwrap();
+    ILStringTag::with_value("ok").serialize(&mut array).unwrap();
+    array.serialize_ilint(IL_STRING_TAG_ID).unwrap();
+    array.serialize_ilint(bad_string.len() as u64).unwrap();
+    array.serialize_bytes(bad_string).unwrap();
+
+    let mut dict = VecWriter::new();
+    dict.serialize_ilint(2).unwrap();
+    ILStringTag::with_value("a").serialize(&mut dict).unwrap();
+    ILNullTag::new().serialize(&mut dict).unwrap();
+    ILStringTag::with_value("payload")
+        .serialize(&mut dict)
+        .unwrap();
+    dict.serialize_ilint(IL_ILTAGARRAY_TAG_ID).unwrap();
+    dict.serialize_ilint(array.as_slice().len() as u64).unwrap();
+    dict.serialize_bytes(array.as_slice()).unwrap();
+
+    let mut writer = VecWriter::new();
+    writer.serialize_ilint(IL_DICTIONARY_TAG_ID).unwrap();
+    writer
+        .serialize_ilint(dict.as_slice().len() as u64)
+        .unwrap();
+    writer.serialize_bytes(dict.as_slice()).unwrap();
+    writer.as_slice().to_vec()
+}
+
+#[test]
+fn test_ilstandardtagfactory_deserialize_with_context() {
+    let f = ILStandardTagFactory::new(false);
+
+    let raw = create_context_sample(b"abc");
+    let tag = f.from_bytes_with_context(&raw).unwrap();
+    assert_eq!(tag.id(), IL_DICTIONARY_TAG_ID);
+    let mut reader = ByteArrayReader::new(&raw);
+    assert!(f.deserialize_with_context(&mut reader).is_ok());
+    let mut reader = ByteArrayReader::new(&raw);
+    let mut tag = ILDictTag::new();
+    assert!(f
+        .deserialize_into_with_context(&mut reader, &mut tag)
+        .is_ok());
+    assert_eq!(tag.value().len(), 2);
+
+    // Invalid string inside the array
+    let raw = create_context_sample(&[0xFF, 0xFF]);
+    let offset = raw.len() - 4;
+    let e = f.from_bytes_with_context(&raw).err().unwrap();
+    assert_eq!(e.offset(), offset as u64);
+    assert_eq!(e.id(), Some(IL_STRING_TAG_ID));
+    assert_eq!(format!("{}", e.path()), "dict[\"payload\"] > array[1]");
+    assert_eq!(
+        format!("{}", e),
+        format!(
+            "{} at offset {} in dict[\"payload\"] > array[1] > string",
+            e.kind(),
+            offset
+        )
+    );
+
+    // The plain API reports the same error
+    let kind = f.from_bytes(&raw).err().unwrap();
+    assert_eq!(format!("{:?}", kind), format!("{:?}", e.kind()));
+    let kind = f
+        .deserialize(&mut ByteArrayReader::new(&raw))
+        .err()
+        .unwrap();
+    assert_eq!(format!("{:?}", kind), format!("{:?}", e.kind()));
+}
+
+#[test]
+fn test_ilstandardtagfactory_deserialize_with_context_locations() {
+    let f = ILStandardTagFactory::new(true);
+
+    // Top level
+    let e = f.from_bytes_with_context(&[]).err().unwrap();
+    assert_eq!(e.offset(), 0);
+    assert_eq!(e.id(), None);
+    assert!(e.path().is_empty());
+
+    let e = f
+        .from_bytes_with_context(&[IL_UINT32_TAG_ID as u8, 1])
+        .err()
+        .unwrap();
+    assert_eq!(e.offset(), 0);
+    assert_eq!(e.id(), Some(IL_UINT32_TAG_ID));
+    assert!(e.path().is_empty());
+
+    let e = f.from_bytes_with_context(&[0x20, 0]).err().unwrap();
+    assert!(matches!(e.kind(), ErrorKind::UnknownTag));
+    assert_eq!(e.id(), Some(0x20));
+
+    let e = f.from_bytes_with_context(&[0, 0]).err().unwrap();
+    assert!(matches!(e.kind(), ErrorKind::CorruptedData));
+    assert_eq!(e.offset(), 1);
+    assert_eq!(e.id(), None);
+
+    let mut reader = ByteArrayReader::new(&[IL_NULL_TAG_ID as u8]);
+    let mut tag = ILBoolTag::new();
+    let e = f
+        .deserialize_into_with_context(&mut reader, &mut tag)
+        .err()
+        .unwrap();
+    assert!(matches!(e.kind(), ErrorKind::UnexpectedTagType));
+    assert_eq!(e.id(), Some(IL_NULL_TAG_ID));
+
+    // Unknown tag inside a sequence
+    let raw: [u8; 7] = [
+        IL_ILTAGSEQ_TAG_ID as u8,
+        5,
+        IL_NULL_TAG_ID as u8,
+        IL_NULL_TAG_ID as u8,
+        IL_NULL_TAG_ID as u8,
+        0x20,
+        0,
+    ];
+    let e = f.from_bytes_with_context(&raw).err().unwrap();
+    assert!(matches!(e.kind(), ErrorKind::UnknownTag));
+    assert_eq!(e.offset(), 5);
+    assert_eq!(e.id(), Some(0x20));
+    assert_eq!(format!("{}", e.path()), "seq[3]");
+
+    // Missing elements inside a nested array
+    let raw: [u8; 7] = [
+        IL_ILTAGSEQ_TAG_ID as u8,
+        5,
+        IL_NULL_TAG_ID as u8,
+        IL_ILTAGARRAY_TAG_ID as u8,
+        2,
+        2,
+        IL_NULL_TAG_ID as u8,
+    ];
+    let e = f.from_bytes_with_context(&raw).err().unwrap();
+    assert_eq!(e.offset(), 7);
+    assert_eq!(e.id(), None);
+    assert_eq!(format!("{}", e.path()), "seq[1] > array[1]");
+
+    // Corrupted array prefix
+    let raw: [u8; 5] = [
+        IL_ILTAGSEQ_TAG_ID as u8,
+        3,
+        IL_ILTAGARRAY_TAG_ID as u8,
+        1,
+        3,
+    ];
+    let e = f.from_bytes_with_context(&raw).err().unwrap();
+    assert_eq!(e.offset(), 2);
+    assert_eq!(e.id(), Some(IL_ILTAGARRAY_TAG_ID));
+    assert_eq!(format!("{}", e.path()), "seq[0]");
+
+    // Invalid dictionary key
+    let raw: [u8; 5] = [
+        IL_DICTIONARY_TAG_ID as u8,
+        3,
+        1,
+        IL_NULL_TAG_ID as u8,
+        IL_NULL_TAG_ID as u8,
+    ];
+    let e = f.from_bytes_with_context(&raw).err().unwrap();
+    assert_eq!(e.offset(), 3);
+    assert_eq!(e.id(), None);
+    assert_eq!(format!("{}", e.path()), "dict[0]");
+
+    // Trailing data
+    let e = f
+        .from_bytes_with_context(&[IL_NULL_TAG_ID as u8, IL_NULL_TAG_ID as u8])
+        .err()
+        .unwrap();
+    assert!(matches!(e.kind(), ErrorKind::CorruptedData));
+    assert_eq!(e.offset(), 1);
+}
+
+/// Id of [`EnvelopeTag`].
+const ENVELOPE_TAG_ID: u64 = 1234;
+
+/// A tag that is not a container but deserializes a nested tag through the
+/// factory.
+#[derive(Default)]
+struct EnvelopeTag {
+    header: u8,
+    payload: Option<Box<dyn ILTag>>,
+}
+
+impl ILTag for EnvelopeTag {
+    fn id(&self) -> u64 {
+        ENVELOPE_TAG_ID
+    }
+
+    iltag_as_any_impl!();
+
+    fn value_size(&self) -> u64 {
+        1 + self.payload.as_ref().map_or(0, |p| p.size())
+    }
+
+    fn serialize_value(&self, writer: &mut dyn crate::io::Writer) -> Result<()> {
+        writer.write(self.header)?;
+        match &self.payload {
+            Some(p) => p.serialize(writer),
+            None => Ok(()),
+        }
+    }
+
+    fn deserialize_value(
+        &mut self,
+        factory: &dyn ILTagFactory,
+        _value_size: usize,
+        reader: &mut dyn Reader,
+    ) -> Result<()> {
+        self.header = reader.read()?;
+        self.payload = Some(factory.deserialize(reader)?);
+        Ok(())
+    }
+}
+
+/// Creates an [`EnvelopeTag`] whose payload is `seq[null, string]` where
+/// the string contains an invalid UTF-8 sequence.
+fn create_envelope_sample() -> Vec<u8> {
+    let payload: [u8; 7] = [
+        IL_ILTAGSEQ_TAG_ID as u8,
+        5,
+        IL_NULL_TAG_ID as u8,
+        IL_STRING_TAG_ID as u8,
+        2,
+        0xFF,
+        0xFF,
+    ];
+    let mut value: Vec<u8> = vec![0xAA];
+    value.extend_from_slice(&payload);
+    ILRawTag::with_value(ENVELOPE_TAG_ID, &value)
+        .to_bytes()
+        .unwrap()
+}
+
+#[test]
+fn test_ilstandardtagfactory_deserialize_with_context_nested() {
+    let mut f = ILStandardTagFactory::new(true);
+    f.engine().register(
+        ENVELOPE_TAG_ID,
+        Box::new(ILDefaultTagCreator::<EnvelopeTag>::new()),
+    );
+
+    // Top level envelope
+    let raw = create_envelope_sample();
+    let e = f.from_bytes_with_context(&raw).err().unwrap();
+    assert_eq!(e.offset(), (raw.len() - 4) as u64);
+    assert_eq!(e.id(), Some(IL_STRING_TAG_ID));
+    assert_eq!(format!("{}", e.path()), "tag(1234)[0] > seq[1]");
+
+    // seq[null, envelope] - The error is two levels under the envelope
+    let mut value: Vec<u8> = vec![IL_NULL_TAG_ID as u8];
+    value.extend_from_slice(&create_envelope_sample());
+    let raw = ILRawTag::with_value(IL_ILTAGSEQ_TAG_ID, &value)
+        .to_bytes()
+        .unwrap();
+    let e = f.from_bytes_with_context(&raw).err().unwrap();
+    assert_eq!(e.offset(), (raw.len() - 4) as u64);
+    assert_eq!(e.id(), Some(IL_STRING_TAG_ID));
+    assert_eq!(format!("{}", e.path()), "seq[1] > tag(1234)[0] > seq[1]");
+    let kind = f.from_bytes(&raw).err().unwrap();
+    assert_eq!(format!("{:?}", kind), format!("{:?}", e.kind()));
+
+    // Offsets relative to the position of the reader
+    let mut prefixed: Vec<u8> = vec![IL_NULL_TAG_ID as u8, IL_NULL_TAG_ID as u8];
+    prefixed.extend_from_slice(&raw);
+    let mut reader = ByteArrayReader::new(&prefixed);
+    reader.skip(2).unwrap();
+    let e = f.deserialize_with_context(&mut reader).err().unwrap();
+    assert_eq!(e.offset(), (raw.len() - 4) as u64);
+
+    // Recovery mode
+    let mut diagnostics = Vec::new();
+    let tag = f
+        .from_bytes_recovering(&raw, RecoveryPolicy::Placeholder, &mut diagnostics)
+        .ok()
+        .unwrap();
+    let seq = tag_downcast_ref::<ILTagSeqTag>(tag.as_ref()).unwrap();
+    assert!(seq.value()[1].as_any().is::<ILRawTag>());
+    assert_eq!(diagnostics.len(), 1);
+    assert_eq!(diagnostics[0].offset(), (raw.len() - 4) as u64);
+    assert_eq!(
+        format!("{}", diagnostics[0].path()),
+        "seq[1] > tag(1234)[0] > seq[1]"
+    );
+
+    // Valid envelope
+    let envelope = EnvelopeTag {
+        header: 0xAA,
+        payload: Some(Box::new(ILNullTag::new())),
+    };
+    let raw = envelope.to_bytes().unwrap();
+    let tag = f.from_bytes_with_context(&raw).unwrap();
+    assert_eq!(tag.to_bytes().unwrap(), raw);
+}
+
+#[test]
+fn test_recoverypolicy_default() {
+    assert_eq!(RecoveryPolicy::default(), RecoveryPolicy::Placeholder);
+}
+
+fn recover_seq(
+    f: &ILStandardTagFactory,
+    raw: &[u8],
+    policy: RecoveryPolicy,
+    diagnostics: &mut Vec<DeserializationError>,
+) -> Vec<u64> {
+    let tag = match f.from_bytes_recovering(raw, policy, diagnostics) {
+        Ok(t) => t,
+        Err(e) => panic!("Unexpected error {}", e),
+    };
+    let seq = tag_downcast_ref::<ILTagSeqTag>(tag.as_ref()).unwrap();
+    seq.value().iter().map(|t| t.id()).collect()
+}
+
+#[test]
+fn test_ilstandardtagfactory_from_bytes_recovering() {
+    let f = ILStandardTagFactory::new(true);
+
+    // Valid
+    let mut seq = ILTagSeqTag::new();
+    seq.mut_value()
+        .push(Box::new(ILStringTag::with_value("abc")));
+    seq.mut_value().push(Box::new(ILUInt32Tag::with_value(1)));
+    let raw = seq.to_bytes().unwrap();
+    let mut diagnostics = Vec::new();
+    let tag = f
+        .from_bytes_recovering(&raw, RecoveryPolicy::Placeholder, &mut diagnostics)
+        .ok()
+        .unwrap();
+    assert!(crate::tags::util::iltag_are_equal(&seq, tag.as_ref()));
+    assert!(diagnostics.is_empty());
+
+    // seq[uint8, string (invalid UTF-8), null, string (invalid UTF-8)]
+    let raw: [u8; 12] = [
+        IL_ILTAGSEQ_TAG_ID as u8,
+        10,
+        IL_UINT8_TAG_ID as u8,
+        1,
+        IL_STRING_TAG_ID as u8,
+        2,
+        0xFF,
+        0xFF,
+        IL_NULL_TAG_ID as u8,
+        IL_STRING_TAG_ID as u8,
+        1,
+        0xFF,
+    ];
+    let mut diagnostics = Vec::new();
+    let tag = f
+        .from_bytes_recovering(&raw, RecoveryPolicy::Placeholder, &mut diagnostics)
+        .ok()
+        .unwrap();
+    let seq = tag_downcast_ref::<ILTagSeqTag>(tag.as_ref()).unwrap();
+    assert_eq!(seq.value().len(), 4);
+    let placeholder = tag_downcast_ref::<ILRawTag>(seq.value()[1].as_ref()).unwrap();
+    assert_eq!(placeholder.id(), IL_STRING_TAG_ID);
+    assert_eq!(placeholder.value().as_slice(), &[0xFF, 0xFF]);
+    assert_eq!(seq.value()[2].id(), IL_NULL_TAG_ID);
+    assert!(tag_downcast_ref::<ILRawTag>(seq.value()[3].as_ref()).is_some());
+    assert_eq!(diagnostics.len(), 2);
+    assert_eq!(diagnostics[0].offset(), 4);
+    assert_eq!(diagnostics[0].id(), Some(IL_STRING_TAG_ID));
+    assert_eq!(format!("{}", diagnostics[0].path()), "seq[1]");
+    assert_eq!(diagnostics[1].offset(), 9);
+    assert_eq!(format!("{}", diagnostics[1].path()), "seq[3]");
+
+    let mut diagnostics = Vec::new();
+    assert_eq!(
+        recover_seq(&f, &raw, RecoveryPolicy::Skip, &mut diagnostics),
+        vec![IL_UINT8_TAG_ID, IL_NULL_TAG_ID]
+    );
+    assert_eq!(diagnostics.len(), 2);
+
+    // Trailing data
+    let mut raw = raw.to_vec();
+    raw.push(0);
+    let mut diagnostics = Vec::new();
+    assert!(f
+        .from_bytes_recovering(&raw, RecoveryPolicy::Skip, &mut diagnostics)
+        .is_ok());
+    assert_eq!(diagnostics.len(), 3);
+    assert_eq!(diagnostics[2].offset(), 12);
+}
+
+#[test]
+fn test_ilstandardtagfactory_from_bytes_recovering_nested() {
+    let f = ILStandardTagFactory::new(true);
+
+    // seq[array(3)[null, null], null] - The array is missing an element,
+    // thus the array itself is replaced.
+    let raw: [u8; 8] = [
+        IL_ILTAGSEQ_TAG_ID as u8,
+        6,
+        IL_ILTAGARRAY_TAG_ID as u8,
+        3,
+        3,
+        IL_NULL_TAG_ID as u8,
+        IL_NULL_TAG_ID as u8,
+        IL_NULL_TAG_ID as u8,
+    ];
+    let mut diagnostics = Vec::new();
+    assert_eq!(
+        recover_seq(&f, &raw, RecoveryPolicy::Placeholder, &mut diagnostics),
+        vec![IL_ILTAGARRAY_TAG_ID, IL_NULL_TAG_ID]
+    );
+    assert_eq!(diagnostics.len(), 1);
+    assert_eq!(diagnostics[0].offset(), 7);
+    assert_eq!(format!("{}", diagnostics[0].path()), "seq[0] > array[2]");
+
+    let tag = f
+        .from_bytes_recovering(&raw, RecoveryPolicy::Placeholder, &mut Vec::new())
+        .ok()
+        .unwrap();
+    let seq = tag_downcast_ref::<ILTagSeqTag>(tag.as_ref()).unwrap();
+    let placeholder = tag_downcast_ref::<ILRawTag>(seq.value()[0].as_ref()).unwrap();
+    assert_eq!(placeholder.value().as_slice(), &raw[4..7]);
+
+    let mut diagnostics = Vec::new();
+    assert_eq!(
+        recover_seq(&f, &raw, RecoveryPolicy::Skip, &mut diagnostics),
+        vec![IL_NULL_TAG_ID]
+    );
+
+    // dict{"a": unknown tag, "b": null}
+    let raw: [u8; 12] = [
+        IL_DICTIONARY_TAG_ID as u8,
+        10,
+        2,
+        IL_STRING_TAG_ID as u8,
+        1,
+        b'a',
+        0x20,
+        0,
+        IL_STRING_TAG_ID as u8,
+        1,
+        b'b',
+        IL_NULL_TAG_ID as u8,
+    ];
+    let mut diagnostics = Vec::new();
+    let tag = f
+        .from_bytes_recovering(&raw, RecoveryPolicy::Placeholder, &mut diagnostics)
+        .ok()
+        .unwrap();
+    let dict = tag_downcast_ref::<ILDictTag>(tag.as_ref()).unwrap();
+    assert_eq!(dict.value().len(), 2);
+    assert_eq!(dict.value()["a"].id(), 0x20);
+    assert_eq!(dict.value()["b"].id(), IL_NULL_TAG_ID);
+    assert_eq!(diagnostics.len(), 1);
+    assert!(matches!(diagnostics[0].kind(), ErrorKind::UnknownTag));
+    assert_eq!(format!("{}", diagnostics[0].path()), "dict[\"a\"]");
+
+    let mut diagnostics = Vec::new();
+    let tag = f
+        .from_bytes_recovering(&raw, RecoveryPolicy::Skip, &mut diagnostics)
+        .ok()
+        .unwrap();
+    let dict = tag_downcast_ref::<ILDictTag>(tag.as_ref()).unwrap();
+    assert_eq!(dict.value().len(), 1);
+    assert!(dict.value().contains_key("b"));
+}
+
+#[test]
+fn test_ilstandardtagfactory_from_bytes_recovering_fatal() {
+    let f = ILStandardTagFactory::new(true);
+
+    // Top level tag
+    let raw: [u8; 3] = [IL_STRING_TAG_ID as u8, 1, 0xFF];
+    let mut diagnostics = Vec::new();
+    let e = f
+        .from_bytes_recovering(&raw, RecoveryPolicy::Placeholder, &mut diagnostics)
+        .err()
+        .unwrap();
+    assert_eq!(e.id(), Some(IL_STRING_TAG_ID));
+    assert!(diagnostics.is_empty());
+
+    // Child larger than the top level tag
+    let raw: [u8; 6] = [
+        IL_ILTAGSEQ_TAG_ID as u8,
+        4,
+        IL_NULL_TAG_ID as u8,
+        IL_STRING_TAG_ID as u8,
+        3,
+        b'a',
+    ];
+    let e = f
+        .from_bytes_recovering(&raw, RecoveryPolicy::Placeholder, &mut diagnostics)
+        .err()
+        .unwrap();
+    assert_eq!(e.offset(), 3);
+    assert_eq!(format!("{}", e.path()), "seq[1]");
+
+    // Missing elements in the top level tag
+    let raw: [u8; 4] = [IL_ILTAGARRAY_TAG_ID as u8, 2, 2, IL_NULL_TAG_ID as u8];
+    assert!(f
+        .from_bytes_recovering(&raw, RecoveryPolicy::Placeholder, &mut diagnostics)
+        .is_err());
+    assert!(diagnostics.is_empty());
+}
@@ -38,6 +38,7 @@ use super::constants::*;
 use super::{DefaultWithId, ErrorKind, ILTag, ILTagFactory, Result};
 use crate::io::{Reader, Writer};
 use crate::tags::serialization::*;
+use crate::tags::CanonicalRule;
 use ::std::any::Any;
 
 /// This macro implements the default of a simple type value tag.
@@ -54,9 +55,9 @@ use ::std::any::Any;
 ///
 /// As such, it can be used as follows:
 ///
-/// ```
+/// ```ignore
 /// use crate::tags::standard::IL_INT8_TAG_ID;
-/// 
+///
 /// pub struct ILInt8 {
 ///     id: u64,
 ///     value: u8,
@@ -163,6 +164,63 @@ macro_rules! int_iltag_impl {
     };
 }
 
+/// This macro creates the ILTag implementation for floating point values.
+/// It behaves exactly like `int_iltag_impl!()` but also rejects NaN values
+/// that are not encoded as the canonical NaN when the factory is in
+/// canonical mode.
+///
+/// Arguments:
+/// - `$tag_type`: Name of the tag struct;
+/// - `$value_size`: Size of the value;
+/// - `$canonical_nan`: The bits of the canonical NaN;
+macro_rules! float_iltag_impl {
+    ($tag_type: ty, $value_size: expr, $canonical_nan: expr) => {
+        impl ILTag for $tag_type {
+            iltag_base_func_impl!();
+
+            fn value_size(&self) -> u64 {
+                $value_size
+            }
+
+            fn serialize_value(&self, writer: &mut dyn Writer) -> Result<()> {
+                writer.serialize_value(self.value)
+            }
+
+            fn deserialize_value(
+                &mut self,
+                factory: &dyn ILTagFactory,
+                value_size: usize,
+                reader: &mut dyn Reader,
+            ) -> Result<()> {
+                match value_size {
+                    $value_size => (),
+                    _ => return Err(ErrorKind::CorruptedData),
+                };
+                self.value = reader.deserialize_value()?;
+                if factory.canonical()
+                    && self.value.is_nan()
+                    && self.value.to_bits() != $canonical_nan
+                {
+                    return Err(ErrorKind::NonCanonical(CanonicalRule::NaN));
+                }
+                Ok(())
+            }
+        }
+    };
+}
+
+/// Bits of the canonical NaN for [`ILBin32Tag`]. It is the quiet NaN without
+/// payload, the same value of [`f32::NAN`].
+///
+/// New since 2.0.0.
+pub const CANONICAL_BIN32_NAN: u32 = 0x7FC0_0000;
+
+/// Bits of the canonical NaN for [`ILBin64Tag`]. It is the quiet NaN without
+/// payload, the same value of [`f64::NAN`].
+///
+/// New since 2.0.0.
+pub const CANONICAL_BIN64_NAN: u64 = 0x7FF8_0000_0000_0000;
+
 /// Returns the size of the implicit tag.
 ///
 /// With the exception of [`ILILInt64Tag`] and [`ILSignedILInt64Tag`],
@@ -174,7 +232,7 @@ macro_rules! int_iltag_impl {
 ///
 /// Returns:
 /// - The size of the tag in bytes or 0 if the id is
-/// not a valid implicit tag.
+///   not a valid implicit tag.
 pub fn implicit_tag_size(id: u64) -> u64 {
     match id {
         IL_NULL_TAG_ID => 0,
@@ -268,7 +326,7 @@ impl ILTag for ILBoolTag {
 
     fn deserialize_value(
         &mut self,
-        _factory: &dyn ILTagFactory,
+        factory: &dyn ILTagFactory,
         value_size: usize,
         reader: &mut dyn Reader,
     ) -> Result<()> {
@@ -280,6 +338,7 @@ impl ILTag for ILBoolTag {
         self.value = match v {
             0 => false,
             1 => true,
+            _ if factory.canonical() => return Err(ErrorKind::NonCanonical(CanonicalRule::Bool)),
             _ => return Err(ErrorKind::CorruptedData),
         };
         Ok(())
@@ -437,7 +496,7 @@ pub struct ILBin32Tag {
 
 simple_value_tag_struct_impl!(ILBin32Tag, f32, IL_BIN32_TAG_ID);
 
-int_iltag_impl!(ILBin32Tag, 4);
+float_iltag_impl!(ILBin32Tag, 4, CANONICAL_BIN32_NAN);
 
 iltag_default_impl!(ILBin32Tag);
 
@@ -454,7 +513,7 @@ pub struct ILBin64Tag {
 
 simple_value_tag_struct_impl!(ILBin64Tag, f64, IL_BIN64_TAG_ID);
 
-int_iltag_impl!(ILBin64Tag, 8);
+float_iltag_impl!(ILBin64Tag, 8, CANONICAL_BIN64_NAN);
 
 iltag_default_impl!(ILBin64Tag);
 
@@ -589,11 +648,11 @@ impl ILTag for ILILInt64Tag {
     /// from 1 to 9 bytes depending on the actual value stored.
     fn deserialize_value(
         &mut self,
-        _factory: &dyn ILTagFactory,
+        factory: &dyn ILTagFactory,
         _value_size: usize,
         reader: &mut dyn Reader,
     ) -> Result<()> {
-        self.value = reader.deserialize_ilint()?;
+        self.value = reader.deserialize_ilint_with(factory.canonical())?;
         Ok(())
     }
 }
@@ -632,11 +691,11 @@ impl ILTag for ILSignedILInt64Tag {
     /// from 1 to 9 bytes depending on the actual value stored.
     fn deserialize_value(
         &mut self,
-        _factory: &dyn ILTagFactory,
+        factory: &dyn ILTagFactory,
         _value_size: usize,
         reader: &mut dyn Reader,
     ) -> Result<()> {
-        self.value = reader.deserialize_signed_ilint()?;
+        self.value = reader.deserialize_signed_ilint_with(factory.canonical())?;
         Ok(())
     }
 }
@@ -32,8 +32,9 @@
 use super::*;
 use crate::io::array::{ByteArrayReader, VecReader, VecWriter};
 use crate::tags::standard::constants::*;
+use crate::tags::standard::factory::ILStandardTagFactory;
 use crate::tags::util::UntouchbleTagFactory;
-use crate::tags::{DefaultWithId, ErrorKind, ILTag};
+use crate::tags::{CanonicalRule, DefaultWithId, ErrorKind, ILTag};
 
 //=============================================================================
 // Common tests
@@ -278,7 +279,7 @@ fn test_ilbooltag_iltag_serialize() {
         _ => panic!(),
     }
     assert_eq!(writer.as_slice().len() as u64, exp_size);
-    assert_eq!(writer.as_slice()[0], 0 as u8);
+    assert_eq!(writer.as_slice()[0], 0_u8);
 
     let t = ILBoolTag::with_value(true);
     let mut writer = VecWriter::new();
@@ -287,7 +288,7 @@ fn test_ilbooltag_iltag_serialize() {
         _ => panic!(),
     }
     assert_eq!(writer.as_slice().len() as u64, exp_size);
-    assert_eq!(writer.as_slice()[0], 1 as u8);
+    assert_eq!(writer.as_slice()[0], 1_u8);
 
     writer.set_read_only(true);
     match t.serialize_value(&mut writer) {
@@ -308,14 +309,14 @@ fn test_ilbooltag_iltag_deserialize() {
         Ok(()) => (),
         _ => panic!(),
     }
-    assert_eq!(t.value(), true);
+    assert!(t.value());
 
     let mut t = ILBoolTag::new();
     match t.deserialize_value(&factory, 1, &mut reader) {
         Ok(()) => (),
         _ => panic!(),
     }
-    assert_eq!(t.value(), false);
+    assert!(!t.value());
 
     let mut t = ILBoolTag::new();
     match t.deserialize_value(&factory, 1, &mut reader) {
@@ -329,6 +330,31 @@ fn test_ilbooltag_iltag_deserialize() {
     }
 }
 
+#[test]
+fn test_ilbooltag_iltag_deserialize_canonical() {
+    let mut factory = ILStandardTagFactory::new(false);
+    factory.set_canonical(true);
+
+    for (sample, exp) in [(0_u8, false), (1_u8, true)] {
+        let mut t = ILBoolTag::with_value(!exp);
+        let mut reader = ByteArrayReader::new(core::slice::from_ref(&sample));
+        match t.deserialize_value(&factory, 1, &mut reader) {
+            Ok(()) => (),
+            _ => panic!(),
+        }
+        assert_eq!(t.value(), exp);
+    }
+
+    for sample in [2_u8, 0x80, 0xFF] {
+        let mut t = ILBoolTag::new();
+        let mut reader = ByteArrayReader::new(core::slice::from_ref(&sample));
+        match t.deserialize_value(&factory, 1, &mut reader) {
+            Err(ErrorKind::NonCanonical(CanonicalRule::Bool)) => (),
+            _ => panic!(),
+        }
+    }
+}
+
 //=============================================================================
 // ILInt8Tag
 //-----------------------------------------------------------------------------
@@ -558,6 +584,49 @@ fn test_ilbin32tag_iltag_deserialize() {
     test_simple_value_iltag_deserialize_impl!(ILBin32Tag, f32, 4, [-1.2345678, 9.87654]);
 }
 
+#[test]
+fn test_ilbin32tag_iltag_deserialize_canonical() {
+    let mut factory = ILStandardTagFactory::new(false);
+    const OTHER_NAN: u32 = 0x7FC0_0001;
+    assert!(f32::from_bits(OTHER_NAN).is_nan());
+
+    // Not canonical, any NaN is accepted
+    let mut t = ILBin32Tag::new();
+    let sample = OTHER_NAN.to_be_bytes();
+    let mut reader = ByteArrayReader::new(&sample);
+    match t.deserialize_value(&factory, 4, &mut reader) {
+        Ok(()) => (),
+        _ => panic!(),
+    }
+    assert_eq!(t.value().to_bits(), OTHER_NAN);
+
+    // Canonical
+    factory.set_canonical(true);
+    for v in [
+        CANONICAL_BIN32_NAN,
+        1.5_f32.to_bits(),
+        f32::INFINITY.to_bits(),
+    ] {
+        let mut t = ILBin32Tag::new();
+        let sample = v.to_be_bytes();
+        let mut reader = ByteArrayReader::new(&sample);
+        match t.deserialize_value(&factory, 4, &mut reader) {
+            Ok(()) => (),
+            _ => panic!(),
+        }
+        assert_eq!(t.value().to_bits(), v);
+    }
+    for v in [OTHER_NAN, 0xFFC0_0000, 0x7F80_0001] {
+        let mut t = ILBin32Tag::new();
+        let sample = v.to_be_bytes();
+        let mut reader = ByteArrayReader::new(&sample);
+        match t.deserialize_value(&factory, 4, &mut reader) {
+            Err(ErrorKind::NonCanonical(CanonicalRule::NaN)) => (),
+            _ => panic!(),
+        }
+    }
+}
+
 //=============================================================================
 // ILBin64Tag
 //-----------------------------------------------------------------------------
@@ -581,6 +650,49 @@ fn test_ilbin64tag_iltag_deserialize() {
     test_simple_value_iltag_deserialize_impl!(ILBin64Tag, f64, 8, [-1.2345678, 9.87654]);
 }
 
+#[test]
+fn test_ilbin64tag_iltag_deserialize_canonical() {
+    let mut factory = ILStandardTagFactory::new(false);
+    const OTHER_NAN: u64 = 0x7FF8_0000_0000_0001;
+    assert!(f64::from_bits(OTHER_NAN).is_nan());
+
+    // Not canonical, any NaN is accepted
+    let mut t = ILBin64Tag::new();
+    let sample = OTHER_NAN.to_be_bytes();
+    let mut reader = ByteArrayReader::new(&sample);
+    match t.deserialize_value(&factory, 8, &mut reader) {
+        Ok(()) => (),
+        _ => panic!(),
+    }
+    assert_eq!(t.value().to_bits(), OTHER_NAN);
+
+    // Canonical
+    factory.set_canonical(true);
+    for v in [
+        CANONICAL_BIN64_NAN,
+        1.5_f64.to_bits(),
+        f64::NEG_INFINITY.to_bits(),
+    ] {
+        let mut t = ILBin64Tag::new();
+        let sample = v.to_be_bytes();
+        let mut reader = ByteArrayReader::new(&sample);
+        match t.deserialize_value(&factory, 8, &mut reader) {
+            Ok(()) => (),
+            _ => panic!(),
+        }
+        assert_eq!(t.value().to_bits(), v);
+    }
+    for v in [OTHER_NAN, 0xFFF8_0000_0000_0000, 0x7FF0_0000_0000_0001] {
+        let mut t = ILBin64Tag::new();
+        let sample = v.to_be_bytes();
+        let mut reader = ByteArrayReader::new(&sample);
+        match t.deserialize_value(&factory, 8, &mut reader) {
+            Err(ErrorKind::NonCanonical(CanonicalRule::NaN)) => (),
+            _ => panic!(),
+        }
+    }
+}
+
 //=============================================================================
 // ILBin128Tag
 //-----------------------------------------------------------------------------
@@ -750,7 +862,7 @@ fn test_ililint64tag_iltag_serialize() {
 #[test]
 fn test_ililint64tag_iltag_deserialize() {
     let factory = UntouchbleTagFactory {};
-    let mut test_size = 0 as usize;
+    let mut test_size = 0_usize;
     for v in &ILINT_SAMPLE {
         let mut exp_writer = VecWriter::new();
         match crate::ilint::encode(*v, &mut exp_writer) {
@@ -834,7 +946,7 @@ fn test_ilsignedilint64tag_iltag_serialize() {
 #[test]
 fn test_ilsignedilint64tag_iltag_deserialize() {
     let factory = UntouchbleTagFactory {};
-    let mut test_size = 0 as usize;
+    let mut test_size = 0_usize;
     for v in crate::io::data::test_samples::SIGNED_SAMPLES {
         let mut exp_writer = VecWriter::new();
         match crate::ilint::signed_encode(v, &mut exp_writer) {
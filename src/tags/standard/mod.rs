@@ -49,12 +49,13 @@ pub use constants::{
     IL_UINT64_TAG_ID, IL_UINT8_TAG_ID, IL_VERSION_TAG_ID,
 };
 pub use explicit::{
-    ILBigDecTag, ILBigIntTag, ILByteArrayTag, ILDictTag, ILILIntArrayTag, ILOIDTag, ILRangeTag,
-    ILStrDictTag, ILStringTag, ILTagArrayTag, ILTagSeqTag, ILVersionTag,
+    is_minimal_twos_complement, ILBigDecTag, ILBigIntTag, ILByteArrayTag, ILDictTag,
+    ILILIntArrayTag, ILOIDTag, ILRangeTag, ILStrDictTag, ILStringTag, ILTagArrayTag, ILTagSeqTag,
+    ILVersionTag,
 };
-pub use factory::ILStandardTagFactory;
+pub use factory::{ILStandardTagFactory, RecoveryPolicy};
 pub use implicit::{
     ILBin128Tag, ILBin32Tag, ILBin64Tag, ILBoolTag, ILILInt64Tag, ILInt16Tag, ILInt32Tag,
     ILInt64Tag, ILInt8Tag, ILNullTag, ILSignedILInt64Tag, ILUInt16Tag, ILUInt32Tag, ILUInt64Tag,
-    ILUInt8Tag,
+    ILUInt8Tag, CANONICAL_BIN32_NAN, CANONICAL_BIN64_NAN,
 };
@@ -40,6 +40,93 @@ fn test_errorkind_debug() {
     assert_eq!(format!("{:?}", ErrorKind::UnknownTag), "UnknownTag");
 }
 
+#[test]
+fn test_errorkind_error() {
+    use std::error::Error;
+
+    assert_eq!(
+        format!("{}", ErrorKind::CorruptedData),
+        "corrupted tag data"
+    );
+    assert_eq!(format!("{}", ErrorKind::UnknownTag), "unknown tag");
+    assert_eq!(
+        format!("{}", ErrorKind::NonCanonical(CanonicalRule::Bool)),
+        "data is not in its canonical form (Bool)"
+    );
+    assert!(ErrorKind::DuplicateKey.source().is_none());
+    assert_eq!(
+        format!("{}", ErrorKind::UnableToSerialize),
+        "unable to serialize the tag"
+    );
+    assert_eq!(
+        format!("{}", ErrorKind::ValueSizeMismatch(16)),
+        "the value of the tag with id 16 does not match its size"
+    );
+
+    // Chain of errors
+    let e: ErrorKind = crate::io::ErrorKind::IOError(std::io::Error::other("oops")).into();
+    assert_eq!(format!("{}", e), "I/O error");
+    let source = e.source().unwrap();
+    assert_eq!(format!("{}", source), "I/O error");
+    assert_eq!(format!("{}", source.source().unwrap()), "oops");
+
+    // Boxed errors are transparent
+    let e = ErrorKind::Boxed(Box::new(ErrorKind::UnknownTag));
+    assert_eq!(format!("{}", e), "unknown tag");
+    assert!(e.source().is_none());
+    let e = ErrorKind::Boxed(Box::new(ErrorKind::IOError(crate::io::ErrorKind::IOError(
+        std::io::Error::other("oops"),
+    ))));
+    assert_eq!(format!("{}", e), "I/O error");
+    assert_eq!(format!("{}", e.source().unwrap()), "I/O error");
+
+    fn assert_send_sync<T: Send + Sync + 'static>() {}
+    assert_send_sync::<ErrorKind>();
+}
+
+#[test]
+fn test_errorkind_from() {
+    let e: ErrorKind = crate::ilint::ErrorKind::NonCanonical.into();
+    assert!(matches!(e, ErrorKind::NonCanonical(CanonicalRule::ILInt)));
+    let e: ErrorKind = crate::ilint::ErrorKind::InvalidFormat.into();
+    assert!(matches!(
+        e,
+        ErrorKind::IOError(crate::io::ErrorKind::CorruptedData)
+    ));
+    let e: ErrorKind = crate::ilint::ErrorKind::ValueOverflow.into();
+    assert!(matches!(
+        e,
+        ErrorKind::IOError(crate::io::ErrorKind::CorruptedData)
+    ));
+    let e: ErrorKind =
+        crate::ilint::ErrorKind::IOError(crate::io::ErrorKind::UnableToReadData).into();
+    assert!(matches!(
+        e,
+        ErrorKind::IOError(crate::io::ErrorKind::UnableToReadData)
+    ));
+
+    let e: ErrorKind = std::io::Error::new(std::io::ErrorKind::UnexpectedEof, "eof").into();
+    assert!(matches!(
+        e,
+        ErrorKind::IOError(crate::io::ErrorKind::EndOfData)
+    ));
+
+    // Works with ? on a boxed error that must be thread safe
+    fn sample() -> std::result::Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        Err(ErrorKind::DuplicateKey)?;
+        Ok(())
+    }
+    assert_eq!(
+        format!("{}", sample().unwrap_err()),
+        "duplicate dictionary key"
+    );
+}
+
+#[test]
+fn test_duplicatekeypolicy_default() {
+    assert_eq!(DuplicateKeyPolicy::default(), DuplicateKeyPolicy::Reject);
+}
+
 #[test]
 fn test_constants() {
     assert_eq!(IMPLICIT_ID_MAX, 15);
@@ -189,11 +276,7 @@ struct DummyTag {
 
 impl DummyTag {
     pub fn new(id: u64, size: u64) -> DummyTag {
-        DummyTag {
-            id: id,
-            size: size,
-            dummy: 0,
-        }
+        DummyTag { id, size, dummy: 0 }
     }
 
     pub fn get_dummy(&self) -> usize {
@@ -284,7 +367,7 @@ fn test_iltag_is_reserved() {
 #[test]
 fn test_iltag_size() {
     let tag = DummyTag::new(15, 3);
-    assert_eq!(tag.size(), 1 + 0 + 3);
+    assert_eq!(tag.size(), 1 + 3);
 
     let tag = DummyTag::new(31, 3);
     assert_eq!(tag.size(), 1 + 1 + 3);
@@ -322,6 +405,112 @@ fn test_iltag_serialize() {
     assert_eq!(writer.as_slice(), exp);
 }
 
+/// A tag that writes `written` bytes but reports `size` as its value size.
+struct WrongSizeTag {
+    size: u64,
+    written: u64,
+}
+
+impl ILTag for WrongSizeTag {
+    fn id(&self) -> u64 {
+        DEFAULT_DUMMY_TAG_ID
+    }
+    fn value_size(&self) -> u64 {
+        self.size
+    }
+    fn serialize_value(&self, writer: &mut dyn Writer) -> Result<()> {
+        for i in 0..self.written {
+            writer.write(i as u8)?;
+        }
+        Ok(())
+    }
+    fn deserialize_value(
+        &mut self,
+        _factory: &dyn ILTagFactory,
+        _value_size: usize,
+        _reader: &mut dyn Reader,
+    ) -> Result<()> {
+        Err(ErrorKind::UnsupportedTag)
+    }
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn as_mut_any(&mut self) -> &mut dyn Any {
+        self
+    }
+}
+
+#[test]
+fn test_serialize_value_checked() {
+    let mut writer = VecWriter::new();
+    let tag = WrongSizeTag {
+        size: 4,
+        written: 4,
+    };
+    serialize_value_checked(&tag, &mut writer).unwrap();
+    assert_eq!(writer.as_slice(), &[0, 1, 2, 3]);
+
+    // Too many bytes, the extra bytes are not written
+    let mut writer = VecWriter::new();
+    let tag = WrongSizeTag {
+        size: 4,
+        written: 5,
+    };
+    assert!(matches!(
+        serialize_value_checked(&tag, &mut writer),
+        Err(ErrorKind::ValueSizeMismatch(DEFAULT_DUMMY_TAG_ID))
+    ));
+    assert_eq!(writer.as_slice(), &[0, 1, 2, 3]);
+
+    // Too few bytes
+    let mut writer = VecWriter::new();
+    let tag = WrongSizeTag {
+        size: 4,
+        written: 3,
+    };
+    assert!(matches!(
+        serialize_value_checked(&tag, &mut writer),
+        Err(ErrorKind::ValueSizeMismatch(DEFAULT_DUMMY_TAG_ID))
+    ));
+
+    // Errors from the writer are preserved
+    let mut buff: [u8; 2] = [0; 2];
+    let mut writer = crate::io::array::ByteArrayWriter::new(&mut buff);
+    let tag = WrongSizeTag {
+        size: 4,
+        written: 4,
+    };
+    assert!(matches!(
+        serialize_value_checked(&tag, &mut writer),
+        Err(ErrorKind::IOError(_))
+    ));
+}
+
+#[test]
+fn test_iltag_serialize_wrong_size() {
+    if !cfg!(any(debug_assertions, feature = "verify-serialization")) {
+        return;
+    }
+    let tag = WrongSizeTag {
+        size: 4,
+        written: 5,
+    };
+    assert!(matches!(
+        tag.to_bytes(),
+        Err(ErrorKind::ValueSizeMismatch(DEFAULT_DUMMY_TAG_ID))
+    ));
+    let tag = WrongSizeTag {
+        size: 4,
+        written: 0,
+    };
+    let mut writer = VecWriter::new();
+    assert!(matches!(
+        tag.serialize(&mut writer),
+        Err(ErrorKind::ValueSizeMismatch(DEFAULT_DUMMY_TAG_ID))
+    ));
+}
+
 #[test]
 fn test_iltag_to_bytes() {
     let tag = DummyTag::new(15, 4);
@@ -329,6 +518,69 @@ fn test_iltag_to_bytes() {
     assert_eq!(serialized.as_slice(), &[0x0F, 0x00, 0x01, 0x02, 0x03]);
 }
 
+#[test]
+fn test_iltag_digest() {
+    use crate::io::Digest;
+    use crate::tests::RecordingDigest;
+
+    let tag = DummyTag::new(16, 4);
+    let serialized = tag.to_bytes().unwrap();
+    assert_eq!(tag.digest::<RecordingDigest>().unwrap(), serialized);
+
+    let mut digest = RecordingDigest::default();
+    tag.update_digest(&mut digest).unwrap();
+    tag.update_digest(&mut digest).unwrap();
+    let mut exp = serialized.clone();
+    exp.extend_from_slice(&serialized);
+    assert_eq!(digest.finalize(), exp);
+
+    let tag: Box<dyn ILTag> = Box::new(DummyTag::new(255, 256));
+    assert_eq!(
+        crate::tags::util::iltag_digest::<RecordingDigest>(tag.as_ref()).unwrap(),
+        tag.to_bytes().unwrap()
+    );
+
+    // Serialization errors
+    if cfg!(any(debug_assertions, feature = "verify-serialization")) {
+        let tag = WrongSizeTag {
+            size: 4,
+            written: 5,
+        };
+        assert!(matches!(
+            tag.digest::<RecordingDigest>(),
+            Err(ErrorKind::ValueSizeMismatch(DEFAULT_DUMMY_TAG_ID))
+        ));
+    }
+}
+
+#[cfg(feature = "sha2")]
+#[test]
+fn test_iltag_digest_sha2() {
+    use crate::io::digest::sha2::{Sha256, Sha512};
+    use crate::io::Digest;
+    use crate::tags::util::iltag_digest;
+
+    let tag: Box<dyn ILTag> = Box::new(DummyTag::new(255, 1000));
+    let serialized = tag.to_bytes().unwrap();
+    let mut digest = Sha256::new();
+    digest.update(&serialized);
+    assert_eq!(
+        iltag_digest::<Sha256>(tag.as_ref()).unwrap(),
+        digest.finalize()
+    );
+    let mut digest = Sha512::new();
+    digest.update(&serialized);
+    assert_eq!(
+        iltag_digest::<Sha512>(tag.as_ref()).unwrap(),
+        digest.finalize()
+    );
+    let concrete = DummyTag::new(255, 1000);
+    assert_eq!(
+        concrete.digest::<Sha256>().unwrap(),
+        iltag_digest::<Sha256>(tag.as_ref()).unwrap()
+    );
+}
+
 #[test]
 fn test_tag_downcast_ref() {
     let tag = DummyTag::new(132, 1);
@@ -0,0 +1,547 @@
+/*
+ * BSD 3-Clause License
+ *
+ * Copyright (c) 2020, InterlockLedger Network
+ * All rights reserved.
+ *
+ * Redistribution and use in source and binary forms, with or without
+ * modification, are permitted provided that the following conditions are met:
+ *
+ * * Redistributions of source code must retain the above copyright notice, this
+ *   list of conditions and the following disclaimer.
+ *
+ * * Redistributions in binary form must reproduce the above copyright notice,
+ *   this list of conditions and the following disclaimer in the documentation
+ *   and/or other materials provided with the distribution.
+ *
+ * * Neither the name of the copyright holder nor the names of its
+ *   contributors may be used to endorse or promote products derived from
+ *   this software without specific prior written permission.
+ *
+ * THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS"
+ * AND ANY EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE
+ * IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+ * DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE
+ * FOR ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL, EXEMPLARY, OR CONSEQUENTIAL
+ * DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+ * SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER
+ * CAUSED AND ON ANY THEORY OF LIABILITY, WHETHER IN CONTRACT, STRICT LIABILITY,
+ * OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE
+ * OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+ */
+//! This module implements a compact and human readable text notation for
+//! tags. It is intended to be used to write test fixtures and documentation
+//! examples without the need to assemble the binary representation of the
+//! tags by hand.
+//!
+//! Each tag is written as its type followed by its value:
+//!
+//! | Notation                         | Tag                    |
+//! |----------------------------------|------------------------|
+//! | `null`                           | [`ILNullTag`]          |
+//! | `true`, `false`                  | [`ILBoolTag`]          |
+//! | `i8 -1`, `u8 1`                  | [`ILInt8Tag`], [`ILUInt8Tag`] |
+//! | `i16 -1`, `u16 1`                | [`ILInt16Tag`], [`ILUInt16Tag`] |
+//! | `i32 -1`, `u32 1`                | [`ILInt32Tag`], [`ILUInt32Tag`] |
+//! | `i64 -1`, `u64 1`                | [`ILInt64Tag`], [`ILUInt64Tag`] |
+//! | `ilint 1`                        | [`ILILInt64Tag`]       |
+//! | `silint -1`                      | [`ILSignedILInt64Tag`] |
+//! | `f32 1.5`, `f64 nan`, `f64 -inf` | [`ILBin32Tag`], [`ILBin64Tag`] |
+//! | `bin128 h'000102...0f'`          | [`ILBin128Tag`]        |
+//! | `bytes h'0a0b'`                  | [`ILByteArrayTag`]     |
+//! | `str"abc"`                       | [`ILStringTag`]        |
+//! | `bint h'00ff'`                   | [`ILBigIntTag`]        |
+//! | `bdec -2 h'7f'`                  | [`ILBigDecTag`]        |
+//! | `ilints[1, 2]`                   | [`ILILIntArrayTag`]    |
+//! | `array[ u8 1, str"a" ]`          | [`ILTagArrayTag`]      |
+//! | `seq[ u8 1, str"a" ]`            | [`ILTagSeqTag`]        |
+//! | `range[1234, 10]`                | [`ILRangeTag`]         |
+//! | `version[1, 2, 3, 4]`            | [`ILVersionTag`]       |
+//! | `oid[1, 2, 3]`                   | [`ILOIDTag`]           |
+//! | `dict{ "a": u8 1 }`              | [`ILDictTag`]          |
+//! | `strdict{ "a": "1" }`            | [`ILStrDictTag`]       |
+//! | `#1234 h'0a0b'`                  | [`ILRawTag`]           |
+//!
+//! Any tag may be prefixed by `#<id>` in order to use a custom id, for
+//! example `#1234 str"abc"`. The prefix is mandatory for raw tags. Strings
+//! accept the escapes `\"`, `\\`, `\n`, `\r`, `\t`, `\0` and `\u{XXXX}`.
+//! Hexadecimal literals may contain whitespaces, lists may have a trailing
+//! comma and `//` starts a comment that runs until the end of the line.
+//!
+//! The parser produces the tags through an [`ILTagFactory`], thus the
+//! resulting tags are exactly the ones that would be deserialized from the
+//! binary representation described by the text.
+//!
+//! Example:
+//!
+//! ```
+//! use il2_iltags::tags::standard::ILStandardTagFactory;
+//! use il2_iltags::tags::text::{from_text, to_text};
+//!
+//! let factory = ILStandardTagFactory::new(false);
+//! let text = r#"dict{ "n": u32 7, "name": str"abc", "raw": #1234 h'0a0b' }"#;
+//! let tag = from_text(&factory, text).unwrap();
+//! assert_eq!(to_text(tag.as_ref()).unwrap(), text);
+//! ```
+//!
+//! New since 2.0.0.
+#[cfg(test)]
+mod tests;
+
+mod parser;
+
+use crate::tags::standard::constants::*;
+use crate::tags::standard::*;
+use crate::tags::util::hex_encode;
+use crate::tags::{
+    is_implicit_tag, tag_downcast_ref, ErrorKind, ILRawTag, ILTag, ILTagFactory, Result,
+};
+use std::fmt;
+
+//=============================================================================
+// TextError
+//-----------------------------------------------------------------------------
+/// This struct describes an error found while parsing the text notation. It
+/// is reported by [`from_text()`] and [`text_to_bytes()`] wrapped in
+/// [`ErrorKind::Boxed`].
+///
+/// New since 2.0.0.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TextError {
+    offset: usize,
+    message: String,
+}
+
+impl TextError {
+    /// Creates a new instance of this struct.
+    ///
+    /// Arguments:
+    /// - `offset`: The offset in bytes of the error in the text;
+    /// - `message`: The description of the error;
+    pub fn new(offset: usize, message: &str) -> Self {
+        Self {
+            offset,
+            message: String::from(message),
+        }
+    }
+
+    /// Returns the offset in bytes of the error in the text.
+    pub fn offset(&self) -> usize {
+        self.offset
+    }
+
+    /// Returns the description of the error.
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+}
+
+impl fmt::Display for TextError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} at offset {}", self.message, self.offset)
+    }
+}
+
+impl std::error::Error for TextError {}
+
+impl From<TextError> for ErrorKind {
+    fn from(error: TextError) -> Self {
+        ErrorKind::Boxed(Box::new(error))
+    }
+}
+
+//=============================================================================
+// Parser
+//-----------------------------------------------------------------------------
+/// Parses the text notation of a tag and creates the tag using the given
+/// factory.
+///
+/// Arguments:
+/// - `factory`: The factory used to create the tags;
+/// - `text`: The text notation of the tag;
+///
+/// Returns:
+/// - `Ok(tag)`: The tag;
+/// - `Err(ErrorKind::Boxed(_))`: If the text is invalid. The boxed error is
+///   a [`TextError`] with the location of the problem;
+/// - `Err(_)`: If the factory is unable to deserialize the tag;
+pub fn from_text(factory: &dyn ILTagFactory, text: &str) -> Result<Box<dyn ILTag>> {
+    factory.from_bytes(&text_to_bytes(text)?)
+}
+
+/// Parses the text notation of a tag and returns its binary representation.
+///
+/// Arguments:
+/// - `text`: The text notation of the tag;
+///
+/// Returns:
+/// - `Ok(bytes)`: The serialized tag;
+/// - `Err(ErrorKind::Boxed(_))`: If the text is invalid. The boxed error is
+///   a [`TextError`] with the location of the problem;
+pub fn text_to_bytes(text: &str) -> Result<Vec<u8>> {
+    parser::parse(text)?.to_bytes()
+}
+
+//=============================================================================
+// Printer
+//-----------------------------------------------------------------------------
+/// Converts a tag into its text notation in a single line.
+///
+/// Tags that are not implemented by the standard structs are written as raw
+/// tags if they are explicit. Implicit tags are written according to their
+/// ids. Trees of any depth can be converted.
+///
+/// Arguments:
+/// - `tag`: The tag;
+///
+/// Returns:
+/// - `Ok(text)`: The text notation of the tag;
+/// - `Err(_)`: If the tag could not be serialized;
+pub fn to_text(tag: &dyn ILTag) -> Result<String> {
+    let mut output = TextOutput::new(false);
+    output.tag(tag)?;
+    Ok(output.text)
+}
+
+/// Converts a tag into its text notation with one element of each array,
+/// sequence or dictionary per line. See [`to_text()`] for further details.
+///
+/// Arguments:
+/// - `tag`: The tag;
+///
+/// Returns:
+/// - `Ok(text)`: The text notation of the tag;
+/// - `Err(_)`: If the tag could not be serialized;
+pub fn to_text_pretty(tag: &dyn ILTag) -> Result<String> {
+    let mut output = TextOutput::new(true);
+    output.tag(tag)?;
+    Ok(output.text)
+}
+
+/// Formats a float according to the text notation.
+macro_rules! float_to_text {
+    ($v: expr) => {
+        if $v.is_nan() {
+            String::from("nan")
+        } else if $v.is_infinite() {
+            String::from(if $v > 0.0 { "inf" } else { "-inf" })
+        } else {
+            format!("{:?}", $v)
+        }
+    };
+}
+
+/// Helper that composes the text notation.
+struct TextOutput {
+    text: String,
+    pretty: bool,
+    level: usize,
+}
+
+impl TextOutput {
+    fn new(pretty: bool) -> Self {
+        Self {
+            text: String::new(),
+            pretty,
+            level: 0,
+        }
+    }
+
+    fn new_line(&mut self) {
+        if self.pretty {
+            self.text.push('\n');
+            for _ in 0..self.level {
+                self.text.push_str("  ");
+            }
+        } else {
+            self.text.push(' ');
+        }
+    }
+
+    fn string(&mut self, s: &str) {
+        self.text.push('"');
+        for c in s.chars() {
+            match c {
+                '"' => self.text.push_str("\\\""),
+                '\\' => self.text.push_str("\\\\"),
+                '\n' => self.text.push_str("\\n"),
+                '\r' => self.text.push_str("\\r"),
+                '\t' => self.text.push_str("\\t"),
+                '\0' => self.text.push_str("\\0"),
+                c if c.is_control() => self.text.push_str(&format!("\\u{{{:x}}}", c as u32)),
+                c => self.text.push(c),
+            }
+        }
+        self.text.push('"');
+    }
+
+    fn hex(&mut self, bytes: &[u8]) {
+        self.text.push_str("h'");
+        self.text.push_str(&hex_encode(bytes));
+        self.text.push('\'');
+    }
+
+    fn integers<T: fmt::Display>(&mut self, values: &[T]) {
+        self.text.push('[');
+        for (i, v) in values.iter().enumerate() {
+            if i > 0 {
+                self.text.push_str(", ");
+            }
+            self.text.push_str(&v.to_string());
+        }
+        self.text.push(']');
+    }
+
+    /// Writes the custom id, if any, followed by the type.
+    fn begin(&mut self, type_name: &str, id: u64, default_id: u64) {
+        if id != default_id {
+            self.text.push_str(&format!("#{} ", id));
+        }
+        self.text.push_str(type_name);
+    }
+
+    fn simple(&mut self, type_name: &str, id: u64, default_id: u64, value: &str) {
+        self.begin(type_name, id, default_id);
+        self.text.push(' ');
+        self.text.push_str(value);
+    }
+
+    /// Writes a list of elements between `open` and `close`, one per line in
+    /// the pretty mode.
+    fn list<T, F>(&mut self, open: char, close: char, values: &[T], mut f: F)
+    where
+        F: FnMut(&mut Self, &T),
+    {
+        self.text.push(open);
+        self.level += 1;
+        for (i, v) in values.iter().enumerate() {
+            if i > 0 {
+                self.text.push(',');
+            }
+            self.new_line();
+            f(self, v);
+        }
+        self.level -= 1;
+        if !values.is_empty() {
+            self.new_line();
+        }
+        self.text.push(close);
+    }
+
+    /// Writes a tag. Containers are written using an explicit stack, thus
+    /// trees of any depth can be written.
+    fn tag(&mut self, tag: &dyn ILTag) -> Result<()> {
+        let mut stack: Vec<TextFrame> = Vec::new();
+        let mut next = Some(tag);
+        loop {
+            if let Some(t) = next.take() {
+                if let Some(frame) = self.tag_node(t)? {
+                    self.level += 1;
+                    stack.push(frame);
+                }
+            }
+            let frame = match stack.last_mut() {
+                Some(frame) => frame,
+                None => return Ok(()),
+            };
+            match frame.next_child() {
+                Some((key, child)) => {
+                    if frame.count > 1 {
+                        self.text.push(',');
+                    }
+                    self.new_line();
+                    if let Some(key) = key {
+                        self.string(key);
+                        self.text.push_str(": ");
+                    }
+                    next = Some(child);
+                }
+                None => {
+                    self.level -= 1;
+                    if frame.count > 0 {
+                        self.new_line();
+                    }
+                    self.text.push(frame.close);
+                    stack.pop();
+                }
+            }
+        }
+    }
+
+    /// Writes a tag that is not a container or the beginning of a container.
+    ///
+    /// Returns:
+    /// - `Ok(None)`: If the tag was written;
+    /// - `Ok(Some(frame))`: If the tag is a container. Its children must be
+    ///   written by [`Self::tag()`];
+    /// - `Err(_)`: If the tag could not be written;
+    fn tag_node<'t>(&mut self, tag: &'t dyn ILTag) -> Result<Option<TextFrame<'t>>> {
+        let id = tag.id();
+        if let Some(t) = tag_downcast_ref::<ILTagArrayTag>(tag) {
+            self.begin("array", id, IL_ILTAGARRAY_TAG_ID);
+            self.text.push('[');
+            return Ok(Some(TextFrame::list(t.value())));
+        } else if let Some(t) = tag_downcast_ref::<ILTagSeqTag>(tag) {
+            self.begin("seq", id, IL_ILTAGSEQ_TAG_ID);
+            self.text.push('[');
+            return Ok(Some(TextFrame::list(t.value())));
+        } else if let Some(t) = tag_downcast_ref::<ILDictTag>(tag) {
+            self.begin("dict", id, IL_DICTIONARY_TAG_ID);
+            self.text.push('{');
+            return Ok(Some(TextFrame::dict(t)));
+        }
+        if self.standard_tag(tag) {
+            return Ok(None);
+        }
+        if is_implicit_tag(id) {
+            // The standard factory always uses the standard structs for
+            // implicit tags.
+            let tag = ILStandardTagFactory::new(false).from_bytes(&tag.to_bytes()?)?;
+            if !self.standard_tag(tag.as_ref()) {
+                return Err(ErrorKind::UnsupportedTag);
+            }
+        } else {
+            let mut value = Vec::with_capacity(tag.value_size() as usize);
+            tag.serialize_value(&mut crate::io::array::BorrowedVecWriter::new(&mut value))?;
+            self.text.push_str(&format!("#{} ", id));
+            self.hex(&value);
+        }
+        Ok(None)
+    }
+
+    /// Writes the tag if it is implemented by one of the standard structs
+    /// that are not containers.
+    ///
+    /// Returns `true` if the tag was written or `false` if the tag is not
+    /// implemented by one of those structs.
+    fn standard_tag(&mut self, tag: &dyn ILTag) -> bool {
+        let id = tag.id();
+        if tag_downcast_ref::<ILNullTag>(tag).is_some() {
+            self.begin("null", id, IL_NULL_TAG_ID);
+        } else if let Some(t) = tag_downcast_ref::<ILBoolTag>(tag) {
+            self.begin(if t.value() { "true" } else { "false" }, id, IL_BOOL_TAG_ID);
+        } else if let Some(t) = tag_downcast_ref::<ILInt8Tag>(tag) {
+            self.simple("i8", id, IL_INT8_TAG_ID, &t.value().to_string());
+        } else if let Some(t) = tag_downcast_ref::<ILUInt8Tag>(tag) {
+            self.simple("u8", id, IL_UINT8_TAG_ID, &t.value().to_string());
+        } else if let Some(t) = tag_downcast_ref::<ILInt16Tag>(tag) {
+            self.simple("i16", id, IL_INT16_TAG_ID, &t.value().to_string());
+        } else if let Some(t) = tag_downcast_ref::<ILUInt16Tag>(tag) {
+            self.simple("u16", id, IL_UINT16_TAG_ID, &t.value().to_string());
+        } else if let Some(t) = tag_downcast_ref::<ILInt32Tag>(tag) {
+            self.simple("i32", id, IL_INT32_TAG_ID, &t.value().to_string());
+        } else if let Some(t) = tag_downcast_ref::<ILUInt32Tag>(tag) {
+            self.simple("u32", id, IL_UINT32_TAG_ID, &t.value().to_string());
+        } else if let Some(t) = tag_downcast_ref::<ILInt64Tag>(tag) {
+            self.simple("i64", id, IL_INT64_TAG_ID, &t.value().to_string());
+        } else if let Some(t) = tag_downcast_ref::<ILUInt64Tag>(tag) {
+            self.simple("u64", id, IL_UINT64_TAG_ID, &t.value().to_string());
+        } else if let Some(t) = tag_downcast_ref::<ILILInt64Tag>(tag) {
+            self.simple("ilint", id, IL_ILINT_TAG_ID, &t.value().to_string());
+        } else if let Some(t) = tag_downcast_ref::<ILSignedILInt64Tag>(tag) {
+            self.simple("silint", id, IL_SIGNED_ILINT_TAG_ID, &t.value().to_string());
+        } else if let Some(t) = tag_downcast_ref::<ILBin32Tag>(tag) {
+            self.simple("f32", id, IL_BIN32_TAG_ID, &float_to_text!(t.value()));
+        } else if let Some(t) = tag_downcast_ref::<ILBin64Tag>(tag) {
+            self.simple("f64", id, IL_BIN64_TAG_ID, &float_to_text!(t.value()));
+        } else if let Some(t) = tag_downcast_ref::<ILBin128Tag>(tag) {
+            self.begin("bin128", id, IL_BIN128_TAG_ID);
+            self.text.push(' ');
+            self.hex(t.value());
+        } else if let Some(t) = tag_downcast_ref::<ILByteArrayTag>(tag) {
+            self.begin("bytes", id, IL_BYTES_TAG_ID);
+            self.text.push(' ');
+            self.hex(t.value());
+        } else if let Some(t) = tag_downcast_ref::<ILStringTag>(tag) {
+            self.begin("str", id, IL_STRING_TAG_ID);
+            self.string(t.value());
+        } else if let Some(t) = tag_downcast_ref::<ILBigIntTag>(tag) {
+            self.begin("bint", id, IL_BINT_TAG_ID);
+            self.text.push(' ');
+            self.hex(t.value());
+        } else if let Some(t) = tag_downcast_ref::<ILBigDecTag>(tag) {
+            self.simple("bdec", id, IL_BDEC_TAG_ID, &t.scale().to_string());
+            self.text.push(' ');
+            self.hex(t.value());
+        } else if let Some(t) = tag_downcast_ref::<ILILIntArrayTag>(tag) {
+            self.begin("ilints", id, IL_ILINTARRAY_TAG_ID);
+            self.integers(t.value());
+        } else if let Some(t) = tag_downcast_ref::<ILRangeTag>(tag) {
+            self.begin("range", id, IL_RANGE_TAG_ID);
+            self.text
+                .push_str(&format!("[{}, {}]", t.start(), t.count()));
+        } else if let Some(t) = tag_downcast_ref::<ILVersionTag>(tag) {
+            self.begin("version", id, IL_VERSION_TAG_ID);
+            self.integers(t.value());
+        } else if let Some(t) = tag_downcast_ref::<ILOIDTag>(tag) {
+            self.begin("oid", id, IL_OID_TAG_ID);
+            self.integers(t.value());
+        } else if let Some(t) = tag_downcast_ref::<ILStrDictTag>(tag) {
+            let mut entries: Vec<_> = t.value().iter().collect();
+            entries.sort_unstable_by(|a, b| a.0.cmp(b.0));
+            self.begin("strdict", id, IL_STRING_DICTIONARY_TAG_ID);
+            self.list('{', '}', &entries, |s, (k, v)| {
+                s.string(k);
+                s.text.push_str(": ");
+                s.string(v);
+            });
+        } else if let Some(t) = tag_downcast_ref::<ILRawTag>(tag) {
+            self.text.push_str(&format!("#{} ", id));
+            self.hex(t.value());
+        } else {
+            return false;
+        }
+        true
+    }
+}
+
+/// The children of a container being written by [`TextOutput::tag()`].
+enum TextChildren<'t> {
+    List(std::slice::Iter<'t, Box<dyn ILTag>>),
+    Dict(std::vec::IntoIter<(&'t str, &'t dyn ILTag)>),
+}
+
+/// A container being written by [`TextOutput::tag()`].
+struct TextFrame<'t> {
+    children: TextChildren<'t>,
+    /// Number of children returned so far.
+    count: usize,
+    /// The character that closes the container.
+    close: char,
+}
+
+impl<'t> TextFrame<'t> {
+    fn list(tags: &'t [Box<dyn ILTag>]) -> Self {
+        Self {
+            children: TextChildren::List(tags.iter()),
+            count: 0,
+            close: ']',
+        }
+    }
+
+    fn dict(tag: &'t ILDictTag) -> Self {
+        let mut entries: Vec<_> = tag
+            .value()
+            .iter()
+            .map(|(k, t)| (k.as_str(), t.as_ref()))
+            .collect();
+        entries.sort_unstable_by(|a, b| a.0.cmp(b.0));
+        Self {
+            children: TextChildren::Dict(entries.into_iter()),
+            count: 0,
+            close: '}',
+        }
+    }
+
+    /// Returns the next child and its key if the container is a dictionary.
+    fn next_child(&mut self) -> Option<(Option<&'t str>, &'t dyn ILTag)> {
+        let child = match &mut self.children {
+            TextChildren::List(tags) => tags.next().map(|t| (None, t.as_ref())),
+            TextChildren::Dict(entries) => entries.next().map(|(k, t)| (Some(k), t)),
+        };
+        if child.is_some() {
+            self.count += 1;
+        }
+        child
+    }
+}
@@ -0,0 +1,498 @@
+/*
+ * BSD 3-Clause License
+ *
+ * Copyright (c) 2020, InterlockLedger Network
+ * All rights reserved.
+ *
+ * Redistribution and use in source and binary forms, with or without
+ * modification, are permitted provided that the following conditions are met:
+ *
+ * * Redistributions of source code must retain the above copyright notice, this
+ *   list of conditions and the following disclaimer.
+ *
+ * * Redistributions in binary form must reproduce the above copyright notice,
+ *   this list of conditions and the following disclaimer in the documentation
+ *   and/or other materials provided with the distribution.
+ *
+ * * Neither the name of the copyright holder nor the names of its
+ *   contributors may be used to endorse or promote products derived from
+ *   this software without specific prior written permission.
+ *
+ * THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS"
+ * AND ANY EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE
+ * IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+ * DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE
+ * FOR ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL, EXEMPLARY, OR CONSEQUENTIAL
+ * DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+ * SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER
+ * CAUSED AND ON ANY THEORY OF LIABILITY, WHETHER IN CONTRACT, STRICT LIABILITY,
+ * OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE
+ * OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+ */
+//! This module implements the parser of the text notation. It converts the
+//! text directly into the standard tags that represent it.
+use super::TextError;
+use crate::io::array::BorrowedVecWriter;
+use crate::tags::is_implicit_tag;
+use crate::tags::standard::constants::*;
+use crate::tags::standard::*;
+use crate::tags::util::hex_decode;
+use crate::tags::{ILRawTag, ILTag};
+use std::str::FromStr;
+
+/// Maximum nesting level of tags accepted by the parser.
+pub(super) const MAX_TEXT_DEPTH: usize = 128;
+
+type TextResult<T> = std::result::Result<T, TextError>;
+
+/// Parses a text that contains exactly one tag.
+///
+/// Arguments:
+/// - `text`: The text;
+///
+/// Returns:
+/// - `Ok(tag)`: The tag;
+/// - `Err(e)`: If the text is invalid;
+pub(super) fn parse(text: &str) -> TextResult<Box<dyn ILTag>> {
+    let mut parser = Parser {
+        src: text,
+        bytes: text.as_bytes(),
+        pos: 0,
+        depth: 0,
+    };
+    let tag = parser.tag()?;
+    parser.skip_whitespace();
+    if parser.pos != parser.bytes.len() {
+        return Err(parser.error("trailing characters after the tag"));
+    }
+    Ok(tag)
+}
+
+struct Parser<'a> {
+    src: &'a str,
+    bytes: &'a [u8],
+    pos: usize,
+    depth: usize,
+}
+
+impl<'a> Parser<'a> {
+    fn error(&self, message: &str) -> TextError {
+        TextError::new(self.pos, message)
+    }
+
+    fn error_at(&self, offset: usize, message: &str) -> TextError {
+        TextError::new(offset, message)
+    }
+
+    fn peek(&self) -> Option<u8> {
+        self.bytes.get(self.pos).copied()
+    }
+
+    /// Skips whitespaces and comments.
+    fn skip_whitespace(&mut self) {
+        loop {
+            match self.peek() {
+                Some(b' ') | Some(b'\t') | Some(b'\n') | Some(b'\r') => self.pos += 1,
+                Some(b'/') if self.bytes.get(self.pos + 1) == Some(&b'/') => {
+                    while !matches!(self.peek(), None | Some(b'\n')) {
+                        self.pos += 1;
+                    }
+                }
+                _ => return,
+            }
+        }
+    }
+
+    /// Consumes the character `c` if it is the next one.
+    fn accept(&mut self, c: u8) -> bool {
+        self.skip_whitespace();
+        if self.peek() == Some(c) {
+            self.pos += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn expect(&mut self, c: u8) -> TextResult<()> {
+        if self.accept(c) {
+            Ok(())
+        } else {
+            Err(self.error(&format!("'{}' expected", c as char)))
+        }
+    }
+
+    /// Reads a token composed by letters, digits, `_`, `.`, `+` and `-`.
+    fn word(&mut self) -> &'a str {
+        self.skip_whitespace();
+        let start = self.pos;
+        while let Some(c) = self.peek() {
+            if c.is_ascii_alphanumeric() || c == b'_' || c == b'.' || c == b'+' || c == b'-' {
+                self.pos += 1;
+            } else {
+                break;
+            }
+        }
+        &self.src[start..self.pos]
+    }
+
+    /// Parses a list of elements separated by commas up to the character
+    /// `end`. A trailing comma is allowed.
+    fn list<F>(&mut self, end: u8, mut f: F) -> TextResult<()>
+    where
+        F: FnMut(&mut Self) -> TextResult<()>,
+    {
+        loop {
+            if self.accept(end) {
+                return Ok(());
+            }
+            f(self)?;
+            if !self.accept(b',') {
+                return self.expect(end);
+            }
+        }
+    }
+
+    fn integer<T: FromStr>(&mut self) -> TextResult<T> {
+        self.skip_whitespace();
+        let offset = self.pos;
+        match self.word().parse() {
+            Ok(v) => Ok(v),
+            Err(_) => Err(self.error_at(offset, "invalid integer")),
+        }
+    }
+
+    fn float<T: FromStr>(&mut self) -> TextResult<T> {
+        self.skip_whitespace();
+        let offset = self.pos;
+        let w = self.word();
+        let w = match w {
+            "nan" => "NaN",
+            "inf" => "inf",
+            "-inf" => "-inf",
+            _ if w.starts_with(|c: char| c == '-' || c.is_ascii_digit()) => w,
+            _ => return Err(self.error_at(offset, "invalid number")),
+        };
+        match w.parse() {
+            Ok(v) => Ok(v),
+            Err(_) => Err(self.error_at(offset, "invalid number")),
+        }
+    }
+
+    fn integers<T: FromStr>(&mut self) -> TextResult<Vec<T>> {
+        self.expect(b'[')?;
+        let mut values = Vec::new();
+        self.list(b']', |p| {
+            values.push(p.integer()?);
+            Ok(())
+        })?;
+        Ok(values)
+    }
+
+    fn hex(&mut self) -> TextResult<Vec<u8>> {
+        self.skip_whitespace();
+        let offset = self.pos;
+        if !self.src[self.pos..].starts_with("h'") {
+            return Err(self.error("hexadecimal literal expected"));
+        }
+        self.pos += 2;
+        let start = self.pos;
+        while !matches!(self.peek(), None | Some(b'\'')) {
+            self.pos += 1;
+        }
+        if self.peek().is_none() {
+            return Err(self.error("unterminated hexadecimal literal"));
+        }
+        let digits: String = self.src[start..self.pos]
+            .chars()
+            .filter(|c| !c.is_ascii_whitespace())
+            .collect();
+        self.pos += 1;
+        match hex_decode(&digits) {
+            Some(v) => Ok(v),
+            None => Err(self.error_at(offset, "invalid hexadecimal literal")),
+        }
+    }
+
+    fn string(&mut self) -> TextResult<String> {
+        self.skip_whitespace();
+        if self.peek() != Some(b'"') {
+            return Err(self.error("string expected"));
+        }
+        self.pos += 1;
+        let mut ret = String::new();
+        loop {
+            let start = self.pos;
+            while !matches!(self.peek(), None | Some(b'"') | Some(b'\\')) {
+                self.pos += 1;
+            }
+            ret.push_str(&self.src[start..self.pos]);
+            match self.peek() {
+                Some(b'"') => {
+                    self.pos += 1;
+                    return Ok(ret);
+                }
+                Some(_) => {
+                    self.pos += 1;
+                    ret.push(self.escape()?);
+                }
+                None => return Err(self.error("unterminated string")),
+            }
+        }
+    }
+
+    /// Parses an escape sequence after the `\`.
+    fn escape(&mut self) -> TextResult<char> {
+        let c = match self.peek() {
+            Some(b'"') => '"',
+            Some(b'\\') => '\\',
+            Some(b'n') => '\n',
+            Some(b'r') => '\r',
+            Some(b't') => '\t',
+            Some(b'0') => '\0',
+            Some(b'u') if self.bytes.get(self.pos + 1) == Some(&b'{') => {
+                let start = self.pos + 2;
+                let end = match self.src[start..].find('}') {
+                    Some(n) => start + n,
+                    None => return Err(self.error("invalid unicode escape")),
+                };
+                let c = u32::from_str_radix(&self.src[start..end], 16)
+                    .ok()
+                    .and_then(char::from_u32);
+                return match c {
+                    Some(c) => {
+                        self.pos = end + 1;
+                        Ok(c)
+                    }
+                    None => Err(self.error("invalid unicode escape")),
+                };
+            }
+            _ => return Err(self.error("invalid escape sequence")),
+        };
+        self.pos += 1;
+        Ok(c)
+    }
+
+    fn tags(&mut self, end: u8) -> TextResult<Vec<Box<dyn ILTag>>> {
+        let mut tags = Vec::new();
+        self.list(end, |p| {
+            tags.push(p.tag()?);
+            Ok(())
+        })?;
+        Ok(tags)
+    }
+
+    fn tag(&mut self) -> TextResult<Box<dyn ILTag>> {
+        self.skip_whitespace();
+        if self.depth == MAX_TEXT_DEPTH {
+            return Err(self.error("maximum nesting level exceeded"));
+        }
+        self.depth += 1;
+        let tag = self.tag_body()?;
+        self.depth -= 1;
+        Ok(tag)
+    }
+
+    fn tag_body(&mut self) -> TextResult<Box<dyn ILTag>> {
+        let offset = self.pos;
+        let id = if self.accept(b'#') {
+            Some(self.integer::<u64>()?)
+        } else {
+            None
+        };
+        self.skip_whitespace();
+        if self.src[self.pos..].starts_with("h'") {
+            let id = match id {
+                Some(id) if !is_implicit_tag(id) => id,
+                Some(_) => return Err(self.error_at(offset, "raw tags cannot have implicit ids")),
+                None => return Err(self.error_at(offset, "raw tags require an id")),
+            };
+            return Ok(Box::new(ILRawTag::with_value(id, &self.hex()?)));
+        }
+        if self.peek() == Some(b'"') {
+            return Err(self.error("tag type expected"));
+        }
+        let type_offset = self.pos;
+        let type_name = self.word();
+        let id_or = |default_id: u64| id.unwrap_or(default_id);
+        let tag: Box<dyn ILTag> = match type_name {
+            "null" => Box::new(ILNullTag::with_id(id_or(IL_NULL_TAG_ID))),
+            "true" => Box::new(ILBoolTag::with_id_value(id_or(IL_BOOL_TAG_ID), true)),
+            "false" => Box::new(ILBoolTag::with_id_value(id_or(IL_BOOL_TAG_ID), false)),
+            "i8" => Box::new(ILInt8Tag::with_id_value(
+                id_or(IL_INT8_TAG_ID),
+                self.integer()?,
+            )),
+            "u8" => Box::new(ILUInt8Tag::with_id_value(
+                id_or(IL_UINT8_TAG_ID),
+                self.integer()?,
+            )),
+            "i16" => Box::new(ILInt16Tag::with_id_value(
+                id_or(IL_INT16_TAG_ID),
+                self.integer()?,
+            )),
+            "u16" => Box::new(ILUInt16Tag::with_id_value(
+                id_or(IL_UINT16_TAG_ID),
+                self.integer()?,
+            )),
+            "i32" => Box::new(ILInt32Tag::with_id_value(
+                id_or(IL_INT32_TAG_ID),
+                self.integer()?,
+            )),
+            "u32" => Box::new(ILUInt32Tag::with_id_value(
+                id_or(IL_UINT32_TAG_ID),
+                self.integer()?,
+            )),
+            "i64" => Box::new(ILInt64Tag::with_id_value(
+                id_or(IL_INT64_TAG_ID),
+                self.integer()?,
+            )),
+            "u64" => Box::new(ILUInt64Tag::with_id_value(
+                id_or(IL_UINT64_TAG_ID),
+                self.integer()?,
+            )),
+            "ilint" => Box::new(ILILInt64Tag::with_id_value(
+                id_or(IL_ILINT_TAG_ID),
+                self.integer()?,
+            )),
+            "silint" => Box::new(ILSignedILInt64Tag::with_id_value(
+                id_or(IL_SIGNED_ILINT_TAG_ID),
+                self.integer()?,
+            )),
+            "f32" => Box::new(ILBin32Tag::with_id_value(
+                id_or(IL_BIN32_TAG_ID),
+                self.float()?,
+            )),
+            "f64" => Box::new(ILBin64Tag::with_id_value(
+                id_or(IL_BIN64_TAG_ID),
+                self.float()?,
+            )),
+            "bin128" => {
+                self.skip_whitespace();
+                let value_offset = self.pos;
+                let bytes = self.hex()?;
+                if bytes.len() != 16 {
+                    return Err(self.error_at(value_offset, "16 bytes expected"));
+                }
+                let mut v = [0u8; 16];
+                v.copy_from_slice(&bytes);
+                Box::new(ILBin128Tag::with_id_value(id_or(IL_BIN128_TAG_ID), &v))
+            }
+            "bytes" => explicit_with_id(offset, id, ILByteArrayTag::with_value(&self.hex()?))?,
+            "str" => Box::new(ILStringTag::with_id_value(
+                id_or(IL_STRING_TAG_ID),
+                &self.string()?,
+            )),
+            "bint" => explicit_with_id(offset, id, ILBigIntTag::with_value(&self.hex()?))?,
+            "bdec" => {
+                let scale = self.integer()?;
+                Box::new(ILBigDecTag::with_id_value(
+                    id_or(IL_BDEC_TAG_ID),
+                    scale,
+                    &self.hex()?,
+                ))
+            }
+            "ilints" => Box::new(ILILIntArrayTag::with_id_value(
+                id_or(IL_ILINTARRAY_TAG_ID),
+                &self.integers()?,
+            )),
+            "array" => {
+                let mut tag = ILTagArrayTag::with_id(id_or(IL_ILTAGARRAY_TAG_ID));
+                self.expect(b'[')?;
+                *tag.mut_value() = self.tags(b']')?;
+                Box::new(tag)
+            }
+            "seq" => {
+                let mut tag = ILTagSeqTag::with_id(id_or(IL_ILTAGSEQ_TAG_ID));
+                self.expect(b'[')?;
+                *tag.mut_value() = self.tags(b']')?;
+                Box::new(tag)
+            }
+            "range" => {
+                self.skip_whitespace();
+                let value_offset = self.pos;
+                let v: Vec<u64> = self.integers()?;
+                if v.len() != 2 || v[1] > u16::MAX as u64 {
+                    return Err(self.error_at(value_offset, "invalid range"));
+                }
+                Box::new(ILRangeTag::with_id_value(
+                    id_or(IL_RANGE_TAG_ID),
+                    v[0],
+                    v[1] as u16,
+                ))
+            }
+            "version" => {
+                self.skip_whitespace();
+                let value_offset = self.pos;
+                let v: Vec<i32> = self.integers()?;
+                if v.len() != 4 {
+                    return Err(self.error_at(value_offset, "4 integers expected"));
+                }
+                Box::new(ILVersionTag::with_id_value_from_slice(
+                    id_or(IL_VERSION_TAG_ID),
+                    &[v[0], v[1], v[2], v[3]],
+                ))
+            }
+            "oid" => Box::new(ILOIDTag::with_id_value(
+                id_or(IL_OID_TAG_ID),
+                &self.integers()?,
+            )),
+            "dict" => {
+                let mut tag = ILDictTag::with_id(id_or(IL_DICTIONARY_TAG_ID));
+                self.expect(b'{')?;
+                self.list(b'}', |p| {
+                    p.skip_whitespace();
+                    let key_offset = p.pos;
+                    let key = p.string()?;
+                    p.expect(b':')?;
+                    if tag.insert(&key, p.tag()?).is_some() {
+                        return Err(p.error_at(key_offset, "duplicated key"));
+                    }
+                    Ok(())
+                })?;
+                Box::new(tag)
+            }
+            "strdict" => {
+                let mut tag = ILStrDictTag::with_id(id_or(IL_STRING_DICTIONARY_TAG_ID));
+                self.expect(b'{')?;
+                self.list(b'}', |p| {
+                    p.skip_whitespace();
+                    let key_offset = p.pos;
+                    let key = p.string()?;
+                    p.expect(b':')?;
+                    if tag.insert(&key, &p.string()?).is_some() {
+                        return Err(p.error_at(key_offset, "duplicated key"));
+                    }
+                    Ok(())
+                })?;
+                Box::new(tag)
+            }
+            "" => return Err(self.error_at(type_offset, "tag type expected")),
+            _ => return Err(self.error_at(type_offset, "unknown tag type")),
+        };
+        Ok(tag)
+    }
+}
+
+/// Applies a custom id to explicit tags that do not support it by
+/// converting them into an [`ILRawTag`] with the same payload.
+fn explicit_with_id<T: ILTag>(
+    offset: usize,
+    id: Option<u64>,
+    tag: T,
+) -> TextResult<Box<dyn ILTag>> {
+    match id {
+        Some(id) if is_implicit_tag(id) => Err(TextError::new(
+            offset,
+            "this tag type cannot have implicit ids",
+        )),
+        Some(id) if id != tag.id() => {
+            let mut value = Vec::with_capacity(tag.value_size() as usize);
+            // Serializing into a vector never fails.
+            tag.serialize_value(&mut BorrowedVecWriter::new(&mut value))
+                .unwrap();
+            Ok(Box::new(ILRawTag::with_value(id, &value)))
+        }
+        _ => Ok(Box::new(tag)),
+    }
+}
@@ -0,0 +1,354 @@
+/*
+ * BSD 3-Clause License
+ *
+ * Copyright (c) 2020, InterlockLedger Network
+ * All rights reserved.
+ *
+ * Redistribution and use in source and binary forms, with or without
+ * modification, are permitted provided that the following conditions are met:
+ *
+ * * Redistributions of source code must retain the above copyright notice, this
+ *   list of conditions and the following disclaimer.
+ *
+ * * Redistributions in binary form must reproduce the above copyright notice,
+ *   this list of conditions and the following disclaimer in the documentation
+ *   and/or other materials provided with the distribution.
+ *
+ * * Neither the name of the copyright holder nor the names of its
+ *   contributors may be used to endorse or promote products derived from
+ *   this software without specific prior written permission.
+ *
+ * THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS"
+ * AND ANY EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE
+ * IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+ * DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE
+ * FOR ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL, EXEMPLARY, OR CONSEQUENTIAL
+ * DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+ * SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER
+ * CAUSED AND ON ANY THEORY OF LIABILITY, WHETHER IN CONTRACT, STRICT LIABILITY,
+ * OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE
+ * OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+ */
+use super::*;
+use crate::io::Writer;
+use crate::tags::util::iltag_are_equal;
+
+fn create_sample() -> ILTagArrayTag {
+    let mut dict = ILDictTag::new();
+    dict.insert("null", Box::new(ILNullTag::new()));
+    dict.insert("bool", Box::new(ILBoolTag::with_value(false)));
+    dict.insert("int8", Box::new(ILInt8Tag::with_value(-128)));
+    dict.insert("uint8", Box::new(ILUInt8Tag::with_value(255)));
+    dict.insert("int16", Box::new(ILInt16Tag::with_value(-2)));
+    dict.insert("uint16", Box::new(ILUInt16Tag::with_value(2)));
+    dict.insert("int32", Box::new(ILInt32Tag::with_value(i32::MIN)));
+    dict.insert("uint32", Box::new(ILUInt32Tag::with_value(u32::MAX)));
+    dict.insert("int64", Box::new(ILInt64Tag::with_value(i64::MIN)));
+    dict.insert("uint64", Box::new(ILUInt64Tag::with_value(u64::MAX)));
+    dict.insert("ilint", Box::new(ILILInt64Tag::with_value(u64::MAX)));
+    dict.insert("silint", Box::new(ILSignedILInt64Tag::with_value(-5)));
+    dict.insert("bin32", Box::new(ILBin32Tag::with_value(0.1)));
+    dict.insert("bin64", Box::new(ILBin64Tag::with_value(f64::NEG_INFINITY)));
+    dict.insert("bin128", Box::new(ILBin128Tag::with_value(&[0xAB; 16])));
+    dict.insert("bytes", Box::new(ILByteArrayTag::with_value(&[1, 2, 0xFF])));
+    dict.insert(
+        "string",
+        Box::new(ILStringTag::with_value("\"a\\b\"\n\u{1}é")),
+    );
+    dict.insert("bint", Box::new(ILBigIntTag::with_value(&[0x80])));
+    dict.insert("bdec", Box::new(ILBigDecTag::with_value(-2, &[0x7F])));
+    dict.insert(
+        "ilints",
+        Box::new(ILILIntArrayTag::with_value(&[0, u64::MAX])),
+    );
+    dict.insert("range", Box::new(ILRangeTag::with_value(1234, 10)));
+    dict.insert("version", Box::new(ILVersionTag::with_value(1, 2, -3, 4)));
+    dict.insert("oid", Box::new(ILOIDTag::with_value(&[1, 2, 3])));
+    let mut str_dict = ILStrDictTag::new();
+    str_dict.insert("b", "2");
+    str_dict.insert("a", "1");
+    dict.insert("strdict", Box::new(str_dict));
+    let mut seq = ILTagSeqTag::new();
+    seq.mut_value()
+        .push(Box::new(ILRawTag::with_value(1234, &[1, 2])));
+    dict.insert("seq", Box::new(seq));
+    dict.insert("empty", Box::new(ILTagArrayTag::new()));
+
+    let mut tag = ILTagArrayTag::new();
+    tag.mut_value().push(Box::new(dict));
+    tag.mut_value()
+        .push(Box::new(ILStringTag::with_id_value(1234, "x")));
+    tag.mut_value().push(Box::new(ILTagArrayTag::with_id(1235)));
+    tag
+}
+
+#[test]
+fn test_to_text() {
+    let mut tag = ILTagArrayTag::new();
+    assert_eq!(to_text(&tag).unwrap(), "array[]");
+    assert_eq!(to_text_pretty(&tag).unwrap(), "array[]");
+
+    let mut dict = ILDictTag::with_id(1234);
+    dict.insert("b", Box::new(ILUInt8Tag::with_value(2)));
+    dict.insert("a", Box::new(ILBin32Tag::with_value(f32::NAN)));
+    tag.mut_value().push(Box::new(dict));
+    tag.mut_value()
+        .push(Box::new(ILBigDecTag::with_value(2, &[1])));
+    tag.mut_value()
+        .push(Box::new(ILVersionTag::with_value(1, 2, 3, 4)));
+    tag.mut_value()
+        .push(Box::new(ILRawTag::with_value(100, &[0xAB])));
+    assert_eq!(
+        to_text(&tag).unwrap(),
+        concat!(
+            r#"array[ #1234 dict{ "a": f32 nan, "b": u8 2 }, "#,
+            r#"bdec 2 h'01', version[1, 2, 3, 4], #100 h'ab' ]"#
+        )
+    );
+    assert_eq!(
+        to_text_pretty(&tag).unwrap(),
+        r#"array[
+  #1234 dict{
+    "a": f32 nan,
+    "b": u8 2
+  },
+  bdec 2 h'01',
+  version[1, 2, 3, 4],
+  #100 h'ab'
+]"#
+    );
+
+    // Tags implemented by other structs
+    assert_eq!(
+        to_text(&ILCustomTag {
+            id: IL_STRING_TAG_ID
+        })
+        .unwrap(),
+        "#17 h'0000cafe'"
+    );
+    assert_eq!(
+        to_text(&ILCustomTag {
+            id: IL_UINT32_TAG_ID
+        })
+        .unwrap(),
+        "u32 51966"
+    );
+}
+
+/// A tag that is not implemented by a standard struct.
+struct ILCustomTag {
+    id: u64,
+}
+
+impl ILTag for ILCustomTag {
+    fn id(&self) -> u64 {
+        self.id
+    }
+
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+
+    fn as_mut_any(&mut self) -> &mut dyn std::any::Any {
+        self
+    }
+
+    fn value_size(&self) -> u64 {
+        4
+    }
+
+    fn serialize_value(&self, writer: &mut dyn Writer) -> Result<()> {
+        writer.write_all(&[0x00, 0x00, 0xCA, 0xFE])?;
+        Ok(())
+    }
+
+    fn deserialize_value(
+        &mut self,
+        _factory: &dyn ILTagFactory,
+        _value_size: usize,
+        _reader: &mut dyn crate::io::Reader,
+    ) -> Result<()> {
+        Err(ErrorKind::UnsupportedTag)
+    }
+}
+
+#[test]
+fn test_from_text_round_trip() {
+    let tag = create_sample();
+    let factory = ILStandardTagFactory::new(false);
+    for text in [to_text(&tag).unwrap(), to_text_pretty(&tag).unwrap()] {
+        assert_eq!(text_to_bytes(&text).unwrap(), tag.to_bytes().unwrap());
+        let parsed = from_text(&factory, &text).unwrap();
+        assert!(iltag_are_equal(&tag, parsed.as_ref()));
+    }
+    // The custom ids of standard explicit types are unknown to the factory
+    let parsed = from_text(&factory, &to_text(&tag).unwrap()).unwrap();
+    let parsed = tag_downcast_ref::<ILTagArrayTag>(parsed.as_ref()).unwrap();
+    assert_eq!(to_text(parsed.value()[1].as_ref()).unwrap(), "#1234 h'78'");
+
+    // Custom ids of bytes and bint
+    assert_eq!(
+        text_to_bytes("#1234 bytes h'0a0b'").unwrap(),
+        text_to_bytes("#1234 h'0a0b'").unwrap()
+    );
+    assert_eq!(
+        text_to_bytes("#18 bytes h'0a0b'").unwrap(),
+        text_to_bytes("bint h'0a0b'").unwrap()
+    );
+}
+
+#[test]
+fn test_from_text_syntax() {
+    let text = r#"
+        // Comments are allowed
+        array[
+            str"\"\\\n\r\t\0\u{e9}\u{1F600}", // A string
+            bytes h'0a 0B
+                   0c',
+            f32 -1.5e3,
+            f64 inf,
+            u16 0x1,
+        ]
+    "#;
+    let tag = from_text(&ILStandardTagFactory::new(true), text);
+    assert!(tag.is_err());
+    let text = text.replace("0x1", "1");
+    let tag = from_text(&ILStandardTagFactory::new(true), &text).unwrap();
+    assert_eq!(
+        to_text(tag.as_ref()).unwrap(),
+        r#"array[ str"\"\\\n\r\t\0é😀", bytes h'0a0b0c', f32 -1500.0, f64 inf, u16 1 ]"#
+    );
+
+    // The factory is used to create the tags
+    assert!(matches!(
+        from_text(&ILStandardTagFactory::new(true), "#1234 h''"),
+        Err(ErrorKind::UnknownTag)
+    ));
+}
+
+fn assert_text_error(text: &str, offset: usize, message: &str) {
+    match text_to_bytes(text) {
+        Err(ErrorKind::Boxed(e)) => {
+            let e = e.downcast_ref::<TextError>().unwrap();
+            assert_eq!(e.offset(), offset, "{}", text);
+            assert_eq!(e.message(), message, "{}", text);
+        }
+        _ => panic!("Error expected for {}", text),
+    }
+}
+
+#[test]
+fn test_from_text_errors() {
+    assert_text_error("", 0, "tag type expected");
+    assert_text_error("xyz", 0, "unknown tag type");
+    assert_text_error(r#""a""#, 0, "tag type expected");
+    assert_text_error("null null", 5, "trailing characters after the tag");
+    assert_text_error("u8 256", 3, "invalid integer");
+    assert_text_error("i8 1.0", 3, "invalid integer");
+    assert_text_error("f32 x", 4, "invalid number");
+    assert_text_error("f64 1e", 4, "invalid number");
+    assert_text_error("str abc", 4, "string expected");
+    assert_text_error(r#"str"abc"#, 7, "unterminated string");
+    assert_text_error(r#"str"\x""#, 5, "invalid escape sequence");
+    assert_text_error(r#"str"\u{d800}""#, 5, "invalid unicode escape");
+    assert_text_error("bytes 0a", 6, "hexadecimal literal expected");
+    assert_text_error("bytes h'0a", 10, "unterminated hexadecimal literal");
+    assert_text_error("bytes h'0a0'", 6, "invalid hexadecimal literal");
+    assert_text_error("bin128 h'00'", 7, "16 bytes expected");
+    assert_text_error("range[1, 65536]", 5, "invalid range");
+    assert_text_error("version[1, 2, 3]", 7, "4 integers expected");
+    assert_text_error("array[ null null ]", 12, "']' expected");
+    assert_text_error("array[ , ]", 7, "tag type expected");
+    assert_text_error("dict{ a: null }", 6, "string expected");
+    assert_text_error(r#"dict{ "a" null }"#, 10, "':' expected");
+    assert_text_error(r#"dict{ "a": null, "a": null }"#, 17, "duplicated key");
+    assert_text_error(r#"strdict{ "a": "1", "a": "2" }"#, 19, "duplicated key");
+    assert_text_error("h'00'", 0, "raw tags require an id");
+    assert_text_error("#15 h'00'", 0, "raw tags cannot have implicit ids");
+    assert_text_error(
+        "#15 bytes h'00'",
+        0,
+        "this tag type cannot have implicit ids",
+    );
+    assert_text_error("#x null", 1, "invalid integer");
+
+    let depth = parser::MAX_TEXT_DEPTH;
+    let text = format!(
+        "{}null{}",
+        "array[".repeat(depth - 1),
+        "]".repeat(depth - 1)
+    );
+    assert!(text_to_bytes(&text).is_ok());
+    let text = format!("{}null{}", "array[".repeat(depth), "]".repeat(depth));
+    assert_text_error(&text, depth * 6, "maximum nesting level exceeded");
+}
+
+const DEEP_LEVELS: usize = 200000;
+
+/// Stack size used to run the tests with very deep trees. It is small enough
+/// to make any recursive implementation fail.
+const SMALL_STACK_SIZE: usize = 128 * 1024;
+
+fn run_with_small_stack<F: FnOnce() + Send + 'static>(f: F) {
+    std::thread::Builder::new()
+        .stack_size(SMALL_STACK_SIZE)
+        .spawn(f)
+        .unwrap()
+        .join()
+        .unwrap();
+}
+
+/// Creates a tree that alternates between sequences, arrays and
+/// dictionaries.
+fn create_very_deep_tag(levels: usize) -> Box<dyn ILTag> {
+    let mut tag: Box<dyn ILTag> = Box::new(ILNullTag::new());
+    for i in 0..levels {
+        tag = match i % 3 {
+            0 => {
+                let mut parent = ILTagSeqTag::new();
+                parent.mut_value().push(tag);
+                Box::new(parent)
+            }
+            1 => {
+                let mut parent = ILTagArrayTag::new();
+                parent.mut_value().push(tag);
+                Box::new(parent)
+            }
+            _ => {
+                let mut parent = ILDictTag::new();
+                parent.insert("a", tag);
+                Box::new(parent)
+            }
+        };
+    }
+    tag
+}
+
+#[test]
+fn test_to_text_very_deep_tree() {
+    run_with_small_stack(|| {
+        let tag = create_very_deep_tag(DEEP_LEVELS);
+        let text = to_text(tag.as_ref()).unwrap();
+        assert!(text.starts_with(r#"array[ seq[ dict{ "a": array[ "#));
+        assert!(text.contains("[ null ]"));
+        assert!(text.ends_with(" } ] ]"));
+        let lists = (0..DEEP_LEVELS).filter(|i| i % 3 != 2).count();
+        assert_eq!(text.matches('[').count(), lists);
+        assert_eq!(text.matches('{').count(), DEEP_LEVELS - lists);
+    });
+}
+
+#[test]
+fn test_to_text_pretty_very_deep_tree() {
+    // The indentation makes the output grow with the square of the depth
+    const LEVELS: usize = 5000;
+
+    run_with_small_stack(|| {
+        let tag = create_very_deep_tag(LEVELS);
+        let text = to_text(tag.as_ref()).unwrap();
+        let pretty = to_text_pretty(tag.as_ref()).unwrap();
+        assert_eq!(pretty.lines().count(), 2 * LEVELS + 1);
+        let compact: Vec<&str> = pretty.lines().map(|l| l.trim_start()).collect();
+        assert_eq!(compact.join(" "), text);
+    });
+}
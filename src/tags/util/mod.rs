@@ -38,7 +38,7 @@ mod tests;
 use super::standard::factory::ILStandardTagFactory;
 use super::ErrorKind;
 use super::{ILTag, ILTagFactory, Result};
-use crate::io::{LimitedReader, Reader};
+use crate::io::{Digest, LimitedReader, NullWriter, Reader};
 
 /// This function compares ILTag instances by serializing them and
 /// compare if the serialization matches.
@@ -111,6 +111,25 @@ pub fn iltag_clone(tag: &dyn ILTag) -> Result<Box<dyn ILTag>> {
     iltag_clone_with_factory(&factory, tag)
 }
 
+/// Computes the digest of the serialization of the given tag using the hash
+/// function `D`. The tag is hashed while it is serialized, thus the
+/// serialization is never held in memory. See [`ILTag::update_digest()`] for
+/// further details.
+///
+/// Arguments:
+/// * `tag`: The tag;
+///
+/// Returns:
+/// * Ok(digest): The digest;
+/// * Err(_): If the tag cannot be serialized;
+///
+/// New since 2.0.0.
+pub fn iltag_digest<D: Digest + Default>(tag: &dyn ILTag) -> Result<Vec<u8>> {
+    let mut digest = D::default();
+    tag.update_digest(&mut digest)?;
+    Ok(digest.finalize())
+}
+
 /// This helper function tests if the given [`LimitedReader`] is empty and
 /// return the specified result according to the status of the reader. It is
 /// very useful to implement the final verification for certain tag deserialization
@@ -131,6 +150,84 @@ pub fn limited_reader_ensure_empty(reader: &LimitedReader, on_error_kind: ErrorK
     }
 }
 
+/// Verifies if the size reported by [`ILTag::size()`] matches the actual
+/// size of its serialization. The tag is serialized into a [`NullWriter`],
+/// thus no memory is allocated to hold the serialized data.
+///
+/// It is useful to test the implementations of [`ILTag::value_size()`] and
+/// [`super::payload::ILTagPayload::serialized_size()`].
+///
+/// Arguments:
+/// - `tag`: The tag to be verified;
+///
+/// Returns:
+/// - `Ok(size)`: The size of the tag if it matches the serialization;
+/// - `Err(ErrorKind::ValueSizeMismatch(id))`: If the size does not match
+///   the serialization;
+/// - `Err(_)`: If the tag cannot be serialized;
+///
+/// New since 2.0.0.
+pub fn verify_tag_size(tag: &dyn ILTag) -> Result<u64> {
+    let mut writer = NullWriter::new();
+    tag.serialize(&mut writer)?;
+    let size = tag.size();
+    if writer.count() == size {
+        Ok(size)
+    } else {
+        Err(ErrorKind::ValueSizeMismatch(tag.id()))
+    }
+}
+
+/// Encodes a byte slice as a lowercase hexadecimal string.
+///
+/// Arguments:
+/// - `bytes`: The bytes to be encoded;
+///
+/// Returns:
+/// - The hexadecimal representation of `bytes`.
+///
+/// New since 2.0.0.
+pub fn hex_encode(bytes: &[u8]) -> String {
+    const DIGITS: &[u8; 16] = b"0123456789abcdef";
+    let mut s = String::with_capacity(bytes.len() * 2);
+    for b in bytes {
+        s.push(DIGITS[(b >> 4) as usize] as char);
+        s.push(DIGITS[(b & 0xF) as usize] as char);
+    }
+    s
+}
+
+/// Decodes a hexadecimal string. Both uppercase and lowercase digits are
+/// accepted.
+///
+/// Arguments:
+/// - `s`: The hexadecimal string;
+///
+/// Returns:
+/// - `Some(bytes)`: The decoded bytes;
+/// - `None`: If `s` has an odd length or contains non hexadecimal digits;
+///
+/// New since 2.0.0.
+pub fn hex_decode(s: &str) -> Option<Vec<u8>> {
+    fn digit(c: u8) -> Option<u8> {
+        match c {
+            b'0'..=b'9' => Some(c - b'0'),
+            b'a'..=b'f' => Some(c - b'a' + 10),
+            b'A'..=b'F' => Some(c - b'A' + 10),
+            _ => None,
+        }
+    }
+    let s = s.as_bytes();
+    // usize::is_multiple_of() requires Rust 1.87
+    #[allow(clippy::manual_is_multiple_of)]
+    if s.len() % 2 != 0 {
+        return None;
+    }
+    s.chunks(2)
+        .map(|p| Some((digit(p[0])? << 4) | digit(p[1])?))
+        .collect()
+}
+
 //=============================================================================
 // UntouchbleTagFactory
 //-----------------------------------------------------------------------------
@@ -143,6 +240,7 @@ pub fn limited_reader_ensure_empty(reader: &LimitedReader, on_error_kind: ErrorK
 /// recommended to keep the use of this struct for testing only.
 ///
 /// New since 1.3.0.
+#[derive(Default)]
 pub struct UntouchbleTagFactory {}
 
 impl UntouchbleTagFactory {
@@ -165,9 +263,3 @@ impl ILTagFactory for UntouchbleTagFactory {
         panic!("UntouchbleTagFactory touched.");
     }
 }
-
-impl Default for UntouchbleTagFactory {
-    fn default() -> Self {
-        Self {}
-    }
-}
@@ -57,7 +57,7 @@ fn test_iltag_clone_with_factory() {
     let mut factory = ILStandardTagFactory::new(true);
 
     factory.engine().register(
-        1234 as u64,
+        1234_u64,
         Box::new(ILDefaultWithIdTagCreator::<ILStringTag>::new()),
     );
     let sample = "abcde";
@@ -139,6 +139,75 @@ fn test_limited_reader_ensure_empty() {
     }
 }
 
+/// A payload that writes `written` bytes but reports `size` as its size.
+#[derive(Default)]
+struct WrongSizePayload {
+    size: usize,
+    written: usize,
+}
+
+impl crate::tags::payload::ILTagPayload for WrongSizePayload {
+    fn serialized_size(&self) -> usize {
+        self.size
+    }
+
+    fn serialize(&self, writer: &mut dyn crate::io::Writer) -> Result<()> {
+        for _ in 0..self.written {
+            writer.write(0)?;
+        }
+        Ok(())
+    }
+
+    fn deserialize(
+        &mut self,
+        _factory: &dyn ILTagFactory,
+        _value_size: usize,
+        _reader: &mut dyn Reader,
+    ) -> Result<()> {
+        Err(ErrorKind::UnsupportedTag)
+    }
+}
+
+#[test]
+fn test_verify_tag_size() {
+    use crate::tags::payload::ILGenericPayloadTag;
+    use crate::tags::standard::ILStrDictTag;
+
+    let tag = ILStringTag::with_value("abcde");
+    assert_eq!(verify_tag_size(&tag).unwrap(), 7);
+    let mut tag = ILStrDictTag::new();
+    tag.mut_value().insert("a".into(), "b".into());
+    assert_eq!(verify_tag_size(&tag).unwrap(), tag.size());
+
+    let mut tag: ILGenericPayloadTag<WrongSizePayload> = ILGenericPayloadTag::new(1234);
+    tag.mut_payload().size = 2;
+    tag.mut_payload().written = 2;
+    assert_eq!(verify_tag_size(&tag).unwrap(), 6);
+    tag.mut_payload().written = 3;
+    assert!(matches!(
+        verify_tag_size(&tag),
+        Err(ErrorKind::ValueSizeMismatch(1234))
+    ));
+    tag.mut_payload().written = 1;
+    assert!(matches!(
+        verify_tag_size(&tag),
+        Err(ErrorKind::ValueSizeMismatch(1234))
+    ));
+}
+
+#[test]
+fn test_hex_encode_decode() {
+    assert_eq!(hex_encode(&[]), "");
+    assert_eq!(hex_encode(&[0x00, 0x0a, 0xb1, 0xff]), "000ab1ff");
+
+    assert_eq!(hex_decode(""), Some(vec![]));
+    assert_eq!(hex_decode("000ab1ff"), Some(vec![0x00, 0x0a, 0xb1, 0xff]));
+    assert_eq!(hex_decode("000AB1FF"), Some(vec![0x00, 0x0a, 0xb1, 0xff]));
+    assert_eq!(hex_decode("0"), None);
+    assert_eq!(hex_decode("0g"), None);
+    assert_eq!(hex_decode("\u{e9}"), None);
+}
+
 //=============================================================================
 // UntouchbleTagFactory
 //-----------------------------------------------------------------------------
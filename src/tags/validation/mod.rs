@@ -0,0 +1,637 @@
+/*
+ * BSD 3-Clause License
+ *
+ * Copyright (c) 2020, InterlockLedger Network
+ * All rights reserved.
+ *
+ * Redistribution and use in source and binary forms, with or without
+ * modification, are permitted provided that the following conditions are met:
+ *
+ * * Redistributions of source code must retain the above copyright notice, this
+ *   list of conditions and the following disclaimer.
+ *
+ * * Redistributions in binary form must reproduce the above copyright notice,
+ *   this list of conditions and the following disclaimer in the documentation
+ *   and/or other materials provided with the distribution.
+ *
+ * * Neither the name of the copyright holder nor the names of its
+ *   contributors may be used to endorse or promote products derived from
+ *   this software without specific prior written permission.
+ *
+ * THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS"
+ * AND ANY EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE
+ * IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+ * DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE
+ * FOR ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL, EXEMPLARY, OR CONSEQUENTIAL
+ * DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+ * SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER
+ * CAUSED AND ON ANY THEORY OF LIABILITY, WHETHER IN CONTRACT, STRICT LIABILITY,
+ * OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE
+ * OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+ */
+//! This module implements a structural validator for serialized tags. It
+//! verifies if a byte slice contains well formed tags without deserializing
+//! them, thus it can be used to reject malformed data before handing it to
+//! a [`crate::tags::ILTagFactory`].
+//!
+//! The validation itself does not allocate memory on the heap. Memory is
+//! allocated only to report the path of the first violation found.
+//!
+//! [`validate()`] uses a fixed size stack that supports up to
+//! [`MAX_VALIDATION_DEPTH`] levels. Deeper trees, like the ones accepted by a
+//! factory configured with a larger
+//! [`crate::tags::limits::DeserializationLimits::max_depth()`], can be
+//! validated by [`validate_with_stack()`] with a [`ValidationStack`] large
+//! enough.
+//!
+//! New since 2.0.0.
+#[cfg(test)]
+mod tests;
+
+use crate::tags::is_implicit_tag;
+use crate::tags::path::{fmt_location, ILTagPath, ILTagPathElement};
+use crate::tags::raw::{standard_container_layout, RawContainerLayout, RawLayoutRegistry};
+use crate::tags::standard::constants::*;
+use crate::tags::standard::implicit::implicit_tag_size;
+use ::std::fmt;
+
+/// Maximum nesting depth supported by [`validate()`]. It uses a fixed size
+/// stack in order to avoid heap allocations. Use [`validate_with_stack()`]
+/// to validate deeper trees.
+///
+/// New since 2.0.0.
+pub const MAX_VALIDATION_DEPTH: usize = 128;
+
+/// Reserved tag id that must never be used.
+const IL_RESERVED_TAG_ID: u64 = 15;
+
+//=============================================================================
+// ValidationOptions
+//-----------------------------------------------------------------------------
+/// This struct defines the options used by [`validate()`].
+///
+/// New since 2.0.0.
+#[derive(Copy, Clone)]
+pub struct ValidationOptions<'a> {
+    max_depth: usize,
+    check_utf8: bool,
+    single_tag: bool,
+    layouts: Option<&'a RawLayoutRegistry>,
+}
+
+impl<'a> ValidationOptions<'a> {
+    /// Creates a new instance of this struct with the default options. By
+    /// default, it expects a single tag, verifies the UTF-8 encoding of the
+    /// strings, allows up to [`MAX_VALIDATION_DEPTH`] levels and knows only
+    /// the standard containers.
+    pub fn new() -> Self {
+        Self {
+            max_depth: MAX_VALIDATION_DEPTH,
+            check_utf8: true,
+            single_tag: true,
+            layouts: None,
+        }
+    }
+
+    /// Returns the maximum nesting depth. The top level tags are at depth 0.
+    pub fn max_depth(&self) -> usize {
+        self.max_depth
+    }
+
+    /// Sets the maximum nesting depth. [`validate()`] rejects values larger
+    /// than [`MAX_VALIDATION_DEPTH`] and [`validate_with_stack()`] rejects
+    /// values larger than [`ValidationStack::max_depth()`] with
+    /// [`ValidationErrorKind::StackTooSmall`].
+    ///
+    /// Arguments:
+    /// - `max_depth`: The new value;
+    pub fn set_max_depth(&mut self, max_depth: usize) {
+        self.max_depth = max_depth;
+    }
+
+    /// Returns true if the UTF-8 encoding of the string tags must be verified.
+    pub fn check_utf8(&self) -> bool {
+        self.check_utf8
+    }
+
+    /// Enables or disables the verification of the UTF-8 encoding of the
+    /// string tags. The keys of the dictionaries are always verified.
+    ///
+    /// Arguments:
+    /// - `check_utf8`: The new value;
+    pub fn set_check_utf8(&mut self, check_utf8: bool) {
+        self.check_utf8 = check_utf8;
+    }
+
+    /// Returns true if the data must contain exactly one top level tag.
+    pub fn single_tag(&self) -> bool {
+        self.single_tag
+    }
+
+    /// Defines if the data must contain exactly one top level tag or a
+    /// sequence of zero or more top level tags.
+    ///
+    /// Arguments:
+    /// - `single_tag`: The new value;
+    pub fn set_single_tag(&mut self, single_tag: bool) {
+        self.single_tag = single_tag;
+    }
+
+    /// Returns the layouts of the custom containers.
+    pub fn layouts(&self) -> Option<&'a RawLayoutRegistry> {
+        self.layouts
+    }
+
+    /// Sets the layouts of the custom containers. If not set, only the
+    /// standard containers are inspected.
+    ///
+    /// Arguments:
+    /// - `layouts`: The layouts;
+    pub fn set_layouts(&mut self, layouts: Option<&'a RawLayoutRegistry>) {
+        self.layouts = layouts;
+    }
+
+    /// Returns the layout of the given tag if it is a known container.
+    fn layout(&self, id: u64) -> Option<&'a dyn RawContainerLayout> {
+        match self.layouts {
+            Some(layouts) => layouts.get(id),
+            None => standard_container_layout(id),
+        }
+    }
+}
+
+impl<'a> Default for ValidationOptions<'a> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+//=============================================================================
+// ValidationError
+//-----------------------------------------------------------------------------
+/// Kinds of violations reported by [`validate()`].
+///
+/// New since 2.0.0.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum ValidationErrorKind {
+    /// The tag id or the tag size is not a valid ILInt.
+    CorruptedHeader,
+    /// The reserved tag id 15 was found.
+    ReservedTag,
+    /// The tag goes beyond the end of its container or of the data.
+    Truncated,
+    /// The value of the tag does not match the layout of its type.
+    InvalidValue,
+    /// The string is not a valid UTF-8 string.
+    InvalidUTF8,
+    /// The key of a dictionary is not a string tag.
+    InvalidDictKey,
+    /// The number of children does not match the count of the container.
+    ElementCountMismatch,
+    /// The maximum nesting depth was exceeded.
+    DepthLimitExceeded,
+    /// There are bytes after the end of the top level tag.
+    TrailingData,
+    /// The maximum nesting depth of the options is larger than the depth
+    /// supported by the stack of the validator.
+    StackTooSmall,
+}
+
+/// This struct describes the first violation found by [`validate()`].
+///
+/// New since 2.0.0.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ValidationError {
+    kind: ValidationErrorKind,
+    offset: u64,
+    id: Option<u64>,
+    path: ILTagPath,
+}
+
+impl ValidationError {
+    /// Returns the kind of the violation.
+    pub fn kind(&self) -> ValidationErrorKind {
+        self.kind
+    }
+
+    /// Returns the offset, in bytes, of the violation. It points to the
+    /// beginning of the offending tag or to the position where a tag was
+    /// expected or not expected.
+    pub fn offset(&self) -> u64 {
+        self.offset
+    }
+
+    /// Returns the id of the offending tag if it is known.
+    pub fn id(&self) -> Option<u64> {
+        self.id
+    }
+
+    /// Returns the path of the containers that hold the offending tag.
+    pub fn path(&self) -> &ILTagPath {
+        &self.path
+    }
+}
+
+impl fmt::Display for ValidationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:?} at offset {}", self.kind, self.offset)?;
+        fmt_location(f, &self.path, self.id)
+    }
+}
+
+impl std::error::Error for ValidationError {}
+
+//=============================================================================
+// ValidationStack
+//-----------------------------------------------------------------------------
+/// This struct implements the stack used by [`validate_with_stack()`]. It is
+/// allocated once, so the validation itself still does not allocate memory
+/// on the heap.
+///
+/// New since 2.0.0.
+pub struct ValidationStack {
+    frames: Vec<ValidationFrame>,
+}
+
+impl ValidationStack {
+    /// Creates a new stack that supports up to the given depth.
+    ///
+    /// Arguments:
+    /// - `max_depth`: The maximum depth supported;
+    pub fn new(max_depth: usize) -> Self {
+        Self {
+            frames: vec![ValidationFrame::default(); max_depth + 2],
+        }
+    }
+
+    /// Returns the maximum depth supported by this stack.
+    pub fn max_depth(&self) -> usize {
+        self.frames.len() - 2
+    }
+}
+
+//=============================================================================
+// validate()
+//-----------------------------------------------------------------------------
+/// A frame of the stack used by [`validate()`].
+#[derive(Copy, Clone, Default)]
+struct ValidationFrame {
+    id: u64,
+    end: usize,
+    remaining: Option<u64>,
+    next_index: u64,
+    dict: bool,
+    string_values: bool,
+    key_start: usize,
+    key_end: usize,
+}
+
+impl ValidationFrame {
+    /// Returns the path element that points to the current child.
+    ///
+    /// Arguments:
+    /// - `raw`: The serialized tags that hold the key of the entry;
+    fn path_element(&self, raw: &[u8]) -> ILTagPathElement {
+        let index = self.next_index.saturating_sub(1);
+        if self.dict {
+            let key = if index % 2 == 1 {
+                Some(String::from_utf8_lossy(&raw[self.key_start..self.key_end]).into_owned())
+            } else {
+                None
+            };
+            ILTagPathElement::new(self.id, index / 2, key)
+        } else {
+            ILTagPathElement::new(self.id, index, None)
+        }
+    }
+}
+
+/// Parses the header of the tag that starts at `raw[0]`.
+///
+/// Returns the id of the tag, the size of its header and the size of its
+/// value.
+fn parse_header(raw: &[u8]) -> Result<(u64, usize, usize), (ValidationErrorKind, Option<u64>)> {
+    let (id, id_size) = match crate::ilint::decode_from_bytes(raw) {
+        Ok(v) => v,
+        Err(_) => return Err((ValidationErrorKind::CorruptedHeader, None)),
+    };
+    if id == IL_RESERVED_TAG_ID {
+        return Err((ValidationErrorKind::ReservedTag, Some(id)));
+    }
+    let (header_size, value_size) = if is_implicit_tag(id) {
+        match id {
+            IL_ILINT_TAG_ID | IL_SIGNED_ILINT_TAG_ID => match raw.get(id_size) {
+                Some(header) => (id_size, crate::ilint::decoded_size(*header) as u64),
+                None => return Err((ValidationErrorKind::Truncated, Some(id))),
+            },
+            _ => (id_size, implicit_tag_size(id)),
+        }
+    } else {
+        match crate::ilint::decode_from_bytes(&raw[id_size..]) {
+            Ok((size, size_size)) => (id_size + size_size, size),
+            Err(_) => return Err((ValidationErrorKind::CorruptedHeader, Some(id))),
+        }
+    };
+    if value_size > (raw.len() - header_size) as u64 {
+        Err((ValidationErrorKind::Truncated, Some(id)))
+    } else {
+        Ok((id, header_size, value_size as usize))
+    }
+}
+
+/// Verifies if the value contains exactly one ILInt.
+fn is_single_ilint(value: &[u8]) -> bool {
+    matches!(crate::ilint::decode_from_bytes(value), Ok((_, size)) if size == value.len())
+}
+
+/// Verifies if the value contains an ILInt count followed by exactly that
+/// number of ILInts.
+fn is_ilint_array(value: &[u8]) -> bool {
+    let (count, mut offset) = match crate::ilint::decode_from_bytes(value) {
+        Ok(v) => v,
+        Err(_) => return false,
+    };
+    for _ in 0..count {
+        match crate::ilint::decode_from_bytes(&value[offset..]) {
+            Ok((_, size)) => offset += size,
+            Err(_) => return false,
+        }
+    }
+    offset == value.len()
+}
+
+/// Validates the value of a tag that is not a container.
+fn validate_leaf(
+    id: u64,
+    value: &[u8],
+    options: &ValidationOptions,
+) -> Result<(), ValidationErrorKind> {
+    let valid = match id {
+        IL_ILINT_TAG_ID | IL_SIGNED_ILINT_TAG_ID => is_single_ilint(value),
+        IL_STRING_TAG_ID => {
+            if options.check_utf8() && std::str::from_utf8(value).is_err() {
+                return Err(ValidationErrorKind::InvalidUTF8);
+            }
+            true
+        }
+        IL_BDEC_TAG_ID => value.len() >= 4,
+        IL_ILINTARRAY_TAG_ID | IL_OID_TAG_ID => is_ilint_array(value),
+        IL_RANGE_TAG_ID => {
+            matches!(crate::ilint::decode_from_bytes(value), Ok((_, size)) if size + 2 == value.len())
+        }
+        IL_VERSION_TAG_ID => value.len() == 16,
+        _ => true,
+    };
+    if valid {
+        Ok(())
+    } else {
+        Err(ValidationErrorKind::InvalidValue)
+    }
+}
+
+/// Builds the error using the frames of the stack.
+///
+/// Arguments:
+/// - `raw`: The serialized tags;
+/// - `stack`: The frames that hold the offending tag;
+/// - `kind`: The kind of the violation;
+/// - `offset`: The offset of the violation;
+/// - `id`: The id of the offending tag;
+fn build_error(
+    raw: &[u8],
+    stack: &[ValidationFrame],
+    kind: ValidationErrorKind,
+    offset: usize,
+    id: Option<u64>,
+) -> ValidationError {
+    let mut path = ILTagPath::new();
+    for frame in stack.iter().skip(1) {
+        path.push(frame.path_element(raw));
+    }
+    ValidationError {
+        kind,
+        offset: offset as u64,
+        id,
+        path,
+    }
+}
+
+/// Verifies if the given bytes contain well formed tags. It checks the
+/// headers of all tags, the sizes of the implicit tags, the boundaries of
+/// the nested containers, the number of elements of the containers, the
+/// keys of the dictionaries, the UTF-8 encoding of the strings and the use
+/// of the reserved tag id 15.
+///
+/// This function does not use recursion nor allocates memory on the heap,
+/// except to build the [`ValidationError`]. Its stack supports up to
+/// [`MAX_VALIDATION_DEPTH`] levels, thus it fails with
+/// [`ValidationErrorKind::StackTooSmall`] if the maximum depth of `options`
+/// is larger than that. Use [`validate_with_stack()`] in this case.
+///
+/// Arguments:
+/// - `raw`: The serialized tags;
+/// - `options`: The validation options;
+///
+/// Returns:
+/// - `Ok(())`: If the data is valid;
+/// - `Err(ValidationError)`: The description of the first violation found;
+///
+/// New since 2.0.0.
+pub fn validate(raw: &[u8], options: &ValidationOptions) -> Result<(), ValidationError> {
+    let mut stack = [ValidationFrame::default(); MAX_VALIDATION_DEPTH + 2];
+    validate_frames(raw, options, &mut stack)
+}
+
+/// Does the same as [`validate()`] but uses the given stack, so it can
+/// validate trees deeper than [`MAX_VALIDATION_DEPTH`]. The stack can be
+/// reused by multiple calls.
+///
+/// Arguments:
+/// - `raw`: The serialized tags;
+/// - `options`: The validation options;
+/// - `stack`: The stack. Its maximum depth must not be smaller than the
+///   maximum depth of `options`;
+///
+/// Returns:
+/// - `Ok(())`: If the data is valid;
+/// - `Err(ValidationError)`: The description of the first violation found;
+///
+/// New since 2.0.0.
+pub fn validate_with_stack(
+    raw: &[u8],
+    options: &ValidationOptions,
+    stack: &mut ValidationStack,
+) -> Result<(), ValidationError> {
+    validate_frames(raw, options, &mut stack.frames)
+}
+
+/// Implementation of [`validate()`] over the given frames.
+fn validate_frames(
+    raw: &[u8],
+    options: &ValidationOptions,
+    stack: &mut [ValidationFrame],
+) -> Result<(), ValidationError> {
+    if options.max_depth() > stack.len() - 2 {
+        return Err(build_error(
+            raw,
+            &[],
+            ValidationErrorKind::StackTooSmall,
+            0,
+            None,
+        ));
+    }
+    stack[0] = ValidationFrame::default();
+    stack[0].end = raw.len();
+    if options.single_tag() {
+        stack[0].remaining = Some(1);
+    }
+    let mut depth: usize = 0;
+    let mut pos: usize = 0;
+    loop {
+        let frame = &mut stack[depth];
+        if pos == frame.end {
+            if matches!(frame.remaining, Some(r) if r > 0) {
+                return Err(if depth == 0 {
+                    build_error(raw, &stack[..1], ValidationErrorKind::Truncated, pos, None)
+                } else {
+                    let id = frame.id;
+                    build_error(
+                        raw,
+                        &stack[..depth],
+                        ValidationErrorKind::ElementCountMismatch,
+                        pos,
+                        Some(id),
+                    )
+                });
+            }
+            if depth == 0 {
+                return Ok(());
+            }
+            depth -= 1;
+            continue;
+        }
+        if frame.remaining == Some(0) {
+            return Err(if depth == 0 {
+                build_error(
+                    raw,
+                    &stack[..1],
+                    ValidationErrorKind::TrailingData,
+                    pos,
+                    None,
+                )
+            } else {
+                let id = frame.id;
+                build_error(
+                    raw,
+                    &stack[..depth],
+                    ValidationErrorKind::ElementCountMismatch,
+                    pos,
+                    Some(id),
+                )
+            });
+        }
+        if let Some(remaining) = frame.remaining.as_mut() {
+            *remaining -= 1;
+        }
+        let child_index = frame.next_index;
+        frame.next_index += 1;
+
+        // Header
+        let (id, header_size, value_size) = match parse_header(&raw[pos..frame.end]) {
+            Ok(v) => v,
+            Err((kind, id)) => return Err(build_error(raw, &stack[..=depth], kind, pos, id)),
+        };
+        let value_start = pos + header_size;
+        let value_end = value_start + value_size;
+        let value = &raw[value_start..value_end];
+
+        // Dictionary entries. u64::is_multiple_of() requires Rust 1.87
+        #[allow(clippy::manual_is_multiple_of)]
+        if frame.dict {
+            if child_index % 2 == 0 {
+                if id != IL_STRING_TAG_ID {
+                    return Err(build_error(
+                        raw,
+                        &stack[..=depth],
+                        ValidationErrorKind::InvalidDictKey,
+                        pos,
+                        Some(id),
+                    ));
+                }
+                match std::str::from_utf8(value) {
+                    Ok(_) => {
+                        frame.key_start = value_start;
+                        frame.key_end = value_end;
+                    }
+                    Err(_) => {
+                        return Err(build_error(
+                            raw,
+                            &stack[..=depth],
+                            ValidationErrorKind::InvalidUTF8,
+                            pos,
+                            Some(id),
+                        ))
+                    }
+                }
+            } else if frame.string_values && id != IL_STRING_TAG_ID {
+                return Err(build_error(
+                    raw,
+                    &stack[..=depth],
+                    ValidationErrorKind::InvalidValue,
+                    pos,
+                    Some(id),
+                ));
+            }
+        }
+
+        if depth > options.max_depth() {
+            return Err(build_error(
+                raw,
+                &stack[..=depth],
+                ValidationErrorKind::DepthLimitExceeded,
+                pos,
+                Some(id),
+            ));
+        }
+
+        match options.layout(id) {
+            Some(layout) => {
+                let prefix_size = match layout.parse_prefix(value) {
+                    Ok((prefix_size, _)) if prefix_size > value.len() as u64 => None,
+                    Ok((prefix_size, remaining)) => Some((prefix_size as usize, remaining)),
+                    Err(_) => None,
+                };
+                let (prefix_size, remaining) = match prefix_size {
+                    Some(v) => v,
+                    None => {
+                        return Err(build_error(
+                            raw,
+                            &stack[..=depth],
+                            ValidationErrorKind::InvalidValue,
+                            pos,
+                            Some(id),
+                        ))
+                    }
+                };
+                depth += 1;
+                stack[depth] = ValidationFrame {
+                    id,
+                    end: value_end,
+                    remaining,
+                    next_index: 0,
+                    dict: id == IL_DICTIONARY_TAG_ID || id == IL_STRING_DICTIONARY_TAG_ID,
+                    string_values: id == IL_STRING_DICTIONARY_TAG_ID,
+                    key_start: 0,
+                    key_end: 0,
+                };
+                pos = value_start + prefix_size;
+            }
+            None => {
+                if let Err(kind) = validate_leaf(id, value, options) {
+                    return Err(build_error(raw, &stack[..=depth], kind, pos, Some(id)));
+                }
+                pos = value_end;
+            }
+        }
+    }
+}
@@ -0,0 +1,556 @@
+/*
+ * BSD 3-Clause License
+ *
+ * Copyright (c) 2020, InterlockLedger Network
+ * All rights reserved.
+ *
+ * Redistribution and use in source and binary forms, with or without
+ * modification, are permitted provided that the following conditions are met:
+ *
+ * * Redistributions of source code must retain the above copyright notice, this
+ *   list of conditions and the following disclaimer.
+ *
+ * * Redistributions in binary form must reproduce the above copyright notice,
+ *   this list of conditions and the following disclaimer in the documentation
+ *   and/or other materials provided with the distribution.
+ *
+ * * Neither the name of the copyright holder nor the names of its
+ *   contributors may be used to endorse or promote products derived from
+ *   this software without specific prior written permission.
+ *
+ * THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS"
+ * AND ANY EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE
+ * IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+ * DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE
+ * FOR ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL, EXEMPLARY, OR CONSEQUENTIAL
+ * DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+ * SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER
+ * CAUSED AND ON ANY THEORY OF LIABILITY, WHETHER IN CONTRACT, STRICT LIABILITY,
+ * OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE
+ * OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+ */
+use super::*;
+use crate::tags::standard::*;
+use crate::tags::{ILRawTag, ILTag};
+
+//=============================================================================
+// Samples
+//-----------------------------------------------------------------------------
+fn create_sample_tag() -> Vec<u8> {
+    let mut inner = ILTagArrayTag::new();
+    inner
+        .mut_value()
+        .push(Box::new(ILStringTag::with_value("x")));
+    inner
+        .mut_value()
+        .push(Box::new(ILILInt64Tag::with_value(0xFEDCBA)));
+    inner
+        .mut_value()
+        .push(Box::new(ILSignedILInt64Tag::with_value(-1234)));
+    inner
+        .mut_value()
+        .push(Box::new(ILBigDecTag::with_value(2, &[1])));
+    inner
+        .mut_value()
+        .push(Box::new(ILRangeTag::with_value(0xFFFF, 2)));
+    inner
+        .mut_value()
+        .push(Box::new(ILVersionTag::with_value(1, 2, 3, 4)));
+    inner
+        .mut_value()
+        .push(Box::new(ILOIDTag::with_value(&[1, 2, 0xFFFF])));
+
+    let mut strdict = ILStrDictTag::new();
+    strdict.insert("a", "b");
+
+    let mut dict = ILDictTag::new();
+    dict.insert("payload", Box::new(inner));
+    dict.insert("strdict", Box::new(strdict));
+    dict.insert("bool", Box::new(ILBoolTag::with_value(true)));
+
+    let mut seq = ILTagSeqTag::new();
+    seq.mut_value().push(Box::new(dict));
+    seq.mut_value().push(Box::new(ILTagSeqTag::new()));
+    seq.mut_value().push(Box::new(ILBin128Tag::new()));
+    seq.to_bytes().unwrap()
+}
+
+fn assert_violation(raw: &[u8], kind: ValidationErrorKind, offset: u64, id: Option<u64>) {
+    let e = validate(raw, &ValidationOptions::default()).unwrap_err();
+    assert_eq!(e.kind(), kind);
+    assert_eq!(e.offset(), offset);
+    assert_eq!(e.id(), id);
+}
+
+//=============================================================================
+// ValidationOptions
+//-----------------------------------------------------------------------------
+#[test]
+fn test_validationoptions() {
+    let mut o = ValidationOptions::new();
+    assert_eq!(o.max_depth(), MAX_VALIDATION_DEPTH);
+    assert!(o.check_utf8());
+    assert!(o.single_tag());
+    assert!(o.layouts().is_none());
+
+    o.set_max_depth(10);
+    assert_eq!(o.max_depth(), 10);
+    o.set_max_depth(MAX_VALIDATION_DEPTH + 1);
+    assert_eq!(o.max_depth(), MAX_VALIDATION_DEPTH + 1);
+    o.set_check_utf8(false);
+    assert!(!o.check_utf8());
+    o.set_single_tag(false);
+    assert!(!o.single_tag());
+    let layouts = RawLayoutRegistry::new();
+    o.set_layouts(Some(&layouts));
+    assert!(o.layouts().is_some());
+
+    let o = ValidationOptions::default();
+    assert_eq!(o.max_depth(), MAX_VALIDATION_DEPTH);
+    assert!(o.single_tag());
+}
+
+//=============================================================================
+// validate()
+//-----------------------------------------------------------------------------
+#[test]
+fn test_validate() {
+    let raw = create_sample_tag();
+    assert!(validate(&raw, &ValidationOptions::default()).is_ok());
+
+    for id in [
+        IL_NULL_TAG_ID,
+        IL_BOOL_TAG_ID,
+        IL_UINT64_TAG_ID,
+        IL_BIN128_TAG_ID,
+        IL_BYTES_TAG_ID,
+        IL_BINT_TAG_ID,
+        1234,
+    ] {
+        let raw = match id {
+            IL_NULL_TAG_ID => ILNullTag::new().to_bytes().unwrap(),
+            IL_BOOL_TAG_ID => ILBoolTag::new().to_bytes().unwrap(),
+            IL_UINT64_TAG_ID => ILUInt64Tag::new().to_bytes().unwrap(),
+            IL_BIN128_TAG_ID => ILBin128Tag::new().to_bytes().unwrap(),
+            _ => ILRawTag::with_value(id, &[1, 2, 3]).to_bytes().unwrap(),
+        };
+        assert!(validate(&raw, &ValidationOptions::default()).is_ok());
+    }
+}
+
+#[test]
+fn test_validate_sequence() {
+    let mut raw = create_sample_tag();
+    let size = raw.len();
+    raw.extend(create_sample_tag());
+
+    assert_violation(&raw, ValidationErrorKind::TrailingData, size as u64, None);
+    assert_violation(&[], ValidationErrorKind::Truncated, 0, None);
+
+    let mut options = ValidationOptions::default();
+    options.set_single_tag(false);
+    assert!(validate(&raw, &options).is_ok());
+    assert!(validate(&[], &options).is_ok());
+}
+
+#[test]
+fn test_validate_truncated() {
+    let raw = create_sample_tag();
+    for size in 0..raw.len() {
+        assert!(validate(&raw[..size], &ValidationOptions::default()).is_err());
+    }
+
+    let raw = ILUInt32Tag::with_value(1).to_bytes().unwrap();
+    assert_violation(
+        &raw[..3],
+        ValidationErrorKind::Truncated,
+        0,
+        Some(IL_UINT32_TAG_ID),
+    );
+    let raw = ILStringTag::with_value("abc").to_bytes().unwrap();
+    assert_violation(
+        &raw[..4],
+        ValidationErrorKind::Truncated,
+        0,
+        Some(IL_STRING_TAG_ID),
+    );
+    assert_violation(
+        &[IL_ILINT_TAG_ID as u8],
+        ValidationErrorKind::Truncated,
+        0,
+        Some(IL_ILINT_TAG_ID),
+    );
+    assert_violation(
+        &[IL_ILINT_TAG_ID as u8, 0xF9, 0x00],
+        ValidationErrorKind::Truncated,
+        0,
+        Some(IL_ILINT_TAG_ID),
+    );
+
+    // Child larger than the parent
+    let raw: [u8; 6] = [
+        IL_ILTAGSEQ_TAG_ID as u8,
+        4,
+        IL_STRING_TAG_ID as u8,
+        3,
+        b'a',
+        b'b',
+    ];
+    let e = validate(&raw[..], &ValidationOptions::default()).unwrap_err();
+    assert_eq!(e.kind(), ValidationErrorKind::Truncated);
+    assert_eq!(e.offset(), 2);
+    assert_eq!(e.id(), Some(IL_STRING_TAG_ID));
+    assert_eq!(format!("{}", e.path()), "seq[0]");
+}
+
+#[test]
+fn test_validate_header() {
+    // Reserved
+    assert_violation(&[15], ValidationErrorKind::ReservedTag, 0, Some(15));
+
+    // Invalid ILInt
+    assert_violation(
+        &[0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF],
+        ValidationErrorKind::CorruptedHeader,
+        0,
+        None,
+    );
+    assert_violation(&[0xF9, 0x00], ValidationErrorKind::CorruptedHeader, 0, None);
+    assert_violation(
+        &[IL_STRING_TAG_ID as u8, 0xF9],
+        ValidationErrorKind::CorruptedHeader,
+        0,
+        Some(IL_STRING_TAG_ID),
+    );
+}
+
+#[test]
+fn test_validate_values() {
+    // ILInt
+    assert_violation(
+        &[
+            IL_ILINT_TAG_ID as u8,
+            0xFF,
+            0xFF,
+            0xFF,
+            0xFF,
+            0xFF,
+            0xFF,
+            0xFF,
+            0xFF,
+            0xFF,
+        ],
+        ValidationErrorKind::InvalidValue,
+        0,
+        Some(IL_ILINT_TAG_ID),
+    );
+
+    // UTF-8
+    let raw: [u8; 4] = [IL_STRING_TAG_ID as u8, 2, 0xC3, 0x28];
+    assert_violation(
+        &raw,
+        ValidationErrorKind::InvalidUTF8,
+        0,
+        Some(IL_STRING_TAG_ID),
+    );
+    let mut options = ValidationOptions::default();
+    options.set_check_utf8(false);
+    assert!(validate(&raw, &options).is_ok());
+
+    // BigDec
+    let raw = ILRawTag::with_value(IL_BDEC_TAG_ID, &[0, 0, 0])
+        .to_bytes()
+        .unwrap();
+    assert_violation(
+        &raw,
+        ValidationErrorKind::InvalidValue,
+        0,
+        Some(IL_BDEC_TAG_ID),
+    );
+
+    // ILInt array
+    for value in [&[][..], &[2, 1][..], &[1, 1, 1][..], &[1, 0xF9][..]] {
+        let raw = ILRawTag::with_value(IL_ILINTARRAY_TAG_ID, value)
+            .to_bytes()
+            .unwrap();
+        assert_violation(
+            &raw,
+            ValidationErrorKind::InvalidValue,
+            0,
+            Some(IL_ILINTARRAY_TAG_ID),
+        );
+        let raw = ILRawTag::with_value(IL_OID_TAG_ID, value)
+            .to_bytes()
+            .unwrap();
+        assert_violation(
+            &raw,
+            ValidationErrorKind::InvalidValue,
+            0,
+            Some(IL_OID_TAG_ID),
+        );
+    }
+
+    // Range
+    let raw = ILRawTag::with_value(IL_RANGE_TAG_ID, &[1, 2])
+        .to_bytes()
+        .unwrap();
+    assert_violation(
+        &raw,
+        ValidationErrorKind::InvalidValue,
+        0,
+        Some(IL_RANGE_TAG_ID),
+    );
+
+    // Version
+    let raw = ILRawTag::with_value(IL_VERSION_TAG_ID, &[0; 15])
+        .to_bytes()
+        .unwrap();
+    assert_violation(
+        &raw,
+        ValidationErrorKind::InvalidValue,
+        0,
+        Some(IL_VERSION_TAG_ID),
+    );
+
+    // Array without count
+    let raw = ILRawTag::with_value(IL_ILTAGARRAY_TAG_ID, &[])
+        .to_bytes()
+        .unwrap();
+    assert_violation(
+        &raw,
+        ValidationErrorKind::InvalidValue,
+        0,
+        Some(IL_ILTAGARRAY_TAG_ID),
+    );
+}
+
+#[test]
+fn test_validate_containers() {
+    // Less elements than expected
+    let raw: [u8; 4] = [IL_ILTAGARRAY_TAG_ID as u8, 2, 2, IL_NULL_TAG_ID as u8];
+    assert_violation(
+        &raw,
+        ValidationErrorKind::ElementCountMismatch,
+        4,
+        Some(IL_ILTAGARRAY_TAG_ID),
+    );
+
+    // More elements than expected
+    let raw: [u8; 5] = [
+        IL_ILTAGARRAY_TAG_ID as u8,
+        3,
+        1,
+        IL_NULL_TAG_ID as u8,
+        IL_NULL_TAG_ID as u8,
+    ];
+    assert_violation(
+        &raw,
+        ValidationErrorKind::ElementCountMismatch,
+        4,
+        Some(IL_ILTAGARRAY_TAG_ID),
+    );
+
+    // Dictionary with a non string key
+    let raw: [u8; 5] = [
+        IL_DICTIONARY_TAG_ID as u8,
+        3,
+        1,
+        IL_NULL_TAG_ID as u8,
+        IL_NULL_TAG_ID as u8,
+    ];
+    let e = validate(&raw, &ValidationOptions::default()).unwrap_err();
+    assert_eq!(e.kind(), ValidationErrorKind::InvalidDictKey);
+    assert_eq!(e.offset(), 3);
+    assert_eq!(format!("{}", e.path()), "dict[0]");
+
+    // Dictionary with a non UTF-8 key
+    let raw: [u8; 6] = [
+        IL_DICTIONARY_TAG_ID as u8,
+        4,
+        1,
+        IL_STRING_TAG_ID as u8,
+        1,
+        0xFF,
+    ];
+    let mut options = ValidationOptions::default();
+    options.set_check_utf8(false);
+    let e = validate(&raw, &options).unwrap_err();
+    assert_eq!(e.kind(), ValidationErrorKind::InvalidUTF8);
+
+    // String dictionary with a non string value
+    let raw: [u8; 7] = [
+        IL_STRING_DICTIONARY_TAG_ID as u8,
+        5,
+        1,
+        IL_STRING_TAG_ID as u8,
+        1,
+        b'a',
+        IL_NULL_TAG_ID as u8,
+    ];
+    let e = validate(&raw, &ValidationOptions::default()).unwrap_err();
+    assert_eq!(e.kind(), ValidationErrorKind::InvalidValue);
+    assert_eq!(e.offset(), 6);
+    assert_eq!(format!("{}", e.path()), "strdict[\"a\"]");
+}
+
+#[test]
+fn test_validate_path() {
+    let raw = create_sample_tag();
+    let mut corrupted = raw.clone();
+    // Replace the "x" by an invalid UTF-8 byte
+    let pos = raw
+        .windows(3)
+        .position(|w| w == [IL_STRING_TAG_ID as u8, 1, b'x'])
+        .unwrap();
+    corrupted[pos + 2] = 0xFF;
+
+    let e = validate(&corrupted, &ValidationOptions::default()).unwrap_err();
+    assert_eq!(e.kind(), ValidationErrorKind::InvalidUTF8);
+    assert_eq!(e.offset(), pos as u64);
+    assert_eq!(e.id(), Some(IL_STRING_TAG_ID));
+    assert_eq!(
+        format!("{}", e.path()),
+        "seq[0] > dict[\"payload\"] > array[0]"
+    );
+    assert_eq!(
+        format!("{}", e),
+        format!(
+            "InvalidUTF8 at offset {} in seq[0] > dict[\"payload\"] > array[0] > string",
+            pos
+        )
+    );
+}
+
+#[test]
+fn test_validate_depth() {
+    let mut tag = ILTagSeqTag::new();
+    for _ in 0..10 {
+        let mut parent = ILTagSeqTag::new();
+        parent.mut_value().push(Box::new(tag));
+        tag = parent;
+    }
+    tag.mut_value().push(Box::new(ILNullTag::new()));
+    let raw = tag.to_bytes().unwrap();
+
+    let mut options = ValidationOptions::default();
+    options.set_max_depth(10);
+    assert!(validate(&raw, &options).is_ok());
+    options.set_max_depth(9);
+    let e = validate(&raw, &options).unwrap_err();
+    assert_eq!(e.kind(), ValidationErrorKind::DepthLimitExceeded);
+    assert_eq!(e.path().len(), 10);
+
+    // Beyond the maximum depth
+    let mut tag = ILTagSeqTag::new();
+    for _ in 0..(MAX_VALIDATION_DEPTH + 10) {
+        let mut parent = ILTagSeqTag::new();
+        parent.mut_value().push(Box::new(tag));
+        tag = parent;
+    }
+    let raw = tag.to_bytes().unwrap();
+    let e = validate(&raw, &ValidationOptions::default()).unwrap_err();
+    assert_eq!(e.kind(), ValidationErrorKind::DepthLimitExceeded);
+
+    // The stack of validate() is too small
+    let mut options = ValidationOptions::default();
+    options.set_max_depth(MAX_VALIDATION_DEPTH + 10);
+    let e = validate(&raw, &options).unwrap_err();
+    assert_eq!(e.kind(), ValidationErrorKind::StackTooSmall);
+    assert_eq!(e.offset(), 0);
+    assert_eq!(e.id(), None);
+    assert_eq!(e.path().len(), 0);
+}
+
+//=============================================================================
+// validate_with_stack()
+//-----------------------------------------------------------------------------
+#[test]
+fn test_validationstack() {
+    let stack = ValidationStack::new(0);
+    assert_eq!(stack.max_depth(), 0);
+    let stack = ValidationStack::new(MAX_VALIDATION_DEPTH * 2);
+    assert_eq!(stack.max_depth(), MAX_VALIDATION_DEPTH * 2);
+}
+
+#[test]
+fn test_validate_with_stack() {
+    let depth = MAX_VALIDATION_DEPTH * 2;
+    let mut tag = ILTagSeqTag::new();
+    for _ in 0..depth {
+        let mut parent = ILTagSeqTag::new();
+        parent.mut_value().push(Box::new(tag));
+        tag = parent;
+    }
+    let raw = tag.to_bytes().unwrap();
+
+    let mut options = ValidationOptions::default();
+    options.set_max_depth(depth);
+    let mut stack = ValidationStack::new(depth);
+    assert!(validate_with_stack(&raw, &options, &mut stack).is_ok());
+
+    // Reuse the stack
+    options.set_max_depth(depth - 1);
+    let e = validate_with_stack(&raw, &options, &mut stack).unwrap_err();
+    assert_eq!(e.kind(), ValidationErrorKind::DepthLimitExceeded);
+    assert_eq!(e.path().len(), depth);
+    let raw = create_sample_tag();
+    assert!(validate_with_stack(&raw, &options, &mut stack).is_ok());
+    let mut corrupted = raw.clone();
+    let pos = raw
+        .windows(3)
+        .position(|w| w == [IL_STRING_TAG_ID as u8, 1, b'x'])
+        .unwrap();
+    corrupted[pos + 2] = 0xFF;
+    let e = validate_with_stack(&corrupted, &options, &mut stack).unwrap_err();
+    assert_eq!(e.kind(), ValidationErrorKind::InvalidUTF8);
+    assert_eq!(
+        format!("{}", e.path()),
+        "seq[0] > dict[\"payload\"] > array[0]"
+    );
+
+    // The stack is too small
+    options.set_max_depth(depth + 1);
+    let e = validate_with_stack(&raw, &options, &mut stack).unwrap_err();
+    assert_eq!(e.kind(), ValidationErrorKind::StackTooSmall);
+}
+
+struct CustomLayout;
+
+impl RawContainerLayout for CustomLayout {
+    fn parse_prefix(&self, value: &[u8]) -> crate::tags::Result<(u64, Option<u64>)> {
+        match value.first() {
+            Some(count) => Ok((1, Some(*count as u64))),
+            None => Err(crate::tags::ErrorKind::CorruptedData),
+        }
+    }
+}
+
+#[test]
+fn test_validate_layouts() {
+    let mut value: Vec<u8> = vec![1];
+    value.extend_from_slice(&[IL_STRING_TAG_ID as u8, 1, 0xFF]);
+    let raw = ILRawTag::with_value(1234, &value).to_bytes().unwrap();
+
+    // Not a container by default
+    assert!(validate(&raw, &ValidationOptions::default()).is_ok());
+
+    let mut layouts = RawLayoutRegistry::new();
+    layouts.register(1234, Box::new(CustomLayout));
+    let mut options = ValidationOptions::default();
+    options.set_layouts(Some(&layouts));
+    let e = validate(&raw, &options).unwrap_err();
+    assert_eq!(e.kind(), ValidationErrorKind::InvalidUTF8);
+    assert_eq!(format!("{}", e.path()), "tag(1234)[0]");
+
+    let raw = ILRawTag::with_value(1234, &[]).to_bytes().unwrap();
+    let e = validate(&raw, &options).unwrap_err();
+    assert_eq!(e.kind(), ValidationErrorKind::InvalidValue);
+}
+
+#[test]
+fn test_validationerror_display() {
+    let e = validate(&[], &ValidationOptions::default()).unwrap_err();
+    assert_eq!(format!("{}", e), "Truncated at offset 0");
+    let e = validate(&[15], &ValidationOptions::default()).unwrap_err();
+    assert_eq!(format!("{}", e), "ReservedTag at offset 0 in tag(15)");
+}
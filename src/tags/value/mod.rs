@@ -0,0 +1,794 @@
+/*
+ * BSD 3-Clause License
+ *
+ * Copyright (c) 2020, InterlockLedger Network
+ * All rights reserved.
+ *
+ * Redistribution and use in source and binary forms, with or without
+ * modification, are permitted provided that the following conditions are met:
+ *
+ * * Redistributions of source code must retain the above copyright notice, this
+ *   list of conditions and the following disclaimer.
+ *
+ * * Redistributions in binary form must reproduce the above copyright notice,
+ *   this list of conditions and the following disclaimer in the documentation
+ *   and/or other materials provided with the distribution.
+ *
+ * * Neither the name of the copyright holder nor the names of its
+ *   contributors may be used to endorse or promote products derived from
+ *   this software without specific prior written permission.
+ *
+ * THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS"
+ * AND ANY EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE
+ * IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+ * DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE
+ * FOR ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL, EXEMPLARY, OR CONSEQUENTIAL
+ * DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+ * SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER
+ * CAUSED AND ON ANY THEORY OF LIABILITY, WHETHER IN CONTRACT, STRICT LIABILITY,
+ * OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE
+ * OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+ */
+//! This module defines [`ILValue`], an owned representation of standard tag
+//! trees that can be inspected with pattern matching instead of downcasts.
+//!
+//! Example:
+//!
+//! ```
+//! use il2_iltags::tags::value::ILValue;
+//!
+//! let mut v = ILValue::Dict(Default::default());
+//! if let ILValue::Dict(d) = &mut v {
+//!     d.insert(String::from("n"), ILValue::UInt32(7));
+//!     d.insert(String::from("l"), ILValue::Array(vec![ILValue::from("a")]));
+//! }
+//! let bytes = v.to_bytes().unwrap();
+//! let w = ILValue::from_bytes(&bytes).unwrap();
+//! assert_eq!(v, w);
+//! assert_eq!(w["n"].as_u64(), Some(7));
+//! assert_eq!(w["l"][0].as_str(), Some("a"));
+//! assert!(w["missing"].is_null());
+//! ```
+//!
+//! New since 2.0.0.
+#[cfg(test)]
+mod tests;
+
+use crate::io::array::BorrowedVecWriter;
+use crate::tags::standard::constants::*;
+use crate::tags::standard::*;
+use crate::tags::{tag_downcast_ref, ErrorKind, ILRawTag, ILTag, ILTagFactory, Result};
+use std::collections::BTreeMap;
+use std::convert::TryFrom;
+use std::ops::Index;
+
+//=============================================================================
+// ILValue
+//-----------------------------------------------------------------------------
+/// This enum represents any standard tag tree. Each variant corresponds to a
+/// standard tag with its default id. Tags with other ids are represented by
+/// [`ILValue::Raw`], which holds the serialized value of the tag.
+///
+/// The conversions from and into tags and the drop of the values use an
+/// explicit stack, thus they can handle trees of any depth.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ILValue {
+    /// [`ILNullTag`].
+    Null,
+    /// [`ILBoolTag`].
+    Bool(bool),
+    /// [`ILInt8Tag`].
+    Int8(i8),
+    /// [`ILUInt8Tag`].
+    UInt8(u8),
+    /// [`ILInt16Tag`].
+    Int16(i16),
+    /// [`ILUInt16Tag`].
+    UInt16(u16),
+    /// [`ILInt32Tag`].
+    Int32(i32),
+    /// [`ILUInt32Tag`].
+    UInt32(u32),
+    /// [`ILInt64Tag`].
+    Int64(i64),
+    /// [`ILUInt64Tag`].
+    UInt64(u64),
+    /// [`ILILInt64Tag`].
+    ILInt(u64),
+    /// [`ILSignedILInt64Tag`].
+    SignedILInt(i64),
+    /// [`ILBin32Tag`].
+    Bin32(f32),
+    /// [`ILBin64Tag`].
+    Bin64(f64),
+    /// [`ILBin128Tag`].
+    Bin128([u8; 16]),
+    /// [`ILByteArrayTag`].
+    Bytes(Vec<u8>),
+    /// [`ILStringTag`].
+    String(String),
+    /// [`ILBigIntTag`].
+    BigInt(Vec<u8>),
+    /// [`ILBigDecTag`].
+    BigDec { scale: i32, value: Vec<u8> },
+    /// [`ILILIntArrayTag`].
+    ILIntArray(Vec<u64>),
+    /// [`ILTagArrayTag`].
+    Array(Vec<ILValue>),
+    /// [`ILTagSeqTag`].
+    Seq(Vec<ILValue>),
+    /// [`ILRangeTag`].
+    Range { start: u64, count: u16 },
+    /// [`ILVersionTag`].
+    Version([i32; 4]),
+    /// [`ILOIDTag`].
+    OID(Vec<u64>),
+    /// [`ILDictTag`].
+    Dict(BTreeMap<String, ILValue>),
+    /// [`ILStrDictTag`].
+    StrDict(BTreeMap<String, String>),
+    /// Any other tag. It holds the id and the serialized value of the tag.
+    Raw { id: u64, bytes: Vec<u8> },
+}
+
+/// Value returned by the indexing operators when the entry does not exist.
+static NULL: ILValue = ILValue::Null;
+
+impl ILValue {
+    /// Returns the id of the tag that represents this value.
+    pub fn id(&self) -> u64 {
+        match self {
+            ILValue::Null => IL_NULL_TAG_ID,
+            ILValue::Bool(_) => IL_BOOL_TAG_ID,
+            ILValue::Int8(_) => IL_INT8_TAG_ID,
+            ILValue::UInt8(_) => IL_UINT8_TAG_ID,
+            ILValue::Int16(_) => IL_INT16_TAG_ID,
+            ILValue::UInt16(_) => IL_UINT16_TAG_ID,
+            ILValue::Int32(_) => IL_INT32_TAG_ID,
+            ILValue::UInt32(_) => IL_UINT32_TAG_ID,
+            ILValue::Int64(_) => IL_INT64_TAG_ID,
+            ILValue::UInt64(_) => IL_UINT64_TAG_ID,
+            ILValue::ILInt(_) => IL_ILINT_TAG_ID,
+            ILValue::SignedILInt(_) => IL_SIGNED_ILINT_TAG_ID,
+            ILValue::Bin32(_) => IL_BIN32_TAG_ID,
+            ILValue::Bin64(_) => IL_BIN64_TAG_ID,
+            ILValue::Bin128(_) => IL_BIN128_TAG_ID,
+            ILValue::Bytes(_) => IL_BYTES_TAG_ID,
+            ILValue::String(_) => IL_STRING_TAG_ID,
+            ILValue::BigInt(_) => IL_BINT_TAG_ID,
+            ILValue::BigDec { .. } => IL_BDEC_TAG_ID,
+            ILValue::ILIntArray(_) => IL_ILINTARRAY_TAG_ID,
+            ILValue::Array(_) => IL_ILTAGARRAY_TAG_ID,
+            ILValue::Seq(_) => IL_ILTAGSEQ_TAG_ID,
+            ILValue::Range { .. } => IL_RANGE_TAG_ID,
+            ILValue::Version(_) => IL_VERSION_TAG_ID,
+            ILValue::OID(_) => IL_OID_TAG_ID,
+            ILValue::Dict(_) => IL_DICTIONARY_TAG_ID,
+            ILValue::StrDict(_) => IL_STRING_DICTIONARY_TAG_ID,
+            ILValue::Raw { id, .. } => *id,
+        }
+    }
+
+    /// Converts a tag into a value. Tags with standard ids that are not
+    /// implemented by the standard tag structs are serialized and
+    /// deserialized again by a [`ILStandardTagFactory`]. Tags with other ids
+    /// are converted into [`ILValue::Raw`].
+    ///
+    /// The tree is converted using an explicit stack, thus it can handle
+    /// trees of any depth.
+    ///
+    /// Arguments:
+    /// - `tag`: The tag;
+    ///
+    /// Returns:
+    /// - `Ok(v)`: The value;
+    /// - `Err(_)`: For failure;
+    pub fn from_tag(tag: &dyn ILTag) -> Result<Self> {
+        let mut stack: Vec<FromTagFrame> = Vec::new();
+        let mut next = tag;
+        loop {
+            let mut value = match Self::from_tag_node(next)? {
+                FromTagNode::Value(v) => Some(v),
+                FromTagNode::Container(frame) => {
+                    stack.push(frame);
+                    None
+                }
+            };
+            // Hands the converted values to their parents until one of them
+            // still has children to convert.
+            loop {
+                let frame = match stack.last_mut() {
+                    Some(frame) => frame,
+                    // The root is always the last value converted.
+                    None => return Ok(value.unwrap()),
+                };
+                if let Some(v) = value.take() {
+                    frame.push(v);
+                }
+                if let Some(child) = frame.next_child() {
+                    next = child;
+                    break;
+                }
+                value = stack.pop().map(FromTagFrame::finish);
+            }
+        }
+    }
+
+    /// Converts a single tag without its children. The standard containers
+    /// are returned as frames that are handled by [`Self::from_tag()`].
+    fn from_tag_node(tag: &dyn ILTag) -> Result<FromTagNode<'_>> {
+        if let Some(frame) = FromTagFrame::new(tag) {
+            return Ok(FromTagNode::Container(frame));
+        }
+        if let Some(v) = Self::from_standard_tag(tag) {
+            return Ok(FromTagNode::Value(v));
+        }
+        if is_standard_id(tag.id()) {
+            let factory = ILStandardTagFactory::new(false);
+            let tag = factory.from_bytes(&tag.to_bytes()?)?;
+            if let Some(v) = Self::from_standard_tag(tag.as_ref()) {
+                Ok(FromTagNode::Value(v))
+            } else if FromTagFrame::new(tag.as_ref()).is_some() {
+                // The new tree is made only of standard tag structs, thus
+                // this recursion happens at most once.
+                Ok(FromTagNode::Value(Self::from_tag(tag.as_ref())?))
+            } else {
+                Err(ErrorKind::UnexpectedTagType)
+            }
+        } else {
+            let mut bytes = Vec::with_capacity(tag.value_size() as usize);
+            tag.serialize_value(&mut BorrowedVecWriter::new(&mut bytes))?;
+            Ok(FromTagNode::Value(ILValue::Raw {
+                id: tag.id(),
+                bytes,
+            }))
+        }
+    }
+
+    /// Converts a standard tag that is not a container into a value.
+    ///
+    /// Returns:
+    /// - `Some(v)`: The value;
+    /// - `None`: If the tag is not a standard tag struct with its default id
+    ///   or if it is a standard container;
+    fn from_standard_tag(tag: &dyn ILTag) -> Option<Self> {
+        match tag.id() {
+            IL_NULL_TAG_ID => tag_downcast_ref::<ILNullTag>(tag).map(|_| ILValue::Null),
+            IL_BOOL_TAG_ID => tag_downcast_ref::<ILBoolTag>(tag).map(|t| ILValue::Bool(t.value())),
+            IL_INT8_TAG_ID => tag_downcast_ref::<ILInt8Tag>(tag).map(|t| ILValue::Int8(t.value())),
+            IL_UINT8_TAG_ID => {
+                tag_downcast_ref::<ILUInt8Tag>(tag).map(|t| ILValue::UInt8(t.value()))
+            }
+            IL_INT16_TAG_ID => {
+                tag_downcast_ref::<ILInt16Tag>(tag).map(|t| ILValue::Int16(t.value()))
+            }
+            IL_UINT16_TAG_ID => {
+                tag_downcast_ref::<ILUInt16Tag>(tag).map(|t| ILValue::UInt16(t.value()))
+            }
+            IL_INT32_TAG_ID => {
+                tag_downcast_ref::<ILInt32Tag>(tag).map(|t| ILValue::Int32(t.value()))
+            }
+            IL_UINT32_TAG_ID => {
+                tag_downcast_ref::<ILUInt32Tag>(tag).map(|t| ILValue::UInt32(t.value()))
+            }
+            IL_INT64_TAG_ID => {
+                tag_downcast_ref::<ILInt64Tag>(tag).map(|t| ILValue::Int64(t.value()))
+            }
+            IL_UINT64_TAG_ID => {
+                tag_downcast_ref::<ILUInt64Tag>(tag).map(|t| ILValue::UInt64(t.value()))
+            }
+            IL_ILINT_TAG_ID => {
+                tag_downcast_ref::<ILILInt64Tag>(tag).map(|t| ILValue::ILInt(t.value()))
+            }
+            IL_SIGNED_ILINT_TAG_ID => {
+                tag_downcast_ref::<ILSignedILInt64Tag>(tag).map(|t| ILValue::SignedILInt(t.value()))
+            }
+            IL_BIN32_TAG_ID => {
+                tag_downcast_ref::<ILBin32Tag>(tag).map(|t| ILValue::Bin32(t.value()))
+            }
+            IL_BIN64_TAG_ID => {
+                tag_downcast_ref::<ILBin64Tag>(tag).map(|t| ILValue::Bin64(t.value()))
+            }
+            IL_BIN128_TAG_ID => {
+                tag_downcast_ref::<ILBin128Tag>(tag).map(|t| ILValue::Bin128(*t.value()))
+            }
+            IL_BYTES_TAG_ID => {
+                tag_downcast_ref::<ILByteArrayTag>(tag).map(|t| ILValue::Bytes(t.value().clone()))
+            }
+            IL_STRING_TAG_ID => {
+                tag_downcast_ref::<ILStringTag>(tag).map(|t| ILValue::String(t.value().into()))
+            }
+            IL_BINT_TAG_ID => {
+                tag_downcast_ref::<ILBigIntTag>(tag).map(|t| ILValue::BigInt(t.value().clone()))
+            }
+            IL_BDEC_TAG_ID => tag_downcast_ref::<ILBigDecTag>(tag).map(|t| ILValue::BigDec {
+                scale: t.scale(),
+                value: t.value().clone(),
+            }),
+            IL_ILINTARRAY_TAG_ID => tag_downcast_ref::<ILILIntArrayTag>(tag)
+                .map(|t| ILValue::ILIntArray(t.value().clone())),
+            IL_RANGE_TAG_ID => tag_downcast_ref::<ILRangeTag>(tag).map(|t| ILValue::Range {
+                start: t.start(),
+                count: t.count(),
+            }),
+            IL_VERSION_TAG_ID => {
+                tag_downcast_ref::<ILVersionTag>(tag).map(|t| ILValue::Version(*t.value()))
+            }
+            IL_OID_TAG_ID => {
+                tag_downcast_ref::<ILOIDTag>(tag).map(|t| ILValue::OID(t.value().clone()))
+            }
+            IL_STRING_DICTIONARY_TAG_ID => tag_downcast_ref::<ILStrDictTag>(tag).map(|t| {
+                ILValue::StrDict(
+                    t.value()
+                        .iter()
+                        .map(|(k, v)| (k.clone(), v.clone()))
+                        .collect(),
+                )
+            }),
+            _ => None,
+        }
+    }
+
+    /// Converts this value into a tag.
+    ///
+    /// The tree is converted using an explicit stack, thus it can handle
+    /// values of any depth.
+    ///
+    /// Returns:
+    /// - The tag that represents this value.
+    pub fn to_tag(&self) -> Box<dyn ILTag> {
+        let mut stack: Vec<ToTagFrame> = Vec::new();
+        let mut next = self;
+        loop {
+            let mut tag = match ToTagFrame::new(next) {
+                Some(frame) => {
+                    stack.push(frame);
+                    None
+                }
+                None => Some(next.to_tag_node()),
+            };
+            // Hands the converted tags to their parents until one of them
+            // still has children to convert.
+            loop {
+                let frame = match stack.last_mut() {
+                    Some(frame) => frame,
+                    // The root is always the last tag converted.
+                    None => return tag.unwrap(),
+                };
+                if let Some(t) = tag.take() {
+                    frame.push(t);
+                }
+                if let Some(child) = frame.next_child() {
+                    next = child;
+                    break;
+                }
+                tag = stack.pop().map(ToTagFrame::finish);
+            }
+        }
+    }
+
+    /// Converts this value into a tag if it is not a container.
+    ///
+    /// Panics if this value is a container.
+    fn to_tag_node(&self) -> Box<dyn ILTag> {
+        match self {
+            ILValue::Null => Box::new(ILNullTag::new()),
+            ILValue::Bool(v) => Box::new(ILBoolTag::with_value(*v)),
+            ILValue::Int8(v) => Box::new(ILInt8Tag::with_value(*v)),
+            ILValue::UInt8(v) => Box::new(ILUInt8Tag::with_value(*v)),
+            ILValue::Int16(v) => Box::new(ILInt16Tag::with_value(*v)),
+            ILValue::UInt16(v) => Box::new(ILUInt16Tag::with_value(*v)),
+            ILValue::Int32(v) => Box::new(ILInt32Tag::with_value(*v)),
+            ILValue::UInt32(v) => Box::new(ILUInt32Tag::with_value(*v)),
+            ILValue::Int64(v) => Box::new(ILInt64Tag::with_value(*v)),
+            ILValue::UInt64(v) => Box::new(ILUInt64Tag::with_value(*v)),
+            ILValue::ILInt(v) => Box::new(ILILInt64Tag::with_value(*v)),
+            ILValue::SignedILInt(v) => Box::new(ILSignedILInt64Tag::with_value(*v)),
+            ILValue::Bin32(v) => Box::new(ILBin32Tag::with_value(*v)),
+            ILValue::Bin64(v) => Box::new(ILBin64Tag::with_value(*v)),
+            ILValue::Bin128(v) => Box::new(ILBin128Tag::with_value(v)),
+            ILValue::Bytes(v) => Box::new(ILByteArrayTag::with_value(v)),
+            ILValue::String(v) => Box::new(ILStringTag::with_value(v)),
+            ILValue::BigInt(v) => Box::new(ILBigIntTag::with_value(v)),
+            ILValue::BigDec { scale, value } => Box::new(ILBigDecTag::with_value(*scale, value)),
+            ILValue::ILIntArray(v) => Box::new(ILILIntArrayTag::with_value(v)),
+            ILValue::Range { start, count } => Box::new(ILRangeTag::with_value(*start, *count)),
+            ILValue::Version(v) => Box::new(ILVersionTag::with_value_from_slice(v)),
+            ILValue::OID(v) => Box::new(ILOIDTag::with_value(v)),
+            ILValue::StrDict(v) => {
+                let mut tag = ILStrDictTag::new();
+                for (k, e) in v {
+                    tag.insert(k, e);
+                }
+                Box::new(tag)
+            }
+            ILValue::Raw { id, bytes } => Box::new(ILRawTag::with_value(*id, bytes)),
+            ILValue::Array(_) | ILValue::Seq(_) | ILValue::Dict(_) => {
+                unreachable!("containers are converted by ILValue::to_tag()")
+            }
+        }
+    }
+
+    /// Deserializes a value. The tag is deserialized by a non strict
+    /// [`ILStandardTagFactory`], thus unknown tags are converted into
+    /// [`ILValue::Raw`].
+    ///
+    /// Arguments:
+    /// - `bytes`: The serialized tag;
+    ///
+    /// Returns:
+    /// - `Ok(v)`: The value;
+    /// - `Err(_)`: For failure;
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self> {
+        let factory = ILStandardTagFactory::new(false);
+        Self::from_tag(factory.from_bytes(bytes)?.as_ref())
+    }
+
+    /// Serializes this value.
+    ///
+    /// Returns:
+    /// - `Ok(bytes)`: The serialized tag;
+    /// - `Err(_)`: For failure;
+    pub fn to_bytes(&self) -> Result<Vec<u8>> {
+        self.to_tag().to_bytes()
+    }
+
+    /// Returns true if this value is [`ILValue::Null`].
+    pub fn is_null(&self) -> bool {
+        matches!(self, ILValue::Null)
+    }
+
+    /// Returns the value of [`ILValue::Bool`].
+    pub fn as_bool(&self) -> Option<bool> {
+        match self {
+            ILValue::Bool(v) => Some(*v),
+            _ => None,
+        }
+    }
+
+    /// Returns the value of the integer variants if it can be represented
+    /// as an u64.
+    pub fn as_u64(&self) -> Option<u64> {
+        match *self {
+            ILValue::UInt8(v) => Some(v as u64),
+            ILValue::UInt16(v) => Some(v as u64),
+            ILValue::UInt32(v) => Some(v as u64),
+            ILValue::UInt64(v) | ILValue::ILInt(v) => Some(v),
+            _ => match self.as_i64() {
+                Some(v) if v >= 0 => Some(v as u64),
+                _ => None,
+            },
+        }
+    }
+
+    /// Returns the value of the integer variants if it can be represented
+    /// as an i64.
+    pub fn as_i64(&self) -> Option<i64> {
+        match *self {
+            ILValue::Int8(v) => Some(v as i64),
+            ILValue::Int16(v) => Some(v as i64),
+            ILValue::Int32(v) => Some(v as i64),
+            ILValue::Int64(v) | ILValue::SignedILInt(v) => Some(v),
+            ILValue::UInt8(v) => Some(v as i64),
+            ILValue::UInt16(v) => Some(v as i64),
+            ILValue::UInt32(v) => Some(v as i64),
+            ILValue::UInt64(v) | ILValue::ILInt(v) if v <= i64::MAX as u64 => Some(v as i64),
+            _ => None,
+        }
+    }
+
+    /// Returns the value of [`ILValue::Bin32`] or [`ILValue::Bin64`].
+    pub fn as_f64(&self) -> Option<f64> {
+        match *self {
+            ILValue::Bin32(v) => Some(v as f64),
+            ILValue::Bin64(v) => Some(v),
+            _ => None,
+        }
+    }
+
+    /// Returns the value of [`ILValue::String`].
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            ILValue::String(v) => Some(v.as_str()),
+            _ => None,
+        }
+    }
+
+    /// Returns the value of [`ILValue::Bytes`].
+    pub fn as_bytes(&self) -> Option<&[u8]> {
+        match self {
+            ILValue::Bytes(v) => Some(v.as_slice()),
+            _ => None,
+        }
+    }
+
+    /// Returns the elements of [`ILValue::Array`] or [`ILValue::Seq`].
+    pub fn as_array(&self) -> Option<&Vec<ILValue>> {
+        match self {
+            ILValue::Array(v) | ILValue::Seq(v) => Some(v),
+            _ => None,
+        }
+    }
+
+    /// Returns the elements of [`ILValue::Array`] or [`ILValue::Seq`].
+    pub fn as_array_mut(&mut self) -> Option<&mut Vec<ILValue>> {
+        match self {
+            ILValue::Array(v) | ILValue::Seq(v) => Some(v),
+            _ => None,
+        }
+    }
+
+    /// Returns the entries of [`ILValue::Dict`].
+    pub fn as_dict(&self) -> Option<&BTreeMap<String, ILValue>> {
+        match self {
+            ILValue::Dict(v) => Some(v),
+            _ => None,
+        }
+    }
+
+    /// Returns the entries of [`ILValue::Dict`].
+    pub fn as_dict_mut(&mut self) -> Option<&mut BTreeMap<String, ILValue>> {
+        match self {
+            ILValue::Dict(v) => Some(v),
+            _ => None,
+        }
+    }
+
+    /// Returns the entry of [`ILValue::Dict`] with the given key.
+    ///
+    /// Arguments:
+    /// - `key`: The key;
+    ///
+    /// Returns:
+    /// - `Some(v)`: The value;
+    /// - `None`: If this is not a dictionary or the key does not exist;
+    pub fn get(&self, key: &str) -> Option<&ILValue> {
+        self.as_dict().and_then(|d| d.get(key))
+    }
+
+    /// Returns the element of [`ILValue::Array`] or [`ILValue::Seq`] at the
+    /// given position.
+    ///
+    /// Arguments:
+    /// - `index`: The index of the element;
+    ///
+    /// Returns:
+    /// - `Some(v)`: The value;
+    /// - `None`: If this is not an array or the index is out of bounds;
+    pub fn get_index(&self, index: usize) -> Option<&ILValue> {
+        self.as_array().and_then(|a| a.get(index))
+    }
+}
+
+/// Verifies if the given id is used by one of the standard tags.
+fn is_standard_id(id: u64) -> bool {
+    matches!(
+        id,
+        IL_NULL_TAG_ID..=IL_SIGNED_ILINT_TAG_ID
+            | IL_BYTES_TAG_ID..=IL_OID_TAG_ID
+            | IL_DICTIONARY_TAG_ID
+            | IL_STRING_DICTIONARY_TAG_ID
+    )
+}
+
+/// Moves the children of the given value into `children`.
+fn take_children(value: &mut ILValue, children: &mut Vec<ILValue>) {
+    match value {
+        ILValue::Array(v) | ILValue::Seq(v) => children.append(v),
+        ILValue::Dict(v) => children.extend(std::mem::take(v).into_values()),
+        _ => (),
+    }
+}
+
+/// The children are dropped using an explicit stack, thus values of any
+/// depth can be dropped.
+impl Drop for ILValue {
+    fn drop(&mut self) {
+        let mut pending = Vec::new();
+        take_children(self, &mut pending);
+        while let Some(mut v) = pending.pop() {
+            take_children(&mut v, &mut pending);
+        }
+    }
+}
+
+//=============================================================================
+// FromTagFrame
+//-----------------------------------------------------------------------------
+/// The result of the conversion of a single tag by [`ILValue::from_tag()`].
+enum FromTagNode<'a> {
+    /// The value of the tag.
+    Value(ILValue),
+    /// A container whose children must be converted.
+    Container(FromTagFrame<'a>),
+}
+
+/// A standard container being converted by [`ILValue::from_tag()`].
+enum FromTagFrame<'a> {
+    Array(Vec<ILValue>, std::slice::Iter<'a, Box<dyn ILTag>>),
+    Seq(Vec<ILValue>, std::slice::Iter<'a, Box<dyn ILTag>>),
+    Dict(
+        BTreeMap<String, ILValue>,
+        std::collections::hash_map::Iter<'a, String, Box<dyn ILTag>>,
+        Option<&'a String>,
+    ),
+}
+
+impl<'a> FromTagFrame<'a> {
+    /// Creates a new frame if the tag is a standard container struct with
+    /// its default id.
+    fn new(tag: &'a dyn ILTag) -> Option<Self> {
+        match tag.id() {
+            IL_ILTAGARRAY_TAG_ID => tag_downcast_ref::<ILTagArrayTag>(tag)
+                .map(|t| Self::Array(Vec::with_capacity(t.value().len()), t.value().iter())),
+            IL_ILTAGSEQ_TAG_ID => tag_downcast_ref::<ILTagSeqTag>(tag)
+                .map(|t| Self::Seq(Vec::with_capacity(t.value().len()), t.value().iter())),
+            IL_DICTIONARY_TAG_ID => tag_downcast_ref::<ILDictTag>(tag)
+                .map(|t| Self::Dict(BTreeMap::new(), t.value().iter(), None)),
+            _ => None,
+        }
+    }
+
+    /// Returns the next child to be converted.
+    fn next_child(&mut self) -> Option<&'a dyn ILTag> {
+        match self {
+            Self::Array(_, children) | Self::Seq(_, children) => {
+                children.next().map(|t| t.as_ref())
+            }
+            Self::Dict(_, children, key) => children.next().map(|(k, t)| {
+                *key = Some(k);
+                t.as_ref()
+            }),
+        }
+    }
+
+    /// Adds the value of the last child returned by [`Self::next_child()`].
+    fn push(&mut self, value: ILValue) {
+        match self {
+            Self::Array(values, _) | Self::Seq(values, _) => values.push(value),
+            Self::Dict(values, _, key) => {
+                if let Some(k) = key.take() {
+                    values.insert(k.clone(), value);
+                }
+            }
+        }
+    }
+
+    /// Returns the value of the container.
+    fn finish(self) -> ILValue {
+        match self {
+            Self::Array(values, _) => ILValue::Array(values),
+            Self::Seq(values, _) => ILValue::Seq(values),
+            Self::Dict(values, _, _) => ILValue::Dict(values),
+        }
+    }
+}
+
+//=============================================================================
+// ToTagFrame
+//-----------------------------------------------------------------------------
+/// A container value being converted by [`ILValue::to_tag()`].
+enum ToTagFrame<'a> {
+    Array(ILTagArrayTag, std::slice::Iter<'a, ILValue>),
+    Seq(ILTagSeqTag, std::slice::Iter<'a, ILValue>),
+    Dict(
+        ILDictTag,
+        std::collections::btree_map::Iter<'a, String, ILValue>,
+        Option<&'a String>,
+    ),
+}
+
+impl<'a> ToTagFrame<'a> {
+    /// Creates a new frame if the value is a container.
+    fn new(value: &'a ILValue) -> Option<Self> {
+        match value {
+            ILValue::Array(v) => Some(Self::Array(ILTagArrayTag::new(), v.iter())),
+            ILValue::Seq(v) => Some(Self::Seq(ILTagSeqTag::new(), v.iter())),
+            ILValue::Dict(v) => Some(Self::Dict(ILDictTag::new(), v.iter(), None)),
+            _ => None,
+        }
+    }
+
+    /// Returns the next child to be converted.
+    fn next_child(&mut self) -> Option<&'a ILValue> {
+        match self {
+            Self::Array(_, children) | Self::Seq(_, children) => children.next(),
+            Self::Dict(_, children, key) => children.next().map(|(k, v)| {
+                *key = Some(k);
+                v
+            }),
+        }
+    }
+
+    /// Adds the tag of the last child returned by [`Self::next_child()`].
+    fn push(&mut self, child: Box<dyn ILTag>) {
+        match self {
+            Self::Array(tag, _) => tag.mut_value().push(child),
+            Self::Seq(tag, _) => tag.mut_value().push(child),
+            Self::Dict(tag, _, key) => {
+                if let Some(k) = key.take() {
+                    tag.insert(k, child);
+                }
+            }
+        }
+    }
+
+    /// Returns the tag of the container.
+    fn finish(self) -> Box<dyn ILTag> {
+        match self {
+            Self::Array(tag, _) => Box::new(tag),
+            Self::Seq(tag, _) => Box::new(tag),
+            Self::Dict(tag, _, _) => Box::new(tag),
+        }
+    }
+}
+
+/// Returns the element at the given position or [`ILValue::Null`] if it does
+/// not exist.
+impl Index<usize> for ILValue {
+    type Output = ILValue;
+
+    fn index(&self, index: usize) -> &ILValue {
+        self.get_index(index).unwrap_or(&NULL)
+    }
+}
+
+/// Returns the entry with the given key or [`ILValue::Null`] if it does not
+/// exist.
+impl Index<&str> for ILValue {
+    type Output = ILValue;
+
+    fn index(&self, key: &str) -> &ILValue {
+        self.get(key).unwrap_or(&NULL)
+    }
+}
+
+impl TryFrom<&dyn ILTag> for ILValue {
+    type Error = ErrorKind;
+
+    fn try_from(tag: &dyn ILTag) -> Result<Self> {
+        Self::from_tag(tag)
+    }
+}
+
+impl From<&ILValue> for Box<dyn ILTag> {
+    fn from(value: &ILValue) -> Self {
+        value.to_tag()
+    }
+}
+
+/// This macro implements the conversion of primitive values into
+/// [`ILValue`].
+macro_rules! ilvalue_from_impl {
+    ($($type: ty => $variant: ident),*) => {
+        $(
+            impl From<$type> for ILValue {
+                fn from(v: $type) -> Self {
+                    ILValue::$variant(v)
+                }
+            }
+        )*
+    };
+}
+
+ilvalue_from_impl!(
+    bool => Bool,
+    i8 => Int8,
+    u8 => UInt8,
+    i16 => Int16,
+    u16 => UInt16,
+    i32 => Int32,
+    u32 => UInt32,
+    i64 => Int64,
+    u64 => UInt64,
+    f32 => Bin32,
+    f64 => Bin64,
+    String => String,
+    Vec<ILValue> => Array
+);
+
+impl From<&str> for ILValue {
+    fn from(v: &str) -> Self {
+        ILValue::String(String::from(v))
+    }
+}
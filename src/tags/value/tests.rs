@@ -0,0 +1,346 @@
+/*
+ * BSD 3-Clause License
+ *
+ * Copyright (c) 2020, InterlockLedger Network
+ * All rights reserved.
+ *
+ * Redistribution and use in source and binary forms, with or without
+ * modification, are permitted provided that the following conditions are met:
+ *
+ * * Redistributions of source code must retain the above copyright notice, this
+ *   list of conditions and the following disclaimer.
+ *
+ * * Redistributions in binary form must reproduce the above copyright notice,
+ *   this list of conditions and the following disclaimer in the documentation
+ *   and/or other materials provided with the distribution.
+ *
+ * * Neither the name of the copyright holder nor the names of its
+ *   contributors may be used to endorse or promote products derived from
+ *   this software without specific prior written permission.
+ *
+ * THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS"
+ * AND ANY EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE
+ * IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+ * DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE
+ * FOR ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL, EXEMPLARY, OR CONSEQUENTIAL
+ * DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+ * SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER
+ * CAUSED AND ON ANY THEORY OF LIABILITY, WHETHER IN CONTRACT, STRICT LIABILITY,
+ * OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE
+ * OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+ */
+use super::*;
+
+fn create_sample() -> ILValue {
+    let mut dict = BTreeMap::new();
+    dict.insert(String::from("null"), ILValue::Null);
+    dict.insert(String::from("bool"), ILValue::Bool(true));
+    dict.insert(String::from("int8"), ILValue::Int8(-1));
+    dict.insert(String::from("uint8"), ILValue::UInt8(1));
+    dict.insert(String::from("int16"), ILValue::Int16(-2));
+    dict.insert(String::from("uint16"), ILValue::UInt16(2));
+    dict.insert(String::from("int32"), ILValue::Int32(-3));
+    dict.insert(String::from("uint32"), ILValue::UInt32(3));
+    dict.insert(String::from("int64"), ILValue::Int64(-4));
+    dict.insert(String::from("uint64"), ILValue::UInt64(4));
+    dict.insert(String::from("ilint"), ILValue::ILInt(1234));
+    dict.insert(String::from("silint"), ILValue::SignedILInt(-1234));
+    dict.insert(String::from("bin32"), ILValue::Bin32(1.5));
+    dict.insert(String::from("bin64"), ILValue::Bin64(-1.5));
+    dict.insert(String::from("bin128"), ILValue::Bin128([7; 16]));
+    dict.insert(String::from("bytes"), ILValue::Bytes(vec![1, 2, 3]));
+    dict.insert(String::from("string"), ILValue::from("abc"));
+    dict.insert(String::from("bint"), ILValue::BigInt(vec![0xFF, 0x00]));
+    dict.insert(
+        String::from("bdec"),
+        ILValue::BigDec {
+            scale: -2,
+            value: vec![0x01],
+        },
+    );
+    dict.insert(
+        String::from("ilintarray"),
+        ILValue::ILIntArray(vec![1, 1234]),
+    );
+    dict.insert(
+        String::from("array"),
+        ILValue::Array(vec![ILValue::Null, ILValue::UInt8(1)]),
+    );
+    dict.insert(
+        String::from("seq"),
+        ILValue::Seq(vec![ILValue::from("a"), ILValue::Array(vec![])]),
+    );
+    dict.insert(
+        String::from("range"),
+        ILValue::Range {
+            start: 10,
+            count: 2,
+        },
+    );
+    dict.insert(String::from("version"), ILValue::Version([1, 2, 3, 4]));
+    dict.insert(String::from("oid"), ILValue::OID(vec![1, 3, 6, 1]));
+    let mut strdict = BTreeMap::new();
+    strdict.insert(String::from("k"), String::from("v"));
+    dict.insert(String::from("strdict"), ILValue::StrDict(strdict));
+    dict.insert(
+        String::from("raw"),
+        ILValue::Raw {
+            id: 1234,
+            bytes: vec![0x0A, 0x0B],
+        },
+    );
+    ILValue::Dict(dict)
+}
+
+#[test]
+fn test_ilvalue_roundtrip() {
+    let v = create_sample();
+    let tag = v.to_tag();
+    assert_eq!(tag.id(), IL_DICTIONARY_TAG_ID);
+    let w = ILValue::from_tag(tag.as_ref()).unwrap();
+    assert_eq!(v, w);
+
+    let bytes = v.to_bytes().unwrap();
+    assert_eq!(bytes, tag.to_bytes().unwrap());
+    let w = ILValue::from_bytes(&bytes).unwrap();
+    assert_eq!(v, w);
+    for (k, e) in v.as_dict().unwrap() {
+        assert_eq!(w[k.as_str()].id(), e.id());
+    }
+
+    let w = ILValue::try_from(tag.as_ref()).unwrap();
+    assert_eq!(v, w);
+    let tag: Box<dyn ILTag> = Box::from(&v);
+    assert_eq!(tag.to_bytes().unwrap(), bytes);
+}
+
+#[test]
+fn test_ilvalue_from_tag_custom() {
+    // Standard tags with custom ids
+    let tag = ILStringTag::with_id_value(1234, "abc");
+    assert_eq!(
+        ILValue::from_tag(&tag).unwrap(),
+        ILValue::Raw {
+            id: 1234,
+            bytes: vec![b'a', b'b', b'c']
+        }
+    );
+    let v = ILValue::from_tag(&tag).unwrap();
+    assert_eq!(v.to_bytes().unwrap(), tag.to_bytes().unwrap());
+
+    // Non standard structs with standard ids
+    let tag = ILRawTag::with_value(IL_STRING_TAG_ID, b"abc");
+    assert_eq!(ILValue::from_tag(&tag).unwrap(), ILValue::from("abc"));
+    let tag = ILRawTag::with_value(IL_STRING_TAG_ID, &[0xFF]);
+    assert!(matches!(
+        ILValue::from_tag(&tag),
+        Err(ErrorKind::CorruptedData)
+    ));
+
+    // Unknown tags
+    let bytes = [0xF9, 0x03, 0xDA, 0x01, 0x00];
+    let v = ILValue::from_bytes(&bytes).unwrap();
+    assert_eq!(
+        v,
+        ILValue::Raw {
+            id: 1234,
+            bytes: vec![0]
+        }
+    );
+    assert_eq!(v.id(), 1234);
+    assert_eq!(v.to_bytes().unwrap(), bytes);
+}
+
+#[test]
+fn test_ilvalue_accessors() {
+    let v = create_sample();
+
+    assert!(v["null"].is_null());
+    assert!(!v["bool"].is_null());
+    assert_eq!(v["bool"].as_bool(), Some(true));
+    assert_eq!(v["int8"].as_bool(), None);
+
+    assert_eq!(v["int8"].as_i64(), Some(-1));
+    assert_eq!(v["int8"].as_u64(), None);
+    assert_eq!(v["uint8"].as_u64(), Some(1));
+    assert_eq!(v["int16"].as_i64(), Some(-2));
+    assert_eq!(v["uint16"].as_u64(), Some(2));
+    assert_eq!(v["int32"].as_i64(), Some(-3));
+    assert_eq!(v["uint32"].as_i64(), Some(3));
+    assert_eq!(v["int64"].as_i64(), Some(-4));
+    assert_eq!(v["uint64"].as_u64(), Some(4));
+    assert_eq!(v["ilint"].as_u64(), Some(1234));
+    assert_eq!(v["silint"].as_i64(), Some(-1234));
+    assert_eq!(ILValue::Int32(5).as_u64(), Some(5));
+    assert_eq!(ILValue::UInt64(u64::MAX).as_i64(), None);
+    assert_eq!(ILValue::UInt64(u64::MAX).as_u64(), Some(u64::MAX));
+    assert_eq!(v["string"].as_i64(), None);
+
+    assert_eq!(v["bin32"].as_f64(), Some(1.5));
+    assert_eq!(v["bin64"].as_f64(), Some(-1.5));
+    assert_eq!(v["int8"].as_f64(), None);
+
+    assert_eq!(v["string"].as_str(), Some("abc"));
+    assert_eq!(v["bytes"].as_str(), None);
+    assert_eq!(v["bytes"].as_bytes(), Some(&[1u8, 2, 3][..]));
+    assert_eq!(v["string"].as_bytes(), None);
+
+    assert_eq!(v["array"].as_array().unwrap().len(), 2);
+    assert_eq!(v["seq"].as_array().unwrap().len(), 2);
+    assert!(v["dict"].as_array().is_none());
+    assert_eq!(v["array"][1], ILValue::UInt8(1));
+    assert_eq!(v["seq"][0].as_str(), Some("a"));
+    assert!(v["array"][2].is_null());
+    assert!(v["string"][0].is_null());
+    assert_eq!(v["array"].get_index(1), Some(&ILValue::UInt8(1)));
+    assert_eq!(v["array"].get_index(2), None);
+
+    assert!(v.as_dict().is_some());
+    assert!(v["array"].as_dict().is_none());
+    assert_eq!(v.get("uint8"), Some(&ILValue::UInt8(1)));
+    assert_eq!(v.get("missing"), None);
+    assert!(v["missing"].is_null());
+    assert!(v["array"]["key"].is_null());
+
+    let mut v = v;
+    v.as_dict_mut()
+        .unwrap()
+        .insert(String::from("new"), ILValue::from(1u8));
+    assert_eq!(v["new"], ILValue::UInt8(1));
+    v.as_dict_mut()
+        .unwrap()
+        .get_mut("array")
+        .unwrap()
+        .as_array_mut()
+        .unwrap()
+        .push(ILValue::from(true));
+    assert_eq!(v["array"][2], ILValue::Bool(true));
+    assert!(ILValue::Null.as_dict_mut().is_none());
+    assert!(ILValue::Null.as_array_mut().is_none());
+}
+
+#[test]
+fn test_ilvalue_from() {
+    assert_eq!(ILValue::from(true), ILValue::Bool(true));
+    assert_eq!(ILValue::from(-1i8), ILValue::Int8(-1));
+    assert_eq!(ILValue::from(1u8), ILValue::UInt8(1));
+    assert_eq!(ILValue::from(-1i16), ILValue::Int16(-1));
+    assert_eq!(ILValue::from(1u16), ILValue::UInt16(1));
+    assert_eq!(ILValue::from(-1i32), ILValue::Int32(-1));
+    assert_eq!(ILValue::from(1u32), ILValue::UInt32(1));
+    assert_eq!(ILValue::from(-1i64), ILValue::Int64(-1));
+    assert_eq!(ILValue::from(1u64), ILValue::UInt64(1));
+    assert_eq!(ILValue::from(1.0f32), ILValue::Bin32(1.0));
+    assert_eq!(ILValue::from(1.0f64), ILValue::Bin64(1.0));
+    assert_eq!(ILValue::from("a"), ILValue::String(String::from("a")));
+    assert_eq!(
+        ILValue::from(String::from("a")),
+        ILValue::String(String::from("a"))
+    );
+    assert_eq!(
+        ILValue::from(vec![ILValue::Null]),
+        ILValue::Array(vec![ILValue::Null])
+    );
+}
+
+#[test]
+fn test_ilvalue_id() {
+    let v = create_sample();
+    for e in v.as_dict().unwrap().values() {
+        assert_eq!(e.to_tag().id(), e.id());
+    }
+}
+
+#[test]
+fn test_is_standard_id() {
+    for id in 0..=IL_SIGNED_ILINT_TAG_ID {
+        assert!(is_standard_id(id));
+    }
+    assert!(!is_standard_id(15));
+    for id in IL_BYTES_TAG_ID..=IL_OID_TAG_ID {
+        assert!(is_standard_id(id));
+    }
+    for id in 26..IL_DICTIONARY_TAG_ID {
+        assert!(!is_standard_id(id));
+    }
+    assert!(is_standard_id(IL_DICTIONARY_TAG_ID));
+    assert!(is_standard_id(IL_STRING_DICTIONARY_TAG_ID));
+    assert!(!is_standard_id(32));
+}
+
+//=============================================================================
+// Deep trees
+//-----------------------------------------------------------------------------
+const DEEP_LEVELS: usize = 200000;
+
+/// Stack size used to run the tests with deep trees. It is small enough to
+/// make any recursive implementation fail.
+const SMALL_STACK_SIZE: usize = 128 * 1024;
+
+fn run_with_small_stack<F: FnOnce() + Send + 'static>(f: F) {
+    std::thread::Builder::new()
+        .stack_size(SMALL_STACK_SIZE)
+        .spawn(f)
+        .unwrap()
+        .join()
+        .unwrap();
+}
+
+/// Creates a tree that alternates between sequences, arrays and
+/// dictionaries.
+fn create_deep_tag(levels: usize) -> Box<dyn ILTag> {
+    let mut tag: Box<dyn ILTag> = Box::new(ILStringTag::with_value("leaf"));
+    for i in 0..levels {
+        tag = match i % 3 {
+            0 => {
+                let mut parent = ILTagSeqTag::new();
+                parent.mut_value().push(Box::new(ILUInt8Tag::with_value(1)));
+                parent.mut_value().push(tag);
+                Box::new(parent)
+            }
+            1 => {
+                let mut parent = ILTagArrayTag::new();
+                parent.mut_value().push(Box::new(ILNullTag::new()));
+                parent.mut_value().push(tag);
+                Box::new(parent)
+            }
+            _ => {
+                let mut parent = ILDictTag::new();
+                parent.insert("a", Box::new(ILBoolTag::with_value(true)));
+                parent.insert("b", tag);
+                Box::new(parent)
+            }
+        };
+    }
+    tag
+}
+
+fn deep_value_depth(value: &ILValue) -> usize {
+    let mut depth = 0;
+    let mut v = value;
+    loop {
+        v = match v {
+            ILValue::Seq(c) | ILValue::Array(c) => c.last().unwrap(),
+            ILValue::Dict(d) => &d["b"],
+            _ => return depth,
+        };
+        depth += 1;
+    }
+}
+
+#[test]
+fn test_ilvalue_deep() {
+    run_with_small_stack(|| {
+        let serialized = create_deep_tag(DEEP_LEVELS).to_bytes().unwrap();
+
+        let factory = ILStandardTagFactory::new(false);
+        let tag = factory.from_bytes(&serialized).unwrap();
+        let v = ILValue::from_tag(tag.as_ref()).unwrap();
+        assert_eq!(deep_value_depth(&v), DEEP_LEVELS);
+        assert_eq!(v.to_tag().to_bytes().unwrap(), serialized);
+        assert_eq!(v.to_bytes().unwrap(), serialized);
+
+        let v = ILValue::from_bytes(&serialized).unwrap();
+        assert_eq!(deep_value_depth(&v), DEEP_LEVELS);
+    });
+}
@@ -0,0 +1,492 @@
+/*
+ * BSD 3-Clause License
+ *
+ * Copyright (c) 2020, InterlockLedger Network
+ * All rights reserved.
+ *
+ * Redistribution and use in source and binary forms, with or without
+ * modification, are permitted provided that the following conditions are met:
+ *
+ * * Redistributions of source code must retain the above copyright notice, this
+ *   list of conditions and the following disclaimer.
+ *
+ * * Redistributions in binary form must reproduce the above copyright notice,
+ *   this list of conditions and the following disclaimer in the documentation
+ *   and/or other materials provided with the distribution.
+ *
+ * * Neither the name of the copyright holder nor the names of its
+ *   contributors may be used to endorse or promote products derived from
+ *   this software without specific prior written permission.
+ *
+ * THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS"
+ * AND ANY EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE
+ * IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+ * DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE
+ * FOR ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL, EXEMPLARY, OR CONSEQUENTIAL
+ * DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+ * SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER
+ * CAUSED AND ON ANY THEORY OF LIABILITY, WHETHER IN CONTRACT, STRICT LIABILITY,
+ * OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE
+ * OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+ */
+//! This module implements zero-copy views over serialized tags. An
+//! [`ILTagView`] borrows the bytes of a serialized tag and exposes typed
+//! accessors to its value without copying it or allocating memory on the
+//! heap. It is useful when large amounts of tags must be read but not
+//! modified.
+//!
+//! The views rely on [`crate::tags::raw::RawTagScanner`] to locate the tags
+//! inside the byte slices.
+//!
+//! New since 2.0.0.
+#[cfg(test)]
+mod tests;
+
+use crate::io::array::ByteArrayReader;
+use crate::tags::raw::{RawTagOffset, RawTagScanner};
+use crate::tags::standard::constants::*;
+use crate::tags::{ErrorKind, Result};
+use ::std::convert::TryInto;
+
+/// This macro implements the accessors of the fixed size integer and
+/// floating point values.
+///
+/// Arguments:
+/// - `$name`: The name of the method;
+/// - `$type`: The type of the value;
+/// - `$tag_id`: The expected tag id;
+macro_rules! view_as_fixed_impl {
+    ($name: ident, $type: ty, $tag_id: expr) => {
+        #[doc = concat!("Returns the value as a `", stringify!($type), "`. It requires the tag id to be [`", stringify!($tag_id), "`].")]
+        ///
+        /// Returns:
+        /// - Ok(value): On success;
+        /// - Err(ErrorKind::UnexpectedTagType): If the tag id does not match;
+        /// - Err(ErrorKind::CorruptedData): If the value is corrupted;
+        pub fn $name(&self) -> Result<$type> {
+            self.check_id($tag_id)?;
+            match self.value.try_into() {
+                Ok(v) => Ok(<$type>::from_be_bytes(v)),
+                Err(_) => Err(ErrorKind::CorruptedData),
+            }
+        }
+    };
+}
+
+//=============================================================================
+// ILTagView
+//-----------------------------------------------------------------------------
+/// This struct implements a read-only view of a serialized tag. It borrows
+/// the serialized bytes instead of copying them.
+///
+/// All typed accessors verify if the id of the tag matches the expected
+/// standard tag id and return [`ErrorKind::UnexpectedTagType`] if it does not.
+/// Tags with custom ids can still be accessed through [`Self::value()`].
+///
+/// New since 2.0.0.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct ILTagView<'a> {
+    id: u64,
+    raw: &'a [u8],
+    value: &'a [u8],
+}
+
+impl<'a> ILTagView<'a> {
+    /// Creates a view of the first tag inside the given bytes. Any bytes
+    /// after the end of the first tag are ignored.
+    ///
+    /// Arguments:
+    /// - `raw`: The bytes that contain the serialized tag;
+    ///
+    /// Returns:
+    /// - Ok(view): The view of the first tag;
+    /// - Err(ErrorKind::CorruptedData): If the tag is truncated or corrupted;
+    pub fn first(raw: &'a [u8]) -> Result<Self> {
+        let mut reader = ByteArrayReader::new(raw);
+        let mut scanner = RawTagScanner::new(&mut reader);
+        match scanner.next_tag() {
+            Ok(Some(offset)) => Self::with_offset(raw, &offset),
+            Ok(None) => Err(ErrorKind::CorruptedData),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Creates a view of the tag that uses all the given bytes.
+    ///
+    /// Arguments:
+    /// - `raw`: The bytes that contain the serialized tag;
+    ///
+    /// Returns:
+    /// - Ok(view): The view of the tag;
+    /// - Err(ErrorKind::CorruptedData): If the tag is corrupted or does not
+    ///   use all bytes;
+    pub fn from_bytes(raw: &'a [u8]) -> Result<Self> {
+        let view = Self::first(raw)?;
+        if view.raw.len() == raw.len() {
+            Ok(view)
+        } else {
+            Err(ErrorKind::CorruptedData)
+        }
+    }
+
+    /// Creates a view of the tag located by a [`RawTagOffset`].
+    ///
+    /// Arguments:
+    /// - `raw`: The bytes that contain the serialized tag;
+    /// - `offset`: The offset of the tag inside `raw`;
+    ///
+    /// Returns:
+    /// - Ok(view): The view of the tag;
+    /// - Err(ErrorKind::CorruptedData): If the offset is outside of `raw`;
+    pub fn with_offset(raw: &'a [u8], offset: &RawTagOffset) -> Result<Self> {
+        if offset.next_tag_offset() > raw.len() as u64 {
+            return Err(ErrorKind::CorruptedData);
+        }
+        Ok(Self {
+            id: offset.id(),
+            raw: offset.tag_slice(raw),
+            value: offset.value_slice(raw),
+        })
+    }
+
+    /// Returns the id of the tag.
+    pub fn id(&self) -> u64 {
+        self.id
+    }
+
+    /// Returns the bytes of the whole tag, including its header.
+    pub fn raw(&self) -> &'a [u8] {
+        self.raw
+    }
+
+    /// Returns the bytes of the value of the tag.
+    pub fn value(&self) -> &'a [u8] {
+        self.value
+    }
+
+    /// Returns the total size of the tag in bytes.
+    pub fn size(&self) -> usize {
+        self.raw.len()
+    }
+
+    /// Verifies if the tag has the expected id.
+    #[inline]
+    fn check_id(&self, tag_id: u64) -> Result<()> {
+        if self.id == tag_id {
+            Ok(())
+        } else {
+            Err(ErrorKind::UnexpectedTagType)
+        }
+    }
+
+    /// Returns the null value. It requires the tag id to be
+    /// [`IL_NULL_TAG_ID`].
+    ///
+    /// Returns:
+    /// - Ok(()): On success;
+    /// - Err(ErrorKind::UnexpectedTagType): If the tag id does not match;
+    pub fn as_null(&self) -> Result<()> {
+        self.check_id(IL_NULL_TAG_ID)
+    }
+
+    /// Returns the value as a boolean. It requires the tag id to be
+    /// [`IL_BOOL_TAG_ID`].
+    ///
+    /// Returns:
+    /// - Ok(value): On success;
+    /// - Err(ErrorKind::UnexpectedTagType): If the tag id does not match;
+    /// - Err(ErrorKind::CorruptedData): If the value is not 0 or 1;
+    pub fn as_bool(&self) -> Result<bool> {
+        self.check_id(IL_BOOL_TAG_ID)?;
+        match self.value {
+            [0] => Ok(false),
+            [1] => Ok(true),
+            _ => Err(ErrorKind::CorruptedData),
+        }
+    }
+
+    view_as_fixed_impl!(as_i8, i8, IL_INT8_TAG_ID);
+    view_as_fixed_impl!(as_u8, u8, IL_UINT8_TAG_ID);
+    view_as_fixed_impl!(as_i16, i16, IL_INT16_TAG_ID);
+    view_as_fixed_impl!(as_u16, u16, IL_UINT16_TAG_ID);
+    view_as_fixed_impl!(as_i32, i32, IL_INT32_TAG_ID);
+    view_as_fixed_impl!(as_u32, u32, IL_UINT32_TAG_ID);
+    view_as_fixed_impl!(as_i64, i64, IL_INT64_TAG_ID);
+    view_as_fixed_impl!(as_u64, u64, IL_UINT64_TAG_ID);
+    view_as_fixed_impl!(as_f32, f32, IL_BIN32_TAG_ID);
+    view_as_fixed_impl!(as_f64, f64, IL_BIN64_TAG_ID);
+
+    /// Returns the value as an ILInt. It requires the tag id to be
+    /// [`IL_ILINT_TAG_ID`].
+    ///
+    /// Returns:
+    /// - Ok(value): On success;
+    /// - Err(ErrorKind::UnexpectedTagType): If the tag id does not match;
+    /// - Err(ErrorKind::CorruptedData): If the value is corrupted;
+    pub fn as_ilint(&self) -> Result<u64> {
+        self.check_id(IL_ILINT_TAG_ID)?;
+        decode_ilint_value(self.value)
+    }
+
+    /// Returns the value as a signed ILInt. It requires the tag id to be
+    /// [`IL_SIGNED_ILINT_TAG_ID`].
+    ///
+    /// Returns:
+    /// - Ok(value): On success;
+    /// - Err(ErrorKind::UnexpectedTagType): If the tag id does not match;
+    /// - Err(ErrorKind::CorruptedData): If the value is corrupted;
+    pub fn as_signed_ilint(&self) -> Result<i64> {
+        self.check_id(IL_SIGNED_ILINT_TAG_ID)?;
+        Ok(crate::ilint::decode_sign(decode_ilint_value(self.value)?))
+    }
+
+    /// Returns the value as a 128-bit floating point. It requires the tag id
+    /// to be [`IL_BIN128_TAG_ID`].
+    ///
+    /// Returns:
+    /// - Ok(value): On success;
+    /// - Err(ErrorKind::UnexpectedTagType): If the tag id does not match;
+    /// - Err(ErrorKind::CorruptedData): If the value is corrupted;
+    pub fn as_bin128(&self) -> Result<&'a [u8; 16]> {
+        self.check_id(IL_BIN128_TAG_ID)?;
+        match self.value.try_into() {
+            Ok(v) => Ok(v),
+            Err(_) => Err(ErrorKind::CorruptedData),
+        }
+    }
+
+    /// Returns the value as a byte array. It requires the tag id to be
+    /// [`IL_BYTES_TAG_ID`].
+    ///
+    /// Returns:
+    /// - Ok(value): On success;
+    /// - Err(ErrorKind::UnexpectedTagType): If the tag id does not match;
+    pub fn as_bytes(&self) -> Result<&'a [u8]> {
+        self.check_id(IL_BYTES_TAG_ID)?;
+        Ok(self.value)
+    }
+
+    /// Returns the value as a string. It requires the tag id to be
+    /// [`IL_STRING_TAG_ID`].
+    ///
+    /// Returns:
+    /// - Ok(value): On success;
+    /// - Err(ErrorKind::UnexpectedTagType): If the tag id does not match;
+    /// - Err(ErrorKind::CorruptedData): If the value is not a valid UTF-8 string;
+    pub fn as_str(&self) -> Result<&'a str> {
+        self.check_id(IL_STRING_TAG_ID)?;
+        match std::str::from_utf8(self.value) {
+            Ok(s) => Ok(s),
+            Err(_) => Err(ErrorKind::CorruptedData),
+        }
+    }
+
+    /// Returns an iterator over the children of this tag. It requires the
+    /// tag id to be [`IL_ILTAGSEQ_TAG_ID`] or [`IL_ILTAGARRAY_TAG_ID`].
+    ///
+    /// Returns:
+    /// - Ok(iter): The iterator over the children;
+    /// - Err(ErrorKind::UnexpectedTagType): If the tag id does not match;
+    /// - Err(ErrorKind::CorruptedData): If the number of elements is corrupted;
+    pub fn children(&self) -> Result<ILTagViewIter<'a>> {
+        match self.id {
+            IL_ILTAGSEQ_TAG_ID => Ok(ILTagViewIter::new(self.value)),
+            IL_ILTAGARRAY_TAG_ID => {
+                let (count, size) = decode_ilint_prefix(self.value)?;
+                Ok(ILTagViewIter::with_count(&self.value[size..], count))
+            }
+            _ => Err(ErrorKind::UnexpectedTagType),
+        }
+    }
+
+    /// Returns an iterator over the entries of this dictionary. It requires
+    /// the tag id to be [`IL_DICTIONARY_TAG_ID`] or
+    /// [`IL_STRING_DICTIONARY_TAG_ID`].
+    ///
+    /// The entries are returned in the same order they appear in the
+    /// serialized data. No duplicate or order verification is performed.
+    ///
+    /// Returns:
+    /// - Ok(iter): The iterator over the entries;
+    /// - Err(ErrorKind::UnexpectedTagType): If the tag id does not match;
+    /// - Err(ErrorKind::CorruptedData): If the number of entries is corrupted;
+    pub fn dict_entries(&self) -> Result<ILDictViewIter<'a>> {
+        match self.id {
+            IL_DICTIONARY_TAG_ID | IL_STRING_DICTIONARY_TAG_ID => {
+                let (count, size) = decode_ilint_prefix(self.value)?;
+                match count.checked_mul(2) {
+                    Some(count) => Ok(ILDictViewIter {
+                        inner: ILTagViewIter::with_count(&self.value[size..], count),
+                    }),
+                    None => Err(ErrorKind::CorruptedData),
+                }
+            }
+            _ => Err(ErrorKind::UnexpectedTagType),
+        }
+    }
+
+    /// Returns the value associated with the given key of this dictionary.
+    /// It requires the tag id to be [`IL_DICTIONARY_TAG_ID`] or
+    /// [`IL_STRING_DICTIONARY_TAG_ID`].
+    ///
+    /// The entries are scanned sequentially and the first entry with the
+    /// given key is returned.
+    ///
+    /// Arguments:
+    /// - `key`: The key;
+    ///
+    /// Returns:
+    /// - Ok(Some(view)): The view of the value;
+    /// - Ok(None): If the key was not found;
+    /// - Err(ErrorKind::UnexpectedTagType): If the tag id does not match;
+    /// - Err(ErrorKind::CorruptedData): If the dictionary is corrupted;
+    pub fn dict_get(&self, key: &str) -> Result<Option<ILTagView<'a>>> {
+        for entry in self.dict_entries()? {
+            let (k, v) = entry?;
+            if k == key {
+                return Ok(Some(v));
+            }
+        }
+        Ok(None)
+    }
+}
+
+/// Decodes an ILInt that uses all bytes of the given slice.
+fn decode_ilint_value(value: &[u8]) -> Result<u64> {
+    match decode_ilint_prefix(value)? {
+        (v, size) if size == value.len() => Ok(v),
+        _ => Err(ErrorKind::CorruptedData),
+    }
+}
+
+/// Decodes an ILInt from the beginning of the given slice.
+///
+/// Returns the value and the number of bytes used.
+fn decode_ilint_prefix(value: &[u8]) -> Result<(u64, usize)> {
+    match crate::ilint::decode_from_bytes(value) {
+        Ok(v) => Ok(v),
+        Err(_) => Err(ErrorKind::CorruptedData),
+    }
+}
+
+//=============================================================================
+// ILTagViewIter
+//-----------------------------------------------------------------------------
+/// This struct implements an iterator over a sequence of serialized tags. It
+/// yields one [`ILTagView`] for each tag found.
+///
+/// Once an error is returned, the iteration stops.
+///
+/// New since 2.0.0.
+pub struct ILTagViewIter<'a> {
+    remaining: &'a [u8],
+    count: Option<u64>,
+    failed: bool,
+}
+
+impl<'a> ILTagViewIter<'a> {
+    /// Creates an iterator over all tags inside the given bytes.
+    ///
+    /// Arguments:
+    /// - `raw`: The bytes that contain the serialized tags;
+    pub fn new(raw: &'a [u8]) -> Self {
+        Self {
+            remaining: raw,
+            count: None,
+            failed: false,
+        }
+    }
+
+    /// Creates an iterator over the given bytes that must contain exactly
+    /// `count` tags.
+    ///
+    /// Arguments:
+    /// - `raw`: The bytes that contain the serialized tags;
+    /// - `count`: The expected number of tags;
+    pub fn with_count(raw: &'a [u8], count: u64) -> Self {
+        Self {
+            remaining: raw,
+            count: Some(count),
+            failed: false,
+        }
+    }
+
+    #[inline]
+    fn fail(&mut self, error: ErrorKind) -> Option<Result<ILTagView<'a>>> {
+        self.failed = true;
+        Some(Err(error))
+    }
+}
+
+impl<'a> Iterator for ILTagViewIter<'a> {
+    type Item = Result<ILTagView<'a>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.failed {
+            return None;
+        }
+        match self.count {
+            Some(0) => {
+                if self.remaining.is_empty() {
+                    return None;
+                } else {
+                    return self.fail(ErrorKind::CorruptedData);
+                }
+            }
+            Some(_) => {
+                if self.remaining.is_empty() {
+                    return self.fail(ErrorKind::CorruptedData);
+                }
+            }
+            None => {
+                if self.remaining.is_empty() {
+                    return None;
+                }
+            }
+        }
+        match ILTagView::first(self.remaining) {
+            Ok(view) => {
+                self.remaining = &self.remaining[view.size()..];
+                if let Some(count) = self.count.as_mut() {
+                    *count -= 1;
+                }
+                Some(Ok(view))
+            }
+            Err(e) => self.fail(e),
+        }
+    }
+}
+
+//=============================================================================
+// ILDictViewIter
+//-----------------------------------------------------------------------------
+/// This struct implements an iterator over the entries of a serialized
+/// dictionary. It yields the key and the view of the value of each entry.
+///
+/// Once an error is returned, the iteration stops.
+///
+/// New since 2.0.0.
+pub struct ILDictViewIter<'a> {
+    inner: ILTagViewIter<'a>,
+}
+
+impl<'a> Iterator for ILDictViewIter<'a> {
+    type Item = Result<(&'a str, ILTagView<'a>)>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let key = match self.inner.next()? {
+            Ok(key) => match key.as_str() {
+                Ok(key) => key,
+                Err(_) => {
+                    self.inner.failed = true;
+                    return Some(Err(ErrorKind::CorruptedData));
+                }
+            },
+            Err(e) => return Some(Err(e)),
+        };
+        match self.inner.next()? {
+            Ok(value) => Some(Ok((key, value))),
+            Err(e) => Some(Err(e)),
+        }
+    }
+}
@@ -0,0 +1,384 @@
+/*
+ * BSD 3-Clause License
+ *
+ * Copyright (c) 2020, InterlockLedger Network
+ * All rights reserved.
+ *
+ * Redistribution and use in source and binary forms, with or without
+ * modification, are permitted provided that the following conditions are met:
+ *
+ * * Redistributions of source code must retain the above copyright notice, this
+ *   list of conditions and the following disclaimer.
+ *
+ * * Redistributions in binary form must reproduce the above copyright notice,
+ *   this list of conditions and the following disclaimer in the documentation
+ *   and/or other materials provided with the distribution.
+ *
+ * * Neither the name of the copyright holder nor the names of its
+ *   contributors may be used to endorse or promote products derived from
+ *   this software without specific prior written permission.
+ *
+ * THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS"
+ * AND ANY EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE
+ * IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+ * DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE
+ * FOR ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL, EXEMPLARY, OR CONSEQUENTIAL
+ * DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+ * SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER
+ * CAUSED AND ON ANY THEORY OF LIABILITY, WHETHER IN CONTRACT, STRICT LIABILITY,
+ * OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE
+ * OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+ */
+use super::*;
+use crate::tags::standard::*;
+use crate::tags::ILTag;
+
+//=============================================================================
+// ILTagView
+//-----------------------------------------------------------------------------
+#[test]
+fn test_iltagview_first() {
+    let mut raw = ILStringTag::with_value("abc").to_bytes().unwrap();
+    let exp_len = raw.len();
+    raw.extend_from_slice(&[0, 1, 2]);
+
+    let v = ILTagView::first(&raw).unwrap();
+    assert_eq!(v.id(), IL_STRING_TAG_ID);
+    assert_eq!(v.size(), exp_len);
+    assert_eq!(v.raw(), &raw[..exp_len]);
+    assert_eq!(v.value(), b"abc");
+
+    assert!(matches!(
+        ILTagView::first(&raw[..exp_len - 1]),
+        Err(ErrorKind::CorruptedData)
+    ));
+    assert!(matches!(
+        ILTagView::first(&[]),
+        Err(ErrorKind::CorruptedData)
+    ));
+}
+
+#[test]
+fn test_iltagview_from_bytes() {
+    let mut raw = ILStringTag::with_value("abc").to_bytes().unwrap();
+    let v = ILTagView::from_bytes(&raw).unwrap();
+    assert_eq!(v.id(), IL_STRING_TAG_ID);
+    assert_eq!(v.as_str().unwrap(), "abc");
+
+    raw.push(0);
+    assert!(matches!(
+        ILTagView::from_bytes(&raw),
+        Err(ErrorKind::CorruptedData)
+    ));
+}
+
+#[test]
+fn test_iltagview_with_offset() {
+    let raw = ILStringTag::with_value("abc").to_bytes().unwrap();
+    let offset = RawTagOffset::new(IL_STRING_TAG_ID, 0, 2, 3);
+    let v = ILTagView::with_offset(&raw, &offset).unwrap();
+    assert_eq!(v.id(), IL_STRING_TAG_ID);
+    assert_eq!(v.value(), b"abc");
+
+    let offset = RawTagOffset::new(IL_STRING_TAG_ID, 1, 2, 3);
+    assert!(matches!(
+        ILTagView::with_offset(&raw, &offset),
+        Err(ErrorKind::CorruptedData)
+    ));
+}
+
+#[test]
+fn test_iltagview_implicit() {
+    let raw = ILNullTag::new().to_bytes().unwrap();
+    let v = ILTagView::from_bytes(&raw).unwrap();
+    assert!(v.as_null().is_ok());
+    assert!(matches!(v.as_bool(), Err(ErrorKind::UnexpectedTagType)));
+
+    let raw = ILBoolTag::with_value(true).to_bytes().unwrap();
+    let v = ILTagView::from_bytes(&raw).unwrap();
+    assert!(v.as_bool().unwrap());
+    assert!(matches!(v.as_null(), Err(ErrorKind::UnexpectedTagType)));
+    let v = ILTagView::from_bytes(&[IL_BOOL_TAG_ID as u8, 2]).unwrap();
+    assert!(matches!(v.as_bool(), Err(ErrorKind::CorruptedData)));
+
+    let raw = ILInt8Tag::with_value(-2).to_bytes().unwrap();
+    assert_eq!(ILTagView::from_bytes(&raw).unwrap().as_i8().unwrap(), -2);
+    let raw = ILUInt8Tag::with_value(0xFE).to_bytes().unwrap();
+    assert_eq!(ILTagView::from_bytes(&raw).unwrap().as_u8().unwrap(), 0xFE);
+    let raw = ILInt16Tag::with_value(-1234).to_bytes().unwrap();
+    assert_eq!(
+        ILTagView::from_bytes(&raw).unwrap().as_i16().unwrap(),
+        -1234
+    );
+    let raw = ILUInt16Tag::with_value(0xFEDC).to_bytes().unwrap();
+    assert_eq!(
+        ILTagView::from_bytes(&raw).unwrap().as_u16().unwrap(),
+        0xFEDC
+    );
+    let raw = ILInt32Tag::with_value(-123456).to_bytes().unwrap();
+    assert_eq!(
+        ILTagView::from_bytes(&raw).unwrap().as_i32().unwrap(),
+        -123456
+    );
+    let raw = ILUInt32Tag::with_value(0xFEDC_BA98).to_bytes().unwrap();
+    let v = ILTagView::from_bytes(&raw).unwrap();
+    assert_eq!(v.as_u32().unwrap(), 0xFEDC_BA98);
+    assert!(matches!(v.as_i32(), Err(ErrorKind::UnexpectedTagType)));
+    let raw = ILInt64Tag::with_value(-1234567890).to_bytes().unwrap();
+    assert_eq!(
+        ILTagView::from_bytes(&raw).unwrap().as_i64().unwrap(),
+        -1234567890
+    );
+    let raw = ILUInt64Tag::with_value(0xFEDC_BA98_7654_3210)
+        .to_bytes()
+        .unwrap();
+    assert_eq!(
+        ILTagView::from_bytes(&raw).unwrap().as_u64().unwrap(),
+        0xFEDC_BA98_7654_3210
+    );
+    let raw = ILBin32Tag::with_value(1.5).to_bytes().unwrap();
+    assert_eq!(ILTagView::from_bytes(&raw).unwrap().as_f32().unwrap(), 1.5);
+    let raw = ILBin64Tag::with_value(-2.5).to_bytes().unwrap();
+    assert_eq!(ILTagView::from_bytes(&raw).unwrap().as_f64().unwrap(), -2.5);
+
+    let sample: [u8; 16] = [
+        0x00, 0x01, 0x02, 0x03, 0x04, 0x05, 0x06, 0x07, 0x08, 0x09, 0x0A, 0x0B, 0x0C, 0x0D, 0x0E,
+        0x0F,
+    ];
+    let raw = ILBin128Tag::with_value(&sample).to_bytes().unwrap();
+    assert_eq!(
+        ILTagView::from_bytes(&raw).unwrap().as_bin128().unwrap(),
+        &sample
+    );
+}
+
+#[test]
+fn test_iltagview_ilint() {
+    for v in [0, 0xF7, 0xF8, 0x1234, 0xFFFF_FFFF_FFFF_FFFF] {
+        let raw = ILILInt64Tag::with_value(v).to_bytes().unwrap();
+        let view = ILTagView::from_bytes(&raw).unwrap();
+        assert_eq!(view.as_ilint().unwrap(), v);
+        assert!(matches!(
+            view.as_signed_ilint(),
+            Err(ErrorKind::UnexpectedTagType)
+        ));
+    }
+    for v in [0, -1, 1, -1234, i64::MIN, i64::MAX] {
+        let raw = ILSignedILInt64Tag::with_value(v).to_bytes().unwrap();
+        let view = ILTagView::from_bytes(&raw).unwrap();
+        assert_eq!(view.as_signed_ilint().unwrap(), v);
+    }
+}
+
+#[test]
+fn test_iltagview_explicit() {
+    let raw = ILByteArrayTag::with_value(&[1, 2, 3]).to_bytes().unwrap();
+    let v = ILTagView::from_bytes(&raw).unwrap();
+    assert_eq!(v.as_bytes().unwrap(), &[1, 2, 3]);
+    assert!(matches!(v.as_str(), Err(ErrorKind::UnexpectedTagType)));
+
+    let raw = ILStringTag::with_value("").to_bytes().unwrap();
+    let v = ILTagView::from_bytes(&raw).unwrap();
+    assert_eq!(v.as_str().unwrap(), "");
+    assert!(matches!(v.as_bytes(), Err(ErrorKind::UnexpectedTagType)));
+
+    let raw: [u8; 4] = [IL_STRING_TAG_ID as u8, 2, 0xC3, 0x28];
+    let v = ILTagView::from_bytes(&raw).unwrap();
+    assert!(matches!(v.as_str(), Err(ErrorKind::CorruptedData)));
+}
+
+fn create_sample_children() -> Vec<Box<dyn ILTag>> {
+    vec![
+        Box::new(ILStringTag::with_value("abc")),
+        Box::new(ILUInt32Tag::with_value(1234)),
+        Box::new(ILNullTag::new()),
+    ]
+}
+
+fn assert_sample_children(iter: ILTagViewIter) {
+    let children: Vec<ILTagView> = iter.map(|c| c.unwrap()).collect();
+    assert_eq!(children.len(), 3);
+    assert_eq!(children[0].as_str().unwrap(), "abc");
+    assert_eq!(children[1].as_u32().unwrap(), 1234);
+    assert!(children[2].as_null().is_ok());
+}
+
+#[test]
+fn test_iltagview_children() {
+    let mut seq = ILTagSeqTag::new();
+    seq.mut_value().extend(create_sample_children());
+    let raw = seq.to_bytes().unwrap();
+    let v = ILTagView::from_bytes(&raw).unwrap();
+    assert_sample_children(v.children().unwrap());
+    assert!(matches!(
+        v.dict_entries(),
+        Err(ErrorKind::UnexpectedTagType)
+    ));
+
+    let mut array = ILTagArrayTag::new();
+    array.mut_value().extend(create_sample_children());
+    let raw = array.to_bytes().unwrap();
+    let v = ILTagView::from_bytes(&raw).unwrap();
+    assert_sample_children(v.children().unwrap());
+
+    let raw = ILTagSeqTag::new().to_bytes().unwrap();
+    let v = ILTagView::from_bytes(&raw).unwrap();
+    assert_eq!(v.children().unwrap().count(), 0);
+
+    let raw = ILStringTag::with_value("abc").to_bytes().unwrap();
+    let v = ILTagView::from_bytes(&raw).unwrap();
+    assert!(matches!(v.children(), Err(ErrorKind::UnexpectedTagType)));
+}
+
+#[test]
+fn test_iltagview_children_nested() {
+    let mut inner = ILTagSeqTag::new();
+    inner.mut_value().extend(create_sample_children());
+    let mut outer = ILTagArrayTag::new();
+    outer.mut_value().push(Box::new(inner));
+    outer
+        .mut_value()
+        .push(Box::new(ILBoolTag::with_value(true)));
+    let raw = outer.to_bytes().unwrap();
+
+    let v = ILTagView::from_bytes(&raw).unwrap();
+    let mut children = v.children().unwrap();
+    let inner = children.next().unwrap().unwrap();
+    assert_sample_children(inner.children().unwrap());
+    assert!(children.next().unwrap().unwrap().as_bool().unwrap());
+    assert!(children.next().is_none());
+}
+
+#[test]
+fn test_iltagview_children_corrupted() {
+    let mut array = ILTagArrayTag::new();
+    array.mut_value().extend(create_sample_children());
+    let raw = array.to_bytes().unwrap();
+
+    // Count larger than the number of elements
+    let mut corrupted = raw.clone();
+    corrupted[2] = 4;
+    let v = ILTagView::from_bytes(&corrupted).unwrap();
+    let mut children = v.children().unwrap();
+    for _ in 0..3 {
+        assert!(children.next().unwrap().is_ok());
+    }
+    assert!(matches!(
+        children.next(),
+        Some(Err(ErrorKind::CorruptedData))
+    ));
+    assert!(children.next().is_none());
+
+    // Count smaller than the number of elements
+    let mut corrupted = raw.clone();
+    corrupted[2] = 2;
+    let v = ILTagView::from_bytes(&corrupted).unwrap();
+    let mut children = v.children().unwrap();
+    for _ in 0..2 {
+        assert!(children.next().unwrap().is_ok());
+    }
+    assert!(matches!(
+        children.next(),
+        Some(Err(ErrorKind::CorruptedData))
+    ));
+    assert!(children.next().is_none());
+
+    // Missing count
+    let raw: [u8; 2] = [IL_ILTAGARRAY_TAG_ID as u8, 0];
+    let v = ILTagView::from_bytes(&raw).unwrap();
+    assert!(matches!(v.children(), Err(ErrorKind::CorruptedData)));
+
+    // Truncated child
+    let raw: [u8; 4] = [IL_ILTAGSEQ_TAG_ID as u8, 2, IL_UINT16_TAG_ID as u8, 0];
+    let v = ILTagView::from_bytes(&raw).unwrap();
+    let mut children = v.children().unwrap();
+    assert!(matches!(
+        children.next(),
+        Some(Err(ErrorKind::CorruptedData))
+    ));
+    assert!(children.next().is_none());
+}
+
+#[test]
+fn test_iltagview_dict() {
+    let mut dict = ILDictTag::new();
+    dict.insert("b", Box::new(ILUInt32Tag::with_value(1234)));
+    dict.insert("a", Box::new(ILStringTag::with_value("abc")));
+    let raw = dict.to_bytes().unwrap();
+    let v = ILTagView::from_bytes(&raw).unwrap();
+
+    let entries: Vec<(&str, ILTagView)> = v.dict_entries().unwrap().map(|e| e.unwrap()).collect();
+    assert_eq!(entries.len(), 2);
+    assert_eq!(entries[0].0, "a");
+    assert_eq!(entries[0].1.as_str().unwrap(), "abc");
+    assert_eq!(entries[1].0, "b");
+    assert_eq!(entries[1].1.as_u32().unwrap(), 1234);
+
+    assert_eq!(v.dict_get("a").unwrap().unwrap().as_str().unwrap(), "abc");
+    assert_eq!(v.dict_get("b").unwrap().unwrap().as_u32().unwrap(), 1234);
+    assert!(v.dict_get("c").unwrap().is_none());
+    assert!(matches!(v.children(), Err(ErrorKind::UnexpectedTagType)));
+
+    let mut dict = ILStrDictTag::new();
+    dict.insert("k", "v");
+    let raw = dict.to_bytes().unwrap();
+    let v = ILTagView::from_bytes(&raw).unwrap();
+    assert_eq!(v.dict_get("k").unwrap().unwrap().as_str().unwrap(), "v");
+    assert!(v.dict_get("v").unwrap().is_none());
+
+    let raw = ILStringTag::with_value("abc").to_bytes().unwrap();
+    let v = ILTagView::from_bytes(&raw).unwrap();
+    assert!(matches!(v.dict_get("a"), Err(ErrorKind::UnexpectedTagType)));
+}
+
+#[test]
+fn test_iltagview_dict_corrupted() {
+    // Key is not a string
+    let raw: [u8; 6] = [
+        IL_DICTIONARY_TAG_ID as u8,
+        4,
+        1,
+        IL_BOOL_TAG_ID as u8,
+        1,
+        IL_NULL_TAG_ID as u8,
+    ];
+    let v = ILTagView::from_bytes(&raw).unwrap();
+    let mut entries = v.dict_entries().unwrap();
+    assert!(matches!(
+        entries.next(),
+        Some(Err(ErrorKind::CorruptedData))
+    ));
+    assert!(entries.next().is_none());
+    assert!(matches!(v.dict_get("a"), Err(ErrorKind::CorruptedData)));
+
+    // Missing value
+    let raw: [u8; 6] = [
+        IL_DICTIONARY_TAG_ID as u8,
+        4,
+        1,
+        IL_STRING_TAG_ID as u8,
+        1,
+        b'a',
+    ];
+    let v = ILTagView::from_bytes(&raw).unwrap();
+    assert!(matches!(v.dict_get("b"), Err(ErrorKind::CorruptedData)));
+}
+
+//=============================================================================
+// ILTagViewIter
+//-----------------------------------------------------------------------------
+#[test]
+fn test_iltagviewiter() {
+    let mut raw: Vec<u8> = Vec::new();
+    for c in create_sample_children() {
+        raw.extend(c.to_bytes().unwrap());
+    }
+    assert_sample_children(ILTagViewIter::new(&raw));
+    assert_sample_children(ILTagViewIter::with_count(&raw, 3));
+    assert_eq!(ILTagViewIter::new(&[]).count(), 0);
+    assert_eq!(ILTagViewIter::with_count(&[], 0).count(), 0);
+
+    let mut iter = ILTagViewIter::with_count(&[], 1);
+    assert!(matches!(iter.next(), Some(Err(ErrorKind::CorruptedData))));
+    assert!(iter.next().is_none());
+}
@@ -32,7 +32,31 @@
 
 /// Fills the an array with values from 0 to src.len() -1.
 pub fn fill_sample(src: &mut [u8]) {
-    for i in 0..src.len() {
-        src[i] = i as u8;
+    for (i, v) in src.iter_mut().enumerate() {
+        *v = i as u8;
+    }
+}
+
+/// A digest that simply records all bytes.
+#[derive(Default)]
+pub struct RecordingDigest {
+    data: Vec<u8>,
+}
+
+impl crate::io::Digest for RecordingDigest {
+    fn output_size(&self) -> usize {
+        self.data.len()
+    }
+
+    fn update(&mut self, data: &[u8]) {
+        self.data.extend_from_slice(data);
+    }
+
+    fn finalize(&mut self) -> Vec<u8> {
+        std::mem::take(&mut self.data)
+    }
+
+    fn reset(&mut self) {
+        self.data.clear();
     }
 }